@@ -13,18 +13,83 @@ _exa()
             return
             ;;
 
+        --columns)
+            COMPREPLY=( $( compgen -W '{1..9}' -- "$cur" ) )
+            return
+            ;;
+
         -s|--sort)
-            COMPREPLY=( $( compgen -W 'name filename Name Filename size filesize extension Extension date time modified accessed created type inode oldest newest age none --' -- "$cur" ) )
+            COMPREPLY=( $( compgen -W 'name filename Name Filename name-raw size filesize extension Extension date time modified changed accessed created type owner git inode oldest newest age none --' -- "$cur" ) )
             return
             ;;
 
         -t|--time)
-            COMPREPLY=( $( compgen -W 'accessed modified created --' -- $cur ) )
+            COMPREPLY=( $( compgen -W 'accessed modified changed created all --' -- $cur ) )
+            return
+            ;;
+
+        --quotes)
+            COMPREPLY=( $( compgen -W 'shell powershell --' -- $cur ) )
+            return
+            ;;
+
+        --git)
+            COMPREPLY=( $( compgen -W 'libgit2 command --' -- $cur ) )
+            return
+            ;;
+
+        --link-targets)
+            COMPREPLY=( $( compgen -W 'full name never --' -- $cur ) )
+            return
+            ;;
+
+        --relative-to|--git-ceiling)
+            COMPREPLY=( $( compgen -d -- "$cur" ) )
+            return
+            ;;
+
+        --checksum)
+            COMPREPLY=( $( compgen -W 'crc32 md5 sha1 sha256 --' -- $cur ) )
+            return
+            ;;
+
+        --size)
+            COMPREPLY=( $( compgen -W 'logical disk both --' -- $cur ) )
+            return
+            ;;
+
+        --owner)
+            COMPREPLY=( $( compgen -W 'user combined none --' -- $cur ) )
+            return
+            ;;
+
+        --order)
+            COMPREPLY=( $( compgen -W 'inode permissions access links size disk blocks user group owner modified changed created accessed volume network zone type checksum signature version git --' -- $cur ) )
+            return
+            ;;
+
+        --tree-summary)
+            COMPREPLY=( $( compgen -W 'immediate recursive --' -- $cur ) )
+            return
+            ;;
+
+        --tree-style)
+            COMPREPLY=( $( compgen -W 'unicode ascii --' -- $cur ) )
             return
             ;;
 
         --time-style)
-            COMPREPLY=( $( compgen -W 'default iso long-iso full-iso --' -- $cur ) )
+            COMPREPLY=( $( compgen -W 'default iso long-iso full-iso unix unix-ms --' -- $cur ) )
+            return
+            ;;
+
+        --time-clock)
+            COMPREPLY=( $( compgen -W '12 24 auto --' -- $cur ) )
+            return
+            ;;
+
+        --locale)
+            COMPREPLY=( $( compgen -W '$( locale -a 2>/dev/null )' -- $cur ) )
             return
             ;;
     esac
@@ -5,11 +5,40 @@ complete -c exa -s '?' -l 'help'    -d "Show list of command-line options"
 # Display options
 complete -c exa -s '1' -l 'oneline'      -d "Display one entry per line"
 complete -c exa -s 'l' -l 'long'         -d "Display extended file metadata as a table"
+complete -c exa        -l 'markdown'     -d "Display the long view's columns as a Markdown table"
+complete -c exa        -l 'format'       -d "Print a line for each file from a template" -x
 complete -c exa -s 'G' -l 'grid'         -d "Display entries in a grid"
 complete -c exa -s 'x' -l 'across'       -d "Sort the grid across, rather than downwards"
+complete -c exa        -l 'columns'      -d "Lay the grid out in exactly this many columns" -x
 complete -c exa -s 'R' -l 'recurse'      -d "Recurse into directories"
 complete -c exa -s 'T' -l 'tree'         -d "Recurse into directories as a tree"
+complete -c exa        -l 'tree-style'   -x -d "Which characters to draw the tree with" -a "
+    unicode\t'Use box-drawing characters'
+    ascii\t'Use plain ASCII characters'
+"
+complete -c exa        -l 'tree-summary' -x -d "Count the files in each directory of the tree" -a "
+    immediate\t'Count the files directly inside'
+    recursive\t'Count every file underneath'
+"
+complete -c exa        -l 'mark-empty'   -d "Mark empty directories in the tree"
+complete -c exa        -l 'summary'      -d "Count the files listed and add up their sizes"
+complete -c exa        -l 'dereference'  -d "Describe the files that symlinks point to, not the links"
+complete -c exa        -l 'dereference-command-line' -d "Describe the files that symlinks given as arguments point to"
 complete -c exa -s 'F' -l 'classify'     -d "Display type indicator by file names"
+complete -c exa        -l 'literal'      -d "Print control characters in file names without escaping"
+complete -c exa        -l 'quotes'       -d "Quote file names with special characters" -x -a "
+    shell\t'Quote names for a POSIX shell'
+    powershell\t'Quote names for PowerShell'
+"
+complete -c exa        -l 'link-targets' -d "How much of each symlink's target to show" -x -a "
+    full\t'Show the whole path to each target'
+    name\t'Show just the name of each target'
+    never\t'Do not show targets'
+"
+complete -c exa        -l 'protected'   -d "Mark files that only root can change"
+complete -c exa        -l 'in-use'      -d "Mark files that other processes have open"
+complete -c exa        -l 'highlight'   -d "Paint the parts of file names that match this" -x
+complete -c exa        -l 'relative-to' -d "Display each file's path relative to this directory" -x -a "(__fish_complete_directories)"
 complete -c exa        -l 'color'        -d "When to use terminal colours"
 complete -c exa        -l 'colour'       -d "When to use terminal colours"
 complete -c exa        -l 'color-scale'  -d "Highlight levels of file sizes distinctly"
@@ -17,31 +46,42 @@ complete -c exa        -l 'colour-scale' -d "Highlight levels of file sizes dist
 
 # Filtering and sorting options
 complete -c exa -l 'group-directories-first' -d "Sort directories before other files"
+complete -c exa -l 'hidden-last'          -d "Sort hidden and system files after other files"
 complete -c exa -l 'git-ignore'           -d "Ignore files mentioned in '.gitignore'"
+complete -c exa -l 'no-exaignore'         -d "Don't ignore files mentioned in '.exaignore'"
+complete -c exa -l 'case-sensitive-patterns' -d "Make globs and highlighting care about case"
+complete -c exa -l 'newer-than' -x        -d "Only show files with a timestamp at or after this"
+complete -c exa -l 'older-than' -x        -d "Only show files with a timestamp at or before this"
 complete -c exa -s 'a' -l 'all'       -d "Show and 'dot' files"
 complete -c exa -s 'd' -l 'list-dirs' -d "List directories like regular files"
 complete -c exa -s 'L' -l 'level'     -d "Limit the depth of recursion" -a "1 2 3 4 5 6 7 8 9"
+complete -c exa        -l 'follow-links' -d "Recurse into symlinked directories, once each"
+complete -c exa        -l 'jobs'      -x -d "How many threads to read directories with"
 complete -c exa -s 'r' -l 'reverse'   -d "Reverse the sort order"
 complete -c exa -s 's' -l 'sort'   -x -d "Which field to sort by" -a "
     accessed\t'Sort by file accessed time'
     age\t'Sort by file modified time (newest first)'
-    created\t'Sort by file modified time'
-    date\t'Sort by file modified time'
+    changed\t'Sort by file changed time'
+    created\t'Sort by file created time'
+    date\t'Sort by the time being listed'
     ext\t'Sort by file extension'
     Ext\t'Sort by file extension (uppercase first)'
     extension\t'Sort by file extension'
     Extension\t'Sort by file extension (uppercase first)'
     filename\t'Sort by filename'
     Filename\t'Sort by filename (uppercase first)'
+    git\t'Sort by Git status (changes first)'
     inode\t'Sort by file inode'
     modified\t'Sort by file modified time'
     name\t'Sort by filename'
     Name\t'Sort by filename (uppercase first)'
+    name-raw\t'Sort by filename, byte by byte'
     newest\t'Sort by file modified time (newest first)'
     none\t'Do not sort files at all'
     oldest\t'Sort by file modified time'
+    owner\t'Sort by file owner name'
     size\t'Sort by file size'
-    time\t'Sort by file modified time'
+    time\t'Sort by the time being listed'
     type\t'Sort by file type'
 "
 
@@ -52,24 +92,69 @@ complete -c exa -s 'b' -l 'binary'   -d "List file sizes with binary prefixes"
 complete -c exa -s 'B' -l 'bytes'    -d "List file sizes in bytes, without any prefixes"
 complete -c exa -s 'g' -l 'group'    -d "List each file's group"
 complete -c exa -s 'h' -l 'header'   -d "Add a header row to each column"
+complete -c exa -s 'n' -l 'numeric'  -d "List owners and groups by ID rather than by name"
 complete -c exa -s 'h' -l 'links'    -d "List each file's number of hard links"
 complete -c exa -s 'g' -l 'group'    -d "List each file's inode number"
+complete -c exa        -l 'hard-links' -d "List the other names of files with several hard links"
+complete -c exa        -l 'volume'   -d "List the mount point of each file's volume"
+complete -c exa        -l 'my-access' -d "List whether you can read, write, and execute each file"
+complete -c exa        -l 'mime' -d "Show what type each file is, judging by its contents"
+complete -c exa        -l 'checksum' -x -d "Show a hash of each file" -a "crc32 md5 sha1 sha256"
+complete -c exa        -l 'checksum-limit' -x -d "Don't hash files bigger than this size"
+complete -c exa        -l 'signatures' -d "Show whether Windows executables are signed"
+complete -c exa        -l 'version-info' -d "Show the file version of Windows executables"
+complete -c exa        -l 'network'  -d "Mark files that are on a network filesystem"
+complete -c exa        -l 'motw'     -d "Mark files downloaded from the internet, and show their zone"
 complete -c exa -s 'm' -l 'modified' -d "Use the modified timestamp field"
 complete -c exa -s 'S' -l 'blocks'   -d "List each file's number of filesystem blocks"
+complete -c exa        -l 'total'       -d "Show the total space the files take up"
+complete -c exa        -l 'dedup-columns' -d "Show a ditto mark for repeated owners and groups"
+complete -c exa        -l 'owner-width' -x -d "Cut owner and group names wider than this short"
+complete -c exa        -l 'uniform-columns' -d "Give every directory's table the same column widths"
+complete -c exa        -l 'dired'       -d "Write the output for Emacs's dired mode"
+complete -c exa        -l 'separator' -x -d "The string to put after each column"
+complete -c exa        -l 'order' -x -d "The order of the table's columns" -a "inode permissions access links size disk blocks user group owner modified changed created accessed volume network zone type checksum signature version git"
+complete -c exa        -l 'size'  -x -d "Which sizes to list" -a "
+    logical\t'Display the size of the file'
+    disk\t'Display the space the file takes up on disk'
+    both\t'Display both sizes'
+"
+complete -c exa        -l 'owner' -x -d "How to list each file's user" -a "
+    user\t'Display the user in its own column'
+    combined\t'Display the user and group together'
+    none\t'Leave the user out'
+"
+complete -c exa        -l 'dir-entries' -d "Show how many entries directories contain as their size"
 complete -c exa -s 't' -l 'time'  -x -d "Which timestamp field to list" -a "
     accessed\t'Display accessed time'
+    changed\t'Display changed time'
     created\t'Display created time'
     modified\t'Display modified time'
+    all\t'Display every time'
 "
 complete -c exa -s 'u' -l 'accessed'      -d "Use the accessed timestamp field"
 complete -c exa -s 'U' -l 'created'       -d "Use the created timestamp field"
+complete -c exa        -l 'changed'       -d "Use the changed timestamp field"
 complete -c exa        -l 'time-style' -x -d "How to format timestamps" -a "
     default\t'Use the default time style'
     iso\t'Display brief ISO timestamps'
     long-iso\t'Display longer ISO timestaps, up to the minute'
     full-iso\t'Display full ISO timestamps, up to the nanosecond'
+    unix\t'Display the seconds since the Unix epoch'
+    unix-ms\t'Display the milliseconds since the Unix epoch'
 "
+complete -c exa        -l 'time-clock' -x -d "Which clock the default time style uses" -a "
+    12\t'Use the 12-hour clock with AM and PM'
+    24\t'Use the 24-hour clock'
+    auto\t'Use the clock the locale prefers'
+"
+complete -c exa        -l 'locale'     -x -d "Which locale's month names to use" -a "(locale -a 2>/dev/null)"
 
 # Optional extras
-complete -c exa -s 'g' -l 'git'      -d "List each file's Git status, if tracked"
+complete -c exa -s 'g' -l 'git'      -d "List each file's Git status, if tracked" -a "
+    libgit2\t'Only read statuses with libgit2'
+    command\t'Only read statuses by running git'
+"
+complete -c exa        -l 'git-ceiling' -d "Stop searching for a repository before this directory" -x -a "(__fish_complete_directories)"
+complete -c exa        -l 'git-only-changed' -d "Only list files with Git changes, and the directories above them"
 complete -c exa -s '@' -l 'extended' -d "List each file's extended attributes and sizes"
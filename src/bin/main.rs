@@ -1,5 +1,5 @@
 extern crate exa;
-use exa::Exa;
+use exa::{Exa, exits};
 
 use std::ffi::OsString;
 use std::env::{args_os, var_os};
@@ -19,8 +19,8 @@ fn main() {
                     match e.kind() {
                         ErrorKind::BrokenPipe => exit(exits::SUCCESS),
                         _ => {
-                            eprintln!("{}", e);
-                            exit(exits::RUNTIME_ERROR);
+                            eprintln!("exa: {}", e);
+                            exit(exits::SERIOUS_TROUBLE);
                         },
                     };
                 }
@@ -35,7 +35,7 @@ fn main() {
                 let _ = writeln!(stderr, "{}", s);
             }
 
-            exit(exits::OPTIONS_ERROR);
+            exit(exits::SERIOUS_TROUBLE);
         },
 
         Err(ref e) => {
@@ -72,14 +72,3 @@ pub fn configure_logger() {
 
     logs.init()
 }
-
-
-extern crate libc;
-#[allow(trivial_numeric_casts)]
-mod exits {
-    use libc::{self, c_int};
-
-    pub const SUCCESS:       c_int = libc::EXIT_SUCCESS;
-    pub const RUNTIME_ERROR: c_int = libc::EXIT_FAILURE;
-    pub const OPTIONS_ERROR: c_int = 3 as c_int;
-}
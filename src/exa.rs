@@ -1,3 +1,16 @@
+//! exa, a modern replacement for `ls`, as a library.
+//!
+//! The `exa` binary is a thin wrapper around `Exa`, which parses the same
+//! arguments as the command line and renders the listing to any `Write`
+//! sink. Programs that want the files themselves rather than rendered text
+//! should use the `listing` module, which runs the filter and sort pipeline
+//! from a plain `FileFilter` and returns owned entries.
+//!
+//! Only `Exa`, `exits`, `vars`, `Misfire`, and the `listing` module are
+//! stable. `File`, `OwnedFile`, and `Dir` are exported for callers that
+//! need more than an `Entry` holds, but their methods follow exa’s own
+//! needs, and can change between minor versions.
+
 #![warn(trivial_casts, trivial_numeric_casts)]
 #![warn(unused_results)]
 
@@ -11,6 +24,7 @@ extern crate num_cpus;
 extern crate number_prefix;
 extern crate scoped_threadpool;
 extern crate term_grid;
+extern crate unicode_normalization;
 extern crate unicode_width;
 extern crate users;
 extern crate zoneinfo_compiled;
@@ -24,24 +38,47 @@ extern crate term_size;
 
 use std::env::var_os;
 use std::ffi::{OsStr, OsString};
-use std::io::{stderr, Write, Result as IOResult};
-use std::path::{Component, PathBuf};
+use std::io::{self, Error as IOError, IsTerminal, Write, Result as IOResult};
+use std::path::{Component, Path, PathBuf};
 
-use ansi_term::{ANSIStrings, Style};
+use ansi_term::Style;
+use scoped_threadpool::Pool;
 
-use fs::{Dir, File};
+use fs::{DirReader, FsReader};
+use fs::dir_action::{RecurseOptions, Visited};
 use fs::feature::ignore::IgnoreCache;
 use fs::feature::git::GitCache;
 use options::{Options, Vars};
 pub use options::vars;
 pub use options::Misfire;
-use output::{escape, lines, grid, grid_details, details, View, Mode};
+pub use fs::{Dir, File, FileTarget, OwnedFile};
+use output::{escape, error, lines, grid, grid_details, details, dired, markdown, format, View, Mode, TextCell, TextCellContents};
+use output::details::Pending;
+use output::table::SharedWidths;
+use output::summary::{GrandTotal, Summary};
 
 mod fs;
 mod info;
 mod options;
 mod output;
 mod style;
+pub mod listing;
+
+
+/// The statuses that exa exits with, which follow the conventions of `ls`.
+pub mod exits {
+
+    /// Everything got listed.
+    pub const SUCCESS: i32 = 0;
+
+    /// Some files couldn’t be listed, such as an argument that doesn’t
+    /// exist or a subdirectory that can’t be read, but the rest were.
+    pub const MINOR_TROUBLE: i32 = 1;
+
+    /// Nothing could be listed, the command-line options were wrong, or
+    /// the output couldn’t be written.
+    pub const SERIOUS_TROUBLE: i32 = 2;
+}
 
 
 /// The main program wrapper.
@@ -67,42 +104,84 @@ pub struct Exa<'args, 'w, W: Write + 'w> {
     /// A cache of git-ignored files.
     /// This lasts the lifetime of the program too, for the same reason.
     pub ignore: Option<IgnoreCache>,
+
+    /// The running total of every file that has been listed so far, and
+    /// the number of listings it covers, for the `--summary` footer.
+    pub summary: GrandTotal,
+
+    /// The status to exit with, which gets worse as problems turn up.
+    pub exit_status: i32,
+
+    /// The directories that `--recurse` has gone into, so it doesn’t follow
+    /// links to them round in circles.
+    visited: Visited,
+
+    /// The threads that `--recurse` reads directories with, which only get
+    /// started once there’s a directory to recurse into.
+    pool: Option<Pool>,
+
+    /// Everything that’s been listed so far, held back until the end when
+    /// every table has to have the same column widths.
+    later: Option<Vec<Pending>>,
+
+    /// Where the file names and directory headings are in the output so
+    /// far, when it’s for Emacs’s dired mode.
+    dired: Option<dired::Offsets>,
+
+    /// What every directory that gets listed or gone into is read with.
+    dirs: Box<dyn DirReader>,
 }
 
 /// The “real” environment variables type.
 /// Instead of just calling `var_os` from within the options module,
 /// the method of looking up environment variables has to be passed in.
-struct LiveVars;
+struct LiveVars {
+
+    /// Whether standard output is a terminal, which gets found out once
+    /// before any of the options that depend on it.
+    terminal: bool,
+}
+
 impl Vars for LiveVars {
     fn get(&self, name: &'static str) -> Option<OsString> {
         var_os(name)
     }
+
+    fn is_terminal(&self) -> bool {
+        self.terminal
+    }
 }
 
 /// Create a Git cache populated with the arguments that are going to be
 /// listed before they’re actually listed, if the options demand it.
 fn git_options(options: &Options, args: &[&OsStr]) -> Option<GitCache> {
-    if options.should_scan_for_git() {
-        Some(args.iter().map(PathBuf::from).collect())
-    }
-    else {
-        None
-    }
+    options.git_backend().map(|backend| GitCache::new(args.iter().map(PathBuf::from), backend, options.git_ceilings()))
 }
 
 fn ignore_cache(options: &Options) -> Option<IgnoreCache> {
-    use fs::filter::GitIgnore;
+    IgnoreCache::for_filter(&options.filter)
+}
 
-    match options.filter.git_ignore {
-        GitIgnore::CheckAndIgnore => Some(IgnoreCache::new()),
-        GitIgnore::Off            => None,
-    }
+/// Reads each of the given directories, several at a time, returning the
+/// results in the same order as the directories so that the output doesn’t
+/// depend on which one finished first.
+fn read_dirs(pool: &mut Pool, dirs: &dyn DirReader, paths: &[PathBuf]) -> Vec<IOResult<Dir>> {
+    let mut results = paths.iter().map(|_| None).collect::<Vec<Option<IOResult<Dir>>>>();
+
+    pool.scoped(|scoped| {
+        for (path, result) in paths.iter().zip(results.iter_mut()) {
+            scoped.execute(move || *result = Some(dirs.read_dir(path.clone())));
+        }
+    });
+
+    results.into_iter().map(|r| r.unwrap()).collect()
 }
 
 impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
     pub fn new<I>(args: I, writer: &'w mut W) -> Result<Exa<'args, 'w, W>, Misfire>
     where I: Iterator<Item=&'args OsString> {
-        Options::parse(args, &LiveVars).map(move |(options, mut args)| {
+        let vars = LiveVars { terminal: io::stdout().is_terminal() };
+        Options::parse(args, &vars).map(move |(options, mut args)| {
             debug!("Dir action from arguments: {:#?}", options.dir_action);
             debug!("Filter from arguments: {:#?}", options.filter);
             debug!("View from arguments: {:#?}", options.view.mode);
@@ -115,26 +194,52 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
 
             let git = git_options(&options, &args);
             let ignore = ignore_cache(&options);
-            Exa { options, writer, args, git, ignore }
+            let later = if options.has_uniform_columns() { Some(Vec::new()) } else { None };
+            let dired = if options.is_dired() { Some(dired::Offsets::default()) } else { None };
+            Exa { options, writer, args, git, ignore, summary: GrandTotal::default(), exit_status: exits::SUCCESS, visited: Visited::default(), pool: None, later, dired, dirs: Box::new(FsReader) }
         })
     }
 
     pub fn run(&mut self) -> IOResult<i32> {
         let mut files = Vec::new();
         let mut dirs = Vec::new();
-        let mut exit_status = 0;
+        let mut failures = 0;
 
         for file_path in &self.args {
             match File::new(PathBuf::from(file_path), None, None) {
                 Err(e) => {
-                    exit_status = 2;
-                    writeln!(stderr(), "{:?}: {}", file_path, e)?;
+                    failures += 1;
+                    error::report(Path::new(file_path), &e);
                 },
-                Ok(f) => {
+                Ok(mut f) => {
+                    if self.options.filter.dereference_args && !self.options.filter.dereference {
+                        if let Err(e) = f.dereference_argument() {
+                            failures += 1;
+                            error::report(Path::new(file_path), &e);
+                            continue;
+                        }
+                    }
+
+                    if self.options.filter.only_git_changes && !self.git.as_ref().is_some_and(|g| g.has_anything_for(&f.path)) {
+                        let e = IOError::other("not in a Git repository, so nothing has changed");
+                        error::report(&f.path, &e);
+                    }
+
                     if f.points_to_directory() && !self.options.dir_action.treat_dirs_as_files() {
-                        match f.to_dir() {
-                            Ok(d) => dirs.push(d),
-                            Err(e) => writeln!(stderr(), "{:?}: {}", file_path, e)?,
+                        let dir = if self.options.lists_as_read() { self.dirs.open(f.path.clone()) }
+                                                                 else { self.dirs.read_dir(f.path.clone()) };
+                        match dir {
+                            Ok(d) => {
+                                if self.options.dir_action.recurse_options().is_some_and(|r| r.follow_links) {
+                                    let _ = self.visited.insert(&d.path);
+                                }
+
+                                dirs.push(d);
+                            },
+                            Err(e) => {
+                                failures += 1;
+                                error::report(Path::new(file_path), &e);
+                            },
                         }
                     }
                     else {
@@ -151,13 +256,39 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
         let no_files = files.is_empty();
         let is_only_dir = dirs.len() == 1 && no_files;
 
-        self.options.filter.filter_argument_files(&mut files);
+        let recursing = self.options.dir_action.recurse_options().is_some();
+        self.options.filter.filter_argument_files(&mut files, recursing);
+        self.options.filter.filter_git_changes(&mut files, self.git.as_ref());
         self.print_files(None, files)?;
 
-        self.print_dirs(dirs, no_files, is_only_dir, exit_status)
+        self.print_dirs(dirs, no_files, is_only_dir)?;
+        self.print_grand_total()?;
+        self.print_later()?;
+
+        if let Some(ref offsets) = self.dired {
+            offsets.write_footer(self.writer, dired::quoting_style(&self.options.view.style))?;
+        }
+
+        // It’s only serious if none of the arguments could be listed.
+        if failures > 0 && failures == self.args.len() {
+            self.exit_status = exits::SERIOUS_TROUBLE;
+        }
+        else if failures > 0 {
+            self.minor_trouble();
+        }
+
+        Ok(self.exit_status)
     }
 
-    fn print_dirs(&mut self, dir_files: Vec<Dir>, mut first: bool, is_only_dir: bool, exit_status: i32) -> IOResult<i32> {
+    /// Notes that something couldn’t be listed, without making the exit
+    /// status any better than it already is.
+    fn minor_trouble(&mut self) {
+        if self.exit_status < exits::MINOR_TROUBLE {
+            self.exit_status = exits::MINOR_TROUBLE;
+        }
+    }
+
+    fn print_dirs(&mut self, dir_files: Vec<Dir>, mut first: bool, is_only_dir: bool) -> IOResult<()> {
         for dir in dir_files {
 
             // Put a gap between directories, or between the list of files and
@@ -166,43 +297,80 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
                 first = false;
             }
             else {
-                writeln!(self.writer)?;
+                self.print_line(TextCell::default())?;
             }
 
             if !is_only_dir {
                 let mut bits = Vec::new();
                 escape(dir.path.display().to_string(), &mut bits, Style::default(), Style::default());
-                writeln!(self.writer, "{}:", ANSIStrings(&bits))?;
+                let path_len = bits.iter().map(|bit| bit.len()).sum();
+                bits.push(Style::default().paint(":"));
+
+                if let Some(state) = self.git.as_ref().and_then(|g| g.repo_state(&dir.path)) {
+                    bits.push(Style::default().paint(" "));
+                    bits.extend(state.render(&self.options.view.colours));
+                }
+
+                self.print_heading(TextCellContents::from(bits).promote(), path_len)?;
+            }
+
+            if self.options.lists_as_read() {
+                self.print_dir_as_read(&dir)?;
+                continue;
             }
 
             let mut children = Vec::new();
+            let mut unreadable = false;
             for file in dir.files(self.options.filter.dot_filter, self.ignore.as_ref()) {
                 match file {
                     Ok(file)       => children.push(file),
-                    Err((path, e)) => writeln!(stderr(), "[{}: {}]", path.display(), e)?,
+                    Err((path, e)) => {
+                        unreadable = true;
+                        error::report(&path, &e);
+                    },
                 }
             };
 
-            self.options.filter.filter_child_files(&mut children);
-            self.options.filter.sort_files(&mut children);
+            if unreadable {
+                self.minor_trouble();
+            }
+
+            let recursing = self.options.dir_action.recurse_options().is_some();
+            self.options.filter.filter_child_files(&mut children, recursing);
+            self.options.filter.filter_git_changes(&mut children, self.git.as_ref());
+            self.options.filter.sort_files(&mut children, self.git.as_ref());
 
             if let Some(recurse_opts) = self.options.dir_action.recurse_options() {
                 let depth = dir.path.components().filter(|&c| c != Component::CurDir).count() + 1;
                 if !recurse_opts.tree && !recurse_opts.is_too_deep(depth) {
 
-                    let mut child_dirs = Vec::new();
-                    for child_dir in children.iter().filter(|f| f.is_directory()) {
-                        match child_dir.to_dir() {
-                            Ok(d)  => child_dirs.push(d),
-                            Err(e) => writeln!(stderr(), "{}: {}", child_dir.path.display(), e)?,
+                    let to_recurse = children.iter().filter(|f| self.should_recurse_into(f, recurse_opts))
+                                             .map(|f| f.path.clone())
+                                             .collect::<Vec<_>>();
+                    self.print_files(Some(&dir), children)?;
+
+                    // Only as many directories as there are threads get read
+                    // ahead of being listed, so the rest of their siblings
+                    // aren’t all held in memory while the first ones are
+                    // being recursed into.
+                    let jobs = self.options.jobs;
+                    for paths in to_recurse.chunks(jobs as usize) {
+                        let results = read_dirs(self.pool.get_or_insert_with(|| Pool::new(jobs)), &*self.dirs, paths);
+
+                        let mut child_dirs = Vec::new();
+                        for (path, result) in paths.iter().zip(results) {
+                            match result {
+                                Ok(d)  => child_dirs.push(d),
+                                Err(e) => {
+                                    self.minor_trouble();
+                                    error::report(path, &e);
+                                },
+                            }
                         }
-                    }
 
-                    self.print_files(Some(&dir), children)?;
-                    match self.print_dirs(child_dirs, false, false, exit_status) {
-                        Ok(_) => (),
-                        Err(e) => return Err(e),
+                        self.print_dirs(child_dirs, false, false)?;
                     }
+
                     continue;
                 }
             }
@@ -210,33 +378,113 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
             self.print_files(Some(&dir), children)?;
         }
 
-        Ok(exit_status)
+        Ok(())
+    }
+
+    /// Whether `--recurse` should go into the given file. Symlinks only get
+    /// followed when the user asked, and then only to directories that
+    /// haven’t been listed already, so every directory gets marked as
+    /// visited while they’re being followed.
+    fn should_recurse_into(&self, file: &File, recurse_opts: RecurseOptions) -> bool {
+        if file.is_directory() && !file.is_dereferenced {
+            if recurse_opts.follow_links {
+                let _ = self.visited.insert(&file.path);
+            }
+
+            true
+        }
+        else if recurse_opts.follow_links && file.is_link_to_directory() {
+            self.visited.insert(&file.path)
+        }
+        else {
+            false
+        }
+    }
+
+    /// Prints the files in a directory one at a time as they get read, for
+    /// views that don’t have to see every file before printing the first.
+    fn print_dir_as_read(&mut self, dir: &Dir) -> IOResult<()> {
+        let View { ref colours, ref style, .. } = self.options.view;
+        let filter = &self.options.filter;
+        let render = lines::Render { files: Vec::new(), colours, style };
+
+        let mut summary = Summary::default();
+        let mut unreadable = false;
+
+        for file in dir.files(filter.dot_filter, self.ignore.as_ref()) {
+            match file {
+                Ok(file) => {
+                    let mut files = vec![ file ];
+                    filter.filter_child_files(&mut files, false);
+
+                    for file in &files {
+                        summary.add(file);
+                        render.render_line(file, self.writer)?;
+                    }
+                },
+                Err((path, e)) => {
+                    unreadable = true;
+                    error::report(&path, &e);
+                },
+            }
+        }
+
+        if unreadable {
+            self.minor_trouble();
+        }
+
+        self.print_summary(&summary, None)?;
+        self.summary.add(&summary);
+        Ok(())
     }
 
     /// Prints the list of files using whichever view is selected.
     /// For various annoying logistical reasons, each one handles
     /// printing differently...
     fn print_files(&mut self, dir: Option<&Dir>, files: Vec<File>) -> IOResult<()> {
+        let mut summary = Summary::default();
+        for file in &files {
+            summary.add(file);
+        }
+
         if !files.is_empty() {
-            let View { ref mode, ref colours, ref style } = self.options.view;
+            let View { ref mode, ref colours, ref style, .. } = self.options.view;
 
             match *mode {
                 Mode::Lines => {
                     let r = lines::Render { files, colours, style };
-                    r.render(self.writer)
+                    r.render(self.writer)?;
                 }
 
                 Mode::Grid(ref opts) => {
                     let r = grid::Render { files, colours, style, opts };
-                    r.render(self.writer)
+                    r.render(self.writer)?;
                 }
 
                 Mode::Details(ref opts) => {
                     let filter = &self.options.filter;
                     let recurse = self.options.dir_action.recurse_options();
 
-                    let r = details::Render { dir, files, colours, style, opts, filter, recurse };
-                    r.render(self.git.as_ref(), self.ignore.as_ref(), self.writer)
+                    // The tree view lists more files than it was given, so
+                    // its summary has to come from the render itself.
+                    let visited = Visited::default();
+                    let jobs = self.options.jobs;
+                    let dirs = &*self.dirs;
+                    let r = details::Render { dir, files, colours, style, opts, filter, recurse, visited, jobs, dirs };
+
+                    if let Some(ref mut later) = self.later {
+                        let (s, pending) = r.render_later(self.git.as_ref(), self.ignore.as_ref());
+                        summary = s;
+                        later.push(pending);
+                    }
+                    else if let Some(ref mut offsets) = self.dired {
+                        let (s, pending) = r.render_later(self.git.as_ref(), self.ignore.as_ref());
+                        summary = s;
+                        pending.write_dired(&mut dired::Writer::new(self.writer, offsets))?;
+                    }
+                    else {
+                        summary = r.render(self.git.as_ref(), self.ignore.as_ref(), self.writer)?;
+                    }
                 }
 
                 Mode::GridDetails(ref opts) => {
@@ -244,14 +492,459 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
                     let filter = &self.options.filter;
                     let details = &opts.details;
                     let row_threshold = opts.row_threshold;
+                    let jobs = self.options.jobs;
 
-                    let r = grid_details::Render { dir, files, colours, style, grid, details, filter, row_threshold };
-                    r.render(self.git.as_ref(), self.writer)
+                    let r = grid_details::Render { dir, files, colours, style, grid, details, filter, row_threshold, jobs };
+                    r.render(self.git.as_ref(), self.writer)?;
+                }
+
+                Mode::Markdown(ref opts) => {
+                    let r = markdown::Render { dir, files, colours, style, opts };
+                    r.render(self.git.as_ref(), self.writer)?;
+                }
+
+                Mode::Format(ref opts) => {
+                    let r = format::Render { dir, files, colours, opts };
+                    r.render(self.git.as_ref(), self.writer)?;
                 }
             }
         }
-        else {
-            Ok(())
+        else if dir.is_none() {
+            return Ok(());
+        }
+
+        if summary.errors > 0 {
+            self.minor_trouble();
+        }
+
+        self.print_summary(&summary, None)?;
+        self.summary.add(&summary);
+        Ok(())
+    }
+
+    /// Prints the total of all the listings, if the user asked for a
+    /// summary and there was more than one listing to add up.
+    fn print_grand_total(&mut self) -> IOResult<()> {
+        if self.options.view.summary.is_some() && self.summary.listings > 1 {
+            let total = self.summary.total;
+            self.print_line(TextCell::default())?;
+            self.print_summary(&total, Some("total: "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the summary footer for a listing, if the user asked for one.
+    fn print_summary(&mut self, summary: &Summary, prefix: Option<&'static str>) -> IOResult<()> {
+        use locale;
+
+        if let Some(size_format) = self.options.view.summary {
+            let colours = &self.options.view.colours;
+            let numerics = locale::Numeric::load_user_locale()
+                               .unwrap_or_else(|_| locale::Numeric::english());
+
+            let mut cell = TextCell::default();
+            if let Some(prefix) = prefix {
+                cell.append(TextCell::paint_str(colours.punctuation, prefix));
+            }
+
+            cell.append(summary.render(colours, size_format, &numerics));
+            self.print_line(cell)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints a line that isn’t part of any table, or holds it back along
+    /// with the tables if they haven’t been printed yet.
+    fn print_line(&mut self, cell: TextCell) -> IOResult<()> {
+        match (&mut self.later, &mut self.dired) {
+            (&mut Some(ref mut later), _)     => later.push(Pending::line(cell)),
+            (&mut None, &mut Some(ref mut o)) => dired::Writer::new(self.writer, o).write_line(&cell.plain_text(), None)?,
+            (&mut None, &mut None)            => writeln!(self.writer, "{}", cell.strings())?,
+        }
+
+        Ok(())
+    }
+
+    /// Prints the line above a directory’s listing, which starts with the
+    /// given number of bytes of its path, or holds it back like any other.
+    fn print_heading(&mut self, cell: TextCell, path_len: usize) -> IOResult<()> {
+        match (&mut self.later, &mut self.dired) {
+            (&mut Some(ref mut later), _)     => later.push(Pending::heading(cell, path_len)),
+            (&mut None, &mut Some(ref mut o)) => dired::Writer::new(self.writer, o).write_heading(&cell.plain_text(), path_len)?,
+            (&mut None, &mut None)            => writeln!(self.writer, "{}", cell.strings())?,
+        }
+
+        Ok(())
+    }
+
+    /// Prints everything that got held back, once the widest each column
+    /// gets in any of the tables is known.
+    fn print_later(&mut self) -> IOResult<()> {
+        if let Some(later) = self.later.take() {
+            let mut widths = SharedWidths::default();
+            for layout in later.iter().filter_map(|p| p.layout.as_ref()) {
+                widths.add(layout);
+            }
+
+            for mut pending in later {
+                if let Some(ref mut layout) = pending.layout {
+                    widths.apply(layout);
+                }
+
+                match self.dired {
+                    Some(ref mut offsets) => pending.write_dired(&mut dired::Writer::new(self.writer, offsets))?,
+                    None                  => pending.write(self.writer)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs as std_fs;
+    use fs::test_dir::TempDir;
+    use std::sync::{Arc, Mutex};
+
+    /// Reads directories from the filesystem, keeping track of which ones
+    /// were read, so tests can check that a listing never looked inside the
+    /// ones it was meant to skip.
+    #[derive(Default, Clone)]
+    struct CountingReader {
+        read: Arc<Mutex<Vec<PathBuf>>>,
+    }
+
+    impl DirReader for CountingReader {
+        fn read_dir(&self, path: PathBuf) -> IOResult<Dir> {
+            self.read.lock().unwrap().push(path.clone());
+            FsReader.read_dir(path)
+        }
+
+        fn open(&self, path: PathBuf) -> IOResult<Dir> {
+            self.read.lock().unwrap().push(path.clone());
+            FsReader.open(path)
+        }
+    }
+
+    /// Builds a directory with one subdirectory that always gets listed and
+    /// four that each get hidden by a different kind of filter.
+    fn fixture(name: &str) -> TempDir {
+        let top = TempDir::new(&format!("prune-{}", name));
+        for dir in &[ "src", "node_modules/pkg", ".cache/blobs", "build/debug", "vendor/lib" ] {
+            std_fs::create_dir_all(top.join(dir)).unwrap();
+        }
+
+        std_fs::write(top.join("src/main.rs"), "").unwrap();
+        std_fs::write(top.join("node_modules/pkg/index.js"), "").unwrap();
+        std_fs::write(top.join(".gitignore"), "build\n").unwrap();
+        std_fs::write(top.join(".exaignore"), "vendor\n").unwrap();
+        top
+    }
+
+    /// Lists the given paths with the given arguments, returning the output
+    /// and the subdirectories of `top` that got read.
+    fn list(top: &Path, flags: &[&str], paths: &[&Path]) -> (String, Vec<PathBuf>) {
+        let args = flags.iter().map(OsString::from)
+                        .chain(paths.iter().map(|p| p.as_os_str().to_os_string()))
+                        .collect::<Vec<_>>();
+
+        let reader = CountingReader::default();
+        let mut output = Vec::new();
+        let mut exa = Exa::new(args.iter(), &mut output).unwrap();
+        exa.dirs = Box::new(reader.clone());
+        let _ = exa.run().unwrap();
+
+        let read = reader.read.lock().unwrap().iter()
+                             .filter_map(|p| p.strip_prefix(top).ok())
+                             .map(Path::to_path_buf)
+                             .collect();
+
+        (String::from_utf8(output).unwrap(), read)
+    }
+
+    fn check_pruned(name: &str, flags: &[&str]) {
+        let top = fixture(name);
+        let (output, read) = list(top.path(), flags, &[ top.path() ]);
+
+        assert!(output.contains("main.rs"));
+        assert!(read.contains(&PathBuf::from("src")));
+
+        for pruned in &[ "node_modules", ".cache", "build", "vendor" ] {
+            assert!(!output.contains(pruned), "{} was listed", pruned);
+            assert!(!read.iter().any(|p| p.starts_with(pruned)), "{} was read", pruned);
+        }
+    }
+
+    #[test]
+    fn tree_prunes_ignored_directories() {
+        check_pruned("tree", &[ "--tree", "--colour=never", "--ignore-glob=node_modules", "--git-ignore" ]);
+    }
+
+    #[test]
+    fn recurse_prunes_ignored_directories() {
+        check_pruned("recurse", &[ "--recurse", "--colour=never", "--ignore-glob=node_modules", "--git-ignore" ]);
+    }
+
+    #[test]
+    fn tree_reads_each_directory_once() {
+        let top = fixture("tree-once");
+        let (one, mut read_one) = list(top.path(), &[ "--tree", "--colour=never", "--jobs=1" ], &[ top.path() ]);
+        let (four, mut read_four) = list(top.path(), &[ "--tree", "--colour=never", "--jobs=4" ], &[ top.path() ]);
+        assert_eq!(one, four);
+
+        read_one.sort();
+        read_four.sort();
+        assert_eq!(read_one, read_four);
+
+        let mut unique = read_four.clone();
+        unique.dedup();
+        assert_eq!(unique, read_four);
+    }
+
+    /// Builds a directory with an old subdirectory that has a new file in
+    /// it, and an old file next to it.
+    fn old_dir_fixture(name: &str) -> TempDir {
+        use std::time::{Duration, SystemTime};
+
+        let top = TempDir::new(&format!("old-dir-{}", name));
+        let _ = top.touch("old/new.txt");
+        let _ = top.touch("old/stale.txt");
+
+        let long_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        std_fs::File::open(top.join("old/stale.txt")).unwrap().set_modified(long_ago).unwrap();
+        std_fs::File::open(top.join("old")).unwrap().set_modified(long_ago).unwrap();
+        top
+    }
+
+    #[test]
+    fn recurse_into_old_directories() {
+        let top = old_dir_fixture("recurse");
+        let (output, _) = list(top.path(), &[ "--recurse", "--colour=never", "--newer-than=1d" ], &[ top.path() ]);
+        assert!(output.contains("new.txt"), "{}", output);
+        assert!(!output.contains("stale.txt"), "{}", output);
+    }
+
+    #[test]
+    fn tree_into_old_directories() {
+        let top = old_dir_fixture("tree");
+        let (output, _) = list(top.path(), &[ "--tree", "--colour=never", "--newer-than=1d" ], &[ top.path() ]);
+        assert!(output.contains("new.txt"), "{}", output);
+        assert!(!output.contains("stale.txt"), "{}", output);
+    }
+
+    #[test]
+    fn old_directories_hidden_without_recursing() {
+        let top = old_dir_fixture("flat");
+        let (output, _) = list(top.path(), &[ "--oneline", "--colour=never", "--newer-than=1d" ], &[ top.path() ]);
+        assert!(!output.contains("old"), "{}", output);
+    }
+
+    /// Builds a directory with two files whose accessed and modified times
+    /// are years apart, and in opposite orders.
+    fn two_times_fixture(name: &str) -> TempDir {
+        use std::fs::FileTimes;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let top = TempDir::new(&format!("two-times-{}", name));
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+
+        // a: accessed in June 2010, modified in September 2001
+        // b: accessed in June 2005, modified in June 2015
+        for &(file, accessed, modified) in &[ ("a", 1_276_603_200, 1_000_000_000), ("b", 1_118_836_800, 1_434_369_600) ] {
+            let path = top.touch(file);
+            let times = FileTimes::new().set_accessed(at(accessed)).set_modified(at(modified));
+            std_fs::File::options().write(true).open(path).unwrap().set_times(times).unwrap();
         }
+
+        top
+    }
+
+    #[test]
+    fn sort_by_first_time_listed() {
+        let top = two_times_fixture("sort");
+        let names = |time| {
+            let (output, _) = list(top.path(), &[ "--oneline", "--colour=never", "--sort=time", time ], &[ top.path() ]);
+            output
+        };
+
+        assert_eq!(names("--time=acc,mod"), "b\na\n");
+        assert_eq!(names("--time=mod,acc"), "a\nb\n");
+    }
+
+    fn tree_summary(name: &str, flags: &[&str]) -> String {
+        let top = fixture(name);
+        let (output, _) = list(top.path(), flags, &[ top.path() ]);
+        output
+    }
+
+    #[test]
+    fn tree_summary_immediate() {
+        let output = tree_summary("immediate", &[ "--tree", "--colour=never", "--tree-summary", "-Inode_modules" ]);
+        assert!(output.contains(" (0 files, 2 dirs)\n"), "{}", output);
+        assert!(output.contains("build (0 files, 1 dir)\n"), "{}", output);
+        assert!(output.contains("debug (0 files, 0 dirs)\n"), "{}", output);
+        assert!(output.contains("src (1 file, 0 dirs)\n"), "{}", output);
+    }
+
+    #[test]
+    fn tree_summary_recursive() {
+        let output = tree_summary("recursive-summary", &[ "--tree", "--colour=never", "--tree-summary=recursive", "-Inode_modules" ]);
+        assert!(output.contains(" (1 file, 3 dirs)\n"), "{}", output);
+        assert!(output.contains("build (0 files, 1 dir)\n"), "{}", output);
+    }
+
+    #[test]
+    fn tree_summary_truncated() {
+        let output = tree_summary("truncated", &[ "--tree", "--colour=never", "--tree-summary", "--level=1", "-Inode_modules" ]);
+        assert!(output.contains(" (0 files, 2 dirs)\n"), "{}", output);
+        assert!(output.contains("build (…)\n"), "{}", output);
+        assert!(output.contains("src (…)\n"), "{}", output);
+        assert!(!output.contains("main.rs"), "{}", output);
+    }
+
+    #[test]
+    fn no_grand_total_without_summary() {
+        let (first, second) = (fixture("total-first"), fixture("total-second"));
+        let (output, _) = list(first.path(), &[ "--colour=never" ], &[ first.path(), second.path() ]);
+        assert!(!output.ends_with("\n\n"), "{:?}", output);
+        assert!(!output.contains("total: "), "{:?}", output);
+    }
+
+    #[test]
+    fn grand_total_with_summary() {
+        let (first, second) = (fixture("total-summary-first"), fixture("total-summary-second"));
+        let (output, _) = list(first.path(), &[ "--colour=never", "--summary" ], &[ first.path(), second.path() ]);
+        assert!(output.contains("\n\ntotal: "), "{:?}", output);
+    }
+
+    /// Builds a directory with symlinks that lead back up to it, and one
+    /// that leads to a hidden directory, which only gets listed through it.
+    fn loop_fixture(name: &str) -> TempDir {
+        use std::os::unix::fs::symlink;
+
+        let top = TempDir::new(&format!("loop-{}", name));
+        std_fs::create_dir_all(top.join("a/b")).unwrap();
+        std_fs::create_dir_all(top.join(".hidden")).unwrap();
+        std_fs::write(top.join(".hidden/inner.txt"), "").unwrap();
+        symlink("../..", top.join("a/b/up")).unwrap();
+        symlink(".", top.join("a/itself")).unwrap();
+        symlink("../.hidden", top.join("a/shortcut")).unwrap();
+        top
+    }
+
+    fn list_loop(name: &str, flags: &[&str]) -> String {
+        let top = loop_fixture(name);
+        let (output, _) = list(top.path(), flags, &[ top.path() ]);
+        output
+    }
+
+    #[test]
+    fn tree_does_not_follow_links() {
+        let output = list_loop("tree", &[ "--tree", "--colour=never" ]);
+        assert!(output.contains("up -> ../.. (not followed)\n"), "{}", output);
+        assert!(output.contains("itself -> . (not followed)\n"), "{}", output);
+        assert!(output.contains("shortcut -> ../.hidden (not followed)\n"), "{}", output);
+        assert!(!output.contains("inner.txt"), "{}", output);
+    }
+
+    #[test]
+    fn tree_follows_links_without_looping() {
+        let output = list_loop("tree-follow", &[ "--tree", "--colour=never", "--follow-links" ]);
+        assert!(output.contains("up -> ../.. (not followed)\n"), "{}", output);
+        assert!(output.contains("itself -> . (not followed)\n"), "{}", output);
+        assert!(output.contains("shortcut -> ../.hidden\n"), "{}", output);
+        assert!(output.contains("inner.txt"), "{}", output);
+    }
+
+    #[test]
+    fn recurse_does_not_follow_links() {
+        let output = list_loop("recurse", &[ "--recurse", "--colour=never", "-1" ]);
+        assert!(!output.contains("/a/shortcut:\n"), "{}", output);
+        assert!(!output.contains("inner.txt"), "{}", output);
+    }
+
+    #[test]
+    fn recurse_follows_links_without_looping() {
+        let output = list_loop("recurse-follow", &[ "--recurse", "--colour=never", "--follow-links", "-1" ]);
+        assert!(output.contains("/a/shortcut:\ninner.txt\n"), "{}", output);
+        assert!(!output.contains("/a/b/up:\n"), "{}", output);
+        assert!(!output.contains("/a/itself:\n"), "{}", output);
+    }
+
+    #[test]
+    fn parallel_output_is_in_order() {
+        let top = TempDir::new("jobs");
+        for i in 0 .. 24 {
+            let dir = top.join(format!("dir-{}", i)).join("inner");
+            std_fs::create_dir_all(&dir).unwrap();
+            std_fs::write(dir.join(format!("file-{}", i)), "").unwrap();
+        }
+
+        let (serial, _)   = list(top.path(), &[ "--recurse", "--colour=never", "--jobs=1" ], &[ top.path() ]);
+        let (parallel, _) = list(top.path(), &[ "--recurse", "--colour=never", "--jobs=8" ], &[ top.path() ]);
+        let (tree, _)     = list(top.path(), &[ "--tree", "--colour=never", "--jobs=8" ], &[ top.path() ]);
+
+        assert_eq!(serial, parallel);
+        assert_eq!(serial.matches("file-").count(), 24);
+        assert_eq!(tree.matches("file-").count(), 24);
+    }
+
+    /// Reads the offsets after the given label in dired output back out,
+    /// and slices out what they point to.
+    fn dired_slices<'a>(output: &'a str, label: &str) -> Vec<&'a str> {
+        let line = output.lines().find(|l| l.starts_with(label)).unwrap();
+        let numbers = line[label.len() ..].split_whitespace().map(|n| n.parse().unwrap()).collect::<Vec<usize>>();
+        numbers.chunks(2).map(|pair| &output[pair[0] .. pair[1]]).collect()
+    }
+
+    #[test]
+    fn dired_offsets_slice_out_names() {
+        use std::os::unix::fs::symlink;
+
+        let top = TempDir::new("dired");
+        std_fs::create_dir_all(top.join("sub dir")).unwrap();
+        std_fs::write(top.join("plain.txt"), "hello").unwrap();
+        std_fs::write(top.join("ünïcödé"), "").unwrap();
+        std_fs::write(top.join("sub dir/inner"), "").unwrap();
+        symlink("plain.txt", top.join("link")).unwrap();
+
+        let (output, _) = list(top.path(), &[ "-l", "--dired", "--recurse", "--classify", "--colour=always" ], &[ top.path() ]);
+
+        let mut names = dired_slices(&output, "//DIRED//");
+        names.sort();
+        assert_eq!(names, vec![ "inner", "link", "plain.txt", "sub dir", "ünïcödé" ], "{}", output);
+
+        let subdirs = dired_slices(&output, "//SUBDIRED//");
+        assert_eq!(subdirs, vec![ top.join("sub dir").to_str().unwrap() ], "{}", output);
+
+        assert!(!output.contains('\x1B'), "{}", output);
+        assert!(output.ends_with("//DIRED-OPTIONS// --quoting-style=escape\n"), "{}", output);
+    }
+
+    #[test]
+    fn named_ignored_directories_are_listed() {
+        let top = fixture("named");
+        let named = top.join("node_modules");
+        let (output, read) = list(top.path(), &[ "--tree", "--colour=never", "--ignore-glob=node_modules" ], &[ &named ]);
+
+        assert!(output.contains("index.js"), "{}", output);
+        assert!(read.contains(&PathBuf::from("node_modules/pkg")));
+    }
+
+    #[test]
+    fn named_ignored_directories_are_listed_as_files() {
+        let top = fixture("named-d");
+        let named = top.join("node_modules");
+        let file = top.join("src/main.rs");
+        let (output, _) = list(top.path(), &[ "--list-dirs", "--colour=never", "--ignore-glob=node_modules|main.rs" ], &[ &named, &file ]);
+
+        assert!(output.contains("node_modules"), "{}", output);
+        assert!(!output.contains("main.rs"), "{}", output);
     }
 }
@@ -1,7 +1,11 @@
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
 use std::io::{self, Result as IOResult};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::os::unix::ffi::OsStrExt;
 use std::slice::Iter as SliceIter;
+use std::sync::{Arc, Mutex, OnceLock};
 
 use fs::File;
 use fs::feature::ignore::IgnoreCache;
@@ -15,8 +19,18 @@ use fs::feature::ignore::IgnoreCache;
 /// accordingly. (See `File#get_source_files`)
 pub struct Dir {
 
-    /// A vector of the files that have been read from this directory.
-    contents: Vec<PathBuf>,
+    /// The paths of the entries that have been read from this directory,
+    /// along with their metadata if it could be read. The `DirEntry` values
+    /// themselves aren’t kept, as each one holds its directory open.
+    contents: Vec<(PathBuf, Option<fs::Metadata>)>,
+
+    /// The entries that are still to be read, for a directory that gets
+    /// read as it’s listed rather than all at once.
+    unread: Option<Mutex<fs::ReadDir>>,
+
+    /// The names of the entries, lowercased, which only get worked out the
+    /// first time a file asks whether it has a sibling with some name.
+    lowercase_names: OnceLock<Arc<LowercaseNames>>,
 
     /// The path that was read.
     pub path: PathBuf,
@@ -36,10 +50,25 @@ impl Dir {
         info!("Reading directory {:?}", &path);
 
         let contents = fs::read_dir(&path)?
-                                             .map(|result| result.map(|entry| entry.path()))
-                                             .collect::<Result<_,_>>()?;
+                           .map(|result| result.map(|entry| (entry.path(), entry.metadata().ok())))
+                           .collect::<Result<_,_>>()?;
+
+        Ok(Dir { contents, unread: None, lowercase_names: OnceLock::new(), path })
+    }
 
-        Ok(Dir { contents, path })
+    /// Create a new Dir object for the directory pointed to by the given
+    /// path, without reading any of its entries yet. They get read one at a
+    /// time as its files are iterated over, so huge directories can start
+    /// being listed straight away without holding every path in memory.
+    ///
+    /// As the directory’s contents aren’t known up front, a file in it
+    /// can’t check for the other files next to it, and the files can only
+    /// be iterated over once.
+    pub fn open(path: PathBuf) -> IOResult<Dir> {
+        info!("Opening directory {:?}", &path);
+
+        let unread = fs::read_dir(&path)?;
+        Ok(Dir { contents: Vec::new(), unread: Some(Mutex::new(unread)), lowercase_names: OnceLock::new(), path })
     }
 
     /// Produce an iterator of IO results of trying to read all the files in
@@ -47,8 +76,13 @@ impl Dir {
     pub fn files<'dir, 'ig>(&'dir self, dots: DotFilter, ignore: Option<&'ig IgnoreCache>) -> Files<'dir, 'ig> {
         if let Some(i) = ignore { i.discover_underneath(&self.path); }
 
+        let inner = match self.unread {
+            Some(ref unread)  => Entries::Unread(unread),
+            None              => Entries::Read(self.contents.iter()),
+        };
+
         Files {
-            inner:     self.contents.iter(),
+            inner,
             dir:       self,
             dotfiles:  dots.shows_dotfiles(),
             dots:      dots.dots(),
@@ -56,9 +90,19 @@ impl Dir {
         }
     }
 
-    /// Whether this directory contains a file with the given path.
-    pub fn contains(&self, path: &Path) -> bool {
-        self.contents.iter().any(|p| p.as_path() == path)
+    /// Whether this directory contains a file with the given path’s name,
+    /// ignoring the case of both names.
+    pub fn contains_ignoring_case(&self, path: &Path) -> bool {
+        self.lowercase_names().contains(path)
+    }
+
+    /// The lowercased names of the entries in this directory, which get
+    /// worked out once and then shared, rather than for every lookup.
+    pub fn lowercase_names(&self) -> Arc<LowercaseNames> {
+        self.lowercase_names.get_or_init(|| {
+            let names = self.contents.iter().filter_map(|(p, _)| p.file_name());
+            Arc::new(LowercaseNames::new(names))
+        }).clone()
     }
 
     /// Append a path onto the path specified by this directory.
@@ -68,11 +112,65 @@ impl Dir {
 }
 
 
+/// The names of the files in a directory, lowercased, for looking up names
+/// that could differ in case.
+#[derive(Debug, Default)]
+pub struct LowercaseNames(HashSet<String>);
+
+impl LowercaseNames {
+    fn new<'a, I: Iterator<Item=&'a OsStr>>(names: I) -> LowercaseNames {
+        LowercaseNames(names.map(|n| lowercase(&n.to_string_lossy())).collect())
+    }
+
+    /// Whether any of the names is the given path’s name, ignoring the case
+    /// of both.
+    pub fn contains(&self, path: &Path) -> bool {
+        match path.file_name() {
+            Some(name)  => self.0.contains(&lowercase(&name.to_string_lossy())),
+            None        => false,
+        }
+    }
+}
+
+fn lowercase(name: &str) -> String {
+    name.chars().flat_map(char::to_lowercase).collect()
+}
+
+
+/// The way the directories being listed get read.
+///
+/// The output code reads every directory it lists or goes into through one
+/// of these, rather than calling `Dir::read_dir` itself, so tests can count
+/// which directories a listing looked inside.
+pub trait DirReader: Sync {
+
+    /// Reads all of a directory’s entries up front. (See `Dir::read_dir`)
+    fn read_dir(&self, path: PathBuf) -> IOResult<Dir>;
+
+    /// Opens a directory to be read as it’s listed. (See `Dir::open`)
+    fn open(&self, path: PathBuf) -> IOResult<Dir>;
+}
+
+/// Reads directories from the filesystem, which is what the `exa` binary
+/// always does.
+pub struct FsReader;
+
+impl DirReader for FsReader {
+    fn read_dir(&self, path: PathBuf) -> IOResult<Dir> {
+        Dir::read_dir(path)
+    }
+
+    fn open(&self, path: PathBuf) -> IOResult<Dir> {
+        Dir::open(path)
+    }
+}
+
+
 /// Iterator over reading the contents of a directory as `File` objects.
 pub struct Files<'dir, 'ig> {
 
-    /// The internal iterator over the paths that have been read already.
-    inner: SliceIter<'dir, PathBuf>,
+    /// The internal iterator over the paths in the directory.
+    inner: Entries<'dir>,
 
     /// The directory that begat those paths.
     dir: &'dir Dir,
@@ -99,26 +197,59 @@ impl<'dir, 'ig> Files<'dir, 'ig> {
 
     /// Go through the directory until we encounter a file we can list (which
     /// varies depending on the dotfile visibility flag)
+    ///
+    /// Each file is made from the metadata of its directory entry, which
+    /// describes the entry itself rather than what it links to. Entries
+    /// that are read as they’re listed only get statted if they’re going to
+    /// be listed. An entry whose metadata couldn’t be read gets statted
+    /// again by path, so the error that gets produced is the real one.
     fn next_visible_file(&mut self) -> Option<Result<File<'dir>, (PathBuf, io::Error)>> {
         loop {
-            if let Some(path) = self.inner.next() {
-                let filename = File::filename(path);
-                if !self.dotfiles && filename.starts_with('.') { continue }
+            let mut unread_entry = None;
+            let (path, read_metadata) = match self.inner {
+                Entries::Read(ref mut entries)  => {
+                    let (path, metadata) = entries.next()?;
+                    (path.clone(), Some(metadata))
+                },
+                Entries::Unread(unread)         => match unread.lock().unwrap().next()? {
+                    Ok(e)   => { let path = e.path(); unread_entry = Some(e); (path, None) },
+                    Err(e)  => return Some(Err((self.dir.path.clone(), e))),
+                },
+            };
+
+            let filename = File::filename(&path);
+            if !self.dotfiles && is_dotfile(&filename) { continue }
+
+            if let Some(i) = self.ignore {
+                let is_dir = match read_metadata {
+                    Some(metadata)  => metadata.as_ref().is_some_and(fs::Metadata::is_dir),
+                    None            => unread_entry.as_ref().and_then(|e| e.file_type().ok()).is_some_and(|t| t.is_dir()),
+                };
+                if i.is_ignored(&path, is_dir) { continue }
+            }
 
-                if let Some(i) = self.ignore {
-                    if i.is_ignored(path) { continue }
-                }
+            let metadata = match read_metadata {
+                Some(metadata)  => metadata.clone(),
+                None            => unread_entry.and_then(|e| e.metadata().ok()),
+            };
 
-                return Some(File::new(path.clone(), self.dir, filename)
-                                 .map_err(|e| (path.clone(), e)))
-            }
-            else {
-                return None
-            }
+            return Some(match metadata {
+                Some(metadata)  => Ok(File::from_metadata(path, self.dir, filename, metadata)),
+                None            => File::new(path.clone(), self.dir, filename).map_err(|e| (path, e)),
+            })
         }
     }
 }
 
+
+/// The entries in a directory, either all read already, or read from the
+/// filesystem one at a time. An entry that can’t be read gets produced as
+/// an error, rather than stopping the iteration.
+enum Entries<'dir> {
+    Read(SliceIter<'dir, (PathBuf, Option<fs::Metadata>)>),
+    Unread(&'dir Mutex<fs::ReadDir>),
+}
+
 /// The dot directories that need to be listed before actual files, if any.
 /// If these aren’t being printed, then `FilesNext` is used to skip them.
 enum Dots {
@@ -140,12 +271,12 @@ impl<'dir, 'ig> Iterator for Files<'dir, 'ig> {
     fn next(&mut self) -> Option<Self::Item> {
         if let Dots::DotNext = self.dots {
             self.dots = Dots::DotDotNext;
-            Some(File::new(self.dir.path.to_path_buf(), self.dir, String::from("."))
+            Some(File::new(self.dir.path.to_path_buf(), self.dir, OsString::from("."))
                       .map_err(|e| (Path::new(".").to_path_buf(), e)))
         }
         else if let Dots::DotDotNext = self.dots {
             self.dots = Dots::FilesNext;
-            Some(File::new(self.parent(), self.dir, String::from(".."))
+            Some(File::new(self.parent(), self.dir, OsString::from(".."))
                       .map_err(|e| (self.parent(), e)))
         }
         else {
@@ -171,6 +302,12 @@ pub enum DotFilter {
     JustFiles,
 }
 
+/// Whether a file with this name is a dotfile, which only gets listed when
+/// the user asks for them with `--all`.
+pub fn is_dotfile(name: &OsStr) -> bool {
+    name.as_bytes().starts_with(b".")
+}
+
 impl Default for DotFilter {
     fn default() -> DotFilter {
         DotFilter::JustFiles
@@ -197,3 +334,71 @@ impl DotFilter {
         }
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+
+    fn names(dir: &Dir) -> Vec<String> {
+        let mut names: Vec<String> = dir.files(DotFilter::Dotfiles, None)
+                                        .map(|f| f.unwrap().display_name().to_string())
+                                        .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn open_lists_the_same_files() {
+        let temp = TempDir::new("dir");
+        for name in &[ "one", "two", ".hidden" ] {
+            let _ = temp.touch(name);
+        }
+
+        let read = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let opened = Dir::open(temp.path().to_path_buf()).unwrap();
+        let (read_names, opened_names) = (names(&read), names(&opened));
+
+        assert_eq!(read_names, vec![ ".hidden", "one", "two" ]);
+        assert_eq!(opened_names, read_names);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_are_not_followed() {
+        let temp = TempDir::new("dir-links");
+        let _ = temp.touch("target");
+        ::std::os::unix::fs::symlink("target", temp.join("link")).unwrap();
+
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles, None).map(|f| f.unwrap()).collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert!(files[0].is_link());
+        assert!(files[1].is_file());
+    }
+
+    #[test]
+    fn contains_ignoring_case() {
+        let temp = TempDir::new("dir-case");
+        let _ = temp.touch("Main.JAVA");
+        let _ = temp.touch("ΣΊΣΥΦΟΣ.c");
+
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        assert!(dir.contains_ignoring_case(Path::new("main.java")));
+        assert!(dir.contains_ignoring_case(&temp.join("MAIN.java")));
+        assert!(dir.contains_ignoring_case(Path::new("σίσυφοσ.C")));
+        assert!(!dir.contains_ignoring_case(Path::new("main.jav")));
+        assert!(!dir.contains_ignoring_case(Path::new("/")));
+    }
+
+    #[test]
+    fn opened_dirs_contain_nothing() {
+        let temp = TempDir::new("dir-case-opened");
+        let _ = temp.touch("file");
+
+        let dir = Dir::open(temp.path().to_path_buf()).unwrap();
+        assert!(!dir.contains_ignoring_case(Path::new("file")));
+    }
+}
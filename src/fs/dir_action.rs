@@ -1,5 +1,12 @@
 //! What to do when encountering a directory?
 
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Mutex;
+
+
 /// The action to take when trying to list a file that turns out to be a
 /// directory.
 ///
@@ -68,6 +75,11 @@ pub struct RecurseOptions {
     /// The maximum number of times that recursion should descend to, if one
     /// is specified.
     pub max_depth: Option<usize>,
+
+    /// Whether to go into directories through symlinks. Links can point
+    /// back up the tree, so these only get followed to directories that
+    /// haven’t been visited yet.
+    pub follow_links: bool,
 }
 
 impl RecurseOptions {
@@ -80,3 +92,44 @@ impl RecurseOptions {
         }
     }
 }
+
+
+/// The directories that have been gone into so far during a recursive
+/// listing, identified by their device and inode numbers, which stay the
+/// same however a directory gets reached. Following a symlink back to one
+/// of these would go round in circles, or list the same files twice.
+#[derive(Debug, Default)]
+pub struct Visited(Mutex<HashSet<(u64, u64)>>);
+
+impl Visited {
+
+    /// Marks the directory at the given path, or the one it links to, as
+    /// visited. Returns whether it hadn’t been visited before. Directories
+    /// that can’t be looked up count as new, as they can’t be compared.
+    pub fn insert(&self, path: &Path) -> bool {
+        match fs::metadata(path) {
+            Ok(m)   => self.0.lock().unwrap().insert((m.dev(), m.ino())),
+            Err(_)  => true,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+
+    #[test]
+    fn links_are_the_same_directory() {
+        let temp = TempDir::new("visited");
+        let path = temp.path();
+        fs::create_dir_all(path.join("inner")).unwrap();
+        ::std::os::unix::fs::symlink("..", path.join("inner").join("up")).unwrap();
+
+        let visited = Visited::default();
+        let results = (visited.insert(path), visited.insert(&path.join("inner")), visited.insert(&path.join("inner").join("up")));
+
+        assert_eq!(results, (true, true, false));
+    }
+}
@@ -0,0 +1,120 @@
+//! Asking the kernel whether the current process can read, write, or
+//! execute a file.
+extern crate libc;
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+
+/// The kinds of access that can be checked for.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Mode {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Mode {
+    fn bits(self) -> libc::c_int {
+        match self {
+            Mode::Read     => libc::R_OK,
+            Mode::Write    => libc::W_OK,
+            Mode::Execute  => libc::X_OK,
+        }
+    }
+}
+
+// The version of libc in use doesn’t have this flag for Linux yet, so it
+// gets defined here, with the value from `<fcntl.h>`.
+#[cfg(target_os = "linux")]
+const AT_EACCESS: libc::c_int = 0x200;
+
+#[cfg(not(target_os = "linux"))]
+use self::libc::AT_EACCESS;
+
+/// Whether the current process could access the file at the given path in
+/// the given way.
+///
+/// This uses the *effective* user and group IDs, the same ones that opening
+/// the file would use, so it gives the right answer under `sudo` or for
+/// setuid programs. The kernel takes ACLs, read-only mounts, and root’s
+/// special powers into account, none of which can be worked out from the
+/// permission bits alone. Paths that can’t be checked count as denied.
+pub fn can_access(path: &Path, mode: Mode) -> bool {
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(p)   => p,
+        Err(_)  => return false,
+    };
+
+    unsafe {
+        libc::faccessat(libc::AT_FDCWD, c_path.as_ptr(), mode.bits(), AT_EACCESS) == 0
+    }
+}
+
+/// Whether the current process is running as root, which can write to
+/// any file that can be written to at all.
+pub fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Whether the current process can’t write to the file at the given path,
+/// but root could.
+///
+/// Only a refusal for lack of permission (`EACCES`) counts. Files on
+/// read-only filesystems (`EROFS`) and immutable files (`EPERM`) can’t be
+/// written by root either, and missing files can’t be written by anyone.
+pub fn only_root_can_write(path: &Path) -> bool {
+    use std::io;
+
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(p)   => p,
+        Err(_)  => return false,
+    };
+
+    let result = unsafe {
+        libc::faccessat(libc::AT_FDCWD, c_path.as_ptr(), libc::W_OK, AT_EACCESS)
+    };
+
+    result != 0 && io::Error::last_os_error().raw_os_error() == Some(libc::EACCES)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn readable_file() {
+        assert!(can_access(Path::new("Cargo.toml"), Mode::Read));
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(!can_access(Path::new("/this/path/does/not/exist"), Mode::Read));
+    }
+
+    #[test]
+    fn nul_byte() {
+        assert!(!can_access(Path::new("nul\0byte"), Mode::Read));
+    }
+
+    #[test]
+    fn own_file_is_not_root_only() {
+        assert!(!only_root_can_write(Path::new("Cargo.toml")));
+    }
+
+    #[test]
+    fn missing_file_is_not_root_only() {
+        assert!(!only_root_can_write(Path::new("/this/path/does/not/exist")));
+    }
+
+    #[test]
+    fn root_only_when_not_root() {
+        // /etc/shadow is only writable by root, but root can write
+        // to it, so the answer depends on who’s running the tests.
+        if Path::new("/etc/shadow").exists() {
+            assert_eq!(only_root_can_write(Path::new("/etc/shadow")), !is_elevated());
+        }
+    }
+}
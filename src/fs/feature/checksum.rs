@@ -0,0 +1,386 @@
+//! Hashing the contents of files, so copies can be checked against each
+//! other without needing a separate tool.
+//!
+//! exa doesn’t depend on any hashing crates, so the handful of algorithms
+//! people actually compare files with are implemented here. None of them
+//! are used for anything security-related, only for spotting differences.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+
+/// The hashing algorithms that can be used.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Algorithm {
+    CRC32,
+    MD5,
+    SHA1,
+    SHA256,
+}
+
+impl Algorithm {
+
+    /// The text to use for the column’s heading.
+    pub fn header(self) -> &'static str {
+        match self {
+            Algorithm::CRC32   => "CRC32",
+            Algorithm::MD5     => "MD5",
+            Algorithm::SHA1    => "SHA-1",
+            Algorithm::SHA256  => "SHA-256",
+        }
+    }
+
+    fn hasher(self) -> Box<dyn Hasher> {
+        match self {
+            Algorithm::CRC32   => Box::new(Crc32::new()),
+            Algorithm::MD5     => Box::new(BlockHasher::<Md5>::new()),
+            Algorithm::SHA1    => Box::new(BlockHasher::<Sha1>::new()),
+            Algorithm::SHA256  => Box::new(BlockHasher::<Sha256>::new()),
+        }
+    }
+}
+
+
+/// How much of a file gets read at a time. Files are hashed in chunks this
+/// big, so a huge file never has to be in memory all at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hashes the contents of the file at the given path, returning the hash
+/// as a string of lowercase hex digits.
+pub fn checksum(path: &Path, algorithm: Algorithm) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = algorithm.hasher();
+    let mut buf = vec![0; CHUNK_SIZE];
+
+    loop {
+        match file.read(&mut buf) {
+            Ok(0)   => break,
+            Ok(n)   => hasher.update(&buf[.. n]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e)  => return Err(e),
+        }
+    }
+
+    Ok(to_hex(&hasher.finish()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+
+trait Hasher {
+    fn update(&mut self, data: &[u8]);
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+
+/// The CRC-32 used by zip, gzip, and PNG, with the reflected polynomial.
+struct Crc32 {
+    table: [u32; 256],
+    crc: u32,
+}
+
+impl Crc32 {
+    fn new() -> Crc32 {
+        let mut table = [0; 256];
+
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0 .. 8 {
+                c = if c & 1 == 1 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+
+        Crc32 { table, crc: !0 }
+    }
+}
+
+impl Hasher for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = self.table[((self.crc ^ u32::from(byte)) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        let crc = !self.crc;
+        vec![ (crc >> 24) as u8, (crc >> 16) as u8, (crc >> 8) as u8, crc as u8 ]
+    }
+}
+
+
+/// MD5, SHA-1, and SHA-256 all work the same way on the outside: the data
+/// gets split into 64-byte blocks, with the last one padded out with a
+/// single 1 bit, zeroes, and the length. They just differ in what they do
+/// with each block, and which way round the length gets written.
+trait Compress {
+    const BIG_ENDIAN: bool;
+    fn initial() -> Self;
+    fn compress(&mut self, block: &[u8]);
+    fn output(&self) -> Vec<u8>;
+}
+
+struct BlockHasher<C> {
+    state: C,
+    buffer: [u8; 64],
+    buffered: usize,
+    length: u64,
+}
+
+impl<C: Compress> BlockHasher<C> {
+    fn new() -> Self {
+        BlockHasher { state: C::initial(), buffer: [0; 64], buffered: 0, length: 0 }
+    }
+
+    fn push(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let count = (64 - self.buffered).min(data.len());
+            self.buffer[self.buffered .. self.buffered + count].copy_from_slice(&data[.. count]);
+            self.buffered += count;
+            data = &data[count ..];
+
+            if self.buffered == 64 {
+                self.state.compress(&self.buffer);
+                self.buffered = 0;
+            }
+        }
+    }
+}
+
+impl<C: Compress> Hasher for BlockHasher<C> {
+    fn update(&mut self, data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+        self.push(data);
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.length.wrapping_mul(8);
+        self.push(&[0x80]);
+
+        while self.buffered != 56 {
+            self.push(&[0]);
+        }
+
+        let length = if C::BIG_ENDIAN { bits.to_be_bytes() } else { bits.to_le_bytes() };
+        self.push(&length);
+        self.state.output()
+    }
+}
+
+
+struct Md5([u32; 4]);
+
+const MD5_SHIFTS: [u32; 16] = [ 7, 12, 17, 22,  5, 9, 14, 20,  4, 11, 16, 23,  6, 10, 15, 21 ];
+
+impl Compress for Md5 {
+    const BIG_ENDIAN: bool = false;
+
+    fn initial() -> Md5 {
+        Md5([ 0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476 ])
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        let mut m = [0u32; 16];
+        for (i, word) in m.iter_mut().enumerate() {
+            *word = u32::from_le_bytes([ block[i * 4], block[i * 4 + 1], block[i * 4 + 2], block[i * 4 + 3] ]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.0;
+
+        for i in 0 .. 64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d),  i),
+                1 => ((d & b) | (!d & c),  (5 * i + 1) % 16),
+                2 => (b ^ c ^ d,           (3 * i + 5) % 16),
+                _ => (c ^ (b | !d),        (7 * i) % 16),
+            };
+
+            // The constants are the integer parts of abs(sin(i + 1)) * 2^32.
+            let k = (((i + 1) as f64).sin().abs() * 4_294_967_296.0) as u32;
+            let shift = MD5_SHIFTS[(i / 16) * 4 + i % 4];
+
+            let rotated = a.wrapping_add(f).wrapping_add(k).wrapping_add(m[g]).rotate_left(shift);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+
+        self.0[0] = self.0[0].wrapping_add(a);
+        self.0[1] = self.0[1].wrapping_add(b);
+        self.0[2] = self.0[2].wrapping_add(c);
+        self.0[3] = self.0[3].wrapping_add(d);
+    }
+
+    fn output(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect()
+    }
+}
+
+
+struct Sha1([u32; 5]);
+
+impl Compress for Sha1 {
+    const BIG_ENDIAN: bool = true;
+
+    fn initial() -> Sha1 {
+        Sha1([ 0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0 ])
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        let mut w = [0u32; 80];
+        for i in 0 .. 16 {
+            w[i] = u32::from_be_bytes([ block[i * 4], block[i * 4 + 1], block[i * 4 + 2], block[i * 4 + 3] ]);
+        }
+        for i in 16 .. 80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.0;
+
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d),           0x5A82_7999),
+                1 => (b ^ c ^ d,                    0x6ED9_EBA1),
+                2 => ((b & c) | (b & d) | (c & d),  0x8F1B_BCDC),
+                _ => (b ^ c ^ d,                    0xCA62_C1D6),
+            };
+
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in self.0.iter_mut().zip(&[a, b, c, d, e]) {
+            *h = h.wrapping_add(*v);
+        }
+    }
+
+    fn output(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|w| w.to_be_bytes().to_vec()).collect()
+    }
+}
+
+
+struct Sha256([u32; 8]);
+
+const SHA256_K: [u32; 64] = [
+    0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5, 0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+    0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3, 0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+    0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc, 0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+    0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7, 0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+    0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13, 0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+    0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3, 0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+    0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5, 0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+    0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208, 0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+impl Compress for Sha256 {
+    const BIG_ENDIAN: bool = true;
+
+    fn initial() -> Sha256 {
+        Sha256([ 0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a, 0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19 ])
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        let mut w = [0u32; 64];
+        for i in 0 .. 16 {
+            w[i] = u32::from_be_bytes([ block[i * 4], block[i * 4 + 1], block[i * 4 + 2], block[i * 4 + 3] ]);
+        }
+        for i in 16 .. 64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.0;
+
+        for (k, word) in SHA256_K.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(*k).wrapping_add(*word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, v) in self.0.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(*v);
+        }
+    }
+
+    fn output(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|w| w.to_be_bytes().to_vec()).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hash(algorithm: Algorithm, data: &[u8]) -> String {
+        let mut hasher = algorithm.hasher();
+        hasher.update(data);
+        to_hex(&hasher.finish())
+    }
+
+    /// Feeds the data in uneven pieces, to check that blocks that span
+    /// more than one update get put together properly.
+    fn hash_in_pieces(algorithm: Algorithm, data: &[u8]) -> String {
+        let mut hasher = algorithm.hasher();
+        for piece in data.chunks(7) {
+            hasher.update(piece);
+        }
+        to_hex(&hasher.finish())
+    }
+
+    const LONG: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    #[test]
+    fn crc32() {
+        assert_eq!(hash(Algorithm::CRC32, b""),          "00000000");
+        assert_eq!(hash(Algorithm::CRC32, b"123456789"), "cbf43926");
+    }
+
+    #[test]
+    fn md5() {
+        assert_eq!(hash(Algorithm::MD5, b""),    "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hash(Algorithm::MD5, b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(hash_in_pieces(Algorithm::MD5, LONG), "8215ef0796a20bcaaae116d3876c664a");
+    }
+
+    #[test]
+    fn sha1() {
+        assert_eq!(hash(Algorithm::SHA1, b""),    "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hash(Algorithm::SHA1, b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hash_in_pieces(Algorithm::SHA1, LONG), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+    }
+
+    #[test]
+    fn sha256() {
+        assert_eq!(hash(Algorithm::SHA256, b""),    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hash(Algorithm::SHA256, b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hash_in_pieces(Algorithm::SHA256, LONG), "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(checksum(Path::new("/this/path/does/not/exist"), Algorithm::SHA256).is_err());
+    }
+}
@@ -1,11 +1,15 @@
 //! Getting the Git status of files and directories.
 
+use std::env;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use git2;
 
 use fs::fields as f;
+use fs::feature::GitBackend;
+use fs::feature::git_command::{self, Change};
+use fs::feature::git_discovery;
 
 
 /// A **Git cache** is assembled based on the user’s input arguments.
@@ -19,6 +23,10 @@ pub struct GitCache {
 
     /// Paths that we’ve confirmed do not have Git repositories underneath them.
     misses: Vec<PathBuf>,
+
+    /// Directories that have been probed and turned out not to be
+    /// repositories, so searches from other paths don’t probe them again.
+    non_repos: Vec<PathBuf>,
 }
 
 impl GitCache {
@@ -32,18 +40,39 @@ impl GitCache {
             .map(|repo| repo.search(index, prefix_lookup))
             .unwrap_or_default()
     }
+
+    /// The branch and dirty state of the repository that the given
+    /// directory is in, or `None` if it isn’t in one.
+    pub fn repo_state(&self, index: &Path) -> Option<f::GitRepoState> {
+        self.repos.iter()
+            .find(|e| e.has_path(index))
+            .map(GitRepo::state)
+    }
+
+    /// Whether Git sees the given file as changed, or, for a directory,
+    /// whether anything inside it is. Ignored files never count, and
+    /// neither does anything outside a repository.
+    pub fn is_changed(&self, index: &Path, is_dir: bool) -> bool {
+        self.repos.iter()
+            .find(|e| e.has_path(index))
+            .is_some_and(|repo| repo.query(|statuses| statuses.is_changed(index, is_dir)))
+    }
 }
 
-use std::iter::FromIterator;
-impl FromIterator<PathBuf> for GitCache {
-    fn from_iter<I: IntoIterator<Item=PathBuf>>(iter: I) -> Self {
-        let iter = iter.into_iter();
+impl GitCache {
+
+    /// Discovers the repositories that the given paths are in, reading
+    /// them with the given backend. Searches for repositories stop before
+    /// reaching any of the ceiling directories.
+    pub fn new<I: IntoIterator<Item=PathBuf>>(paths: I, backend: GitBackend, ceilings: &[PathBuf]) -> Self {
+        let paths = paths.into_iter();
         let mut git = GitCache {
-            repos: Vec::with_capacity(iter.size_hint().0),
+            repos: Vec::with_capacity(paths.size_hint().0),
             misses: Vec::new(),
+            non_repos: Vec::new(),
         };
 
-        for path in iter {
+        for path in paths {
             if git.misses.contains(&path) {
                 debug!("Skipping {:?} because it already came back Gitless", path);
             }
@@ -51,7 +80,7 @@ impl FromIterator<PathBuf> for GitCache {
                 debug!("Skipping {:?} because we already queried it", path);
             }
             else {
-                match GitRepo::discover(path) {
+                match GitRepo::discover(path, backend, ceilings, &mut git.non_repos) {
                     Ok(r) => {
                         if let Some(mut r2) = git.repos.iter_mut().find(|e| e.has_workdir(&r.workdir)) {
                             debug!("Adding to existing repo (workdir matches with {:?})", r2.workdir);
@@ -93,6 +122,9 @@ pub struct GitRepo {
     /// Any other paths that were checked only to result in this same
     /// repository.
     extra_paths: Vec<PathBuf>,
+
+    /// How this repository’s statuses are allowed to be read.
+    backend: GitBackend,
 }
 
 /// A repository’s queried state.
@@ -101,6 +133,10 @@ enum GitContents {
     /// All the interesting Git stuff goes through this.
     Before { repo: git2::Repository },
 
+    /// A repository that gets read by running the `git` program at the
+    /// given path instead of through libgit2.
+    BeforeCommand { git: PathBuf },
+
     /// Temporary value used in `repo_to_statuses` so we can move the
     /// repository out of the `Before` variant.
     Processing,
@@ -123,19 +159,36 @@ impl GitRepo {
     /// repository is moved out, but before the results have been moved in!
     /// See https://stackoverflow.com/q/45985827/3484614
     fn search(&self, index: &Path, prefix_lookup: bool) -> f::Git {
+        self.query(|statuses| statuses.status(index, prefix_lookup))
+    }
+
+    /// Returns the branch and dirty state of this repository, which get
+    /// read and cached along with the statuses.
+    fn state(&self) -> f::GitRepoState {
+        self.query(Git::state)
+    }
+
+    /// Runs the given function over this repository’s statuses, querying
+    /// the `git2` repository for them if this is the first time.
+    fn query<T, F>(&self, f: F) -> T
+    where F: FnOnce(&Git) -> T
+    {
         use self::GitContents::*;
         use std::mem::replace;
 
         let mut contents = self.contents.lock().unwrap();
         if let After { ref statuses } = *contents {
             debug!("Git repo {:?} has been found in cache", &self.workdir);
-            return statuses.status(index, prefix_lookup);
+            return f(statuses);
         }
 
         debug!("Querying Git repo {:?} for the first time", &self.workdir);
-        let repo = replace(&mut *contents, Processing).inner_repo();
-        let statuses = repo_to_statuses(&repo, &self.workdir);
-        let result = statuses.status(index, prefix_lookup);
+        let statuses = match replace(&mut *contents, Processing) {
+            Before { repo }        => repo_to_statuses(&repo, &self.workdir, self.backend == GitBackend::Fallback),
+            BeforeCommand { git }  => command_to_statuses(&git, &self.workdir),
+            Processing | After { .. } => unreachable!("Tried to query a repository twice"),
+        };
+        let result = f(&statuses);
         let _processing = replace(&mut *contents, After { statuses });
         result
     }
@@ -152,49 +205,110 @@ impl GitRepo {
 
     /// Searches for a Git repository at any point above the given path.
     /// Returns the original buffer if none is found.
-    fn discover(path: PathBuf) -> Result<GitRepo, PathBuf> {
+    ///
+    /// When falling back, the `git` program only gets run if libgit2 found
+    /// something it couldn’t open, rather than for every path that isn’t
+    /// in a repository at all.
+    fn discover(path: PathBuf, backend: GitBackend, ceilings: &[PathBuf], non_repos: &mut Vec<PathBuf>) -> Result<GitRepo, PathBuf> {
         info!("Searching for Git repository above {:?}", path);
-        let repo = match git2::Repository::discover(&path) {
-            Ok(r) => r,
-            Err(e) => {
-                error!("Error discovering Git repositories: {:?}", e);
-                return Err(path);
-            }
+        let found = match backend {
+            GitBackend::Command => discover_command(&path, ceilings),
+            GitBackend::LibGit2 | GitBackend::Fallback => match open_nearest(&path, ceilings, non_repos) {
+                Ok(None) => {
+                    info!("No Git repository above {:?}", path);
+                    None
+                },
+                Ok(Some(repo)) => {
+                    match repo.workdir().map(|wd| wd.to_path_buf()) {
+                        Some(workdir) => Some((GitContents::Before { repo }, workdir)),
+                        None => {
+                            warn!("Repository has no workdir?");
+                            None
+                        }
+                    }
+                },
+                Err(ref e) if backend == GitBackend::Fallback => {
+                    warn!("libgit2 couldn’t open the repository above {:?} ({}), so running git instead", path, e.message());
+                    discover_command(&path, ceilings)
+                },
+                Err(e) => {
+                    error!("Error discovering Git repositories: {:?}", e);
+                    None
+                }
+            },
         };
 
-        match repo.workdir().map(|wd| wd.to_path_buf()) {
-            Some(workdir) => {
-                let contents = Mutex::new(GitContents::Before { repo });
-                Ok(GitRepo { contents, workdir, original_path: path, extra_paths: Vec::new() })
+        match found {
+            Some((contents, workdir)) => {
+                let contents = Mutex::new(contents);
+                Ok(GitRepo { contents, workdir, original_path: path, extra_paths: Vec::new(), backend })
             },
-            None => {
-                warn!("Repository has no workdir?");
-                Err(path)
-            }
+            None => Err(path),
         }
     }
 }
 
+/// Opens the nearest repository at or above the given path, without going
+/// up into any of the ceilings, and probing each directory on the way by
+/// itself. Directories that turn out not to be repositories get remembered
+/// so they only get probed once per run.
+///
+/// Returns `None` if there’s no repository to find, and an error if there
+/// is one but libgit2 couldn’t open it.
+fn open_nearest(path: &Path, ceilings: &[PathBuf], non_repos: &mut Vec<PathBuf>) -> Result<Option<git2::Repository>, git2::Error> {
+    let mut start = match env::current_dir() {
+        Ok(cwd)  => cwd.join(path),
+        Err(_)   => path.to_path_buf(),
+    };
+
+    if !start.is_dir() {
+        let _ = start.pop();
+    }
 
-impl GitContents {
-    /// Assumes that the repository hasn’t been queried, and extracts it
-    /// (consuming the value) if it has. This is needed because the entire
-    /// enum variant gets replaced when a repo is queried (see above).
-    fn inner_repo(self) -> git2::Repository {
-        if let GitContents::Before { repo } = self {
-            repo
+    for dir in git_discovery::search_path(&start, ceilings) {
+        if non_repos.contains(&dir) {
+            debug!("Skipping {:?} because it’s already been probed", dir);
+            continue;
         }
-        else {
-            unreachable!("Tried to extract a non-Repository")
+
+        match git2::Repository::open_ext(&dir, git2::RepositoryOpenFlags::NO_SEARCH, ceilings) {
+            Ok(repo)                                                => return Ok(Some(repo)),
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound  => non_repos.push(dir),
+            Err(e)                                                  => return Err(e),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Searches for a Git repository above the given path by asking the `git`
+/// program, returning how to read it and its working directory.
+fn discover_command(path: &Path, ceilings: &[PathBuf]) -> Option<(GitContents, PathBuf)> {
+    let git = match git_command::find_git() {
+        Some(g) => g,
+        None => {
+            warn!("Couldn’t find git in the PATH");
+            return None;
+        }
+    };
+
+    match git_command::workdir(&git, path, ceilings) {
+        Ok(workdir) => Some((GitContents::BeforeCommand { git }, workdir)),
+        Err(e) => {
+            error!("Error discovering Git repositories with {:?}: {:?}", git, e);
+            None
         }
     }
 }
 
 /// Iterates through a repository’s statuses, consuming it and returning the
-/// mapping of files to their Git status.
+/// mapping of files to their Git status, along with what its HEAD is.
 /// We will have already used the working directory at this point, so it gets
 /// passed in rather than deriving it from the `Repository` again.
-fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
+///
+/// If libgit2 can’t read the statuses and falling back is allowed, they get
+/// read by running the `git` program instead.
+fn repo_to_statuses(repo: &git2::Repository, workdir: &Path, fallback: bool) -> Git {
     let mut statuses = Vec::new();
 
     info!("Getting Git statuses for repo with workdir {:?}", workdir);
@@ -206,10 +320,92 @@ fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
                 statuses.push(elem);
             }
         },
+        Err(ref e) if fallback => {
+            warn!("libgit2 couldn’t read the statuses ({}), so running git instead", e.message());
+            if let Some(git) = git_command::find_git() {
+                return command_to_statuses(&git, workdir);
+            }
+        },
         Err(e) => error!("Error looking up Git statuses: {:?}", e),
     }
 
-    Git { statuses }
+    Git { statuses, head: repo_head(repo) }
+}
+
+/// Reads a repository’s statuses by running the `git` program, converting
+/// them to the statuses that libgit2 would have given.
+fn command_to_statuses(git: &Path, workdir: &Path) -> Git {
+    info!("Running git for statuses of repo with workdir {:?}", workdir);
+    match git_command::status(git, workdir) {
+        Ok(porcelain) => {
+            let statuses = porcelain.entries.into_iter()
+                                    .map(|e| (workdir.join(e.path), change_to_status(e.change)))
+                                    .collect();
+
+            Git { statuses, head: porcelain.head }
+        },
+        Err(e) => {
+            error!("Error running git for statuses: {:?}", e);
+            Git { statuses: Vec::new(), head: f::GitHead::Unknown }
+        },
+    }
+}
+
+/// Converts one of the changes in `git status` output to libgit2’s flags.
+fn change_to_status(change: Change) -> git2::Status {
+    match change {
+        Change::Untracked   => git2::Status::WT_NEW,
+        Change::Ignored     => git2::Status::IGNORED,
+        Change::Conflicted  => git2::Status::CONFLICTED,
+        Change::Tracked { index, worktree } => {
+            let staged = match index {
+                b'A'         => git2::Status::INDEX_NEW,
+                b'M'         => git2::Status::INDEX_MODIFIED,
+                b'D'         => git2::Status::INDEX_DELETED,
+                b'R' | b'C'  => git2::Status::INDEX_RENAMED,
+                b'T'         => git2::Status::INDEX_TYPECHANGE,
+                _            => git2::Status::empty(),
+            };
+
+            let unstaged = match worktree {
+                b'A'         => git2::Status::WT_NEW,
+                b'M'         => git2::Status::WT_MODIFIED,
+                b'D'         => git2::Status::WT_DELETED,
+                b'R' | b'C'  => git2::Status::WT_RENAMED,
+                b'T'         => git2::Status::WT_TYPECHANGE,
+                _            => git2::Status::empty(),
+            };
+
+            staged | unstaged
+        },
+    }
+}
+
+/// Works out what a repository’s HEAD points at. A repository that’s just
+/// been created has a HEAD that points at a branch with no commits, which
+/// `git2` reports as an error, so its name has to be read from the HEAD
+/// reference itself.
+fn repo_head(repo: &git2::Repository) -> f::GitHead {
+    match repo.head() {
+        Ok(head) => {
+            if repo.head_detached().unwrap_or(false) {
+                let hash = head.target().map(|oid| oid.to_string()).unwrap_or_default();
+                f::GitHead::Detached(hash.chars().take(7).collect())
+            }
+            else {
+                f::GitHead::Branch(head.shorthand().unwrap_or("HEAD").to_string())
+            }
+        },
+        Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch => {
+            let name = repo.find_reference("HEAD").ok()
+                           .and_then(|r| r.symbolic_target().map(|t| t.trim_start_matches("refs/heads/").to_string()));
+            f::GitHead::Unborn(name.unwrap_or_default())
+        },
+        Err(e) => {
+            error!("Error looking up Git HEAD: {:?}", e);
+            f::GitHead::Unknown
+        },
+    }
 }
 
 // The `repo.statuses` call above takes a long time. exa debug output:
@@ -224,10 +420,20 @@ fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
 /// Container of Git statuses for all the files in this folder’s Git repository.
 struct Git {
     statuses: Vec<(PathBuf, git2::Status)>,
+
+    /// What the repository’s HEAD points at.
+    head: f::GitHead,
 }
 
 impl Git {
 
+    /// The repository’s branch, and whether anything in it has changed.
+    /// Files that are only ignored don’t count as changes.
+    fn state(&self) -> f::GitRepoState {
+        let dirty = self.statuses.iter().any(|&(_, s)| s != git2::Status::IGNORED);
+        f::GitRepoState { head: self.head.clone(), dirty }
+    }
+
     /// Get either the file or directory status for the given path.
     /// “Prefix lookup” means that it should report an aggregate status of all
     /// paths starting with the given prefix (in other words, a directory).
@@ -236,6 +442,18 @@ impl Git {
                     else { self.file_status(index) }
     }
 
+    /// Whether the file or directory at the given path has a status that
+    /// isn’t just being ignored. A file inside an untracked directory is
+    /// new along with it, as Git only lists the directory itself.
+    fn is_changed(&self, index: &Path, is_dir: bool) -> bool {
+        let path = reorient(index);
+        self.statuses.iter()
+            .filter(|&&(_, s)| !s.is_empty() && s != git2::Status::IGNORED)
+            .any(|&(ref p, s)| *p == path
+                            || (is_dir && p.starts_with(&path))
+                            || (s.contains(git2::Status::WT_NEW) && path.starts_with(p)))
+    }
+
     /// Get the status for the file at the given path.
     fn file_status(&self, file: &Path) -> f::Git {
         let path = reorient(file);
@@ -274,6 +492,7 @@ fn reorient(path: &Path) -> PathBuf {
 /// The character to display if the file has been modified, but not staged.
 fn working_tree_status(status: git2::Status) -> f::GitStatus {
     match status {
+        s if s.contains(git2::Status::CONFLICTED)     => f::GitStatus::Conflicted,
         s if s.contains(git2::Status::WT_NEW)         => f::GitStatus::New,
         s if s.contains(git2::Status::WT_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::Status::WT_DELETED)     => f::GitStatus::Deleted,
@@ -288,6 +507,7 @@ fn working_tree_status(status: git2::Status) -> f::GitStatus {
 /// has been staged.
 fn index_status(status: git2::Status) -> f::GitStatus {
     match status {
+        s if s.contains(git2::Status::CONFLICTED)        => f::GitStatus::Conflicted,
         s if s.contains(git2::Status::INDEX_NEW)         => f::GitStatus::New,
         s if s.contains(git2::Status::INDEX_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::Status::INDEX_DELETED)     => f::GitStatus::Deleted,
@@ -296,3 +516,58 @@ fn index_status(status: git2::Status) -> f::GitStatus {
         _                                                => f::GitStatus::NotModified,
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn git(statuses: &[(&str, git2::Status)]) -> Git {
+        let statuses = statuses.iter().map(|&(p, s)| (PathBuf::from(p), s)).collect();
+        Git { statuses, head: f::GitHead::Unknown }
+    }
+
+    #[test]
+    fn modified_file() {
+        let g = git(&[ ("/repo/src/main.rs", git2::Status::WT_MODIFIED) ]);
+        assert!(g.is_changed(Path::new("/repo/src/main.rs"), false));
+        assert!(!g.is_changed(Path::new("/repo/src/lib.rs"), false));
+    }
+
+    #[test]
+    fn staged_and_conflicted() {
+        let g = git(&[ ("/repo/new.rs", git2::Status::INDEX_NEW), ("/repo/both.rs", git2::Status::CONFLICTED) ]);
+        assert!(g.is_changed(Path::new("/repo/new.rs"), false));
+        assert!(g.is_changed(Path::new("/repo/both.rs"), false));
+    }
+
+    #[test]
+    fn directory_with_a_change() {
+        let g = git(&[ ("/repo/src/deep/main.rs", git2::Status::WT_MODIFIED) ]);
+        assert!(g.is_changed(Path::new("/repo/src"), true));
+        assert!(g.is_changed(Path::new("/repo/src/deep"), true));
+        assert!(!g.is_changed(Path::new("/repo/docs"), true));
+    }
+
+    #[test]
+    fn no_prefix_match_on_names() {
+        let g = git(&[ ("/repo/srcs/main.rs", git2::Status::WT_MODIFIED) ]);
+        assert!(!g.is_changed(Path::new("/repo/src"), true));
+    }
+
+    #[test]
+    fn inside_untracked_directory() {
+        let g = git(&[ ("/repo/vendor/", git2::Status::WT_NEW) ]);
+        assert!(g.is_changed(Path::new("/repo/vendor"), true));
+        assert!(g.is_changed(Path::new("/repo/vendor/lib.rs"), false));
+    }
+
+    #[test]
+    fn ignored_is_never_changed() {
+        let g = git(&[ ("/repo/target/", git2::Status::IGNORED), ("/repo/build.log", git2::Status::IGNORED) ]);
+        assert!(!g.is_changed(Path::new("/repo/target"), true));
+        assert!(!g.is_changed(Path::new("/repo/target/debug/exa"), false));
+        assert!(!g.is_changed(Path::new("/repo/build.log"), false));
+        assert!(!g.is_changed(Path::new("/repo"), true));
+    }
+}
@@ -0,0 +1,338 @@
+//! Getting Git statuses by running the `git` program, for repositories that
+//! libgit2 can’t open or read the statuses of.
+//!
+//! This is only a fallback: starting a process and parsing its output is
+//! slower than asking libgit2, but `git` itself understands every feature a
+//! repository can use, including ones libgit2 doesn’t support yet.
+//!
+//! Nothing here knows about `git2`’s types. It only finds the program, runs
+//! it, and parses the porcelain output into paths with status characters,
+//! which the `git` module turns into the same statuses libgit2 would give.
+
+use std::env;
+use std::ffi::OsStr;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use fs::fields as f;
+
+
+/// How long to wait for `git` to finish before giving up on it. A status
+/// that takes longer than this would make exa look like it had hung.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+
+/// Searches the directories in `$PATH` for the `git` program, returning
+/// the first one that exists.
+pub fn find_git() -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    let names: &[&str] = if cfg!(windows) { &[ "git.exe", "git" ] }
+                                     else { &[ "git" ] };
+
+    env::split_paths(&path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Asks `git` for the working directory of the repository that the given
+/// path is in. This fails if the path isn’t in a repository, or if the
+/// repository is above one of the ceiling directories.
+pub fn workdir(git: &Path, path: &Path, ceilings: &[PathBuf]) -> io::Result<PathBuf> {
+    let dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    let ceilings = env::join_paths(ceilings).map_err(io::Error::other)?;
+    let output = run(git, dir, &ceilings, &[ "rev-parse", "--show-toplevel" ])?;
+    let line = String::from_utf8_lossy(&output);
+    Ok(PathBuf::from(line.trim_end_matches(&['\r', '\n'][..])))
+}
+
+/// Runs `git status` in the given working directory, returning what it
+/// says about the repository.
+///
+/// The options are picked to list the same files that libgit2 lists by
+/// default: untracked files get listed individually, ignored directories
+/// get listed as a whole, and renames aren’t detected.
+pub fn status(git: &Path, workdir: &Path) -> io::Result<Porcelain> {
+    let args = [ "status", "--porcelain=v2", "-z", "--branch", "--no-renames",
+                 "--untracked-files=all", "--ignored" ];
+
+    let output = run(git, workdir, OsStr::new(""), &args)?;
+    Ok(Porcelain::parse(&output))
+}
+
+/// Runs `git` with the given arguments in the given directory, returning
+/// what it printed. Fails if it exits unsuccessfully, or if it runs for
+/// longer than the timeout, in which case it gets killed.
+///
+/// The ceilings are passed on through `GIT_CEILING_DIRECTORIES`, replacing
+/// the user’s own, as exa’s ceilings already include those.
+fn run(git: &Path, dir: &Path, ceilings: &OsStr, args: &[&str]) -> io::Result<Vec<u8>> {
+    debug!("Running {:?} {:?} in {:?}", git, args, dir);
+
+    let mut child = Command::new(git)
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .env("GIT_CEILING_DIRECTORIES", ceilings)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // The output gets read on another thread so that the wait for it can
+    // time out. Reading it here would block for as long as git runs.
+    let mut stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let _reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        let result = stdout.read_to_end(&mut output).map(|_| output);
+        let _ = sender.send(result);
+    });
+
+    let output = match receiver.recv_timeout(TIMEOUT) {
+        Ok(result) => result?,
+        Err(_) => {
+            warn!("Killing {:?} after {:?}", git, TIMEOUT);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "git took too long"));
+        }
+    };
+
+    let exit = child.wait()?;
+    if exit.success() {
+        Ok(output)
+    }
+    else {
+        Err(io::Error::other(format!("git exited with {}", exit)))
+    }
+}
+
+
+/// What `git status --porcelain=v2` says about a repository.
+#[derive(PartialEq, Debug)]
+pub struct Porcelain {
+
+    /// The files that have a status, relative to the working directory.
+    pub entries: Vec<Entry>,
+
+    /// What the repository’s HEAD points at, which is only known when
+    /// the branch headers were asked for.
+    pub head: f::GitHead,
+}
+
+/// One file that isn’t unmodified.
+#[derive(PartialEq, Debug)]
+pub struct Entry {
+
+    /// The file’s path, relative to the working directory.
+    pub path: PathBuf,
+
+    /// What’s happened to the file, which is one of the kinds below.
+    pub change: Change,
+}
+
+/// The kinds of status a file can have in porcelain output.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Change {
+
+    /// The file is tracked, and has changed in the index, the working
+    /// tree, or both. These are the `XY` characters, with `.` meaning
+    /// nothing has changed on that side.
+    Tracked { index: u8, worktree: u8 },
+
+    /// The file is in the middle of a merge conflict.
+    Conflicted,
+
+    /// The file isn’t tracked.
+    Untracked,
+
+    /// The file is ignored.
+    Ignored,
+}
+
+impl Porcelain {
+
+    /// Parses the NUL-separated output of `git status --porcelain=v2 -z`.
+    /// Lines that can’t be parsed get skipped, so a future version of git
+    /// adding new kinds of line doesn’t stop the rest from being used.
+    pub fn parse(output: &[u8]) -> Porcelain {
+        let mut entries = Vec::new();
+        let mut oid = None;
+        let mut branch = None;
+
+        let mut records = output.split(|b| *b == 0).filter(|r| !r.is_empty());
+        while let Some(record) = records.next() {
+            let record = String::from_utf8_lossy(record);
+
+            if let Some(header) = record.strip_prefix("# ") {
+                if let Some(o) = header.strip_prefix("branch.oid ") {
+                    oid = Some(o.to_string());
+                }
+                else if let Some(b) = header.strip_prefix("branch.head ") {
+                    branch = Some(b.to_string());
+                }
+                continue;
+            }
+
+            // A rename or copy has its original path in the next record,
+            // which has to be skipped over whether it gets used or not.
+            if record.starts_with("2 ") {
+                let _ = records.next();
+            }
+
+            let (change, path) = match record.chars().next() {
+                Some('1')  => (tracked(&record), field(&record, 8)),
+                Some('2')  => (tracked(&record), field(&record, 9)),
+                Some('u')  => (Some(Change::Conflicted), field(&record, 10)),
+                Some('?')  => (Some(Change::Untracked), field(&record, 1)),
+                Some('!')  => (Some(Change::Ignored), field(&record, 1)),
+                _          => (None, None),
+            };
+
+            match (change, path) {
+                (Some(change), Some(path))  => entries.push(Entry { path: PathBuf::from(path), change }),
+                _                           => warn!("Couldn’t parse git status line {:?}", record),
+            }
+        }
+
+        Porcelain { entries, head: head(oid, branch) }
+    }
+}
+
+/// Reads the `XY` field of a changed or renamed entry.
+fn tracked(record: &str) -> Option<Change> {
+    let xy = record.split(' ').nth(1)?.as_bytes();
+    if xy.len() != 2 {
+        return None;
+    }
+
+    Some(Change::Tracked { index: xy[0], worktree: xy[1] })
+}
+
+/// Reads everything after the given number of space-separated fields,
+/// which is where the path is. Paths can contain spaces themselves, so
+/// the rest of the line is used, rather than only the next field.
+fn field(record: &str, index: usize) -> Option<&str> {
+    record.splitn(index + 1, ' ').nth(index)
+}
+
+/// Works out the HEAD from the branch headers. A branch with no commits
+/// has its commit given as “(initial)”, and a detached HEAD has its branch
+/// given as “(detached)”.
+fn head(oid: Option<String>, branch: Option<String>) -> f::GitHead {
+    match (oid, branch) {
+        (Some(ref oid), Some(ref branch)) if branch == "(detached)" => f::GitHead::Detached(oid.chars().take(7).collect()),
+        (Some(ref oid), Some(branch)) if oid == "(initial)"         => f::GitHead::Unborn(branch),
+        (_, Some(branch))                                           => f::GitHead::Branch(branch),
+        _                                                           => f::GitHead::Unknown,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+
+    fn change(index: u8, worktree: u8) -> Change {
+        Change::Tracked { index, worktree }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Porcelain::parse(b""), Porcelain { entries: Vec::new(), head: f::GitHead::Unknown });
+    }
+
+    #[test]
+    fn changed_entries() {
+        let output = b"1 .M N... 100644 100644 100644 3f2a 3f2a README.md\0\
+                       1 A. N... 000000 100644 100644 0000 9b1c src/new file.rs\0\
+                       1 MD N... 100644 100644 000000 3f2a 9b1c gone\0";
+
+        let porcelain = Porcelain::parse(output);
+        assert_eq!(porcelain.entries, vec![
+            Entry { path: PathBuf::from("README.md"),        change: change(b'.', b'M') },
+            Entry { path: PathBuf::from("src/new file.rs"),  change: change(b'A', b'.') },
+            Entry { path: PathBuf::from("gone"),             change: change(b'M', b'D') },
+        ]);
+    }
+
+    #[test]
+    fn renamed_entries_skip_the_original_path() {
+        let output = b"2 R. N... 100644 100644 100644 3f2a 3f2a R100 thither\0hither\0\
+                       ? untracked\0";
+
+        let porcelain = Porcelain::parse(output);
+        assert_eq!(porcelain.entries, vec![
+            Entry { path: PathBuf::from("thither"),    change: change(b'R', b'.') },
+            Entry { path: PathBuf::from("untracked"),  change: Change::Untracked },
+        ]);
+    }
+
+    #[test]
+    fn other_entries() {
+        let output = b"u UU N... 100644 100644 100644 100644 3f2a 9b1c 77de merge me\0\
+                       ? new\0\
+                       ! target/\0";
+
+        let porcelain = Porcelain::parse(output);
+        assert_eq!(porcelain.entries, vec![
+            Entry { path: PathBuf::from("merge me"),  change: Change::Conflicted },
+            Entry { path: PathBuf::from("new"),       change: Change::Untracked },
+            Entry { path: PathBuf::from("target"),    change: Change::Ignored },
+        ]);
+    }
+
+    #[test]
+    fn unknown_lines_are_skipped() {
+        let output = b"9 what is this\0? new\0";
+        assert_eq!(Porcelain::parse(output).entries, vec![ Entry { path: PathBuf::from("new"), change: Change::Untracked } ]);
+    }
+
+    #[test]
+    fn branch() {
+        let output = b"# branch.oid 0123456789abcdef\0# branch.head main\0# branch.upstream origin/main\0";
+        assert_eq!(Porcelain::parse(output).head, f::GitHead::Branch("main".into()));
+    }
+
+    #[test]
+    fn detached() {
+        let output = b"# branch.oid 0123456789abcdef\0# branch.head (detached)\0";
+        assert_eq!(Porcelain::parse(output).head, f::GitHead::Detached("0123456".into()));
+    }
+
+    #[test]
+    fn unborn() {
+        let output = b"# branch.oid (initial)\0# branch.head trunk\0";
+        assert_eq!(Porcelain::parse(output).head, f::GitHead::Unborn("trunk".into()));
+    }
+
+    #[test]
+    fn real_repository() {
+        let git = match find_git() {
+            Some(g) => g,
+            None    => return,
+        };
+
+        let temp = TempDir::new("git-command");
+        let path = temp.path();
+        ::std::fs::create_dir_all(path.join("sub")).unwrap();
+        let initialised = run(&git, path, OsStr::new(""), &[ "init", "-q" ]).is_ok();
+        let _ = temp.touch("sub/file");
+
+        let top = workdir(&git, &path.join("sub"), &[]);
+        let ceilings = vec![ path.to_path_buf() ];
+        let above_ceiling = workdir(&git, &path.join("sub"), &ceilings);
+        let porcelain = status(&git, path);
+
+        if initialised {
+            assert_eq!(top.unwrap().file_name(), path.file_name());
+            assert!(above_ceiling.is_err());
+            assert_eq!(porcelain.unwrap().entries, vec![ Entry { path: PathBuf::from("sub/file"), change: Change::Untracked } ]);
+        }
+    }
+}
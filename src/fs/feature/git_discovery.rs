@@ -0,0 +1,139 @@
+//! Working out where to look for a Git repository, without looking too far.
+//!
+//! Left to itself, libgit2 walks all the way up from a path to the root of
+//! its drive, following it across mount points and probing for a `.git` at
+//! every level. On a network share, each of those probes is a round trip,
+//! so listing a directory that isn’t in a repository can stall for a long
+//! time before finding nothing. Instead, exa works out the directories to
+//! probe up front, stopping at any ceiling directories, and probes them one
+//! at a time so the misses can be remembered.
+
+use std::path::{Path, PathBuf};
+
+use fs::normalise;
+
+
+/// Lists the directories that could hold the repository that the given
+/// directory is in, nearest first.
+///
+/// The search stops before reaching any of the ceiling directories, as with
+/// Git’s `GIT_CEILING_DIRECTORIES`, and doesn’t go above one either. The
+/// start directory always gets searched, even when it’s a ceiling itself.
+/// Ceilings that aren’t absolute get ignored, as Git ignores them too.
+///
+/// Only the start directory’s own parents get searched, and they’re worked
+/// out lexically rather than by resolving the path. This keeps the search on
+/// the drive or UNC share that the path started on: resolving a path on a
+/// mapped drive turns it into a UNC path, and the search would carry on up
+/// the network share from there.
+///
+/// The start directory has to be absolute.
+pub fn search_path(start: &Path, ceilings: &[PathBuf]) -> Vec<PathBuf> {
+    let start = normalise(start);
+    let ceilings = ceilings.iter()
+                           .filter(|c| c.is_absolute())
+                           .map(|c| normalise(c))
+                           .collect::<Vec<_>>();
+
+    let mut dirs = Vec::new();
+    for dir in start.ancestors() {
+        let is_ceiling = ceilings.iter().any(|c| c == dir);
+        if is_ceiling && dir != start {
+            break;
+        }
+
+        dirs.push(dir.to_path_buf());
+        if is_ceiling {
+            break;
+        }
+    }
+
+    dirs
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn search(start: &str, ceilings: &[&str]) -> Vec<PathBuf> {
+        let ceilings = ceilings.iter().map(PathBuf::from).collect::<Vec<_>>();
+        search_path(Path::new(start), &ceilings)
+    }
+
+    fn paths(dirs: &[&str]) -> Vec<PathBuf> {
+        dirs.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn up_to_the_root() {
+        assert_eq!(search("/home/ben/src", &[]), paths(&[ "/home/ben/src", "/home/ben", "/home", "/" ]));
+    }
+
+    #[test]
+    fn the_root() {
+        assert_eq!(search("/", &[]), paths(&[ "/" ]));
+    }
+
+    #[test]
+    fn ceiling() {
+        assert_eq!(search("/mnt/share/project/src", &[ "/mnt/share" ]), paths(&[ "/mnt/share/project/src", "/mnt/share/project" ]));
+    }
+
+    #[test]
+    fn nearest_ceiling_wins() {
+        assert_eq!(search("/mnt/share/project/src", &[ "/mnt", "/mnt/share/project" ]), paths(&[ "/mnt/share/project/src" ]));
+    }
+
+    #[test]
+    fn ceiling_at_the_root() {
+        assert_eq!(search("/home/ben", &[ "/" ]), paths(&[ "/home/ben", "/home" ]));
+    }
+
+    #[test]
+    fn start_is_a_ceiling() {
+        assert_eq!(search("/mnt/share", &[ "/mnt/share" ]), paths(&[ "/mnt/share" ]));
+    }
+
+    #[test]
+    fn ceilings_elsewhere() {
+        assert_eq!(search("/home/ben", &[ "/mnt/share", "/home/ben/src" ]), paths(&[ "/home/ben", "/home", "/" ]));
+    }
+
+    #[test]
+    fn relative_ceilings_are_ignored() {
+        assert_eq!(search("/home/ben", &[ "home", "." ]), paths(&[ "/home/ben", "/home", "/" ]));
+    }
+
+    #[test]
+    fn lexically_normalised() {
+        assert_eq!(search("/home/./ben/src/..", &[ "/home/ben/../" ]), paths(&[ "/home/ben" ]));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drive_root() {
+        assert_eq!(search(r"C:\", &[]), paths(&[ r"C:\" ]));
+        assert_eq!(search(r"C:\Users\ben", &[]), paths(&[ r"C:\Users\ben", r"C:\Users", r"C:\" ]));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn other_drives_are_not_ceilings() {
+        assert_eq!(search(r"Z:\project", &[ r"C:\" ]), paths(&[ r"Z:\project", r"Z:\" ]));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unc_share() {
+        assert_eq!(search(r"\\server\share\project\src", &[]),
+                   paths(&[ r"\\server\share\project\src", r"\\server\share\project", r"\\server\share\" ]));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unc_ceiling() {
+        assert_eq!(search(r"\\server\share\project\src", &[ r"\\server\share\" ]),
+                   paths(&[ r"\\server\share\project\src", r"\\server\share\project" ]));
+    }
+}
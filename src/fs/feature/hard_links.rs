@@ -0,0 +1,170 @@
+//! Finding the other names of files that have more than one hard link.
+//!
+//! Nothing records where a file’s other links are, so the only way to find
+//! them is to search the whole volume the file is on. That gets done once
+//! per volume, the first time a file on it is asked about, and every file
+//! with several links is indexed at the same time, so the rest of the files
+//! on that volume get their answers without searching again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+
+/// The names of every regular file with more than one link on a volume,
+/// keyed by their inode numbers.
+pub struct LinkIndex {
+    names: HashMap<u64, Vec<PathBuf>>,
+}
+
+impl LinkIndex {
+
+    /// Searches everything under the given directory that’s on the given
+    /// device. Links can’t cross volumes, so anything mounted below it gets
+    /// skipped, and symlinks aren’t followed, so every path that gets
+    /// recorded is a real one. Directories that can’t be read get skipped
+    /// too, so a link in one of them won’t be found.
+    pub fn build(root: &Path, device: u64) -> LinkIndex {
+        info!("Indexing hard links under {:?}", root);
+        let mut names: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        let mut dirs = vec![ root.to_path_buf() ];
+
+        while let Some(dir) = dirs.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries)  => entries,
+                Err(_)       => continue,
+            };
+
+            for entry in entries.filter_map(Result::ok) {
+                let metadata = match entry.metadata() {
+                    Ok(m)   => m,
+                    Err(_)  => continue,
+                };
+
+                if metadata.dev() != device {
+                    continue;
+                }
+
+                if metadata.is_file() && metadata.nlink() > 1 {
+                    names.entry(metadata.ino()).or_default().push(entry.path());
+                }
+                else if metadata.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+
+        for paths in names.values_mut() {
+            paths.sort();
+        }
+
+        LinkIndex { names }
+    }
+
+    /// The names of the file with the given inode, apart from the given
+    /// one, which should have been canonicalised, so that a file reached
+    /// through a symlinked directory doesn’t get listed as its own link.
+    pub fn other_names(&self, inode: u64, own: &Path) -> Vec<PathBuf> {
+        self.names.get(&inode)
+            .map(|paths| paths.iter().filter(|p| *p != own).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+
+/// Where a volume’s index goes, which gets filled in once it’s built.
+type Slot = Arc<OnceLock<Arc<LinkIndex>>>;
+
+/// The slots for the indexes, by device.
+static INDEXES: OnceLock<Mutex<HashMap<u64, Slot>>> = OnceLock::new();
+
+/// The index for the volume with the given device number, which gets built
+/// from its mount point the first time it’s needed and then kept for the
+/// rest of the run.
+///
+/// Building an index means searching a whole volume, so the map of them
+/// only stays locked long enough to find the device’s slot. Threads asking
+/// about the same volume wait for the one building its index, but threads
+/// asking about other volumes don’t.
+pub fn index_for(device: u64, mount_point: &Path) -> Arc<LinkIndex> {
+    let slot = INDEXES.get_or_init(Mutex::default).lock().unwrap()
+                      .entry(device)
+                      .or_default()
+                      .clone();
+
+    slot.get_or_init(|| Arc::new(LinkIndex::build(mount_point, device))).clone()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use std::os::unix::fs::symlink;
+
+    /// A directory with a file that has two names, one of which is in a
+    /// directory that can also be reached through a symlink.
+    fn fixture() -> (TempDir, PathBuf, u64) {
+        let temp = TempDir::new("hard-links");
+        let original = temp.touch("real/original");
+        fs::create_dir(temp.join("other")).unwrap();
+        fs::hard_link(&original, temp.join("other/copy")).unwrap();
+        symlink("real", temp.join("shortcut")).unwrap();
+        let _ = temp.touch("real/single");
+
+        let root = fs::canonicalize(temp.path()).unwrap();
+        let device = fs::metadata(&root).unwrap().dev();
+        (temp, root, device)
+    }
+
+    fn inode(path: &Path) -> u64 {
+        fs::metadata(path).unwrap().ino()
+    }
+
+    #[test]
+    fn finds_the_other_name() {
+        let (_temp, root, device) = fixture();
+        let index = LinkIndex::build(&root, device);
+
+        let original = root.join("real/original");
+        assert_eq!(index.other_names(inode(&original), &original), vec![ root.join("other/copy") ]);
+    }
+
+    #[test]
+    fn both_names_see_each_other() {
+        let (_temp, root, device) = fixture();
+        let index = LinkIndex::build(&root, device);
+
+        let copy = root.join("other/copy");
+        assert_eq!(index.other_names(inode(&copy), &copy), vec![ root.join("real/original") ]);
+    }
+
+    #[test]
+    fn not_its_own_link_through_a_symlink() {
+        let (_temp, root, device) = fixture();
+        let index = LinkIndex::build(&root, device);
+
+        let through_link = fs::canonicalize(root.join("shortcut/original")).unwrap();
+        assert_eq!(index.other_names(inode(&through_link), &through_link), vec![ root.join("other/copy") ]);
+    }
+
+    #[test]
+    fn single_links_not_indexed() {
+        let (_temp, root, device) = fixture();
+        let index = LinkIndex::build(&root, device);
+
+        let single = root.join("real/single");
+        assert!(index.other_names(inode(&single), &single).is_empty());
+    }
+
+    #[test]
+    fn built_once_per_device() {
+        let (_temp, root, _) = fixture();
+
+        // A device that nothing is on, so no other test shares its index.
+        let device = u64::MAX - 1;
+        assert!(Arc::ptr_eq(&index_for(device, &root), &index_for(device, &root)));
+    }
+}
@@ -1,14 +1,18 @@
-//! Ignoring globs in `.gitignore` files.
+//! Ignoring globs in `.gitignore` and `.exaignore` files.
 //!
 //! This uses a cache because the file with the globs in might not be the same
 //! directory that we’re listing!
 
-use std::fs::File;
-use std::io::Read;
+use std::cmp::Reverse;
+use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
-use fs::filter::IgnorePatterns;
+use glob;
+
+use fs::filter::{FileFilter, GitIgnore, ExaIgnore};
+use fs::pattern::{NamePattern, PatternCase};
 
 
 /// An **ignore cache** holds sets of glob patterns paired with the
@@ -16,68 +20,224 @@ use fs::filter::IgnorePatterns;
 /// that’s a valid English sentence.
 #[derive(Default, Debug)]
 pub struct IgnoreCache {
-    entries: RwLock<Vec<(PathBuf, IgnorePatterns)>>
+
+    /// The names of the ignore files to look for, and how many of a listed
+    /// directory’s ancestors to look in for each, if there’s a limit.
+    sources: Vec<(&'static str, Option<usize>)>,
+
+    /// Every ignore file that has been looked for so far, including the
+    /// ones that turned out not to exist, so none get read twice. They’re
+    /// kept in the order they get checked in: deepest first, and later
+    /// sources before earlier ones in the same directory.
+    entries: RwLock<Vec<IgnoreFile>>,
+
+    /// Whether the files’ patterns care about case.
+    case: PatternCase,
+
+    /// The user’s home directory, which searches for ignore files don’t go
+    /// any further up than.
+    home: Option<PathBuf>,
+}
+
+/// One ignore file that has been looked for.
+#[derive(Debug)]
+struct IgnoreFile {
+
+    /// The directory the file is in, which its patterns are relative to.
+    base: PathBuf,
+
+    /// Which of the cache’s sources this file is. Files from later
+    /// sources win over earlier ones in the same directory.
+    source: usize,
+
+    /// The file’s rules, or `None` if there was no file to read.
+    rules: Option<IgnoreRules>,
 }
 
 impl IgnoreCache {
-    pub fn new() -> IgnoreCache {
-        IgnoreCache::default()
+
+    /// Creates a cache that reads whichever ignore files the filter asks
+    /// for, or nothing if it asks for neither of them.
+    pub fn for_filter(filter: &FileFilter) -> Option<IgnoreCache> {
+        let mut sources = Vec::new();
+
+        if filter.git_ignore == GitIgnore::CheckAndIgnore {
+            sources.push((".gitignore", None));
+        }
+
+        if let ExaIgnore::CheckAndIgnore { depth } = filter.exa_ignore {
+            sources.push((".exaignore", depth));
+        }
+
+        if sources.is_empty() {
+            None
+        }
+        else {
+            let home = env::var_os("HOME").map(PathBuf::from);
+            Some(IgnoreCache { sources, entries: RwLock::default(), case: filter.pattern_case, home })
+        }
     }
 
+    /// Reads the ignore files in the given directory and its ancestors,
+    /// skipping any that have already been read. Once an ancestor’s file
+    /// has been read, its patterns apply to everything underneath it.
+    ///
+    /// The search stops at the top of the Git repository the directory is
+    /// in, or at the user’s home directory, whichever comes first, so
+    /// listing a directory doesn’t mean looking in every one above it.
     pub fn discover_underneath(&self, path: &Path) {
-        let mut path = Some(path);
         let mut entries = self.entries.write().unwrap();
+        let count = entries.len();
 
-        while let Some(p) = path {
+        for (level, p) in path.ancestors().enumerate() {
             if p.components().next().is_none() { break }
 
-            let ignore_file = p.join(".gitignore");
-            if ignore_file.is_file() {
-                debug!("Found a .gitignore file: {:?}", ignore_file);
-                if let Ok(mut file) = File::open(ignore_file) {
-                    let mut contents = String::new();
-
-                    match file.read_to_string(&mut contents) {
-                        Ok(_) => {
-                            let patterns = file_lines_to_patterns(contents.lines());
-                            entries.push((p.into(), patterns));
-                        }
-                        Err(e) => debug!("Failed to read a .gitignore: {:?}", e)
-                    }
-                }
+            for (source, &(name, depth)) in self.sources.iter().enumerate() {
+                if depth.is_some_and(|d| level > d) { continue }
+                if entries.iter().any(|e| e.base == p && e.source == source) { continue }
+
+                let rules = read_ignore_file(&p.join(name), self.case);
+                entries.push(IgnoreFile { base: p.into(), source, rules });
             }
-            else {
-                debug!("Found no .gitignore file at {:?}", ignore_file);
+
+            if self.home.as_deref() == Some(p) || p.join(".git").symlink_metadata().is_ok() {
+                break;
             }
+        }
 
-            path = p.parent();
+        if entries.len() > count {
+            entries.sort_by_key(|e| Reverse((e.base.components().count(), e.source)));
         }
     }
 
-    pub fn is_ignored(&self, suspect: &Path) -> bool {
+    /// Whether the file at the given path should be hidden. The deepest
+    /// ignore file with a rule that matches gets the final say, so a file
+    /// can be un-ignored with a `!` pattern further down, like in Git.
+    pub fn is_ignored(&self, suspect: &Path, is_dir: bool) -> bool {
         let entries = self.entries.read().unwrap();
-        entries.iter().any(|&(ref base_path, ref patterns)| {
-            if let Ok(suffix) = suspect.strip_prefix(&base_path) {
-                patterns.is_ignored_path(suffix)
-            }
-            else {
-                false
-            }
-        })
+
+        entries.iter()
+               .filter_map(|e| e.rules.as_ref()?.check(suspect.strip_prefix(&e.base).ok()?, is_dir))
+               .next()
+               .unwrap_or(false)
     }
 }
 
+fn read_ignore_file(path: &Path, case: PatternCase) -> Option<IgnoreRules> {
+    if !path.is_file() {
+        debug!("Found no ignore file at {:?}", path);
+        return None;
+    }
 
-fn file_lines_to_patterns<'a, I>(iter: I) -> IgnorePatterns
-where I: Iterator<Item=&'a str>
-{
-    let iter = iter.filter(|el| !el.is_empty());
-    let iter = iter.filter(|el| !el.starts_with('#'));
+    debug!("Found an ignore file: {:?}", path);
+    match fs::read_to_string(path) {
+        Ok(contents) => Some(IgnoreRules::parse(contents.lines(), case)),
+        Err(e) => {
+            debug!("Failed to read an ignore file: {:?}", e);
+            None
+        }
+    }
+}
 
-    // TODO: Figure out if this should trim whitespace or not
 
-    // Errors are currently being ignored... not a good look
-    IgnorePatterns::parse_from_iter(iter).0
+/// The rules from one ignore file, in the order they were written.
+#[derive(PartialEq, Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+/// One line of an ignore file.
+#[derive(PartialEq, Debug)]
+struct IgnoreRule {
+
+    /// The glob to match, with any `!`, and leading or trailing slashes,
+    /// taken off.
+    pattern: NamePattern,
+
+    /// Whether the line started with `!`, so it un-ignores files that
+    /// an earlier rule ignored.
+    negated: bool,
+
+    /// Whether the line ended with `/`, so it only matches directories.
+    dirs_only: bool,
+
+    /// Whether the line had a slash before its end, so it matches paths
+    /// relative to the ignore file’s directory, rather than file names at
+    /// any depth.
+    anchored: bool,
+}
+
+/// How anchored patterns match paths: a `*` doesn’t match a `/`, but a
+/// `**` does.
+const PATH_MATCHING: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive:              true,
+    require_literal_separator:   true,
+    require_literal_leading_dot: false,
+};
+
+impl IgnoreRules {
+
+    /// Parses the lines of an ignore file, which use the same syntax as
+    /// `.gitignore`. Blank lines and lines starting with `#` are skipped, as
+    /// are lines that aren’t valid globs.
+    pub fn parse<'a, I: IntoIterator<Item=&'a str>>(lines: I, case: PatternCase) -> IgnoreRules {
+        IgnoreRules { rules: lines.into_iter().filter_map(|l| IgnoreRule::parse(l, case)).collect() }
+    }
+
+    /// Whether the last rule that matches the given path, relative to the
+    /// ignore file’s directory, ignores it or un-ignores it. Returns `None`
+    /// if no rule matches at all.
+    pub fn check(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        self.rules.iter().rev()
+            .find(|r| r.matches(path, is_dir))
+            .map(|r| !r.negated)
+    }
+}
+
+impl IgnoreRule {
+    fn parse(line: &str, case: PatternCase) -> Option<IgnoreRule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(l)                                                     => (true,  l),
+            None if line.starts_with("\\!") || line.starts_with("\\#")  => (false, &line[1..]),
+            None                                                        => (false, line),
+        };
+
+        let (dirs_only, line) = match line.strip_suffix('/') {
+            Some(l)  => (true, l),
+            None     => (false, line),
+        };
+
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        match NamePattern::new(line, case) {
+            Ok(pattern) => Some(IgnoreRule { pattern, negated, dirs_only, anchored }),
+            Err(e) => {
+                debug!("Skipping invalid ignore pattern {:?}: {}", line, e);
+                None
+            }
+        }
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dirs_only && !is_dir {
+            false
+        }
+        else if self.anchored {
+            self.pattern.matches_path_with(path, &PATH_MATCHING)
+        }
+        else {
+            path.file_name().is_some_and(|name| self.pattern.matches(&name.to_string_lossy()))
+        }
+    }
 }
 
 
@@ -88,109 +248,250 @@ mod test {
     #[test]
     fn parse_nothing() {
         use std::iter::empty;
-        let (patterns, _) = IgnorePatterns::parse_from_iter(empty());
-        assert_eq!(patterns, file_lines_to_patterns(empty()));
+        assert_eq!(IgnoreRules::default(), IgnoreRules::parse(empty(), PatternCase::Sensitive));
     }
 
     #[test]
     fn parse_some_globs() {
         let stuff = vec![ "*.mp3", "README.md" ];
         let reals = vec![ "*.mp3", "README.md" ];
-        let (patterns, _) = IgnorePatterns::parse_from_iter(reals.into_iter());
-        assert_eq!(patterns, file_lines_to_patterns(stuff.into_iter()));
+        assert_eq!(IgnoreRules::parse(reals, PatternCase::Sensitive), IgnoreRules::parse(stuff, PatternCase::Sensitive));
     }
 
     #[test]
     fn parse_some_comments() {
         let stuff = vec![ "*.mp3", "# I am a comment!", "#", "README.md" ];
         let reals = vec![ "*.mp3",                           "README.md" ];
-        let (patterns, _) = IgnorePatterns::parse_from_iter(reals.into_iter());
-        assert_eq!(patterns, file_lines_to_patterns(stuff.into_iter()));
+        assert_eq!(IgnoreRules::parse(reals, PatternCase::Sensitive), IgnoreRules::parse(stuff, PatternCase::Sensitive));
     }
 
     #[test]
     fn parse_some_blank_lines() {
         let stuff = vec![ "*.mp3", "", "", "README.md" ];
         let reals = vec![ "*.mp3",         "README.md" ];
-        let (patterns, _) = IgnorePatterns::parse_from_iter(reals.into_iter());
-        assert_eq!(patterns, file_lines_to_patterns(stuff.into_iter()));
+        assert_eq!(IgnoreRules::parse(reals, PatternCase::Sensitive), IgnoreRules::parse(stuff, PatternCase::Sensitive));
     }
 
     #[test]
     fn parse_some_whitespacey_lines() {
         let stuff = vec![ " *.mp3", "  ", "  a  ", "README.md   " ];
-        let reals = vec![ " *.mp3", "  ", "  a  ", "README.md   " ];
-        let (patterns, _) = IgnorePatterns::parse_from_iter(reals.into_iter());
-        assert_eq!(patterns, file_lines_to_patterns(stuff.into_iter()));
+        let reals = vec![ " *.mp3",       "  a",   "README.md" ];
+        assert_eq!(IgnoreRules::parse(reals, PatternCase::Sensitive), IgnoreRules::parse(stuff, PatternCase::Sensitive));
     }
 
 
     fn test_cache(dir: &'static str, pats: Vec<&str>) -> IgnoreCache {
-        IgnoreCache { entries: RwLock::new(vec![ (dir.into(), IgnorePatterns::parse_from_iter(pats.into_iter()).0) ]) }
+        let file = IgnoreFile { base: dir.into(), source: 0, rules: Some(IgnoreRules::parse(pats, PatternCase::Sensitive)) };
+        IgnoreCache { sources: vec![ (".gitignore", None) ], entries: RwLock::new(vec![ file ]), case: PatternCase::Sensitive, home: None }
     }
 
     #[test]
     fn an_empty_cache_ignores_nothing() {
         let ignores = IgnoreCache::default();
-        assert_eq!(false, ignores.is_ignored(Path::new("/usr/bin/drinking")));
-        assert_eq!(false, ignores.is_ignored(Path::new("target/debug/exa")));
+        assert_eq!(false, ignores.is_ignored(Path::new("/usr/bin/drinking"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("target/debug/exa"), false));
     }
 
     #[test]
     fn a_nonempty_cache_ignores_some_things() {
         let ignores = test_cache("/vagrant", vec![ "target" ]);
-        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/src")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/target")));
+        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/src"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/target"), false));
     }
 
     #[test]
     fn ignore_some_globs() {
         let ignores = test_cache("/vagrant", vec![ "*.ipr", "*.iws", ".docker" ]);
-        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/exa.ipr")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/exa.iws")));
-        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/exa.iwiwal")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/.docker")));
-        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/exa.docker")));
-
-        assert_eq!(false, ignores.is_ignored(Path::new("/srcode/exa.ipr")));
-        assert_eq!(false, ignores.is_ignored(Path::new("/srcode/exa.iws")));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/exa.ipr"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/exa.iws"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/exa.iwiwal"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/vagrant/.docker"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("/vagrant/exa.docker"), false));
+
+        assert_eq!(false, ignores.is_ignored(Path::new("/srcode/exa.ipr"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("/srcode/exa.iws"), false));
     }
 
-    #[test] #[ignore]
+    #[test]
     fn ignore_relatively() {
         let ignores = test_cache(".", vec![ "target" ]);
-        assert_eq!(true,  ignores.is_ignored(Path::new("./target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target")));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target"), false));
 
-        assert_eq!(false, ignores.is_ignored(Path::new("./.target")));
+        assert_eq!(false, ignores.is_ignored(Path::new("./.target"), false));
     }
 
     #[test] #[ignore]
     fn ignore_relatively_sometimes() {
         let ignores = test_cache(".", vec![ "project/target" ]);
-        assert_eq!(false, ignores.is_ignored(Path::new("./target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target")));
+        assert_eq!(false, ignores.is_ignored(Path::new("./target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target"), false));
     }
 
     #[test] #[ignore]
     fn ignore_relatively_absolutely() {
         let ignores = test_cache(".", vec![ "/project/target" ]);
-        assert_eq!(false, ignores.is_ignored(Path::new("./target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target")));
+        assert_eq!(false, ignores.is_ignored(Path::new("./target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target"), false));
     }
 
     #[test] #[ignore]   // not 100% sure if dot works this way...
     fn ignore_relatively_absolutely_dot() {
         let ignores = test_cache(".", vec![ "./project/target" ]);
-        assert_eq!(false, ignores.is_ignored(Path::new("./target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target")));
-        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target")));
+        assert_eq!(false, ignores.is_ignored(Path::new("./target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/target"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("./project/project/project/target"), false));
+    }
+
+
+    fn check(pats: Vec<&str>, path: &str, is_dir: bool) -> Option<bool> {
+        IgnoreRules::parse(pats, PatternCase::Sensitive).check(Path::new(path), is_dir)
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let rules = IgnoreRules::parse(vec![ "*.OBJ", "Build/", "Docs/*.MD" ], PatternCase::Insensitive);
+        assert_eq!(rules.check(Path::new("module.obj"), false),      Some(true));
+        assert_eq!(rules.check(Path::new("build"), true),            Some(true));
+        assert_eq!(rules.check(Path::new("docs/readme.md"), false),  Some(true));
+        assert_eq!(rules.check(Path::new("module.c"), false),        None);
+    }
+
+    #[test]
+    fn case_sensitive() {
+        assert_eq!(check(vec![ "*.OBJ" ], "module.obj", false), None);
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(check(vec![ "*.log", "!keep.log" ], "keep.log", false),  Some(false));
+        assert_eq!(check(vec![ "*.log", "!keep.log" ], "other.log", false), Some(true));
+        assert_eq!(check(vec![ "!keep.log", "*.log" ], "keep.log", false),  Some(true));
+        assert_eq!(check(vec![ "*.log" ],              "notes.txt", false), None);
+    }
+
+    #[test]
+    fn escaped_characters() {
+        assert_eq!(check(vec![ "\\!important" ], "!important", false), Some(true));
+        assert_eq!(check(vec![ "\\#hashed" ],    "#hashed", false),    Some(true));
+    }
+
+    #[test]
+    fn directories_only() {
+        assert_eq!(check(vec![ "node_modules/" ], "node_modules", true),  Some(true));
+        assert_eq!(check(vec![ "node_modules/" ], "node_modules", false), None);
+    }
+
+    #[test]
+    fn names_match_at_any_depth() {
+        assert_eq!(check(vec![ "Thumbs.db" ], "photos/2019/Thumbs.db", false), Some(true));
+        assert_eq!(check(vec![ "*.pdb" ],     "target/debug/exa.pdb", false),  Some(true));
+    }
+
+    #[test]
+    fn slashes_anchor() {
+        assert_eq!(check(vec![ "/build" ],     "build", true),           Some(true));
+        assert_eq!(check(vec![ "/build" ],     "src/build", true),       None);
+        assert_eq!(check(vec![ "doc/*.html" ], "doc/index.html", false), Some(true));
+        assert_eq!(check(vec![ "doc/*.html" ], "doc/api/x.html", false), None);
+        assert_eq!(check(vec![ "doc/**/*.html" ], "doc/api/x.html", false), Some(true));
+    }
+
+    fn two_level_cache(shallow: Vec<&str>, deep: Vec<&str>) -> IgnoreCache {
+        // Deepest first, in the order discover_underneath keeps them in.
+        let files = vec![
+            IgnoreFile { base: "/a/b".into(), source: 0, rules: Some(IgnoreRules::parse(deep, PatternCase::Sensitive)) },
+            IgnoreFile { base: "/a".into(),   source: 0, rules: Some(IgnoreRules::parse(shallow, PatternCase::Sensitive)) },
+        ];
+
+        IgnoreCache { sources: vec![ (".exaignore", None) ], entries: RwLock::new(files), case: PatternCase::Sensitive, home: None }
+    }
+
+    #[test]
+    fn deeper_files_override_shallower_ones() {
+        let ignores = two_level_cache(vec![ "*.pdb" ], vec![ "!keep.pdb" ]);
+        assert_eq!(true,  ignores.is_ignored(Path::new("/a/other.pdb"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/a/b/other.pdb"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("/a/b/keep.pdb"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/a/keep.pdb"), false));
+    }
+
+    #[test]
+    fn shallower_files_still_apply() {
+        let ignores = two_level_cache(vec![ "*.pdb" ], vec![ "Thumbs.db" ]);
+        assert_eq!(true,  ignores.is_ignored(Path::new("/a/b/exa.pdb"), false));
+        assert_eq!(true,  ignores.is_ignored(Path::new("/a/b/Thumbs.db"), false));
+        assert_eq!(false, ignores.is_ignored(Path::new("/a/Thumbs.db"), false));
+    }
+
+    #[test]
+    fn discovery() {
+        use fs::test_dir::TempDir;
+
+        let top = TempDir::new("ignore");
+        let sub = top.join("one").join("two");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(top.join(".exaignore"), "*.pdb\n").unwrap();
+        fs::write(top.join("one").join(".exaignore"), "Thumbs.db\n").unwrap();
+        fs::write(sub.join(".gitignore"), "*.pdb\n").unwrap();
+
+        let filter = |depth| FileFilter { exa_ignore: ExaIgnore::CheckAndIgnore { depth }, ..FileFilter::default() };
+
+        let near = IgnoreCache::for_filter(&filter(Some(1))).unwrap();
+        near.discover_underneath(&sub);
+        let far = IgnoreCache::for_filter(&filter(None)).unwrap();
+        far.discover_underneath(&sub);
+        let off = IgnoreCache::for_filter(&FileFilter { exa_ignore: ExaIgnore::Off, ..FileFilter::default() });
+
+        let thumbs = sub.join("Thumbs.db");
+        let pdb = sub.join("exa.pdb");
+        let results = (near.is_ignored(&thumbs, false), near.is_ignored(&pdb, false), far.is_ignored(&pdb, false));
+
+        assert_eq!(results, (true, false, true));
+        assert!(off.is_none());
+    }
+
+    #[test]
+    fn deeper_files_found_later_still_win() {
+        use fs::test_dir::TempDir;
+
+        let top = TempDir::new("ignore-later");
+        let sub = top.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(top.join(".exaignore"), "*.pdb\n").unwrap();
+        fs::write(sub.join(".exaignore"), "!keep.pdb\n").unwrap();
+
+        let ignores = IgnoreCache::for_filter(&FileFilter::default()).unwrap();
+        ignores.discover_underneath(top.path());
+        ignores.discover_underneath(&sub);
+
+        assert!(!ignores.is_ignored(&sub.join("keep.pdb"), false));
+        assert!(ignores.is_ignored(&sub.join("other.pdb"), false));
+    }
+
+    #[test]
+    fn discovery_stops_at_repository() {
+        use fs::test_dir::TempDir;
+
+        let top = TempDir::new("ignore-repo");
+        let repo = top.join("repo");
+        let sub = repo.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::create_dir(repo.join(".git")).unwrap();
+        fs::write(top.join(".exaignore"), "*.pdb\n").unwrap();
+        fs::write(repo.join(".exaignore"), "Thumbs.db\n").unwrap();
+
+        let ignores = IgnoreCache::for_filter(&FileFilter::default()).unwrap();
+        ignores.discover_underneath(&sub);
+
+        assert!(ignores.is_ignored(&sub.join("Thumbs.db"), false));
+        assert!(!ignores.is_ignored(&sub.join("exa.pdb"), false));
     }
 }
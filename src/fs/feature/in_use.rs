@@ -0,0 +1,108 @@
+//! Finding out which files other processes have open, for Linux systems.
+//!
+//! This is inherently racy: a process can open or close a file between
+//! exa looking and the user acting on what it printed.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+
+/// The paths of the files other processes have open, found the first time
+/// they’re needed.
+static OPEN_FILES: OnceLock<HashSet<PathBuf>> = OnceLock::new();
+
+/// Whether another process has the file at the given path open. The path
+/// has to be canonical to match, and a file that was opened through
+/// another of its hard links doesn’t count. Every process’s open files get
+/// found the first time this is asked, and the answers for the rest of the
+/// files come from that.
+pub fn is_open_elsewhere(path: &Path) -> bool {
+    OPEN_FILES.get_or_init(open_files).contains(path)
+}
+
+
+/// The paths of the files that other processes have open, read from the
+/// links in each of their `/proc/PID/fd` directories. The links only get
+/// read, never followed, so a file on a mount that’s stopped responding
+/// can’t hold things up; sockets and pipes don’t have paths, so they get
+/// skipped. Processes that belong to other users can’t be looked into
+/// unless exa is running as root, so their files get left out, as do
+/// processes that exit while being read.
+#[cfg(target_os = "linux")]
+fn open_files() -> HashSet<PathBuf> {
+    use std::fs;
+    use std::process;
+
+    let mut files = HashSet::new();
+    let own_pid = process::id().to_string();
+
+    let procs = match fs::read_dir("/proc") {
+        Ok(procs)  => procs,
+        Err(e)     => { debug!("Error reading /proc: {}", e); return files; }
+    };
+
+    for entry in procs.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let is_pid = name.to_str().is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()));
+        if !is_pid || name == own_pid.as_str() {
+            continue;
+        }
+
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds)  => fds,
+            Err(_)   => continue,
+        };
+
+        for fd in fds.filter_map(Result::ok) {
+            if let Ok(target) = fs::read_link(fd.path()) {
+                if target.is_absolute() {
+                    let _ = files.insert(target);
+                }
+            }
+        }
+    }
+
+    files
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_files() -> HashSet<PathBuf> {
+    HashSet::new()
+}
+
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use std::fs;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn own_files_dont_count() {
+        let temp = TempDir::new("in-use");
+        let path = temp.touch("open");
+        let _file = fs::File::open(&path).unwrap();
+
+        assert!(!open_files().contains(&fs::canonicalize(&path).unwrap()));
+    }
+
+    #[test]
+    fn other_processes_files_count() {
+        let temp = TempDir::new("in-use-elsewhere");
+        let path = temp.touch("open");
+
+        let mut child = Command::new("sleep").arg("30")
+                                             .stdin(fs::File::open(&path).unwrap())
+                                             .stdout(Stdio::null())
+                                             .spawn().unwrap();
+
+        let found = open_files().contains(&fs::canonicalize(&path).unwrap());
+        child.kill().unwrap();
+        let _ = child.wait();
+
+        assert!(found);
+    }
+}
@@ -1,25 +1,47 @@
 pub mod xattr;
 pub mod ignore;
+pub mod network;
+pub mod access;
+pub mod in_use;
+pub mod hard_links;
+pub mod pe;
+pub mod checksum;
 
 #[cfg(feature="git")] pub mod git;
+#[cfg(any(feature="git", test))] pub mod git_command;
+#[cfg(any(feature="git", test))] pub mod git_discovery;
+
+
+/// Which ways of reading a repository’s Git statuses exa is allowed to use.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum GitBackend {
+
+    /// Use libgit2, but run the `git` program instead for repositories
+    /// that libgit2 can’t open or read the statuses of.
+    Fallback,
+
+    /// Only use libgit2, and show nothing for repositories it can’t read.
+    LibGit2,
+
+    /// Only run the `git` program, and never use libgit2.
+    Command,
+}
 
 #[cfg(not(feature="git"))]
 pub mod git {
-    use std::iter::FromIterator;
     use std::path::{Path, PathBuf};
 
     use fs::fields as f;
+    use super::GitBackend;
 
 
     pub struct GitCache;
 
-    impl FromIterator<PathBuf> for GitCache {
-        fn from_iter<I: IntoIterator<Item=PathBuf>>(_iter: I) -> Self {
+    impl GitCache {
+        pub fn new<I: IntoIterator<Item=PathBuf>>(_paths: I, _backend: GitBackend, _ceilings: &[PathBuf]) -> Self {
             GitCache
         }
-    }
 
-    impl GitCache {
         pub fn has_anything_for(&self, _index: &Path) -> bool {
             false
         }
@@ -27,5 +49,13 @@ pub mod git {
         pub fn get(&self, _index: &Path, _prefix_lookup: bool) -> f::Git {
             panic!("Tried to query a Git cache, but Git support is disabled")
         }
+
+        pub fn repo_state(&self, _index: &Path) -> Option<f::GitRepoState> {
+            None
+        }
+
+        pub fn is_changed(&self, _index: &Path, _is_dir: bool) -> bool {
+            false
+        }
     }
 }
@@ -0,0 +1,181 @@
+//! Detecting whether a file lives on a network filesystem, or on a FAT
+//! one that doesn’t record owners, for Darwin and Linux systems.
+#![allow(trivial_casts)]  // for ARM
+extern crate libc;
+
+use std::path::Path;
+
+
+/// Whether the filesystem that the given path is on is served over the
+/// network, such as NFS or SMB.
+///
+/// This only asks the kernel which type of filesystem the path is on, which
+/// it already knows, so it doesn’t have to wait for the server to reply.
+/// Paths that can’t be checked are assumed to be local.
+pub fn is_network_path(path: &Path) -> bool {
+    match filesystem_type(path) {
+        Some(t) => is_network_type(t),
+        None    => false,
+    }
+}
+
+/// Whether the filesystem that the given path is on is FAT or exFAT, as
+/// found on USB sticks and memory cards.
+pub fn is_fat_path(path: &Path) -> bool {
+    match filesystem_type(path) {
+        Some(t) => is_fat_type(t),
+        None    => false,
+    }
+}
+
+/// Whether the filesystem that the given path is on records who owns each
+/// file and what its permissions are. FAT and exFAT don’t, so the kernel
+/// makes them up from the options the volume was mounted with, and every
+/// file gets the same ones.
+pub fn records_owners(path: &Path) -> bool {
+    !is_fat_path(path)
+}
+
+
+#[cfg(target_os = "linux")]
+type FilesystemType = u32;
+
+#[cfg(target_os = "linux")]
+fn filesystem_type(path: &Path) -> Option<FilesystemType> {
+    use std::ffi::CString;
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+
+    unsafe {
+        let mut stats: libc::statfs = mem::zeroed();
+        if libc::statfs(c_path.as_ptr(), &mut stats) == 0 {
+            Some(stats.f_type as FilesystemType)
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// The magic numbers of the network filesystems Linux knows about, from
+/// the `statfs(2)` man page.
+#[cfg(target_os = "linux")]
+fn is_network_type(t: FilesystemType) -> bool {
+    match t {
+        0x0000_6969 |  // NFS
+        0x0000_517B |  // SMB
+        0xFF53_4D42 |  // CIFS
+        0xFE53_4D42 |  // SMB2
+        0x5346_414F |  // AFS
+        0x0000_564C |  // NCP
+        0x00C3_6400 |  // Ceph
+        0x0102_1997 |  // 9P
+        0x0BD0_0BD0 |  // Lustre
+        0x4745_4652 => true,  // GFS2
+        _ => false,
+    }
+}
+
+/// The magic numbers of FAT (all of FAT12, FAT16, and FAT32) and exFAT.
+#[cfg(target_os = "linux")]
+fn is_fat_type(t: FilesystemType) -> bool {
+    t == 0x0000_4D44 || t == 0x2011_BAB0
+}
+
+
+#[cfg(target_os = "macos")]
+type FilesystemType = [u8; 16];
+
+#[cfg(target_os = "macos")]
+fn filesystem_type(path: &Path) -> Option<FilesystemType> {
+    use std::ffi::CString;
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+
+    unsafe {
+        let mut stats: libc::statfs = mem::zeroed();
+        if libc::statfs(c_path.as_ptr(), &mut stats) == 0 {
+            let mut name = [0; 16];
+            for (n, c) in name.iter_mut().zip(stats.f_fstypename.iter()) {
+                *n = *c as u8;
+            }
+            Some(name)
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// The names of the network filesystems macOS comes with.
+#[cfg(target_os = "macos")]
+fn is_network_type(t: FilesystemType) -> bool {
+    let len = t.iter().position(|&c| c == 0).unwrap_or(t.len());
+
+    match &t[.. len] {
+        b"nfs" | b"smbfs" | b"afpfs" | b"webdav" | b"cifs" => true,
+        _ => false,
+    }
+}
+
+/// The names macOS gives FAT and exFAT.
+#[cfg(target_os = "macos")]
+fn is_fat_type(t: FilesystemType) -> bool {
+    let len = t.iter().position(|&c| c == 0).unwrap_or(t.len());
+    &t[.. len] == b"msdos" || &t[.. len] == b"exfat"
+}
+
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+type FilesystemType = ();
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn filesystem_type(_path: &Path) -> Option<FilesystemType> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn is_network_type(_t: FilesystemType) -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn is_fat_type(_t: FilesystemType) -> bool {
+    false
+}
+
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nfs() {
+        assert!(is_network_type(0x6969));
+    }
+
+    #[test]
+    fn cifs() {
+        assert!(is_network_type(0xFF53_4D42));
+    }
+
+    #[test]
+    fn ext4() {
+        assert!(!is_network_type(0xEF53));
+        assert!(!is_fat_type(0xEF53));
+    }
+
+    #[test]
+    fn vfat() {
+        assert!(is_fat_type(0x4D44));
+    }
+
+    #[test]
+    fn exfat() {
+        assert!(is_fat_type(0x2011_BAB0));
+    }
+}
@@ -0,0 +1,354 @@
+//! Reading bits of information out of Windows executables, by parsing just
+//! enough of their PE headers to find them.
+//!
+//! This works on any platform, without needing any Windows APIs, which is
+//! handy for auditing a directory of binaries from somewhere else. It only
+//! ever reads a few header fields and the resources section, never the
+//! whole file.
+
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use fs::fields as f;
+
+
+/// The extensions of the files that get checked. Anything else is assumed
+/// not to be a PE file without opening it.
+pub const PE_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "sys", "ocx", "efi", "scr", "cpl", "drv",
+];
+
+/// Opens the file at the given path and looks for its Authenticode
+/// signature. Files that can’t be read count as invalid, as they claim to
+/// be executables.
+///
+/// This only checks that a signature is *present* and well-formed: actually
+/// verifying it would mean checking the certificate chain, which needs the
+/// Windows trust store.
+pub fn signature(path: &Path) -> f::Signature {
+    match fs::File::open(path) {
+        Ok(mut file)  => read_signature(&mut file).unwrap_or(f::Signature::Invalid),
+        Err(_)        => f::Signature::Invalid,
+    }
+}
+
+/// Opens the file at the given path and looks for the file version in its
+/// `VERSIONINFO` resource.
+pub fn version(path: &Path) -> f::Version {
+    match fs::File::open(path) {
+        Ok(mut file)  => read_version(&mut file).unwrap_or(f::Version::Unreadable),
+        Err(_)        => f::Version::Unreadable,
+    }
+}
+
+
+/// The offsets of the parts of a PE file’s headers that get used.
+struct Headers {
+
+    /// The length of the whole file.
+    len: u64,
+
+    /// Where the optional header’s data directories start.
+    directories: u64,
+
+    /// Where the section table starts.
+    sections: u64,
+
+    /// The number of entries in the section table.
+    section_count: u16,
+}
+
+/// The index of the resource table among the optional header’s data
+/// directories.
+const RESOURCE_TABLE: u64 = 2;
+
+/// The index of the certificate table among the optional header’s data
+/// directories.
+const CERTIFICATE_TABLE: u64 = 4;
+
+/// Reads the DOS, COFF, and optional headers, returning `None` if this
+/// isn’t a PE file at all.
+fn read_headers<R: Read + Seek>(pe: &mut R) -> io::Result<Option<Headers>> {
+    let len = pe.seek(SeekFrom::End(0))?;
+
+    // The DOS header starts with “MZ”, and has the offset of the PE header
+    // at 0x3C.
+    let _ = pe.seek(SeekFrom::Start(0))?;
+    if read_bytes(pe, 2)? != b"MZ" {
+        return Ok(None);
+    }
+
+    let _ = pe.seek(SeekFrom::Start(0x3C))?;
+    let pe_offset = u64::from(read_u32(pe)?);
+
+    let _ = pe.seek(SeekFrom::Start(pe_offset))?;
+    if read_bytes(pe, 4)? != b"PE\0\0" {
+        return Ok(None);
+    }
+
+    // The COFF header has the sizes of the tables that follow it.
+    let _ = pe.seek(SeekFrom::Start(pe_offset + 4 + 2))?;
+    let section_count = read_u16(pe)?;
+    let _ = pe.seek(SeekFrom::Current(12))?;
+    let optional_size = u64::from(read_u16(pe)?);
+
+    // The optional header comes after the 20-byte COFF header, and where
+    // its data directories start depends on whether it’s PE32 or PE32+.
+    let optional_header = pe_offset + 4 + 20;
+    let _ = pe.seek(SeekFrom::Start(optional_header))?;
+    let directories = match read_u16(pe)? {
+        0x10B  => optional_header + 96,
+        0x20B  => optional_header + 112,
+        _      => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown optional header")),
+    };
+
+    let sections = optional_header + optional_size;
+    Ok(Some(Headers { len, directories, sections, section_count }))
+}
+
+/// Reads the address and size of one of the data directories.
+fn read_directory<R: Read + Seek>(pe: &mut R, headers: &Headers, index: u64) -> io::Result<(u64, u64)> {
+    let _ = pe.seek(SeekFrom::Start(headers.directories + index * 8))?;
+    let address = u64::from(read_u32(pe)?);
+    let size    = u64::from(read_u32(pe)?);
+    Ok((address, size))
+}
+
+
+/// The `wCertificateType` of a `WIN_CERTIFICATE` holding a PKCS#7
+/// signature, which is the only kind Authenticode uses.
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+
+fn read_signature<R: Read + Seek>(pe: &mut R) -> io::Result<f::Signature> {
+    let headers = match read_headers(pe)? {
+        Some(h)  => h,
+        None     => return Ok(f::Signature::NotPE),
+    };
+
+    // Unlike the other data directories, the certificate table’s address
+    // is an offset into the file, rather than a virtual address.
+    let (table_offset, table_size) = read_directory(pe, &headers, CERTIFICATE_TABLE)?;
+
+    if table_offset == 0 && table_size == 0 {
+        return Ok(f::Signature::Unsigned);
+    }
+    else if table_size < 8 || table_offset + table_size > headers.len {
+        return Ok(f::Signature::Invalid);
+    }
+
+    let _ = pe.seek(SeekFrom::Start(table_offset))?;
+    let cert_length = u64::from(read_u32(pe)?);
+    let _revision   = read_u16(pe)?;
+    let cert_type   = read_u16(pe)?;
+
+    if cert_length < 8 || cert_length > table_size || cert_type != WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+        Ok(f::Signature::Invalid)
+    }
+    else {
+        Ok(f::Signature::Signed)
+    }
+}
+
+
+/// The `dwSignature` that starts a `VS_FIXEDFILEINFO` structure, followed
+/// by the only `dwStrucVersion` there has ever been.
+const FIXED_FILE_INFO: &[u8] = &[ 0xBD, 0x04, 0xEF, 0xFE, 0x00, 0x00, 0x01, 0x00 ];
+
+/// Resources sections bigger than this don’t get searched, so a huge
+/// installer with its payload in a resource doesn’t get read into memory.
+const MAX_RESOURCES_SIZE: u64 = 16 * 1024 * 1024;
+
+fn read_version<R: Read + Seek>(pe: &mut R) -> io::Result<f::Version> {
+    let headers = match read_headers(pe)? {
+        Some(h)  => h,
+        None     => return Ok(f::Version::None),
+    };
+
+    let (resources_rva, _) = read_directory(pe, &headers, RESOURCE_TABLE)?;
+    if resources_rva == 0 {
+        return Ok(f::Version::None);
+    }
+
+    // The resource table’s address is a virtual address, so the section
+    // containing it has to be found to know where it is in the file.
+    for index in 0 .. u64::from(headers.section_count) {
+        let _ = pe.seek(SeekFrom::Start(headers.sections + index * 40 + 8))?;
+        let virtual_size    = u64::from(read_u32(pe)?);
+        let virtual_address = u64::from(read_u32(pe)?);
+        let raw_size        = u64::from(read_u32(pe)?);
+        let raw_offset      = u64::from(read_u32(pe)?);
+
+        if resources_rva < virtual_address || resources_rva >= virtual_address + virtual_size.max(raw_size) {
+            continue;
+        }
+
+        if raw_size > MAX_RESOURCES_SIZE || raw_offset + raw_size > headers.len {
+            return Ok(f::Version::Unreadable);
+        }
+
+        let _ = pe.seek(SeekFrom::Start(raw_offset))?;
+        let section = read_bytes(pe, raw_size as usize)?;
+
+        // Rather than walking the resource directory tree, this just looks
+        // for the fixed part of the version resource, which is always
+        // aligned to four bytes.
+        let found = section.chunks(4)
+                           .position(|c| c == &FIXED_FILE_INFO[.. 4])
+                           .map(|i| i * 4)
+                           .filter(|&i| section[i ..].starts_with(FIXED_FILE_INFO) && i + 16 <= section.len());
+
+        return Ok(match found {
+            Some(i) => {
+                let ms = le_u32(&section[i + 8 ..]);
+                let ls = le_u32(&section[i + 12 ..]);
+                f::Version::Some(format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF))
+            }
+            None => f::Version::None,
+        });
+    }
+
+    Ok(f::Version::None)
+}
+
+
+fn read_bytes<R: Read>(r: &mut R, count: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; count];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u16<R: Read>(r: &mut R) -> io::Result<u16> {
+    let buf = read_bytes(r, 2)?;
+    Ok(u16::from(buf[0]) | u16::from(buf[1]) << 8)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    Ok(le_u32(&read_bytes(r, 4)?))
+}
+
+fn le_u32(buf: &[u8]) -> u32 {
+    u32::from(buf[0]) | u32::from(buf[1]) << 8 | u32::from(buf[2]) << 16 | u32::from(buf[3]) << 24
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    /// The offset that the data directories start at in the files built
+    /// below.
+    const DIRECTORIES: usize = 0x40 + 4 + 20 + 112;
+
+    /// The offset that `rest` starts at in the files built below.
+    const REST: u32 = (DIRECTORIES + 16 * 8 + 40) as u32;
+
+    /// Builds a minimal PE32+ file with the given data directories and one
+    /// section, followed by the given bytes.
+    fn pe(directories: &[(u64, u32, u32)], section: (u32, u32, u32, u32), rest: &[u8]) -> Cursor<Vec<u8>> {
+        let mut bytes = vec![0; 0x40];
+        bytes[0] = b'M';
+        bytes[1] = b'Z';
+        bytes[0x3C] = 0x40;
+
+        bytes.extend_from_slice(b"PE\0\0");
+        let mut coff = vec![0; 20];
+        coff[2] = 1;
+        coff[16] = (112 + 16 * 8) as u8;
+        bytes.extend_from_slice(&coff);
+
+        let mut optional = vec![0; 112 + 16 * 8];
+        optional[0] = 0x0B;
+        optional[1] = 0x02;
+        for &(index, address, size) in directories {
+            let at = 112 + index as usize * 8;
+            optional[at .. at + 4].copy_from_slice(&le32(address));
+            optional[at + 4 .. at + 8].copy_from_slice(&le32(size));
+        }
+        bytes.extend_from_slice(&optional);
+
+        let (virtual_size, virtual_address, raw_size, raw_offset) = section;
+        bytes.extend_from_slice(b".rsrc\0\0\0");
+        bytes.extend_from_slice(&le32(virtual_size));
+        bytes.extend_from_slice(&le32(virtual_address));
+        bytes.extend_from_slice(&le32(raw_size));
+        bytes.extend_from_slice(&le32(raw_offset));
+        bytes.extend_from_slice(&[0; 16]);
+
+        bytes.extend_from_slice(rest);
+        Cursor::new(bytes)
+    }
+
+    fn le32(n: u32) -> [u8; 4] {
+        [ n as u8, (n >> 8) as u8, (n >> 16) as u8, (n >> 24) as u8 ]
+    }
+
+    fn certificate(cert_type: u8) -> Vec<u8> {
+        let mut cert = le32(16).to_vec();
+        cert.extend_from_slice(&[ 0x00, 0x02, cert_type, 0x00 ]);
+        cert.extend_from_slice(&[0; 8]);
+        cert
+    }
+
+    #[test]
+    fn not_pe() {
+        let mut text = Cursor::new(b"#!/bin/sh\necho hello\n".to_vec());
+        assert_eq!(read_signature(&mut text).unwrap(), f::Signature::NotPE);
+        assert_eq!(read_version(&mut text).unwrap(), f::Version::None);
+    }
+
+    #[test]
+    fn dos_only() {
+        let mut dos = Cursor::new({ let mut b = vec![0; 0x80]; b[0] = b'M'; b[1] = b'Z'; b[0x3C] = 0x40; b });
+        assert_eq!(read_signature(&mut dos).unwrap(), f::Signature::NotPE);
+    }
+
+    #[test]
+    fn unsigned() {
+        assert_eq!(read_signature(&mut pe(&[], (0, 0, 0, 0), &[])).unwrap(), f::Signature::Unsigned);
+    }
+
+    #[test]
+    fn signed() {
+        let mut file = pe(&[ (CERTIFICATE_TABLE, REST, 16) ], (0, 0, 0, 0), &certificate(2));
+        assert_eq!(read_signature(&mut file).unwrap(), f::Signature::Signed);
+    }
+
+    #[test]
+    fn wrong_certificate_type() {
+        let mut file = pe(&[ (CERTIFICATE_TABLE, REST, 16) ], (0, 0, 0, 0), &certificate(1));
+        assert_eq!(read_signature(&mut file).unwrap(), f::Signature::Invalid);
+    }
+
+    #[test]
+    fn table_past_the_end() {
+        let mut file = pe(&[ (CERTIFICATE_TABLE, REST, 4096) ], (0, 0, 0, 0), &[0; 16]);
+        assert_eq!(read_signature(&mut file).unwrap(), f::Signature::Invalid);
+    }
+
+    #[test]
+    fn version() {
+        let mut resources = vec![0; 8];
+        resources.extend_from_slice(FIXED_FILE_INFO);
+        resources.extend_from_slice(&le32(10 << 16));
+        resources.extend_from_slice(&le32(19041 << 16 | 1));
+        resources.extend_from_slice(&[0; 8]);
+
+        let len = resources.len() as u32;
+        let mut file = pe(&[ (RESOURCE_TABLE, 0x3000, len) ], (len, 0x3000, len, REST), &resources);
+        assert_eq!(read_version(&mut file).unwrap(), f::Version::Some("10.0.19041.1".into()));
+    }
+
+    #[test]
+    fn no_resources() {
+        assert_eq!(read_version(&mut pe(&[], (0, 0, 0, 0), &[])).unwrap(), f::Version::None);
+    }
+
+    #[test]
+    fn no_version_resource() {
+        let resources = vec![0; 32];
+        let mut file = pe(&[ (RESOURCE_TABLE, 0x3000, 32) ], (32, 0x3000, 32, REST), &resources);
+        assert_eq!(read_version(&mut file).unwrap(), f::Version::None);
+    }
+}
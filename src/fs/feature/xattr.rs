@@ -49,6 +49,22 @@ pub struct Attribute {
     pub size: usize,
 }
 
+/// Whether a file’s attributes include a POSIX access control list, which
+/// Linux stores as the `system.posix_acl_*` attributes. A directory counts
+/// if it only has a default ACL for new files to inherit.
+pub fn has_acl(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(Attribute::is_acl)
+}
+
+impl Attribute {
+
+    /// Whether this is one of the attributes that a POSIX access control
+    /// list gets stored in, rather than an attribute in its own right.
+    pub fn is_acl(&self) -> bool {
+        self.name == "system.posix_acl_access" || self.name == "system.posix_acl_default"
+    }
+}
+
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 pub fn list_attrs(lister: &lister::Lister, path: &Path) -> io::Result<Vec<Attribute>> {
     use std::ffi::CString;
@@ -252,3 +268,39 @@ mod lister {
         }
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attr(name: &str) -> Attribute {
+        Attribute { name: name.into(), size: 28 }
+    }
+
+    #[test]
+    fn no_attributes() {
+        assert!(!has_acl(&[]));
+    }
+
+    #[test]
+    fn access_acl() {
+        assert!(has_acl(&[ attr("user.comment"), attr("system.posix_acl_access") ]));
+    }
+
+    #[test]
+    fn default_acl() {
+        assert!(has_acl(&[ attr("system.posix_acl_default") ]));
+    }
+
+    #[test]
+    fn other_attributes() {
+        assert!(!has_acl(&[ attr("user.comment"), attr("security.selinux") ]));
+    }
+
+    #[test]
+    fn acls_are_not_other_attributes() {
+        assert!(attr("system.posix_acl_access").is_acl());
+        assert!(!attr("user.comment").is_acl());
+    }
+}
@@ -15,6 +15,8 @@
 // C-style `blkcnt_t` types don’t follow Rust’s rules!
 #![allow(non_camel_case_types)]
 
+use std::path::PathBuf;
+
 
 /// The type of a file’s block count.
 pub type blkcnt_t = u64;
@@ -41,9 +43,7 @@ pub type uid_t = u32;
 /// This type is set entirely by the filesystem, rather than relying on a
 /// file’s contents. So “link” is a type, but “image” is just a type of
 /// regular file. (See the `filetype` module for those checks.)
-///
-/// Its ordering is used when sorting by type.
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
 pub enum Type {
     Directory, File, Link, Pipe, Socket, CharDevice, BlockDevice, Special,
 }
@@ -59,6 +59,7 @@ impl Type {
 
 
 /// The file’s Unix permission bitfield, with one entry per bit.
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub struct Permissions {
     pub user_read:      bool,
     pub user_write:     bool,
@@ -77,13 +78,30 @@ pub struct Permissions {
     pub setuid:         bool,
 }
 
-/// The three pieces of information that are displayed as a single column in
-/// the details view. These values are fused together to make the output a
+/// The pieces of information that are displayed as a single column in the
+/// details view. These values are fused together to make the output a
 /// little more compressed.
 pub struct PermissionsPlus {
     pub file_type:   Type,
     pub permissions: Permissions,
     pub xattrs:      bool,
+    pub acl:         bool,
+}
+
+impl PermissionsPlus {
+
+    /// Whether anyone at all can change this file, which gets it a warning
+    /// colour. Only regular files and directories count: links and devices
+    /// such as `/dev/null` are expected to be writable by everyone, and so
+    /// are directories with the sticky bit set, such as `/tmp`, where
+    /// people can only remove their own files.
+    pub fn is_world_writable(&self) -> bool {
+        match self.file_type {
+            Type::File       => self.permissions.other_write,
+            Type::Directory  => self.permissions.other_write && !self.permissions.sticky,
+            _                => false,
+        }
+    }
 }
 
 
@@ -109,6 +127,120 @@ pub struct Links {
 pub struct Inode(pub ino_t);
 
 
+/// Whether the current user can read, write, and execute a file, as opposed
+/// to what its permission bits say about its owner, group, and everyone else.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Access {
+    pub read:     bool,
+    pub write:    bool,
+    pub execute:  bool,
+}
+
+
+/// Whether a Windows executable has an Authenticode signature.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Signature {
+
+    /// The file has a well-formed signature. It hasn’t been verified.
+    Signed,
+
+    /// The file is a PE file with no signature.
+    Unsigned,
+
+    /// The file looks like a PE file, but its signature or headers are
+    /// broken, or it couldn’t be read.
+    Invalid,
+
+    /// The file isn’t a PE file at all.
+    NotPE,
+}
+
+
+/// The file version in a Windows executable’s `VERSIONINFO` resource.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Version {
+
+    /// The version, as four numbers separated by dots.
+    Some(String),
+
+    /// The file doesn’t have a version resource, or isn’t an executable.
+    None,
+
+    /// The file couldn’t be read, or its headers are broken.
+    Unreadable,
+}
+
+
+/// What kind of file something is, judging by its contents.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ContentType {
+    PE,
+    Elf,
+    Zip,
+    Ooxml,
+    Png,
+    Jpeg,
+    Pdf,
+    Text,
+    UTF16,
+    Binary,
+    Empty,
+
+    /// The file isn’t a regular file, or its contents couldn’t (or
+    /// shouldn’t) be read.
+    Unknown,
+}
+
+
+/// The hash of a file’s contents.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Checksum {
+
+    /// The hash, as a string of hex digits.
+    Some(String),
+
+    /// The file didn’t get hashed, because it isn’t a regular file, or
+    /// it’s too big, or it’s a cloud placeholder.
+    Skipped,
+
+    /// The file couldn’t be read.
+    Error,
+}
+
+
+/// Whether a file lives on a filesystem that’s served over the network.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Location {
+    Local,
+    Network,
+}
+
+
+/// The security zone that a file was downloaded from, as Windows records
+/// it in the file’s Mark of the Web. Zone 3 is the Internet.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Zone {
+
+    /// The file has no Mark of the Web.
+    Unmarked,
+
+    /// The file has one, and the zone number, if it could be read.
+    Marked(Option<u8>),
+}
+
+
+/// The volume — the mounted filesystem — that a file lives on.
+pub struct Volume {
+
+    /// The path that the volume is mounted at, if it could be found.
+    pub mount_point: Option<PathBuf>,
+
+    /// Whether this file is itself the root of the volume, meaning it lives
+    /// on a different volume from the directory it’s in.
+    pub is_mount_point: bool,
+}
+
+
 /// The number of blocks that a file takes up on the filesystem, if any.
 pub enum Blocks {
 
@@ -130,6 +262,7 @@ pub struct Group(pub gid_t);
 
 /// A file’s size, in bytes. This is usually formatted by the `number_prefix`
 /// crate into something human-readable.
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum Size {
 
     /// This file has a defined size.
@@ -153,6 +286,10 @@ pub enum Size {
     /// This is what ls does as well. Without it, the devices will just have
     /// file sizes of zero.
     DeviceIDs(DeviceIDs),
+
+    /// This file is a directory, so instead of a size, print out how many
+    /// entries it contains. This is only used when the user asks for it.
+    Entries(u64),
 }
 
 /// The major and minor device IDs that gets displayed for device files.
@@ -160,6 +297,7 @@ pub enum Size {
 /// You can see what these device numbers mean:
 /// - http://www.lanana.org/docs/device-list/
 /// - http://www.lanana.org/docs/device-list/devices-2.6+.txt
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub struct DeviceIDs {
     pub major: u8,
     pub minor: u8,
@@ -167,12 +305,57 @@ pub struct DeviceIDs {
 
 
 /// One of a file’s timestamps (created, accessed, or modified).
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// The seconds are counted from the Unix epoch, and are negative for times
+/// before 1970. The nanoseconds are always counted *forwards* from there,
+/// so they’re always between zero and one second.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct Time {
     pub seconds: time_t,
     pub nanoseconds: time_t,
 }
 
+/// The number of seconds between the start of 1601, which is when NTFS and
+/// Windows count their timestamps from, and the start of 1970.
+const NT_EPOCH_OFFSET: time_t = 11_644_473_600;
+
+/// The number of seconds between the start of 1970 and the start of 1980,
+/// which is the earliest time that FAT and exFAT can store.
+const FAT_EPOCH: time_t = 315_532_800;
+
+impl Time {
+
+    /// Builds a timestamp from the seconds and nanoseconds that the OS
+    /// gave us, moving any negative nanoseconds into the seconds.
+    ///
+    /// Returns nothing for a timestamp of zero on the NTFS epoch, because
+    /// that’s what Windows uses when it didn’t record a time at all, rather
+    /// than a time that really happened. Zero on the Unix epoch is a real
+    /// time: reproducible builds and package stores set their files’ times
+    /// to it on purpose.
+    pub fn from_raw(seconds: time_t, nanoseconds: time_t) -> Option<Time> {
+        let time = Time {
+            seconds:     seconds + nanoseconds.div_euclid(1_000_000_000),
+            nanoseconds: nanoseconds.rem_euclid(1_000_000_000),
+        };
+
+        if time.nanoseconds == 0 && time.seconds == -NT_EPOCH_OFFSET {
+            None
+        }
+        else {
+            Some(time)
+        }
+    }
+
+    /// Whether this is the earliest time that FAT and exFAT can store,
+    /// which they report for a time they never recorded. They store local
+    /// times, so the start of 1980 in any time zone counts.
+    pub fn is_fat_epoch(self) -> bool {
+        let offset = self.seconds - FAT_EPOCH;
+        self.nanoseconds == 0 && offset.abs() <= 14 * 60 * 60 && offset % (15 * 60) == 0
+    }
+}
+
 
 /// A file’s status in a Git repository. Whether a file is in a repository or
 /// not is handled by the Git module, rather than having a “null” variant in
@@ -200,6 +383,9 @@ pub enum GitStatus {
 
     /// A file that’s ignored (that matches a line in .gitignore)
     Ignored,
+
+    /// A file with a merge conflict that hasn’t been resolved yet.
+    Conflicted,
 }
 
 /// A file’s complete Git status. It’s possible to make changes to a file, add
@@ -210,6 +396,37 @@ pub struct Git {
     pub unstaged: GitStatus,
 }
 
+/// The state of the Git repository that a directory is in, for showing in
+/// the header above the directory’s contents.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GitRepoState {
+
+    /// What the repository’s HEAD points at.
+    pub head: GitHead,
+
+    /// Whether there are any changes in the repository that haven’t been
+    /// committed, not counting files that are only ignored.
+    pub dirty: bool,
+}
+
+/// What a repository’s HEAD points at.
+#[derive(PartialEq, Debug, Clone)]
+pub enum GitHead {
+
+    /// A branch, with its short name, such as “main”.
+    Branch(String),
+
+    /// A commit, rather than a branch, with its abbreviated hash.
+    Detached(String),
+
+    /// A branch that has no commits yet, as in a repository that’s just
+    /// been created with `git init`.
+    Unborn(String),
+
+    /// HEAD couldn’t be read.
+    Unknown,
+}
+
 use std::default::Default;
 impl Default for Git {
 
@@ -218,3 +435,100 @@ impl Default for Git {
         Git { staged: GitStatus::NotModified, unstaged: GitStatus::NotModified }
     }
 }
+
+
+#[cfg(test)]
+mod world_writable_test {
+    use super::*;
+
+    fn perms(file_type: Type, other_write: bool, sticky: bool) -> PermissionsPlus {
+        let permissions = Permissions {
+            user_read:  true,  user_write:  true,         user_execute:  false,  setuid: false,
+            group_read: true,  group_write: false,        group_execute: false,  setgid: false,
+            other_read: true,  other_write,               other_execute: false,  sticky,
+        };
+
+        PermissionsPlus { file_type, permissions, xattrs: false, acl: false }
+    }
+
+    #[test]
+    fn files() {
+        assert!(perms(Type::File, true, false).is_world_writable());
+        assert!(!perms(Type::File, false, false).is_world_writable());
+    }
+
+    #[test]
+    fn directories() {
+        assert!(perms(Type::Directory, true, false).is_world_writable());
+        assert!(!perms(Type::Directory, false, false).is_world_writable());
+    }
+
+    #[test]
+    fn sticky_directories() {
+        assert!(!perms(Type::Directory, true, true).is_world_writable());
+    }
+
+    #[test]
+    fn other_types() {
+        assert!(!perms(Type::Link, true, false).is_world_writable());
+        assert!(!perms(Type::CharDevice, true, false).is_world_writable());
+        assert!(!perms(Type::Pipe, true, false).is_world_writable());
+    }
+}
+
+
+#[cfg(test)]
+mod time_test {
+    use super::Time;
+
+    #[test]
+    fn modern() {
+        assert_eq!(Time::from_raw(1_500_000_000, 123), Some(Time { seconds: 1_500_000_000, nanoseconds: 123 }));
+    }
+
+    #[test]
+    fn last_second_of_1969() {
+        assert_eq!(Time::from_raw(-1, 0), Some(Time { seconds: -1, nanoseconds: 0 }));
+    }
+
+    #[test]
+    fn negative_nanoseconds() {
+        assert_eq!(Time::from_raw(0, -250_000_000), Some(Time { seconds: -1, nanoseconds: 750_000_000 }));
+    }
+
+    #[test]
+    fn overflowing_nanoseconds() {
+        assert_eq!(Time::from_raw(10, 1_500_000_000), Some(Time { seconds: 11, nanoseconds: 500_000_000 }));
+    }
+
+    #[test]
+    fn start_of_1601() {
+        assert_eq!(Time::from_raw(-11_644_473_600, 0), None);
+    }
+
+    #[test]
+    fn just_after_1601() {
+        assert_eq!(Time::from_raw(-11_644_473_599, 0), Some(Time { seconds: -11_644_473_599, nanoseconds: 0 }));
+    }
+
+    #[test]
+    fn start_of_1970() {
+        assert_eq!(Time::from_raw(0, 0), Some(Time { seconds: 0, nanoseconds: 0 }));
+    }
+
+    #[test]
+    fn start_of_1980() {
+        assert!(Time { seconds: 315_532_800, nanoseconds: 0 }.is_fat_epoch());
+    }
+
+    #[test]
+    fn start_of_1980_in_tokyo() {
+        assert!(Time { seconds: 315_532_800 - 9 * 60 * 60, nanoseconds: 0 }.is_fat_epoch());
+    }
+
+    #[test]
+    fn just_after_1980() {
+        assert!(!Time { seconds: 315_532_802, nanoseconds: 0 }.is_fat_epoch());
+        assert!(!Time { seconds: 315_532_800, nanoseconds: 10_000_000 }.is_fat_epoch());
+    }
+}
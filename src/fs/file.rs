@@ -1,13 +1,25 @@
 //! Files, and methods and fields to access their metadata.
 
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::io::Error as IOError;
 use std::io::Result as IOResult;
-use std::os::unix::fs::{MetadataExt, PermissionsExt, FileTypeExt};
-use std::path::{Path, PathBuf};
-
-use fs::dir::Dir;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{self, Component, Path, PathBuf};
+use std::sync::Arc;
+
+use fs::dir::{Dir, LowercaseNames, is_dotfile};
+use fs::feature::access;
+use fs::feature::checksum;
+use fs::feature::hard_links;
+use fs::feature::in_use;
+use fs::feature::network;
+use fs::feature::pe;
 use fs::fields as f;
+use fs::stat::Stat;
+use info::filetype;
+use info::magic;
 
 
 /// A **File** is a wrapper around one of Rust's Path objects, along with
@@ -24,13 +36,24 @@ pub struct File<'dir> {
     /// This is used to compare against certain filenames (such as checking if
     /// it’s “Makefile” or something) and to highlight only the filename in
     /// colour when displaying the path.
-    pub name: String,
+    ///
+    /// This is kept exactly as the OS gave it to us, because names aren’t
+    /// guaranteed to be valid Unicode, and converting them eagerly would
+    /// make two different names compare as equal. Use `display_name` to
+    /// get something that can be printed.
+    pub name: OsString,
 
     /// The file’s name’s extension, if present, extracted from the name.
     ///
-    /// This is queried many times over, so it’s worth caching it.
+    /// This is queried many times over, so it’s worth caching it. It’s
+    /// extracted from the display form of the name, as it only ever gets
+    /// compared against lists of known extensions.
     pub ext: Option<String>,
 
+    /// The file’s name’s *compound* extension, if present, which is its last
+    /// two extensions together, such as “tar.gz”.
+    pub compound_ext: Option<String>,
+
     /// The path that begat this file.
     ///
     /// Even though the file’s name is extracted, the path needs to be kept
@@ -55,54 +78,173 @@ pub struct File<'dir> {
     /// contain a reference to it, which is used in certain operations (such
     /// as looking up compiled files).
     pub parent_dir: Option<&'dir Dir>,
+
+    /// Whether this file was a symlink whose metadata has been swapped for
+    /// that of the file it points to. Directories reached this way don’t
+    /// get recursed into, as that could loop forever.
+    pub is_dereferenced: bool,
 }
 
 impl<'dir> File<'dir> {
     pub fn new<PD, FN>(path: PathBuf, parent_dir: PD, filename: FN) -> IOResult<File<'dir>>
     where PD: Into<Option<&'dir Dir>>,
-          FN: Into<Option<String>>
+          FN: Into<Option<OsString>>
+    {
+        debug!("Statting file {:?}", &path);
+        let metadata = fs::symlink_metadata(&path)?;
+
+        Ok(File::from_metadata(path, parent_dir, filename, metadata))
+    }
+
+    /// Creates a file from metadata that has already been read, such as
+    /// from a directory entry, rather than statting the path again. The
+    /// metadata should be the link’s own, not its target’s, just like the
+    /// metadata that `new` reads.
+    pub fn from_metadata<PD, FN>(path: PathBuf, parent_dir: PD, filename: FN, metadata: fs::Metadata) -> File<'dir>
+    where PD: Into<Option<&'dir Dir>>,
+          FN: Into<Option<OsString>>
     {
         let parent_dir = parent_dir.into();
         let name       = filename.into().unwrap_or_else(|| File::filename(&path));
         let ext        = File::ext(&path);
+        let compound_ext = File::compound_ext(&path);
 
-        debug!("Statting file {:?}", &path);
-        let metadata   = fs::symlink_metadata(&path)?;
+        File { path, parent_dir, metadata, ext, compound_ext, name, is_dereferenced: false }
+    }
+
+    /// If this file is a symlink, replaces its metadata with that of the
+    /// file it points to, so every column, its type, and its sort order
+    /// describe the target, while its name stays the link’s. Broken links
+    /// keep their own metadata, so they still show up as broken links.
+    pub fn dereference(&mut self) {
+        if !self.is_link() {
+            return;
+        }
 
-        Ok(File { path, parent_dir, metadata, ext, name })
+        match fs::metadata(&self.path) {
+            Ok(metadata) => {
+                self.metadata = metadata;
+                self.is_dereferenced = true;
+            }
+            Err(e) => {
+                debug!("Not dereferencing broken link {:?}: {}", &self.path, e);
+            }
+        }
+    }
+
+    /// Dereferences a symlink that was named on the command line, getting
+    /// the target’s metadata from `link_target`. Unlike `dereference`, a
+    /// broken link is an error, as the user asked for its target by name.
+    pub fn dereference_argument(&mut self) -> IOResult<()> {
+        if !self.is_link() {
+            return Ok(());
+        }
+
+        match self.link_target() {
+            FileTarget::Ok(target) => {
+                self.metadata = target.metadata;
+                self.is_dereferenced = true;
+                Ok(())
+            }
+            FileTarget::Broken(_, e) | FileTarget::Err(_, e) => Err(e),
+        }
     }
 
     /// A file’s name is derived from its string. This needs to handle directories
     /// such as `/` or `..`, which have no `file_name` component. So instead, just
     /// use the last component as the name.
-    pub fn filename(path: &Path) -> String {
+    pub fn filename(path: &Path) -> OsString {
         if let Some(back) = path.components().next_back() {
-            back.as_os_str().to_string_lossy().to_string()
+            back.as_os_str().to_os_string()
         }
         else {
             // use the path as fallback
             error!("Path {:?} has no last component", path);
-            path.display().to_string()
+            path.as_os_str().to_os_string()
         }
     }
 
+    /// The name of this file as something that can be displayed. This is
+    /// borrowed from the original name when it’s valid Unicode, and only
+    /// gets allocated when some of it has to be replaced.
+    pub fn display_name(&self) -> Cow<'_, str> {
+        self.name.to_string_lossy()
+    }
+
+    /// Whether this file’s name can’t be displayed without losing
+    /// information, in which case its display form contains replacement
+    /// characters that should be highlighted.
+    pub fn name_is_lossy(&self) -> bool {
+        is_lossy(&self.name)
+    }
+
+    /// The part of this file’s path that comes before its name, exactly as
+    /// it was typed, such as `../src/` or `C:\Users\`. Only files given on
+    /// the command line have one, as files read from a directory get shown
+    /// with just their names.
+    pub fn typed_dir(&self) -> String {
+        if self.parent_dir.is_some() {
+            return String::new();
+        }
+
+        dir_as_typed(&self.path.to_string_lossy(), &self.display_name()).to_owned()
+    }
+
     /// Extract an extension from a file path, if one is present, in lowercase.
     ///
     /// The extension is the series of characters after the last dot. This
     /// deliberately counts dotfiles, so the “.git” folder has the extension “git”.
     ///
-    /// ASCII lowercasing is used because these extensions are only compared
-    /// against a pre-compiled list of extensions which are known to only exist
-    /// within ASCII, so it’s alright.
+    /// Full Unicode lowercasing is used, rather than just ASCII, so that
+    /// extensions in other scripts get folded the same way as Latin ones.
     fn ext(path: &Path) -> Option<String> {
         let name = path.file_name().map(|f| f.to_string_lossy().to_string())?;
 
-        name.rfind('.').map(|p| name[p+1..].to_ascii_lowercase())
+        name.rfind('.').map(|p| name[p+1..].to_lowercase())
+    }
+
+    /// Extract a compound extension from a file path, if one is present, in
+    /// lowercase.
+    ///
+    /// The compound extension is the last two dot-separated parts of the
+    /// name, so “backup.tar.gz” has the compound extension “tar.gz” as well
+    /// as the extension “gz”. Like with extensions, dotfiles count, so the
+    /// dotfile “.tar.gz” has it too; but a name with an empty part in the
+    /// way, such as “backup..gz”, does not.
+    fn compound_ext(path: &Path) -> Option<String> {
+        let name = path.file_name().map(|f| f.to_string_lossy().to_string())?;
+
+        let last = name.rfind('.')?;
+        let second = name[.. last].rfind('.')?;
+
+        if second + 1 == last || last + 1 == name.len() {
+            None
+        }
+        else {
+            Some(name[second+1 ..].to_lowercase())
+        }
     }
 
     /// Whether this file is a directory on the filesystem.
     pub fn is_directory(&self) -> bool {
-        self.metadata.is_dir()
+        Stat::is_directory(&self.metadata)
+    }
+
+    /// Whether this file is a directory with nothing in it.
+    ///
+    /// This has to read the directory, so it only gets checked when it’s
+    /// needed, and it stops after the first entry rather than reading the
+    /// whole thing. The `.` and `..` entries don’t count. A directory that
+    /// can’t be read is treated as non-empty, as there’s no way to tell.
+    pub fn is_empty_dir(&self) -> bool {
+        if !self.is_directory() {
+            return false;
+        }
+
+        match fs::read_dir(&self.path) {
+            Ok(mut entries)  => entries.next().is_none(),
+            Err(_)           => false,
+        }
     }
 
     /// Whether this file is a directory, or a symlink pointing to a directory.
@@ -121,6 +263,18 @@ impl<'dir> File<'dir> {
         return false;
     }
 
+    /// Whether this file is a symlink pointing to a directory, including
+    /// one that has already been dereferenced. The recursive views only go
+    /// into these when asked to, as they can lead back up the tree.
+    pub fn is_link_to_directory(&self) -> bool {
+        if self.is_dereferenced {
+            self.is_directory()
+        }
+        else {
+            self.is_link() && self.points_to_directory()
+        }
+    }
+
     /// If this file is a directory on the filesystem, then clone its
     /// `PathBuf` for use in one of our own `Dir` values, and read a list of
     /// its contents.
@@ -131,10 +285,16 @@ impl<'dir> File<'dir> {
         Dir::read_dir(self.path.clone())
     }
 
+    /// Opens this file as a directory whose entries get read as they’re
+    /// listed, rather than all up front. (See `Dir::open`)
+    pub fn open_dir(&self) -> IOResult<Dir> {
+        Dir::open(self.path.clone())
+    }
+
     /// Whether this file is a regular file on the filesystem — that is, not a
     /// directory, a link, or anything else treated specially.
     pub fn is_file(&self) -> bool {
-        self.metadata.is_file()
+        self.metadata.is_regular_file()
     }
 
     /// Whether this file is both a regular file *and* executable for the
@@ -147,27 +307,33 @@ impl<'dir> File<'dir> {
 
     /// Whether this file is a symlink on the filesystem.
     pub fn is_link(&self) -> bool {
-        self.metadata.file_type().is_symlink()
+        self.metadata.is_symlink()
     }
 
     /// Whether this file is a named pipe on the filesystem.
     pub fn is_pipe(&self) -> bool {
-        self.metadata.file_type().is_fifo()
+        self.metadata.is_pipe()
     }
 
     /// Whether this file is a char device on the filesystem.
     pub fn is_char_device(&self) -> bool {
-        self.metadata.file_type().is_char_device()
+        Stat::is_char_device(&self.metadata)
     }
 
     /// Whether this file is a block device on the filesystem.
     pub fn is_block_device(&self) -> bool {
-        self.metadata.file_type().is_block_device()
+        Stat::is_block_device(&self.metadata)
     }
 
     /// Whether this file is a socket on the filesystem.
     pub fn is_socket(&self) -> bool {
-        self.metadata.file_type().is_socket()
+        Stat::is_socket(&self.metadata)
+    }
+
+    /// Whether this file is one that’s usually kept out of sight: a dotfile,
+    /// or one that Windows has given the hidden or system attribute.
+    pub fn is_hidden(&self) -> bool {
+        is_dotfile(&self.name) || filetype::has_hidden_attribute(self) || filetype::has_system_attribute(self)
     }
 
 
@@ -175,7 +341,7 @@ impl<'dir> File<'dir> {
     /// make it an absolute path that can be accessed from whichever
     /// directory exa is being run from.
     fn reorient_target_path(&self, path: &Path) -> PathBuf {
-        if path.is_absolute() {
+        let joined = if path.is_absolute() {
             path.to_path_buf()
         }
         else if let Some(dir) = self.parent_dir {
@@ -186,7 +352,9 @@ impl<'dir> File<'dir> {
         }
         else {
             self.path.join(&*path)
-        }
+        };
+
+        normalise(&joined)
     }
 
     /// Again assuming this file is a symlink, follows that link and returns
@@ -208,22 +376,27 @@ impl<'dir> File<'dir> {
         debug!("Reading link {:?}", &self.path);
         let path = match fs::read_link(&self.path) {
             Ok(p)   => p,
-            Err(e)  => return FileTarget::Err(e),
+            Err(e)  => {
+                error!("Error reading link {:?}: {:#?}", &self.path, e);
+                return FileTarget::Err(self.path.clone(), e);
+            }
         };
 
         let absolute_path = self.reorient_target_path(&path);
+        let path = normalise(&path);
 
         // Use plain `metadata` instead of `symlink_metadata` - we *want* to
         // follow links.
         match fs::metadata(&absolute_path) {
             Ok(metadata) => {
                 let ext  = File::ext(&path);
+                let compound_ext = File::compound_ext(&path);
                 let name = File::filename(&path);
-                FileTarget::Ok(Box::new(File { parent_dir: None, path, ext, metadata, name }))
+                FileTarget::Ok(Box::new(File { parent_dir: None, path, ext, compound_ext, metadata, name, is_dereferenced: false }))
             }
             Err(e) => {
                 error!("Error following link {:?}: {:#?}", &path, e);
-                FileTarget::Broken(path)
+                FileTarget::Broken(path, e)
             }
         }
     }
@@ -236,12 +409,34 @@ impl<'dir> File<'dir> {
     /// with multiple links much more often. Thus, it should get highlighted
     /// more attentively.
     pub fn links(&self) -> f::Links {
-        let count = self.metadata.nlink();
+        Stat::links(&self.metadata)
+    }
 
-        f::Links {
-            count,
-            multiple: self.is_file() && count > 1,
+    /// The other names this file goes by, if it’s a regular file with more
+    /// than one hard link.
+    ///
+    /// Nothing records where a file’s other links are, so this has to search
+    /// the whole of the volume the file is on, starting from its mount point.
+    /// That can take a while, so it’s only done when the user asks, and only
+    /// once for each volume. (See `hard_links::LinkIndex`)
+    ///
+    /// The search only finds real paths, so the file’s own path gets made
+    /// into one before it’s left out of the results. Otherwise, a file that
+    /// was reached through a symlinked directory would be one of its own
+    /// other names.
+    pub fn other_link_names(&self) -> Vec<PathBuf> {
+        if !self.is_file() || self.metadata.nlink() <= 1 {
+            return Vec::new();
         }
+
+        let own_name = match fs::canonicalize(&self.path) {
+            Ok(path)  => path,
+            Err(_)    => return Vec::new(),
+        };
+
+        let device = self.device();
+        let mount_point = mount_point_of(&own_name, device);
+        hard_links::index_for(device, &mount_point).other_names(self.metadata.ino(), &own_name)
     }
 
     /// This file's inode.
@@ -261,6 +456,229 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// The amount of space this file takes up on the filesystem, in bytes.
+    ///
+    /// Unlike the number of blocks above, this counts directories too, as
+    /// `ls` does when it prints its total. The block count is always in
+    /// units of 512 bytes, whatever size the filesystem’s blocks really are.
+    pub fn allocated_size(&self) -> u64 {
+        self.metadata.blocks() * 512
+    }
+
+    /// The ID of the device this file lives on. Every file on the same
+    /// volume has the same one.
+    pub fn device(&self) -> u64 {
+        self.metadata.dev()
+    }
+
+    /// Finds the path that this file’s volume is mounted at, by walking up
+    /// its absolute path until the parent is on a different device.
+    ///
+    /// This has to examine every directory on the way up, so it’s worth
+    /// caching the result for each device rather than calling it for
+    /// every file.
+    pub fn mount_point(&self) -> Option<PathBuf> {
+        let absolute = self.absolute_path()?;
+        Some(mount_point_of(&absolute, self.device()))
+    }
+
+    /// This file’s path, made relative to the given absolute directory, for
+    /// displaying it. Paths that can’t be made relative to it, such as ones
+    /// on another drive on Windows, are returned as absolute paths instead.
+    pub fn path_relative_to(&self, base: &Path) -> PathBuf {
+        let absolute = match self.absolute_path() {
+            Some(a)  => a,
+            None     => return self.path.clone(),
+        };
+
+        relative_path(&absolute, base).unwrap_or_else(|| {
+            debug!("Path {:?} can't be made relative to {:?}", absolute, base);
+            absolute
+        })
+    }
+
+    /// This file’s path, made absolute by joining it to the current
+    /// directory if it isn’t already, and then normalised.
+    pub fn absolute_path(&self) -> Option<PathBuf> {
+        if self.path.is_absolute() {
+            Some(normalise(&self.path))
+        }
+        else {
+            Some(normalise(&::std::env::current_dir().ok()?.join(&self.path)))
+        }
+    }
+
+    /// Whether the user running exa can read, write, and execute this file.
+    ///
+    /// This asks the kernel three times, so it only gets done when the
+    /// column is shown. There’s nothing to cache between files, as ACLs
+    /// can give two files with the same owner and mode different answers.
+    pub fn my_access(&self) -> f::Access {
+        f::Access {
+            read:     access::can_access(&self.path, access::Mode::Read),
+            write:    access::can_access(&self.path, access::Mode::Write),
+            execute:  access::can_access(&self.path, access::Mode::Execute),
+        }
+    }
+
+    /// Whether this file can only be changed by root, because the user
+    /// running exa isn’t allowed to write to it. This asks the kernel, so
+    /// it only gets done when the user asked for these files to be marked.
+    pub fn only_root_can_write(&self) -> bool {
+        access::only_root_can_write(&self.path)
+    }
+
+    /// Whether another process has this file open. Only regular files are
+    /// checked, and not placeholders, as opening those would fetch them.
+    pub fn is_in_use(&self) -> bool {
+        if !self.is_file() || self.is_placeholder() {
+            return false;
+        }
+
+        match fs::canonicalize(&self.path) {
+            Ok(path)  => in_use::is_open_elsewhere(&path),
+            Err(_)    => false,
+        }
+    }
+
+    /// Whether this file is a signed Windows executable. Only regular files
+    /// with an executable’s extension get opened to find out.
+    pub fn signature(&self) -> f::Signature {
+        if self.is_file() && self.extension_is_one_of(pe::PE_EXTENSIONS) {
+            pe::signature(&self.path)
+        }
+        else {
+            f::Signature::NotPE
+        }
+    }
+
+    /// The file version of this file, if it’s a Windows executable with a
+    /// version resource. As above, only executables get opened.
+    pub fn version_info(&self) -> f::Version {
+        if self.is_file() && self.extension_is_one_of(pe::PE_EXTENSIONS) {
+            pe::version(&self.path)
+        }
+        else {
+            f::Version::None
+        }
+    }
+
+    /// What kind of file this is, judging by the first few bytes of its
+    /// contents.
+    ///
+    /// Placeholder files don’t get read, as reading them would fetch them.
+    pub fn content_type(&self) -> f::ContentType {
+        use std::io::Read;
+
+        if !self.is_file() || self.is_placeholder() {
+            return f::ContentType::Unknown;
+        }
+
+        let mut sample = Vec::with_capacity(magic::SAMPLE_SIZE);
+        match fs::File::open(&self.path).and_then(|f| f.take(magic::SAMPLE_SIZE as u64).read_to_end(&mut sample)) {
+            Ok(_)   => magic::detect(&sample),
+            Err(_)  => f::ContentType::Unknown,
+        }
+    }
+
+    /// Hashes this file’s contents with the given algorithm. Only regular
+    /// files get hashed, and not placeholders or ones bigger than the limit,
+    /// as it means reading the whole thing.
+    pub fn checksum(&self, algorithm: checksum::Algorithm, limit: Option<u64>) -> IOResult<f::Checksum> {
+        let too_big = limit.map(|l| self.metadata.len() > l) == Some(true);
+
+        if !self.is_file() || self.is_placeholder() || too_big {
+            Ok(f::Checksum::Skipped)
+        }
+        else {
+            checksum::checksum(&self.path, algorithm).map(f::Checksum::Some)
+        }
+    }
+
+    /// Whether this file looks like a placeholder for a file that has been
+    /// moved off to cloud storage: one that’s bigger than a block, but has
+    /// no blocks allocated.
+    fn is_placeholder(&self) -> bool {
+        self.metadata.len() > 4096 && self.metadata.blocks() == 0
+    }
+
+    /// Whether this file lives on a network filesystem, such as NFS or SMB.
+    /// Every file on the same device gives the same answer, so it’s worth
+    /// caching the result for each device.
+    pub fn location(&self) -> f::Location {
+        if network::is_network_path(&self.path) { f::Location::Network }
+                                           else { f::Location::Local }
+    }
+
+    /// Whether this file carries the Mark of the Web, which Windows gives to
+    /// files downloaded from the internet. It lives in an alternate data
+    /// stream, which turns up next to the file as a file of its own called
+    /// `name:Zone.Identifier` when the file gets copied somewhere without
+    /// them, such as into WSL. Only regular files count, and a directory
+    /// with the stream’s name doesn’t.
+    pub fn has_motw(&self) -> bool {
+        self.zone_identifier().is_some()
+    }
+
+    /// The security zone that this file was downloaded from, read out of
+    /// its Mark of the Web.
+    pub fn zone(&self) -> f::Zone {
+        use std::io::Read;
+
+        let path = match self.zone_identifier() {
+            Some(path)  => path,
+            None        => return f::Zone::Unmarked,
+        };
+
+        let mut contents = Vec::new();
+        match fs::File::open(path).and_then(|f| f.take(4096).read_to_end(&mut contents)) {
+            Ok(_)   => f::Zone::Marked(zone_id(&String::from_utf8_lossy(&contents))),
+            Err(_)  => f::Zone::Marked(None),
+        }
+    }
+
+    /// The path of this file’s `Zone.Identifier` stream, if it has one.
+    fn zone_identifier(&self) -> Option<PathBuf> {
+        if !self.is_file() {
+            return None;
+        }
+
+        let mut name = self.path.file_name()?.to_os_string();
+        name.push(":Zone.Identifier");
+
+        let path = self.path.with_file_name(name);
+        match fs::metadata(&path) {
+            Ok(ref m) if m.is_file()  => Some(path),
+            _                         => None,
+        }
+    }
+
+    /// Whether this file lives on a FAT or exFAT filesystem, which makes up
+    /// times that it didn’t record.
+    pub fn is_on_fat(&self) -> bool {
+        network::is_fat_path(&self.path)
+    }
+
+    /// Whether this file’s filesystem records its owner and permissions,
+    /// rather than making them up. Every file on the same device gives the
+    /// same answer, so it’s worth caching the result for each device.
+    pub fn records_owners(&self) -> bool {
+        network::records_owners(&self.path)
+    }
+
+    /// Whether this file is a directory that another volume is mounted on,
+    /// meaning it lives on a different device from its parent.
+    pub fn is_mount_point(&self) -> bool {
+        if !self.is_directory() {
+            return false;
+        }
+
+        match fs::symlink_metadata(self.path.join("..")) {
+            Ok(parent)  => parent.dev() != self.device(),
+            Err(_)      => false,
+        }
+    }
+
     /// The ID of the user that own this file.
     pub fn user(&self) -> f::User {
         f::User(self.metadata.uid())
@@ -280,43 +698,53 @@ impl<'dir> File<'dir> {
     /// Block and character devices return their device IDs, because they
     /// usually just have a file size of zero.
     pub fn size(&self) -> f::Size {
-        if self.is_directory() {
+        Stat::size(&self.metadata)
+    }
+
+    /// The amount of space this file takes up on disk, for the “Disk”
+    /// column. Like the logical size above, this is left blank for
+    /// directories, and for devices, which only take up an inode.
+    pub fn disk_size(&self) -> f::Size {
+        if self.is_directory() || self.is_char_device() || self.is_block_device() {
             f::Size::None
         }
-        else if self.is_char_device() || self.is_block_device() {
-            let dev = self.metadata.rdev();
-            f::Size::DeviceIDs(f::DeviceIDs {
-                major: (dev / 256) as u8,
-                minor: (dev % 256) as u8,
-            })
-        }
         else {
-            f::Size::Some(self.metadata.len())
+            f::Size::Some(self.allocated_size())
         }
     }
 
-    /// This file’s last modified timestamp.
-    pub fn modified_time(&self) -> f::Time {
-        f::Time {
-            seconds:     self.metadata.mtime(),
-            nanoseconds: self.metadata.mtime_nsec()
-        }
+    /// The number of entries in this directory, not counting `.` and `..`,
+    /// or `None` if it can’t be read.
+    ///
+    /// The entries only get counted, not statted, so this is much cheaper
+    /// than listing the directory, but it still has to read all of it.
+    pub fn entry_count(&self) -> Option<u64> {
+        fs::read_dir(&self.path).ok()
+            .map(|entries| entries.count() as u64)
     }
 
-    /// This file’s created timestamp.
-    pub fn created_time(&self) -> f::Time {
-        f::Time {
-            seconds:     self.metadata.ctime(),
-            nanoseconds: self.metadata.ctime_nsec()
-        }
+    /// This file’s last modified timestamp, if it has one.
+    pub fn modified_time(&self) -> Option<f::Time> {
+        Stat::modified_time(&self.metadata)
     }
 
-    /// This file’s last accessed timestamp.
-    pub fn accessed_time(&self) -> f::Time {
-        f::Time {
-            seconds:     self.metadata.atime(),
-            nanoseconds: self.metadata.atime_nsec()
-        }
+    /// This file’s created timestamp, if it has one.
+    ///
+    /// Not every filesystem records when a file was born, and older kernels
+    /// can’t report it even when it does, so this is often missing.
+    pub fn created_time(&self) -> Option<f::Time> {
+        Stat::created_time(&self.metadata)
+    }
+
+    /// This file’s status changed timestamp (the “ctime”), which gets
+    /// updated whenever its contents or its metadata change.
+    pub fn changed_time(&self) -> Option<f::Time> {
+        Stat::changed_time(&self.metadata)
+    }
+
+    /// This file’s last accessed timestamp, if it has one.
+    pub fn accessed_time(&self) -> Option<f::Time> {
+        Stat::accessed_time(&self.metadata)
     }
 
     /// This file’s ‘type’.
@@ -325,30 +753,7 @@ impl<'dir> File<'dir> {
     /// The file type can usually be guessed from the colour of the file, but
     /// ls puts this character there.
     pub fn type_char(&self) -> f::Type {
-        if self.is_file() {
-            f::Type::File
-        }
-        else if self.is_directory() {
-            f::Type::Directory
-        }
-        else if self.is_pipe() {
-            f::Type::Pipe
-        }
-        else if self.is_link() {
-            f::Type::Link
-        }
-        else if self.is_char_device() {
-            f::Type::CharDevice
-        }
-        else if self.is_block_device() {
-            f::Type::BlockDevice
-        }
-        else if self.is_socket() {
-            f::Type::Socket
-        }
-        else {
-            f::Type::Special
-        }
+        Stat::type_char(&self.metadata)
     }
 
     /// This file’s permissions, with flags for each bit.
@@ -375,21 +780,140 @@ impl<'dir> File<'dir> {
         }
     }
 
-    /// Whether this file’s extension is any of the strings that get passed in.
+    /// Whether this file’s extension, or its compound extension, is any of
+    /// the strings that get passed in, ignoring case.
     ///
     /// This will always return `false` if the file has no extension.
     pub fn extension_is_one_of(&self, choices: &[&str]) -> bool {
-        match self.ext {
-            Some(ref ext)  => choices.contains(&&ext[..]),
+        let matches = |ext: &Option<String>| match *ext {
+            Some(ref ext)  => choices.iter().any(|c| ext_eq(ext, c)),
             None           => false,
-        }
+        };
+
+        matches(&self.ext) || matches(&self.compound_ext)
     }
 
     /// Whether this file's name, including extension, is any of the strings
     /// that get passed in.
     pub fn name_is_one_of(&self, choices: &[&str]) -> bool {
-        choices.contains(&&self.name[..])
+        choices.contains(&&*self.display_name())
+    }
+}
+
+
+/// Lexically normalises a path, removing any `.` components and resolving
+/// `..` components against the component before them, without touching the
+/// filesystem. A `..` that would climb above the root is dropped, and one at
+/// the start of a relative path is kept, as there’s nothing to resolve it
+/// against.
+pub fn normalise(path: &Path) -> PathBuf {
+    let mut components: Vec<Component> = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_))                          => { let _ = components.pop(); },
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {},
+                _                                                   => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+
+    if components.is_empty() {
+        PathBuf::from(".")
+    }
+    else {
+        components.iter().collect()
+    }
+}
+
+
+/// Works out the path that leads from the `base` directory to `path`
+/// lexically, without touching the filesystem, climbing out of the base
+/// with `..` components where needed. Both paths should be absolute and
+/// normalised already.
+///
+/// Returns nothing when there’s no way from one to the other: on Windows,
+/// paths on two different drives can’t be relative to each other.
+pub fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+
+    let common = path_components.iter().zip(base_components.iter())
+                                .take_while(|&(p, b)| p == b)
+                                .count();
+
+    let starts_with_prefix = |cs: &[Component]| matches!(cs.first(), Some(&Component::Prefix(_)));
+
+    if common == 0 && (starts_with_prefix(&path_components) || starts_with_prefix(&base_components)) {
+        return None;
     }
+
+    let mut relative = PathBuf::new();
+    for _ in common .. base_components.len() {
+        relative.push("..");
+    }
+    for component in &path_components[common ..] {
+        relative.push(component.as_os_str());
+    }
+
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+
+    Some(relative)
+}
+
+
+/// Finds the path that the volume with the given device is mounted at, by
+/// walking up the given absolute path until the parent is on a different
+/// device.
+fn mount_point_of(absolute: &Path, device: u64) -> PathBuf {
+    let mut mount_point = absolute;
+
+    for ancestor in absolute.ancestors().skip(1) {
+        match fs::symlink_metadata(ancestor) {
+            Ok(ref m) if m.dev() == device  => mount_point = ancestor,
+            _                               => break,
+        }
+    }
+
+    mount_point.to_path_buf()
+}
+
+
+/// Splits off the part of a path that comes before the given name, which is
+/// its last component, keeping whatever separators and prefixes it was typed
+/// with. Paths that are nothing but their name, such as `.` or `/`, have no
+/// part before it.
+fn dir_as_typed<'a>(path: &'a str, name: &str) -> &'a str {
+    path.trim_end_matches(path::is_separator)
+        .strip_suffix(name)
+        .unwrap_or("")
+}
+
+
+/// Whether converting the given name to a string would have to replace any
+/// part of it.
+pub fn is_lossy(name: &OsStr) -> bool {
+    name.to_str().is_none()
+}
+
+/// The zone number in the contents of a `Zone.Identifier` stream, which
+/// has it on a line such as `ZoneId=3` under a `[ZoneTransfer]` heading.
+fn zone_id(contents: &str) -> Option<u8> {
+    contents.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == "ZoneId")
+            .and_then(|(_, value)| value.trim().parse().ok())
+}
+
+/// Whether an extension, which has already been lowercased, is the same as
+/// the given choice once that has been lowercased too.
+fn ext_eq(ext: &str, choice: &str) -> bool {
+    choice.chars().flat_map(char::to_lowercase).eq(ext.chars())
 }
 
 
@@ -400,20 +924,130 @@ impl<'a> AsRef<File<'a>> for File<'a> {
 }
 
 
+/// A file that owns everything it knows about itself, including the path
+/// of the directory it was found in, rather than borrowing that directory.
+///
+/// A `File` can’t outlive the `Dir` it came from, so it can’t be sent to
+/// another thread or kept around once the directory is gone. Converting it
+/// to one of these lifts that restriction, and `as_file` borrows it back as
+/// a `File` when it needs to be displayed.
+#[derive(Debug, Clone)]
+pub struct OwnedFile {
+
+    /// The file’s name. (See `File::name`)
+    pub name: OsString,
+
+    /// The file’s name’s extension. (See `File::ext`)
+    pub ext: Option<String>,
+
+    /// The file’s name’s compound extension. (See `File::compound_ext`)
+    pub compound_ext: Option<String>,
+
+    /// The path that begat this file. (See `File::path`)
+    pub path: PathBuf,
+
+    /// The file’s metadata. (See `File::metadata`)
+    pub metadata: fs::Metadata,
+
+    /// The path of the directory that contains this file, if it was found
+    /// by reading one, rather than given on the command-line.
+    pub parent_path: Option<PathBuf>,
+
+    /// Whether the file’s metadata is its link target’s.
+    /// (See `File::is_dereferenced`)
+    pub is_dereferenced: bool,
+
+    /// The names of the other files in the same directory, shared between
+    /// every file that came from it, so whether this one was compiled from
+    /// one of them can be found without reading the directory again. Only
+    /// kept for files with an extension, as no others can be compiled.
+    siblings: Option<Arc<LowercaseNames>>,
+}
+
+impl<'dir> File<'dir> {
+
+    /// Copies this file’s fields into an `OwnedFile`, keeping its parent
+    /// directory’s path rather than a reference to it.
+    pub fn to_owned_file(&self) -> OwnedFile {
+        OwnedFile {
+            name:             self.name.clone(),
+            ext:              self.ext.clone(),
+            compound_ext:     self.compound_ext.clone(),
+            path:             self.path.clone(),
+            metadata:         self.metadata.clone(),
+            parent_path:      self.parent_dir.map(|d| d.path.clone()),
+            is_dereferenced:  self.is_dereferenced,
+            siblings:         self.sibling_names(),
+        }
+    }
+
+    /// The names of the files in this file’s directory, if it has an
+    /// extension and was found by reading one.
+    fn sibling_names(&self) -> Option<Arc<LowercaseNames>> {
+        match self.parent_dir {
+            Some(dir) if self.ext.is_some()  => Some(dir.lowercase_names()),
+            _                                => None,
+        }
+    }
+}
+
+impl<'dir> From<File<'dir>> for OwnedFile {
+    fn from(file: File<'dir>) -> OwnedFile {
+        OwnedFile {
+            siblings: file.sibling_names(),
+            parent_path: file.parent_dir.map(|d| d.path.clone()),
+            name: file.name,
+            ext: file.ext,
+            compound_ext: file.compound_ext,
+            path: file.path,
+            metadata: file.metadata,
+            is_dereferenced: file.is_dereferenced,
+        }
+    }
+}
+
+impl OwnedFile {
+
+    /// The names of the other files in this file’s directory, lowercased,
+    /// if they were kept when it was made. (See `OwnedFile::is_compiled`)
+    pub fn sibling_names(&self) -> Option<&LowercaseNames> {
+        self.siblings.as_deref()
+    }
+
+    /// Borrows this file back as a `File` in the given directory, which
+    /// should be the one at `parent_path`. Files without a parent path are
+    /// given no directory, whatever gets passed in, so they keep being
+    /// displayed as they were given.
+    pub fn as_file<'dir>(&self, parent_dir: Option<&'dir Dir>) -> File<'dir> {
+        File {
+            name:             self.name.clone(),
+            ext:              self.ext.clone(),
+            compound_ext:     self.compound_ext.clone(),
+            path:             self.path.clone(),
+            metadata:         self.metadata.clone(),
+            parent_dir:       if self.parent_path.is_some() { parent_dir } else { None },
+            is_dereferenced:  self.is_dereferenced,
+        }
+    }
+}
+
+
 /// The result of following a symlink.
 pub enum FileTarget<'dir> {
 
     /// The symlink pointed at a file that exists.
     Ok(Box<File<'dir>>),
 
-    /// The symlink pointed at a file that does not exist. Holds the path
-    /// where the file would be, if it existed.
-    Broken(PathBuf),
+    /// The symlink pointed at a file that does not exist, or that can’t be
+    /// reached. Holds the path where the file would be, if it existed, and
+    /// the error that came from trying to look at it.
+    Broken(PathBuf, IOError),
 
     /// There was an IO error when following the link. This can happen if the
     /// file isn’t a link to begin with, but also if, say, we don’t have
-    /// permission to follow it.
-    Err(IOError),
+    /// permission to follow it. Holds the path of the link that couldn’t be
+    /// read, along with the error.
+    Err(PathBuf, IOError),
 
     // Err is its own variant, instead of having the whole thing be inside an
     // `IOResult`, because being unable to follow a symlink is not a serious
@@ -426,8 +1060,8 @@ impl<'dir> FileTarget<'dir> {
     /// gets used to determine how to highlight the link in grid views.
     pub fn is_broken(&self) -> bool {
         match *self {
-            FileTarget::Ok(_)                           => false,
-            FileTarget::Broken(_) | FileTarget::Err(_)  => true,
+            FileTarget::Ok(_)                            => false,
+            FileTarget::Broken(..) | FileTarget::Err(..)  => true,
         }
     }
 }
@@ -479,12 +1113,70 @@ mod ext_test {
     fn no_extension() {
         assert_eq!(None, File::ext(Path::new("jarlsberg")))
     }
+
+    #[test]
+    fn compound() {
+        assert_eq!(Some("gz".to_string()), File::ext(Path::new("backup.tar.gz")));
+        assert_eq!(Some("tar.gz".to_string()), File::compound_ext(Path::new("backup.tar.gz")));
+    }
+
+    #[test]
+    fn compound_uppercase() {
+        assert_eq!(Some("tar.xz".to_string()), File::compound_ext(Path::new("BACKUP.TAR.XZ")));
+    }
+
+    #[test]
+    fn mixed_case() {
+        assert_eq!(Some("jpeg".to_string()), File::ext(Path::new("Photo.JpEg")));
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert_eq!(Some("ñandú".to_string()), File::ext(Path::new("notes.ÑANDÚ")));
+        assert_eq!(Some("σχέδιο".to_string()), File::ext(Path::new("plan.ΣΧΈΔΙΟ")));
+    }
+
+    #[test]
+    fn compound_non_ascii() {
+        assert_eq!(Some("ärchiv.gz".to_string()), File::compound_ext(Path::new("backup.ÄRCHIV.GZ")));
+    }
+
+    #[test]
+    fn ext_eq_ignores_case() {
+        assert!(super::ext_eq("jpeg", "JPEG"));
+        assert!(super::ext_eq("ñandú", "ÑANDÚ"));
+        assert!(!super::ext_eq("jpeg", "jpg"));
+    }
+
+    #[test]
+    fn compound_many_dots() {
+        assert_eq!(Some("tar.gz".to_string()), File::compound_ext(Path::new("exa-0.9.0.tar.gz")));
+    }
+
+    #[test]
+    fn compound_dotfile() {
+        assert_eq!(Some("tar.gz".to_string()), File::compound_ext(Path::new(".tar.gz")));
+    }
+
+    #[test]
+    fn no_compound_single_extension() {
+        assert_eq!(None, File::compound_ext(Path::new("fester.dat")));
+        assert_eq!(None, File::compound_ext(Path::new(".vimrc")));
+    }
+
+    #[test]
+    fn no_compound_empty_part() {
+        assert_eq!(None, File::compound_ext(Path::new("backup..gz")));
+        assert_eq!(None, File::compound_ext(Path::new("backup.tar.")));
+    }
 }
 
 
 #[cfg(test)]
 mod filename_test {
-    use super::File;
+    use super::{File, is_lossy};
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
     use std::path::Path;
 
     #[test]
@@ -516,4 +1208,365 @@ mod filename_test {
     fn topmost() {
         assert_eq!("/", File::filename(Path::new("/")))
     }
+
+    #[test]
+    fn invalid_unicode() {
+        let name = OsStr::from_bytes(b"bad\xFFname");
+        assert_eq!(name, File::filename(Path::new(name)));
+        assert!(is_lossy(&File::filename(Path::new(name))));
+    }
+
+    #[test]
+    fn valid_unicode() {
+        assert!(!is_lossy(&File::filename(Path::new("caf\u{e9}"))));
+    }
+}
+
+
+#[cfg(test)]
+mod typed_dir_test {
+    use super::{File, dir_as_typed};
+    use std::path::PathBuf;
+
+    #[test]
+    fn bare_name() {
+        assert_eq!(dir_as_typed("foo.txt", "foo.txt"), "");
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(dir_as_typed("../src/main.rs", "main.rs"), "../src/");
+        assert_eq!(dir_as_typed("./a//b.txt", "b.txt"), "./a//");
+    }
+
+    #[test]
+    fn absolute() {
+        assert_eq!(dir_as_typed("/usr/bin/exa", "exa"), "/usr/bin/");
+        assert_eq!(dir_as_typed("/etc", "etc"), "/");
+    }
+
+    #[test]
+    fn drive() {
+        assert_eq!(dir_as_typed("C:\\a\\foo.txt", "foo.txt"), "C:\\a\\");
+        assert_eq!(dir_as_typed("D:foo.txt", "foo.txt"), "D:");
+    }
+
+    #[test]
+    fn unc() {
+        assert_eq!(dir_as_typed("\\\\server\\share\\docs\\foo.txt", "foo.txt"), "\\\\server\\share\\docs\\");
+    }
+
+    #[test]
+    fn only_a_name() {
+        assert_eq!(dir_as_typed("/", "/"), "");
+        assert_eq!(dir_as_typed(".", "."), "");
+        assert_eq!(dir_as_typed("src/", "src"), "");
+    }
+
+    #[test]
+    fn from_the_command_line() {
+        let file = File::new(PathBuf::from("src/../Cargo.toml"), None, None).unwrap();
+        assert_eq!(file.name, "Cargo.toml");
+        assert_eq!(file.typed_dir(), "src/../");
+    }
+}
+
+
+#[cfg(test)]
+mod normalise_test {
+    use super::normalise;
+    use std::path::{Path, PathBuf};
+
+    fn norm(input: &str) -> PathBuf {
+        normalise(Path::new(input))
+    }
+
+    #[test]
+    fn already_normal() {
+        assert_eq!(norm("/usr/bin/exa"), PathBuf::from("/usr/bin/exa"));
+    }
+
+    #[test]
+    fn current_dirs() {
+        assert_eq!(norm("./a/./b/."), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    fn parent_dirs() {
+        assert_eq!(norm("/home/user/links/../../other/file"), PathBuf::from("/home/other/file"));
+    }
+
+    #[test]
+    fn above_the_root() {
+        assert_eq!(norm("/a/../../../b"), PathBuf::from("/b"));
+    }
+
+    #[test]
+    fn just_the_root() {
+        assert_eq!(norm("/.."), PathBuf::from("/"));
+    }
+
+    #[test]
+    fn just_parent() {
+        assert_eq!(norm(".."), PathBuf::from(".."));
+    }
+
+    #[test]
+    fn parent_of_relative_dir() {
+        assert_eq!(norm("dir/.."), PathBuf::from("."));
+    }
+
+    #[test]
+    fn leading_parents_kept() {
+        assert_eq!(norm("dir/../../../other/file"), PathBuf::from("../../other/file"));
+    }
+}
+
+
+#[cfg(test)]
+mod relative_test {
+    use super::relative_path;
+    use std::path::{Path, PathBuf};
+
+    fn rel(path: &str, base: &str) -> Option<PathBuf> {
+        relative_path(Path::new(path), Path::new(base))
+    }
+
+    #[test]
+    fn inside() {
+        assert_eq!(rel("/project/src/main.rs", "/project"), Some(PathBuf::from("src/main.rs")));
+    }
+
+    #[test]
+    fn same() {
+        assert_eq!(rel("/project", "/project"), Some(PathBuf::from(".")));
+    }
+
+    #[test]
+    fn sibling() {
+        assert_eq!(rel("/project/docs/a.md", "/project/src"), Some(PathBuf::from("../docs/a.md")));
+    }
+
+    #[test]
+    fn above() {
+        assert_eq!(rel("/project", "/project/src/bin"), Some(PathBuf::from("../..")));
+    }
+
+    #[test]
+    fn nothing_in_common() {
+        assert_eq!(rel("/usr/bin/exa", "/home/ben"), Some(PathBuf::from("../../usr/bin/exa")));
+    }
+
+    #[test]
+    fn common_name_prefix() {
+        assert_eq!(rel("/project-old/a", "/project"), Some(PathBuf::from("../project-old/a")));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn same_drive() {
+        assert_eq!(rel("C:\\project\\src", "C:\\project"), Some(PathBuf::from("src")));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn different_drives() {
+        assert_eq!(rel("D:\\data\\file.txt", "C:\\project"), None);
+    }
+}
+
+
+#[cfg(test)]
+mod target_test {
+    use super::{File, FileTarget};
+    use fs::test_dir::TempDir;
+    use std::io::{Error as IOError, ErrorKind};
+    use std::path::PathBuf;
+    use libc;
+
+    #[test]
+    fn ok() {
+        let file = File::new(PathBuf::from("Cargo.toml"), None, None).unwrap();
+        assert!(!FileTarget::Ok(Box::new(file)).is_broken());
+    }
+
+    #[test]
+    fn broken() {
+        let target = FileTarget::Broken(PathBuf::from("nowhere"), IOError::from(ErrorKind::NotFound));
+        assert!(target.is_broken());
+    }
+
+    #[test]
+    fn err() {
+        let target = FileTarget::Err(PathBuf::from("/proc/1/root"), IOError::from(ErrorKind::PermissionDenied));
+        assert!(target.is_broken());
+    }
+
+    #[test]
+    fn err_holds_the_unreadable_path() {
+        let temp = TempDir::new("target-err");
+        let path = temp.touch("not-a-link");
+        let file = File::new(path.clone(), None, None).unwrap();
+
+        match file.link_target() {
+            FileTarget::Err(p, e) => {
+                assert_eq!(p, path);
+                assert_eq!(e.raw_os_error(), Some(libc::EINVAL));
+            },
+            _ => panic!("{:?} was read as a link", path),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod motw_test {
+    use super::*;
+    use fs::test_dir::TempDir;
+
+    fn temp_dir(name: &str) -> TempDir {
+        let temp = TempDir::new(&format!("motw-{}", name));
+        fs::write(temp.join("setup.exe"), b"MZ").unwrap();
+        temp
+    }
+
+    #[test]
+    fn unmarked() {
+        let temp = temp_dir("unmarked");
+        let file = File::new(temp.join("setup.exe"), None, None).unwrap();
+
+        assert!(!file.has_motw());
+        assert_eq!(file.zone(), f::Zone::Unmarked);
+    }
+
+    #[test]
+    fn internet() {
+        let temp = temp_dir("internet");
+        fs::write(temp.join("setup.exe:Zone.Identifier"), b"[ZoneTransfer]\r\nZoneId=3\r\nHostUrl=https://example.com/\r\n").unwrap();
+        let file = File::new(temp.join("setup.exe"), None, None).unwrap();
+
+        assert!(file.has_motw());
+        assert_eq!(file.zone(), f::Zone::Marked(Some(3)));
+    }
+
+    #[test]
+    fn directory_of_the_same_name() {
+        let temp = temp_dir("directory");
+        fs::create_dir(temp.join("setup.exe:Zone.Identifier")).unwrap();
+        let file = File::new(temp.join("setup.exe"), None, None).unwrap();
+
+        assert!(!file.has_motw());
+    }
+
+    #[test]
+    fn zone_ids() {
+        assert_eq!(zone_id("[ZoneTransfer]\nZoneId=4\n"), Some(4));
+        assert_eq!(zone_id("[ZoneTransfer]\r\nZoneId = 2 \r\n"), Some(2));
+        assert_eq!(zone_id("[ZoneTransfer]\r\n"), None);
+        assert_eq!(zone_id("ZoneId=Internet"), None);
+    }
+}
+
+
+#[cfg(test)]
+mod dereference_argument_test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use std::os::unix::fs::symlink;
+
+    fn temp_dir(name: &str) -> TempDir {
+        let temp = TempDir::new(&format!("deref-arg-{}", name));
+        fs::write(temp.join("target.txt"), b"some data").unwrap();
+        temp
+    }
+
+    #[test]
+    fn link_to_file() {
+        let temp = temp_dir("file");
+        symlink(temp.join("target.txt"), temp.join("link")).unwrap();
+
+        let mut file = File::new(temp.join("link"), None, None).unwrap();
+        file.dereference_argument().unwrap();
+
+        assert!(file.is_file());
+        assert!(file.is_dereferenced);
+        assert_eq!(file.name, "link");
+    }
+
+    #[test]
+    fn broken_link() {
+        let temp = temp_dir("broken");
+        symlink(temp.join("missing.txt"), temp.join("link")).unwrap();
+
+        let mut file = File::new(temp.join("link"), None, None).unwrap();
+        assert_eq!(file.dereference_argument().unwrap_err().kind(), ::std::io::ErrorKind::NotFound);
+        assert!(file.is_link());
+    }
+
+    #[test]
+    fn not_a_link() {
+        let temp = temp_dir("plain");
+
+        let mut file = File::new(temp.join("target.txt"), None, None).unwrap();
+        file.dereference_argument().unwrap();
+
+        assert!(!file.is_dereferenced);
+    }
+}
+
+#[cfg(test)]
+mod owned_test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use std::thread;
+
+    fn temp_dir(name: &str) -> TempDir {
+        let temp = TempDir::new(&format!("owned-{}", name));
+        fs::write(temp.join("data.txt"), b"some data").unwrap();
+        temp
+    }
+
+    #[test]
+    fn round_trip() {
+        let temp = temp_dir("round-trip");
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let file = dir.files(Default::default(), None).next().unwrap().unwrap();
+
+        let owned = file.to_owned_file();
+        let borrowed = owned.as_file(Some(&dir));
+
+        assert_eq!(owned.parent_path, Some(temp.path().to_path_buf()));
+        assert_eq!(borrowed.name, file.name);
+        assert_eq!(borrowed.ext, Some("txt".to_string()));
+        assert_eq!(borrowed.size(), file.size());
+        assert!(borrowed.parent_dir.is_some());
+    }
+
+    #[test]
+    fn argument_files_stay_without_a_parent() {
+        let temp = temp_dir("argument");
+        let file = File::new(temp.join("data.txt"), None, None).unwrap();
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let owned = OwnedFile::from(file);
+
+        assert_eq!(owned.parent_path, None);
+        assert!(owned.as_file(Some(&dir)).parent_dir.is_none());
+    }
+
+    #[test]
+    fn move_into_threads() {
+        let temp = temp_dir("threads");
+        let owned: Vec<OwnedFile> = {
+            let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+            dir.files(Default::default(), None).map(|f| f.unwrap().into()).collect()
+        };
+
+        let handles: Vec<_> = owned.into_iter()
+            .map(|file| thread::spawn(move || (file.as_file(None).display_name().into_owned(), file.as_file(None).size())))
+            .collect();
+
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(results, vec![ ("data.txt".to_string(), f::Size::Some(9)) ]);
+    }
 }
@@ -1,15 +1,24 @@
 //! Filtering and sorting the list of files before displaying them.
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::iter::FromIterator;
 use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 
 use glob;
 use natord;
+use users::{Users, UsersCache, uid_t};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
 use fs::File;
 use fs::DotFilter;
+use fs::fields as f;
+use fs::feature::git::GitCache;
+use fs::pattern::{NamePattern, PatternCase};
+use info::filetype::TypeGroup;
+use output::table::TimeType;
 
 
 /// The **file filter** processes a list of files before displaying them to
@@ -27,12 +36,17 @@ use fs::DotFilter;
 /// files are compared and sorted based on the result, with the sort field
 /// performing the comparison.
 #[derive(PartialEq, Debug, Clone)]
+#[non_exhaustive]
 pub struct FileFilter {
 
     /// Whether directories should be listed first, and other types of file
     /// second. Some users prefer it like this.
     pub list_dirs_first: bool,
 
+    /// Whether hidden files, including ones with the Windows system
+    /// attribute, should be listed after all the others.
+    pub list_hidden_last: bool,
+
     /// The metadata field to sort by.
     pub sort_field: SortField,
 
@@ -44,6 +58,24 @@ pub struct FileFilter {
     /// Whether to only show directories.
     pub only_dirs: bool,
 
+    /// Whether to only show files that Git sees as changed, along with
+    /// the directories that have changes inside them.
+    pub only_git_changes: bool,
+
+    /// The window of time that files’ timestamps have to be in for them to
+    /// be shown.
+    pub time_range: TimeRange,
+
+    /// Whether to describe the files that symlinks point to, rather than
+    /// the links themselves. This happens before any filtering or sorting,
+    /// so those use the same metadata that gets displayed.
+    pub dereference: bool,
+
+    /// Whether to describe the files that symlinks named on the command
+    /// line point to, leaving the links found inside directories alone.
+    /// This does nothing more when everything is being dereferenced.
+    pub dereference_args: bool,
+
     /// Which invisible “dot” files to include when listing a directory.
     ///
     /// Files starting with a single “.” are used to determine “system” or
@@ -89,20 +121,78 @@ pub struct FileFilter {
     /// repository scanning — a `.gitignore` file will still be scanned even
     /// if there’s no `.git` folder present.
     pub git_ignore: GitIgnore,
+
+    /// Whether to ignore the patterns in `.exaignore` files, which work
+    /// like `.gitignore` files, but only for exa.
+    pub exa_ignore: ExaIgnore,
+
+    /// Whether the patterns in `.gitignore` and `.exaignore` files care
+    /// about the case of file names.
+    pub pattern_case: PatternCase,
 }
 
+/// The filter that the `exa` binary uses when it’s given no options: dot
+/// files and anything in an `.exaignore` file get hidden, and files are
+/// sorted by name.
+impl Default for FileFilter {
+    fn default() -> FileFilter {
+        FileFilter {
+            list_dirs_first:  false,
+            list_hidden_last: false,
+            sort_field:       SortField::default(),
+            reverse:          false,
+            only_dirs:        false,
+            only_git_changes: false,
+            time_range:       TimeRange::default(),
+            dereference:      false,
+            dereference_args: false,
+            dot_filter:       DotFilter::default(),
+            ignore_patterns:  IgnorePatterns::default(),
+            git_ignore:       GitIgnore::Off,
+            exa_ignore:       ExaIgnore::CheckAndIgnore { depth: None },
+            pattern_case:     PatternCase::default(),
+        }
+    }
+}
 
 impl FileFilter {
     /// Remove every file in the given vector that does *not* pass the
-    /// filter predicate for files found inside a directory.
-    pub fn filter_child_files(&self, files: &mut Vec<File>) {
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
+    /// filter predicate for files found inside a directory. Symlinks get
+    /// dereferenced first, if they’re going to be, so the filter sees the
+    /// same files that get displayed.
+    ///
+    /// When the directories are going to be recursed into, they’re kept
+    /// whatever their timestamps, as `find -newer` does: a directory’s
+    /// timestamp says nothing about the files further down, so an old one
+    /// can still have new files in it.
+    pub fn filter_child_files(&self, files: &mut Vec<File>, recursing: bool) {
+        self.dereference_files(files);
+        files.retain(|f| !self.ignore_patterns.is_ignored(&f.display_name()));
+        files.retain(|f| (recursing && f.is_directory()) || self.time_range.contains(f));
 
         if self.only_dirs {
             files.retain(|f| f.is_directory());
         }
     }
 
+    /// Remove every file that Git doesn’t see as changed, if only changes
+    /// are being shown. Directories stay when there’s a change somewhere
+    /// inside them, so the tree view shows the path to it. Without a
+    /// repository to ask, nothing counts as changed.
+    ///
+    /// This is separate from the other filters because it needs the Git
+    /// cache, which only exists once the statuses are going to be read.
+    pub fn filter_git_changes(&self, files: &mut Vec<File>, git: Option<&GitCache>) {
+        if !self.only_git_changes {
+            return;
+        }
+
+        match git {
+            Some(git) => files.retain(|f| git.is_changed(&f.path, f.is_directory())),
+            None      => files.clear(),
+        }
+    }
+
     /// Remove every file in the given vector that does *not* pass the
     /// filter predicate for file names specified on the command-line.
     ///
@@ -112,15 +202,69 @@ impl FileFilter {
     /// dotfile, because it’s been directly specified. But running
     /// `exa -I='*.ogg' music/*` should filter out the ogg files obtained
     /// from the glob, even though the globbing is done by the shell!
-    pub fn filter_argument_files(&self, files: &mut Vec<File>) {
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
+    ///
+    /// Directories are never ignored, though, whatever the view: naming an
+    /// ignored directory is asking to see it, so `exa -I node_modules
+    /// node_modules` lists it, with or without `--tree` or `--list-dirs`.
+    /// When recursing, directories outside the time range are kept too, as
+    /// `filter_child_files` keeps them.
+    pub fn filter_argument_files(&self, files: &mut Vec<File>, recursing: bool) {
+        self.dereference_files(files);
+        files.retain(|f| {
+            let is_dir = f.is_directory();
+            (is_dir || !self.ignore_patterns.is_ignored(&f.display_name()))
+                && ((recursing && is_dir) || self.time_range.contains(f))
+        });
+    }
+
+    /// Swap in the metadata of the files that the symlinks in the given
+    /// vector point to, if the user asked for links to be dereferenced.
+    fn dereference_files(&self, files: &mut Vec<File>) {
+        if self.dereference {
+            for file in files.iter_mut() {
+                file.dereference();
+            }
+        }
     }
 
     /// Sort the files in the given vector based on the sort field option.
-    pub fn sort_files<'a, F>(&self, files: &mut Vec<F>)
+    /// Sorting by Git status needs the statuses in the given cache; files
+    /// it knows nothing about get sorted as though they were unchanged.
+    pub fn sort_files<'a, F>(&self, files: &mut Vec<F>, git: Option<&GitCache>)
     where F: AsRef<File<'a>> {
 
-        files.sort_by(|a, b| self.sort_field.compare_files(a.as_ref(), b.as_ref()));
+        if self.sort_field == SortField::GitStatus {
+            // Sorting by name first and then by rank, which `sort_by_cached_key`
+            // does stably, makes the name break ties without each file’s
+            // status having to be looked up more than once.
+            files.sort_by(|a, b| SortField::compare_names(a.as_ref(), b.as_ref(), SortCase::AaBbCc, false));
+            files.sort_by_cached_key(|f| git_rank(f.as_ref(), git));
+        }
+        else if self.sort_field == SortField::SizeOrEntries {
+            // Counting a directory’s entries means reading all of it, so
+            // each one gets counted once rather than on every comparison.
+            files.sort_by_cached_key(|f| size_or_entries(f.as_ref()));
+        }
+        else if self.sort_field == SortField::Owner {
+            // Looking up a user means reading the password database, so
+            // each owner gets looked up once, rather than once for every
+            // comparison the sort makes.
+            let users = UsersCache::new();
+            let mut owners = HashMap::new();
+            for file in files.iter() {
+                let uid = file.as_ref().user().0;
+                let _ = owners.entry(uid).or_insert_with(|| owner_name(&users, uid));
+            }
+
+            files.sort_by(|a, b| {
+                let (a, b) = (a.as_ref(), b.as_ref());
+                compare_owners(owners[&a.user().0].as_deref(), owners[&b.user().0].as_deref())
+                    .then_with(|| SortField::compare_names(a, b, SortCase::AaBbCc, false))
+            });
+        }
+        else {
+            files.sort_by(|a, b| self.sort_field.compare_files(a.as_ref(), b.as_ref()));
+        }
 
         if self.reverse {
             files.reverse();
@@ -131,12 +275,68 @@ impl FileFilter {
             // adjacent elements next to each other.
             files.sort_by(|a, b| b.as_ref().is_directory().cmp(&a.as_ref().is_directory()));
         }
+
+        if self.list_hidden_last {
+            // This goes last so that it splits the list in two, with each
+            // half keeping the order from above, even when reversed.
+            files.sort_by_key(|f| f.as_ref().is_hidden());
+        }
+    }
+}
+
+
+/// The window of time that files’ timestamps have to be in for them to be
+/// shown, from `--newer-than` and `--older-than`. Both ends are inclusive.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct TimeRange {
+
+    /// Which of a file’s timestamps gets compared.
+    pub time_type: TimeType,
+
+    /// The earliest a file’s timestamp can be, if there’s a limit.
+    pub newer_than: Option<f::Time>,
+
+    /// The latest a file’s timestamp can be, if there’s a limit.
+    pub older_than: Option<f::Time>,
+}
+
+impl Default for TimeRange {
+    fn default() -> TimeRange {
+        TimeRange { time_type: TimeType::Modified, newer_than: None, older_than: None }
+    }
+}
+
+impl TimeRange {
+
+    /// Whether the given file’s timestamp is inside the window.
+    ///
+    /// A file that doesn’t have the timestamp, such as a creation time on a
+    /// filesystem that doesn’t record one, can’t be shown to be new enough,
+    /// so it gets hidden by `--newer-than`. But `--older-than` is usually
+    /// used to find files to clean up, and hiding a file there would make it
+    /// look like there’s nothing to clean, so it gets kept.
+    pub fn contains(&self, file: &File) -> bool {
+        if self.newer_than.is_none() && self.older_than.is_none() {
+            return true;
+        }
+
+        self.contains_time(self.time_type.of(file))
+    }
+
+    fn contains_time(&self, time: Option<f::Time>) -> bool {
+        match time {
+            Some(time) => self.newer_than.is_none_or(|t| time >= t)
+                       && self.older_than.is_none_or(|t| time <= t),
+            None       => self.newer_than.is_none(),
+        }
     }
 }
 
 
 /// User-supplied field to sort by.
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum SortField {
 
     /// Don’t apply any sorting. This is usually used as an optimisation in
@@ -144,14 +344,31 @@ pub enum SortField {
     Unsorted,
 
     /// The file name. This is the default sorting.
+    ///
+    /// Names are compared after being decomposed and having their accents
+    /// stripped, so `Ärger` sorts next to `Arger` rather than after `Zebra`.
     Name(SortCase),
 
+    /// The file name, compared byte-by-byte without any collation. This
+    /// order is less friendly, but never changes between versions.
+    NameRaw,
+
     /// The file’s extension, with extensionless files being listed first.
     Extension(SortCase),
 
     /// The file’s size, in bytes.
     Size,
 
+    /// The file’s size, in bytes, for files; and the number of entries it
+    /// contains, for directories. As these can’t be compared with each
+    /// other, directories get listed first. This is used when the size
+    /// column is showing directories’ entry counts.
+    SizeOrEntries,
+
+    /// The amount of space the file takes up on disk, in bytes. This is
+    /// used instead of the file’s size when only the disk size is shown.
+    DiskSize,
+
     /// The file’s inode, which usually corresponds to the order in which
     /// files were created on the filesystem, more or less.
     FileInode,
@@ -173,21 +390,40 @@ pub enum SortField {
     /// http://unix.stackexchange.com/a/8842
     AccessedDate,
 
-    /// The time the file was changed or created (the “ctime”).
+    /// The time the file’s status last changed (the “ctime”).
     ///
-    /// Contrary to the name, this field is used to mark the time when a
-    /// file’s metadata changed -- its permissions, owners, or link count.
+    /// This field is used to mark the time when a file’s contents or its
+    /// metadata changed -- its permissions, owners, or link count.
     ///
     /// In original Unix, this was, however, meant as creation time.
     /// https://www.bell-labs.com/usr/dmr/www/cacm.html
+    ChangedDate,
+
+    /// The time the file was created (the “birth time”), on filesystems
+    /// that record it. Files without one sort before those with one.
     CreatedDate,
 
-    /// The type of the file: directories, links, pipes, regular, files, etc.
+    /// The type of the file: directories, then links, then executables,
+    /// then regular files grouped by their kind, then pipes, sockets, and
+    /// devices.
     ///
-    /// Files are ordered according to the `PartialOrd` implementation of
-    /// `fs::fields::Type`, so changing that will change this.
+    /// Files are ordered according to the `Ord` implementation of
+    /// `info::filetype::TypeGroup`, so changing that will change this.
     FileType,
 
+    /// The name of the user that owns the file, compared case-insensitively.
+    /// Files whose owner has no name get listed after all the others. Like
+    /// the Git status, this needs a lookup for each owner, so `sort_files`
+    /// sorts by it, and comparing two files on their own only compares
+    /// their names.
+    Owner,
+
+    /// The file’s Git status, with conflicted and changed files first and
+    /// ignored ones last, in the order given by `f::Git::sort_rank`. This
+    /// needs the statuses from the Git cache, so `sort_files` sorts by it,
+    /// and comparing two files on their own only compares their names.
+    GitStatus,
+
     /// The “age” of the file, which is the time it was modified sorted
     /// backwards. The reverse of the `ModifiedDate` ordering!
     ///
@@ -240,42 +476,55 @@ impl SortField {
         match self {
             SortField::Unsorted  => Ordering::Equal,
 
-            SortField::Name(ABCabc)  => natord::compare(&a.name, &b.name),
-            SortField::Name(AaBbCc)  => natord::compare_ignore_case(&a.name, &b.name),
+            SortField::Name(ABCabc)  => SortField::compare_names(a, b, ABCabc, false),
+            SortField::Name(AaBbCc)  => SortField::compare_names(a, b, AaBbCc, false),
+            SortField::NameRaw       => a.name.cmp(&b.name),
 
             SortField::Size          => a.metadata.len().cmp(&b.metadata.len()),
+            SortField::SizeOrEntries => size_or_entries(a).cmp(&size_or_entries(b)),
+            SortField::DiskSize      => a.allocated_size().cmp(&b.allocated_size()),
             SortField::FileInode     => a.metadata.ino().cmp(&b.metadata.ino()),
             SortField::ModifiedDate  => a.modified_time().cmp(&b.modified_time()),
             SortField::AccessedDate  => a.accessed_time().cmp(&b.accessed_time()),
+            SortField::ChangedDate   => a.changed_time().cmp(&b.changed_time()),
             SortField::CreatedDate   => a.created_time().cmp(&b.created_time()),
             SortField::ModifiedAge   => b.modified_time().cmp(&a.modified_time()),  // flip b and a
 
-            SortField::FileType => match a.type_char().cmp(&b.type_char()) { // todo: this recomputes
-                Ordering::Equal  => natord::compare(&*a.name, &*b.name),
+            SortField::FileType => match TypeGroup::of(a).cmp(&TypeGroup::of(b)) {
+                Ordering::Equal  => SortField::compare_names(a, b, ABCabc, false),
                 order            => order,
             },
 
+            SortField::Owner |
+            SortField::GitStatus => SortField::compare_names(a, b, AaBbCc, false),
+
             SortField::Extension(ABCabc) => match a.ext.cmp(&b.ext) {
-                Ordering::Equal  => natord::compare(&*a.name, &*b.name),
+                Ordering::Equal  => SortField::compare_names(a, b, ABCabc, false),
                 order            => order,
             },
 
             SortField::Extension(AaBbCc) => match a.ext.cmp(&b.ext) {
-                Ordering::Equal  => natord::compare_ignore_case(&*a.name, &*b.name),
+                Ordering::Equal  => SortField::compare_names(a, b, AaBbCc, false),
                 order            => order,
             },
 
-            SortField::NameMixHidden(ABCabc) => natord::compare(
-                SortField::strip_dot(&a.name),
-                SortField::strip_dot(&b.name)
-            ),
-            SortField::NameMixHidden(AaBbCc) => natord::compare_ignore_case(
-                SortField::strip_dot(&a.name),
-                SortField::strip_dot(&b.name)
-            )
+            SortField::NameMixHidden(case) => SortField::compare_names(a, b, case, true),
         }
     }
 
+    /// Compares two files’ names naturally using their display forms. When
+    /// those are equal — which can happen when a name isn’t valid Unicode
+    /// and parts of it got replaced — the original names are compared, so
+    /// that different files always get a consistent order.
+    fn compare_names(a: &File, b: &File, case: SortCase, mix_hidden: bool) -> Ordering {
+        let (a_name, b_name) = (a.display_name(), b.display_name());
+        let (a_str, b_str) = if mix_hidden { (SortField::strip_dot(&a_name), SortField::strip_dot(&b_name)) }
+                                      else { (&*a_name, &*b_name) };
+
+        collate(a_str, b_str, case)
+            .then_with(|| a.name.cmp(&b.name))
+    }
+
     fn strip_dot(n: &str) -> &str {
         if n.starts_with('.') {
             &n[1..]
@@ -286,17 +535,91 @@ impl SortField {
 }
 
 
+/// Compares two names using a collation order that’s friendlier than just
+/// comparing code points: both names are decomposed with NFKD and have their
+/// combining marks removed, so accented letters sort next to their base
+/// letters, and names that only differ in how they were normalised end up
+/// next to each other. Names with the same collation key are then compared
+/// as they are, so the order stays consistent.
+///
+/// Sorts make a lot of comparisons, so the keys get compared as they’re
+/// decomposed, rather than being collected into strings first.
+pub fn collate(a: &str, b: &str, case: SortCase) -> Ordering {
+    let order = match case {
+        SortCase::ABCabc => compare_naturally(collation_key(a), collation_key(b)),
+        SortCase::AaBbCc => compare_naturally(collation_key(a).flat_map(char::to_lowercase),
+                                              collation_key(b).flat_map(char::to_lowercase)),
+    };
+
+    order.then_with(|| natord::compare(a, b))
+}
+
+fn collation_key(name: &str) -> impl Iterator<Item=char> + '_ {
+    name.nfkd()
+        .filter(|c| !is_combining_mark(*c))
+}
+
+/// Compares two sequences of characters the way `natord::compare` compares
+/// two strings.
+fn compare_naturally<A, B>(a: A, b: B) -> Ordering
+where A: Iterator<Item=char>, B: Iterator<Item=char> {
+    natord::compare_iter(a, b,
+                         |c| c.is_whitespace(),
+                         |l, r| l.cmp(r),
+                         |c| c.to_digit(10).map(|d| d as isize))
+}
+
+
+/// What to sort a file by when the size column is showing directories’
+/// entry counts: directories come first, ordered by their counts, with
+/// the ones that can’t be read before the rest, then the other files,
+/// ordered by their sizes.
+fn size_or_entries(file: &File) -> (bool, Option<u64>) {
+    if file.is_directory() { (false, file.entry_count()) }
+                      else { (true, Some(file.metadata.len())) }
+}
+
+/// The rank of the file’s Git status, from the cache if it knows about the
+/// file’s repository. Directories get the combined status of their contents,
+/// as they do in the Git column.
+fn git_rank(file: &File, git: Option<&GitCache>) -> u8 {
+    match git {
+        Some(git) if git.has_anything_for(&file.path) => git.get(&file.path, file.is_directory()).sort_rank(),
+        _                                             => f::Git::default().sort_rank(),
+    }
+}
+
+/// The name of the user with the given ID, if there is one, and if it’s
+/// valid Unicode.
+fn owner_name(users: &UsersCache, uid: uid_t) -> Option<String> {
+    users.get_user_by_uid(uid).and_then(|u| u.name().to_str().map(String::from))
+}
+
+/// Compares two files’ owner names case-insensitively, with the same
+/// collation as file names. A missing name sorts after any present one.
+fn compare_owners(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b))  => collate(a, b, SortCase::AaBbCc),
+        (Some(_), None)     => Ordering::Less,
+        (None,    Some(_))  => Ordering::Greater,
+        (None,    None)     => Ordering::Equal,
+    }
+}
+
+
 /// The **ignore patterns** are a list of globs that are tested against
 /// each filename, and if any of them match, that file isn’t displayed.
 /// This lets a user hide, say, text files by ignoring `*.txt`.
 #[derive(PartialEq, Default, Debug, Clone)]
 pub struct IgnorePatterns {
-    patterns: Vec<glob::Pattern>,
+    patterns: Vec<NamePattern>,
 }
 
+/// Globs collected this way match with the platform’s usual case rules.
 impl FromIterator<glob::Pattern> for IgnorePatterns {
     fn from_iter<I: IntoIterator<Item = glob::Pattern>>(iter: I) -> Self {
-        IgnorePatterns { patterns: iter.into_iter().collect() }
+        let patterns = iter.into_iter().map(|p| NamePattern::from_glob(p, PatternCase::default())).collect();
+        IgnorePatterns { patterns }
     }
 }
 
@@ -305,7 +628,15 @@ impl IgnorePatterns {
     /// Create a new list from the input glob strings, turning the inputs that
     /// are valid glob patterns into an IgnorePatterns. The inputs that don’t
     /// parse correctly are returned separately.
+    ///
+    /// The globs match with the platform’s usual case rules, which is to
+    /// say they ignore case on Windows.
     pub fn parse_from_iter<'a, I: IntoIterator<Item = &'a str>>(iter: I) -> (Self, Vec<glob::PatternError>) {
+        IgnorePatterns::parse_from_iter_with_case(iter, PatternCase::default())
+    }
+
+    /// Like `parse_from_iter`, but with the given case rules.
+    pub fn parse_from_iter_with_case<'a, I: IntoIterator<Item = &'a str>>(iter: I, case: PatternCase) -> (Self, Vec<glob::PatternError>) {
         let iter = iter.into_iter();
 
         // Almost all glob patterns are valid, so it’s worth pre-allocating
@@ -319,7 +650,7 @@ impl IgnorePatterns {
         let mut errors = Vec::new();
 
         for input in iter {
-            match glob::Pattern::new(input) {
+            match NamePattern::new(input, case) {
                 Ok(pat) => patterns.push(pat),
                 Err(e)  => errors.push(e),
             }
@@ -360,6 +691,20 @@ pub enum GitIgnore {
     Off,
 }
 
+/// Whether to ignore or display files that are mentioned in `.exaignore`
+/// files.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ExaIgnore {
+
+    /// Ignore the files that match the patterns in the `.exaignore` files in
+    /// the directory being listed and its ancestors, going up at most
+    /// `depth` directories, if there’s a limit.
+    CheckAndIgnore { depth: Option<usize> },
+
+    /// Display files, even if an `.exaignore` file mentions them.
+    Off,
+}
+
 // This is not fully baked yet. The `ignore` crate lists a lot more files that
 // we aren’t checking:
 //
@@ -396,6 +741,22 @@ mod test_ignores {
         assert_eq!(false, pats.is_ignored("test.mp3"));
     }
 
+    #[test]
+    fn case_insensitive() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter_with_case(vec![ "*.obj" ], PatternCase::Insensitive);
+        assert!(fails.is_empty());
+        assert_eq!(true,  pats.is_ignored("MODULE.OBJ"));
+        assert_eq!(false, pats.is_ignored("module.c"));
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter_with_case(vec![ "*.obj" ], PatternCase::Sensitive);
+        assert!(fails.is_empty());
+        assert_eq!(false, pats.is_ignored("MODULE.OBJ"));
+        assert_eq!(true,  pats.is_ignored("module.obj"));
+    }
+
     #[test]
     fn ignores_both() {
         let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "nothing", "*.mp3" ]);
@@ -404,3 +765,319 @@ mod test_ignores {
         assert_eq!(true, pats.is_ignored("test.mp3"));
     }
 }
+
+
+#[cfg(test)]
+mod test_hidden_last {
+    use super::*;
+    use fs::Dir;
+    use fs::test_dir::TempDir;
+    use std::fs;
+
+    /// Sorts the files in a directory made of the given files and
+    /// directories with the given filter, returning their names.
+    fn sorted(name: &str, filter: FileFilter) -> Vec<String> {
+        let temp = TempDir::new(&format!("hidden-last-{}", name));
+        for file in &[ "b", ".a", "c", ".d" ] {
+            let _ = temp.touch(file);
+        }
+        for dir in &[ "e", ".f" ] {
+            fs::create_dir(temp.join(dir)).unwrap();
+        }
+
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let mut files = dir.files(DotFilter::Dotfiles, None).map(|f| f.unwrap()).collect::<Vec<_>>();
+        filter.sort_files(&mut files, None);
+        files.iter().map(|f| f.display_name().into_owned()).collect()
+    }
+
+    /// Sorting by name with the dots ignored, so the hidden files would
+    /// otherwise be mixed in with the rest.
+    fn filter() -> FileFilter {
+        FileFilter { list_hidden_last: true, sort_field: SortField::NameMixHidden(SortCase::AaBbCc), ..FileFilter::default() }
+    }
+
+    #[test]
+    fn mixed_in() {
+        let filter = FileFilter { list_hidden_last: false, ..filter() };
+        assert_eq!(sorted("mixed", filter), vec![ ".a", "b", "c", ".d", "e", ".f" ]);
+    }
+
+    #[test]
+    fn hidden_last() {
+        assert_eq!(sorted("last", filter()), vec![ "b", "c", "e", ".a", ".d", ".f" ]);
+    }
+
+    #[test]
+    fn reversed() {
+        let filter = FileFilter { reverse: true, ..filter() };
+        assert_eq!(sorted("reversed", filter), vec![ "e", "c", "b", ".f", ".d", ".a" ]);
+    }
+
+    #[test]
+    fn with_dirs_first() {
+        let filter = FileFilter { list_dirs_first: true, ..filter() };
+        assert_eq!(sorted("dirs", filter), vec![ "e", "b", "c", ".f", ".a", ".d" ]);
+    }
+
+    #[test]
+    fn dots_first_sort() {
+        let filter = FileFilter { sort_field: SortField::default(), ..filter() };
+        assert_eq!(sorted("dots", filter), vec![ "b", "c", "e", ".a", ".d", ".f" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_sort_by_type {
+    use super::*;
+    use fs::Dir;
+    use fs::test_dir::TempDir;
+    use std::ffi::CString;
+    use std::fs;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    /// Sorts a directory with one of most kinds of file in it, returning
+    /// their names.
+    fn sorted(name: &str, reverse: bool) -> Vec<String> {
+        let temp = TempDir::new(&format!("sort-type-{}", name));
+        for file in &[ "notes.txt", "photo.png", "backup.zip", "Makefile", "scan.jpg", "draft~", "clip.mp4", "README" ] {
+            let _ = temp.touch(file);
+        }
+        let run = temp.touch("run");
+        fs::set_permissions(run, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::create_dir(temp.join("src")).unwrap();
+        symlink("notes.txt", temp.join("latest")).unwrap();
+
+        let fifo = CString::new(temp.join("queue").as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let mut files = dir.files(DotFilter::JustFiles, None).map(|f| f.unwrap()).collect::<Vec<_>>();
+        let filter = FileFilter { sort_field: SortField::FileType, reverse, ..FileFilter::default() };
+        filter.sort_files(&mut files, None);
+        files.iter().map(|f| f.display_name().into_owned()).collect()
+    }
+
+    #[test]
+    fn grouped() {
+        assert_eq!(sorted("grouped", false), vec![
+            "src", "latest", "run",
+            "Makefile", "README",
+            "photo.png", "scan.jpg",
+            "clip.mp4",
+            "backup.zip",
+            "draft~",
+            "notes.txt",
+            "queue",
+        ]);
+    }
+
+    #[test]
+    fn reversed() {
+        assert_eq!(sorted("reversed", true), vec![
+            "queue",
+            "notes.txt",
+            "draft~",
+            "backup.zip",
+            "clip.mp4",
+            "scan.jpg", "photo.png",
+            "README", "Makefile",
+            "run", "latest", "src",
+        ]);
+    }
+
+    #[test]
+    fn group_order() {
+        assert!(TypeGroup::Directory < TypeGroup::Link);
+        assert!(TypeGroup::Link < TypeGroup::Executable);
+        assert!(TypeGroup::Executable < TypeGroup::Image);
+        assert!(TypeGroup::Compressed < TypeGroup::OtherFile);
+        assert!(TypeGroup::OtherFile < TypeGroup::Special);
+    }
+}
+
+
+#[cfg(test)]
+mod test_time_range {
+    use super::*;
+
+    fn at(seconds: f::time_t) -> Option<f::Time> {
+        Some(f::Time { seconds, nanoseconds: 0 })
+    }
+
+    fn range(newer_than: Option<f::time_t>, older_than: Option<f::time_t>) -> TimeRange {
+        TimeRange {
+            time_type: TimeType::Modified,
+            newer_than: newer_than.and_then(at),
+            older_than: older_than.and_then(at),
+        }
+    }
+
+    #[test]
+    fn newer_than() {
+        let range = range(Some(100), None);
+        assert_eq!(false, range.contains_time(at(99)));
+        assert_eq!(true,  range.contains_time(at(100)));
+        assert_eq!(true,  range.contains_time(at(101)));
+    }
+
+    #[test]
+    fn older_than() {
+        let range = range(None, Some(100));
+        assert_eq!(true,  range.contains_time(at(99)));
+        assert_eq!(true,  range.contains_time(at(100)));
+        assert_eq!(false, range.contains_time(at(101)));
+    }
+
+    #[test]
+    fn window() {
+        let range = range(Some(100), Some(200));
+        assert_eq!(false, range.contains_time(at(99)));
+        assert_eq!(true,  range.contains_time(at(150)));
+        assert_eq!(false, range.contains_time(at(201)));
+    }
+
+    #[test]
+    fn nanoseconds_count() {
+        let range = range(None, Some(100));
+        assert_eq!(false, range.contains_time(Some(f::Time { seconds: 100, nanoseconds: 1 })));
+    }
+
+    #[test]
+    fn missing_times() {
+        assert_eq!(false, range(Some(100), None).contains_time(None));
+        assert_eq!(true,  range(None, Some(100)).contains_time(None));
+        assert_eq!(false, range(Some(100), Some(200)).contains_time(None));
+    }
+}
+
+
+#[cfg(test)]
+mod test_sort_by_entries {
+    use super::*;
+    use fs::Dir;
+    use fs::test_dir::TempDir;
+    use std::fs;
+
+    fn sorted(reverse: bool) -> Vec<String> {
+        let temp = TempDir::new("sort-entries");
+        fs::write(temp.join("big"), b"four").unwrap();
+        fs::write(temp.join("small"), b"1").unwrap();
+        let _ = temp.touch("two/a");
+        let _ = temp.touch("two/b");
+        let _ = temp.touch("one/a");
+        fs::create_dir(temp.join("none")).unwrap();
+
+        let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+        let mut files = dir.files(DotFilter::JustFiles, None).map(|f| f.unwrap()).collect::<Vec<_>>();
+        let filter = FileFilter { sort_field: SortField::SizeOrEntries, reverse, ..FileFilter::default() };
+        filter.sort_files(&mut files, None);
+        files.iter().map(|f| f.display_name().into_owned()).collect()
+    }
+
+    #[test]
+    fn directories_by_count_then_files_by_size() {
+        assert_eq!(sorted(false), vec![ "none", "one", "two", "small", "big" ]);
+    }
+
+    #[test]
+    fn reversed() {
+        assert_eq!(sorted(true), vec![ "big", "small", "two", "one", "none" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_sort_by_owner {
+    use super::*;
+
+    fn sorted(owners: &[Option<&'static str>]) -> Vec<Option<&'static str>> {
+        let mut owners = owners.to_vec();
+        owners.sort_by(|a, b| compare_owners(*a, *b));
+        owners
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let owners = [ Some("root"), Some("Bob"), Some("alice") ];
+        assert_eq!(sorted(&owners), vec![ Some("alice"), Some("Bob"), Some("root") ]);
+    }
+
+    #[test]
+    fn unresolved_last() {
+        let owners = [ None, Some("zed"), None, Some("adam") ];
+        assert_eq!(sorted(&owners), vec![ Some("adam"), Some("zed"), None, None ]);
+    }
+
+    #[test]
+    fn non_ascii() {
+        let owners = [ Some("\u{d6}zil"), Some("zoe"), Some("oscar"), Some("\u{e9}lodie"), Some("\u{3b1}lpha") ];
+        assert_eq!(sorted(&owners), vec![ Some("\u{e9}lodie"), Some("oscar"), Some("\u{d6}zil"), Some("zoe"), Some("\u{3b1}lpha") ]);
+    }
+
+    #[test]
+    fn same_owner_is_equal() {
+        assert_eq!(compare_owners(Some("alice"), Some("alice")), Ordering::Equal);
+        assert_eq!(compare_owners(None, None), Ordering::Equal);
+    }
+}
+
+
+#[cfg(test)]
+mod test_collation {
+    use super::*;
+
+    fn sorted(names: &[&'static str], case: SortCase) -> Vec<&'static str> {
+        let mut names = names.to_vec();
+        names.sort_by(|a, b| collate(a, b, case));
+        names
+    }
+
+    #[test]
+    fn accents_next_to_base_letters() {
+        let names = [ "Zebra", "\u{c4}rger", "Apfel", "Bach" ];
+        assert_eq!(sorted(&names, SortCase::AaBbCc), vec![ "Apfel", "\u{c4}rger", "Bach", "Zebra" ]);
+    }
+
+    #[test]
+    fn normalisation_forms_sort_together() {
+        let names = [ "ecole", "e\u{301}cole", "fable", "\u{e9}cole" ];
+        assert_eq!(sorted(&names, SortCase::AaBbCc), vec![ "ecole", "e\u{301}cole", "\u{e9}cole", "fable" ]);
+    }
+
+    #[test]
+    fn compatibility_forms() {
+        let names = [ "\u{fb01}le", "fig", "fjord" ];
+        assert_eq!(sorted(&names, SortCase::AaBbCc), vec![ "fig", "\u{fb01}le", "fjord" ]);
+    }
+
+    #[test]
+    fn mixed_scripts() {
+        let names = [ "日本", "\u{3ab}psilon", "zulu", "にほん", "\u{e9}t\u{e9}", "Alpha", "ｆｕｌｌ" ];
+        assert_eq!(sorted(&names, SortCase::AaBbCc), vec![ "Alpha", "\u{e9}t\u{e9}", "ｆｕｌｌ", "zulu", "\u{3ab}psilon", "にほん", "日本" ]);
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let names = [ "\u{e9}t\u{e9}", "Zoo", "Eagle" ];
+        assert_eq!(sorted(&names, SortCase::ABCabc), vec![ "Eagle", "Zoo", "\u{e9}t\u{e9}" ]);
+    }
+
+    #[test]
+    fn numbers_still_natural() {
+        let names = [ "\u{e9}t\u{e9}10", "ete9" ];
+        assert_eq!(sorted(&names, SortCase::AaBbCc), vec![ "ete9", "\u{e9}t\u{e9}10" ]);
+    }
+
+    #[test]
+    fn plain_names_as_natord_has_them() {
+        let names = [ "file 10", "File2", "file1", "a b", "ab", "007", "7" ];
+        for a in &names {
+            for b in &names {
+                assert_eq!(compare_naturally(a.chars(), b.chars()), natord::compare(a, b), "{:?} {:?}", a, b);
+            }
+        }
+    }
+}
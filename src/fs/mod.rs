@@ -1,10 +1,13 @@
 mod dir;
-pub use self::dir::{Dir, DotFilter};
+pub use self::dir::{Dir, DirReader, FsReader, DotFilter};
 
 mod file;
-pub use self::file::{File, FileTarget};
+pub use self::file::{File, FileTarget, OwnedFile, normalise};
 
 pub mod feature;
 pub mod fields;
 pub mod filter;
+pub mod pattern;
 pub mod dir_action;
+pub mod stat;
+#[cfg(test)] pub mod test_dir;
@@ -0,0 +1,161 @@
+//! Matching file names against glob patterns, with or without caring about
+//! the case of their letters.
+//!
+//! Every pattern that gets tested against file names goes through here: the
+//! `--ignore-glob` patterns, the rules in ignore files, the globs in the
+//! colour variables, and `--highlight`. Windows file names don’t care about
+//! case, so by default the patterns don’t either there.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use glob;
+
+
+/// Whether patterns care about the case of the letters in file names.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum PatternCase {
+    Sensitive,
+    Insensitive,
+}
+
+impl PatternCase {
+
+    /// Folds the given text so that two strings that only differ in case
+    /// come out the same, if case doesn’t matter. Every character gets its
+    /// full Unicode lowercase form, rather than just the ASCII letters that
+    /// the glob crate can fold by itself.
+    pub fn fold(self, text: &str) -> Cow<'_, str> {
+        match self {
+            PatternCase::Sensitive    => Cow::Borrowed(text),
+            PatternCase::Insensitive  => Cow::Owned(text.chars().flat_map(char::to_lowercase).collect()),
+        }
+    }
+}
+
+impl Default for PatternCase {
+    fn default() -> PatternCase {
+        if cfg!(windows) { PatternCase::Insensitive }
+                    else { PatternCase::Sensitive }
+    }
+}
+
+
+/// A glob pattern that gets matched against file names or paths, folding
+/// both the pattern and the names when case doesn’t matter.
+#[derive(PartialEq, Debug, Clone)]
+pub struct NamePattern {
+    glob: glob::Pattern,
+    case: PatternCase,
+}
+
+impl NamePattern {
+
+    /// Parses the given glob, which gets folded first if case doesn’t
+    /// matter.
+    pub fn new(text: &str, case: PatternCase) -> Result<NamePattern, glob::PatternError> {
+        let glob = glob::Pattern::new(&case.fold(text))?;
+        Ok(NamePattern { glob, case })
+    }
+
+    /// Wraps a glob that has already been parsed.
+    pub fn from_glob(glob: glob::Pattern, case: PatternCase) -> NamePattern {
+        match case {
+            PatternCase::Sensitive    => NamePattern { glob, case },
+            PatternCase::Insensitive  => NamePattern::new(glob.as_str(), case).unwrap_or(NamePattern { glob, case }),
+        }
+    }
+
+    /// The glob as it was parsed, after any folding.
+    pub fn as_str(&self) -> &str {
+        self.glob.as_str()
+    }
+
+    /// Whether the given file name matches the pattern.
+    pub fn matches(&self, name: &str) -> bool {
+        self.matches_with(name, &glob::MatchOptions::new())
+    }
+
+    /// Whether the given file name matches the pattern, with the given
+    /// options for separators and leading dots. Case is up to the pattern,
+    /// whatever the options say.
+    pub fn matches_with(&self, name: &str, options: &glob::MatchOptions) -> bool {
+        let options = glob::MatchOptions { case_sensitive: true, ..*options };
+        self.glob.matches_with(&self.case.fold(name), &options)
+    }
+
+    /// Whether the given file name, which has already been folded with this
+    /// pattern’s case, matches the pattern. This saves folding a name over
+    /// and over when it gets tested against a lot of patterns.
+    pub fn matches_folded(&self, name: &str) -> bool {
+        self.glob.matches_with(name, &glob::MatchOptions::new())
+    }
+
+    /// Whether the given path matches the pattern. Paths that aren’t valid
+    /// Unicode never match.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.matches_path_with(path, &glob::MatchOptions::new())
+    }
+
+    /// Whether the given path matches the pattern, with the given options.
+    pub fn matches_path_with(&self, path: &Path, options: &glob::MatchOptions) -> bool {
+        path.to_str().is_some_and(|p| self.matches_with(p, options))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::PatternCase::*;
+
+    fn matches(pattern: &str, case: PatternCase, name: &str) -> bool {
+        NamePattern::new(pattern, case).unwrap().matches(name)
+    }
+
+    #[test]
+    fn sensitive() {
+        assert!(matches("*.obj", Sensitive, "module.obj"));
+        assert!(!matches("*.obj", Sensitive, "MODULE.OBJ"));
+    }
+
+    #[test]
+    fn insensitive() {
+        assert!(matches("*.obj", Insensitive, "MODULE.OBJ"));
+        assert!(matches("*.OBJ", Insensitive, "module.obj"));
+        assert!(matches("README.*", Insensitive, "readme.md"));
+    }
+
+    #[test]
+    fn insensitive_unicode() {
+        assert!(matches("*.ÄRCHIV", Insensitive, "backup.ärchiv"));
+        assert!(matches("Ωmega*", Insensitive, "ωMEGA.txt"));
+        assert!(!matches("*.ÄRCHIV", Sensitive, "backup.ärchiv"));
+    }
+
+    #[test]
+    fn classes_fold_too() {
+        assert!(matches("[A-C]*", Insensitive, "beta"));
+        assert!(!matches("[A-C]*", Sensitive, "beta"));
+    }
+
+    #[test]
+    fn options_cannot_undo_the_case() {
+        let options = glob::MatchOptions { case_sensitive: false, ..glob::MatchOptions::new() };
+        let pattern = NamePattern::new("*.obj", Sensitive).unwrap();
+        assert!(!pattern.matches_with("MODULE.OBJ", &options));
+    }
+
+    #[test]
+    fn paths() {
+        let pattern = NamePattern::new("Build/*.LOG", Insensitive).unwrap();
+        assert!(pattern.matches_path(Path::new("build/out.log")));
+    }
+
+    #[test]
+    fn from_glob() {
+        let glob = glob::Pattern::new("*.MD").unwrap();
+        assert!(NamePattern::from_glob(glob.clone(), Insensitive).matches("readme.md"));
+        assert!(!NamePattern::from_glob(glob, Sensitive).matches("readme.md"));
+    }
+}
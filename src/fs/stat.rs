@@ -0,0 +1,326 @@
+//! The parts of a file’s metadata that exa classifies files by.
+//!
+//! `File` holds a real `fs::Metadata`, but working out a file’s type, size,
+//! and timestamps only needs a few of its fields. Those fields are behind
+//! the `Stat` trait so the classification can be tested against made-up
+//! metadata, rather than having to create real files with the right modes
+//! and timestamps.
+
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use fs::fields as f;
+
+
+/// The raw values `File` needs from the OS, with everything worked out from
+/// them as provided methods.
+///
+/// The required methods are named after the `stat` fields they return, so
+/// they don’t clash with the methods that `fs::Metadata` already has.
+pub trait Stat {
+
+    /// The file’s type and permission bits, as in `st_mode`.
+    fn stat_mode(&self) -> u32;
+
+    /// The file’s size in bytes, as in `st_size`.
+    fn stat_size(&self) -> u64;
+
+    /// The number of hard links to the file, as in `st_nlink`.
+    fn stat_nlink(&self) -> u64;
+
+    /// The device ID of a device file, as in `st_rdev`.
+    fn stat_rdev(&self) -> u64;
+
+    /// The last modified time, in seconds and nanoseconds.
+    fn stat_mtime(&self) -> (f::time_t, f::time_t);
+
+    /// The status changed time, in seconds and nanoseconds.
+    fn stat_ctime(&self) -> (f::time_t, f::time_t);
+
+    /// The last accessed time, in seconds and nanoseconds.
+    fn stat_atime(&self) -> (f::time_t, f::time_t);
+
+    /// The time the file was born, if the filesystem recorded one.
+    fn stat_birthtime(&self) -> Option<SystemTime>;
+
+
+    /// Whether the mode says this is the given type of file.
+    fn has_type(&self, bits: u32) -> bool {
+        self.stat_mode() & types::MASK == bits
+    }
+
+    /// Whether this is a directory.
+    fn is_directory(&self) -> bool {
+        self.has_type(types::DIRECTORY)
+    }
+
+    /// Whether this is a regular file.
+    fn is_regular_file(&self) -> bool {
+        self.has_type(types::FILE)
+    }
+
+    /// Whether this is a symlink.
+    fn is_symlink(&self) -> bool {
+        self.has_type(types::LINK)
+    }
+
+    /// Whether this is a named pipe.
+    fn is_pipe(&self) -> bool {
+        self.has_type(types::PIPE)
+    }
+
+    /// Whether this is a char device.
+    fn is_char_device(&self) -> bool {
+        self.has_type(types::CHAR_DEVICE)
+    }
+
+    /// Whether this is a block device.
+    fn is_block_device(&self) -> bool {
+        self.has_type(types::BLOCK_DEVICE)
+    }
+
+    /// Whether this is a socket.
+    fn is_socket(&self) -> bool {
+        self.has_type(types::SOCKET)
+    }
+
+    /// The file’s type, for the leftmost character of the permissions
+    /// column. (See `File::type_char`)
+    fn type_char(&self) -> f::Type {
+        if      self.is_regular_file()  { f::Type::File }
+        else if self.is_directory()     { f::Type::Directory }
+        else if self.is_pipe()          { f::Type::Pipe }
+        else if self.is_symlink()       { f::Type::Link }
+        else if self.is_char_device()   { f::Type::CharDevice }
+        else if self.is_block_device()  { f::Type::BlockDevice }
+        else if self.is_socket()        { f::Type::Socket }
+        else                            { f::Type::Special }
+    }
+
+    /// The number of hard links, and whether a regular file has more than
+    /// one of them. (See `File::links`)
+    fn links(&self) -> f::Links {
+        let count = self.stat_nlink();
+
+        f::Links {
+            count,
+            multiple: self.is_regular_file() && count > 1,
+        }
+    }
+
+    /// The file’s size: nothing for directories, the device IDs for
+    /// devices, and the number of bytes for everything else.
+    /// (See `File::size`)
+    fn size(&self) -> f::Size {
+        if self.is_directory() {
+            f::Size::None
+        }
+        else if self.is_char_device() || self.is_block_device() {
+            let dev = self.stat_rdev();
+            f::Size::DeviceIDs(f::DeviceIDs {
+                major: (dev / 256) as u8,
+                minor: (dev % 256) as u8,
+            })
+        }
+        else {
+            f::Size::Some(self.stat_size())
+        }
+    }
+
+    /// The last modified timestamp, if there is one.
+    fn modified_time(&self) -> Option<f::Time> {
+        let (seconds, nanoseconds) = self.stat_mtime();
+        f::Time::from_raw(seconds, nanoseconds)
+    }
+
+    /// The status changed timestamp, if there is one.
+    fn changed_time(&self) -> Option<f::Time> {
+        let (seconds, nanoseconds) = self.stat_ctime();
+        f::Time::from_raw(seconds, nanoseconds)
+    }
+
+    /// The last accessed timestamp, if there is one.
+    fn accessed_time(&self) -> Option<f::Time> {
+        let (seconds, nanoseconds) = self.stat_atime();
+        f::Time::from_raw(seconds, nanoseconds)
+    }
+
+    /// The created timestamp, if there is one. Birth times before the Unix
+    /// epoch come out negative.
+    fn created_time(&self) -> Option<f::Time> {
+        let created = self.stat_birthtime()?;
+
+        match created.duration_since(UNIX_EPOCH) {
+            Ok(since)  => f::Time::from_raw(since.as_secs() as f::time_t, since.subsec_nanos() as f::time_t),
+            Err(e)     => {
+                let before = e.duration();
+                f::Time::from_raw(-(before.as_secs() as f::time_t), -(before.subsec_nanos() as f::time_t))
+            }
+        }
+    }
+}
+
+impl Stat for fs::Metadata {
+    fn stat_mode(&self) -> u32 {
+        self.mode()
+    }
+
+    fn stat_size(&self) -> u64 {
+        MetadataExt::size(self)
+    }
+
+    fn stat_nlink(&self) -> u64 {
+        self.nlink()
+    }
+
+    fn stat_rdev(&self) -> u64 {
+        self.rdev()
+    }
+
+    fn stat_mtime(&self) -> (f::time_t, f::time_t) {
+        (self.mtime(), self.mtime_nsec())
+    }
+
+    fn stat_ctime(&self) -> (f::time_t, f::time_t) {
+        (self.ctime(), self.ctime_nsec())
+    }
+
+    fn stat_atime(&self) -> (f::time_t, f::time_t) {
+        (self.atime(), self.atime_nsec())
+    }
+
+    fn stat_birthtime(&self) -> Option<SystemTime> {
+        self.created().ok()
+    }
+}
+
+
+/// The file type bits of a mode. These have the same values on every Unix,
+/// so they’re written out here rather than cast from libc’s `mode_t`.
+mod types {
+    pub const MASK: u32          = 0o170_000;
+    pub const FILE: u32          = 0o100_000;
+    pub const DIRECTORY: u32     = 0o040_000;
+    pub const LINK: u32          = 0o120_000;
+    pub const PIPE: u32          = 0o010_000;
+    pub const CHAR_DEVICE: u32   = 0o020_000;
+    pub const BLOCK_DEVICE: u32  = 0o060_000;
+    pub const SOCKET: u32        = 0o140_000;
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    /// Metadata with every field given directly.
+    #[derive(Default)]
+    struct FakeStat {
+        mode: u32,
+        size: u64,
+        nlink: u64,
+        rdev: u64,
+        mtime: (f::time_t, f::time_t),
+        ctime: (f::time_t, f::time_t),
+        atime: (f::time_t, f::time_t),
+        birthtime: Option<SystemTime>,
+    }
+
+    impl Stat for FakeStat {
+        fn stat_mode(&self) -> u32                         { self.mode }
+        fn stat_size(&self) -> u64                         { self.size }
+        fn stat_nlink(&self) -> u64                        { self.nlink }
+        fn stat_rdev(&self) -> u64                         { self.rdev }
+        fn stat_mtime(&self) -> (f::time_t, f::time_t)     { self.mtime }
+        fn stat_ctime(&self) -> (f::time_t, f::time_t)     { self.ctime }
+        fn stat_atime(&self) -> (f::time_t, f::time_t)     { self.atime }
+        fn stat_birthtime(&self) -> Option<SystemTime>     { self.birthtime }
+    }
+
+    fn of_type(bits: u32) -> FakeStat {
+        FakeStat { mode: bits | 0o644, size: 1_234, nlink: 1, ..FakeStat::default() }
+    }
+
+    #[test]
+    fn type_chars() {
+        assert_eq!(of_type(types::FILE).type_char(),          f::Type::File);
+        assert_eq!(of_type(types::DIRECTORY).type_char(),     f::Type::Directory);
+        assert_eq!(of_type(types::LINK).type_char(),          f::Type::Link);
+        assert_eq!(of_type(types::PIPE).type_char(),          f::Type::Pipe);
+        assert_eq!(of_type(types::CHAR_DEVICE).type_char(),   f::Type::CharDevice);
+        assert_eq!(of_type(types::BLOCK_DEVICE).type_char(),  f::Type::BlockDevice);
+        assert_eq!(of_type(types::SOCKET).type_char(),        f::Type::Socket);
+        assert_eq!(of_type(0).type_char(),                    f::Type::Special);
+    }
+
+    #[test]
+    fn permission_bits_dont_change_the_type() {
+        let stat = FakeStat { mode: types::LINK | 0o7777, ..FakeStat::default() };
+        assert!(stat.is_symlink());
+        assert!(!stat.is_regular_file());
+    }
+
+    #[test]
+    fn directories_have_no_size() {
+        assert_eq!(of_type(types::DIRECTORY).size(), f::Size::None);
+    }
+
+    #[test]
+    fn files_and_links_have_sizes() {
+        assert_eq!(of_type(types::FILE).size(), f::Size::Some(1_234));
+        assert_eq!(of_type(types::LINK).size(), f::Size::Some(1_234));
+    }
+
+    #[test]
+    fn devices_have_ids() {
+        let stat = FakeStat { rdev: 8 * 256 + 1, ..of_type(types::BLOCK_DEVICE) };
+        assert_eq!(stat.size(), f::Size::DeviceIDs(f::DeviceIDs { major: 8, minor: 1 }));
+    }
+
+    #[test]
+    fn only_files_have_multiple_links() {
+        let file = FakeStat { nlink: 3, ..of_type(types::FILE) };
+        let dir = FakeStat { nlink: 3, ..of_type(types::DIRECTORY) };
+        assert!(file.links().multiple);
+        assert!(!dir.links().multiple);
+        assert_eq!(dir.links().count, 3);
+    }
+
+    #[test]
+    fn zero_times_are_the_epoch() {
+        let epoch = Some(f::Time { seconds: 0, nanoseconds: 0 });
+        let stat = of_type(types::FILE);
+        assert_eq!(stat.modified_time(), epoch);
+        assert_eq!(stat.changed_time(), epoch);
+        assert_eq!(stat.accessed_time(), epoch);
+    }
+
+    #[test]
+    fn no_birth_time() {
+        assert_eq!(of_type(types::FILE).created_time(), None);
+    }
+
+    #[test]
+    fn times() {
+        let stat = FakeStat { mtime: (1_500_000_000, 5), ctime: (1_600_000_000, 0), atime: (-1, 0), ..of_type(types::FILE) };
+        assert_eq!(stat.modified_time(), Some(f::Time { seconds: 1_500_000_000, nanoseconds: 5 }));
+        assert_eq!(stat.changed_time(),  Some(f::Time { seconds: 1_600_000_000, nanoseconds: 0 }));
+        assert_eq!(stat.accessed_time(), Some(f::Time { seconds: -1, nanoseconds: 0 }));
+    }
+
+    #[test]
+    fn birth_before_the_epoch() {
+        let born = UNIX_EPOCH - Duration::new(10, 250_000_000);
+        let stat = FakeStat { birthtime: Some(born), ..of_type(types::FILE) };
+        assert_eq!(stat.created_time(), Some(f::Time { seconds: -11, nanoseconds: 750_000_000 }));
+    }
+
+    #[test]
+    fn real_metadata() {
+        let metadata = fs::symlink_metadata(".").unwrap();
+        assert!(Stat::is_directory(&metadata));
+        assert_eq!(Stat::size(&metadata), f::Size::None);
+    }
+}
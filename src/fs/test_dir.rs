@@ -0,0 +1,96 @@
+//! Temporary directories for tests that need real files on disk.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+
+/// Counts the directories made so far, so that two tests running at the
+/// same time never get the same one, even if they ask for the same name.
+static MADE: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory under the system’s temporary directory that gets removed,
+/// along with everything in it, when it goes out of scope — including when
+/// the test using it panics on a failed assertion.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+
+    /// Creates a new, empty directory whose name starts with “exa-” and
+    /// the given name.
+    pub fn new(name: &str) -> TempDir {
+        let count = MADE.fetch_add(1, Ordering::SeqCst);
+        let path = env::temp_dir().join(format!("exa-{}-{}-{}", name, process::id(), count));
+
+        fs::create_dir(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// The path to the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path to something inside the directory.
+    pub fn join<P: AsRef<Path>>(&self, child: P) -> PathBuf {
+        self.path.join(child)
+    }
+
+    /// Creates an empty file inside the directory, along with any of the
+    /// directories above it that don’t exist yet, and returns its path.
+    pub fn touch<P: AsRef<Path>>(&self, child: P) -> PathBuf {
+        let path = self.join(child);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+
+        let _ = fs::File::create(&path).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn removed_when_dropped() {
+        let dir = TempDir::new("test-dir");
+        let path = dir.touch("a/b/c");
+        assert!(path.is_file());
+
+        let top = dir.path().to_path_buf();
+        drop(dir);
+        assert!(!top.exists());
+    }
+
+    #[test]
+    fn removed_after_a_panic() {
+        let top = ::std::panic::catch_unwind(|| {
+            let dir = TempDir::new("test-dir-panic");
+            let _ = dir.touch("file");
+            let top = dir.path().to_path_buf();
+            if top.exists() { panic!("{:?}", top) }
+        }).unwrap_err();
+
+        let top = PathBuf::from(top.downcast_ref::<String>().unwrap().trim_matches('"'));
+        assert!(!top.exists());
+    }
+
+    #[test]
+    fn names_never_clash() {
+        let (a, b) = (TempDir::new("test-dir-same"), TempDir::new("test-dir-same"));
+        assert_ne!(a.path(), b.path());
+    }
+}
@@ -10,20 +10,48 @@ use fs::File;
 use output::file_name::FileColours;
 
 
+/// The files that kick off the build of a project, as patterns that
+/// `name_matches` understands: whole file names, or extensions of project
+/// files that there can be any number of.
+static IMMEDIATE_FILES: &[&str] = &[
+    "Makefile", "Cargo.toml", "SConstruct", "CMakeLists.txt",
+    "build.gradle", "Rakefile", "Gruntfile.js",
+    "Gruntfile.coffee", "BUILD", "WORKSPACE", "build.xml",
+    "package.json", "build.bat", "build.ps1", "Directory.Build.props",
+    "*.sln", "*.csproj", "*.vcxproj", "*.fsproj", "*.vbproj",
+];
+
+
 #[derive(Debug, Default, PartialEq)]
-pub struct FileExtensions;
+pub struct FileExtensions {
+
+    /// More patterns for immediate files, on top of the built-in ones,
+    /// which can be given in the `EXA_IMMEDIATE` environment variable.
+    immediate: Vec<String>,
+}
 
 impl FileExtensions {
 
+    /// Parses extra immediate file patterns, separated by colons, such as
+    /// `justfile:*.vcxitems`. Empty patterns get skipped.
+    pub fn with_immediate(patterns: &str) -> FileExtensions {
+        let immediate = patterns.split(':')
+                                .filter(|p| !p.is_empty())
+                                .map(String::from)
+                                .collect();
+
+        FileExtensions { immediate }
+    }
+
     /// An “immediate” file is something that can be run or activated somehow
     /// in order to kick off the build of a project. It’s usually only present
     /// in directories full of source code.
     fn is_immediate(&self, file: &File) -> bool {
-        file.name.to_lowercase().starts_with("readme") || file.name_is_one_of( &[
-            "Makefile", "Cargo.toml", "SConstruct", "CMakeLists.txt",
-            "build.gradle", "Rakefile", "Gruntfile.js",
-            "Gruntfile.coffee", "BUILD", "WORKSPACE", "build.xml"
-        ])
+        let name = file.display_name();
+
+        name.to_lowercase().starts_with("readme")
+            || IMMEDIATE_FILES.iter().any(|p| name_matches(&name, p))
+            || self.immediate.iter().any(|p| name_matches(&name, p))
     }
 
     fn is_image(&self, file: &File) -> bool {
@@ -73,35 +101,218 @@ impl FileExtensions {
         file.extension_is_one_of( &[
             "zip", "tar", "Z", "z", "gz", "bz2", "a", "ar", "7z",
             "iso", "dmg", "tc", "rar", "par", "tgz", "xz", "txz",
-            "lzma", "deb", "rpm", "zst",
+            "lzma", "deb", "rpm", "zst", "tar.br", "tar.lz", "tar.lz4",
+            "tar.lzo",
         ])
     }
+}
+
+
+/// The extensions of files that were only meant to exist for a while, such
+/// as editor swap files and backups.
+static TEMPORARY_EXTENSIONS: &[&str] = &[
+    "tmp", "temp", "swp", "swo", "swn", "bak", "bk", "old",
+];
 
-    fn is_temp(&self, file: &File) -> bool {
-        file.name.ends_with('~')
-            || (file.name.starts_with('#') && file.name.ends_with('#'))
-            || file.extension_is_one_of( &[ "tmp", "swp", "swo", "swn", "bak", "bk" ])
+/// Whether a file name looks like it belongs to a temporary file, going by
+/// the conventions of editors and office suites:
+///
+/// - `foo~`, a backup file left by Emacs, Vim, and many others;
+/// - `#foo#`, an Emacs auto-save file;
+/// - `~$foo.docx`, the owner file Microsoft Office keeps next to an open
+///   document;
+/// - `.~lock.foo.ods#`, the lock file of a LibreOffice document;
+/// - anything with one of the extensions above, such as `~WRL0001.tmp`.
+///
+/// A name that just starts with a tilde, like `~notes.txt`, isn’t enough.
+/// Whether a file name matches a pattern, which is either a whole file
+/// name, such as `Makefile`, or `*` followed by an extension, such as
+/// `*.sln`. Extensions get compared ignoring case, as they are when
+/// highlighting files by extension; whole names have to match exactly.
+fn name_matches(name: &str, pattern: &str) -> bool {
+    match pattern.strip_prefix('*') {
+        Some(ext) if ext.starts_with('.') => {
+            name.len() > ext.len() && name.to_lowercase().ends_with(&ext.to_lowercase())
+        },
+        _ => name == pattern,
     }
+}
+
+fn is_temporary_name(name: &str) -> bool {
+    let ext = name.rfind('.').map(|p| name[p+1..].to_lowercase());
+
+    name.ends_with('~')
+        || (name.len() > 1 && name.starts_with('#') && name.ends_with('#'))
+        || name.starts_with("~$")
+        || (name.starts_with(".~lock.") && name.ends_with('#'))
+        || ext.map(|e| TEMPORARY_EXTENSIONS.contains(&e.as_str())) == Some(true)
+}
+
+/// Whether a file is a temporary one, either because of its name, or
+/// because Windows has marked it as temporary with an attribute.
+pub fn is_temporary(file: &File) -> bool {
+    is_temporary_name(&file.display_name()) || has_temporary_attribute(file)
+}
+
+#[cfg(windows)]
+fn has_temporary_attribute(file: &File) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_TEMPORARY: u32 = 0x100;
+    file.metadata.file_attributes() & FILE_ATTRIBUTE_TEMPORARY != 0
+}
+
+#[cfg(not(windows))]
+fn has_temporary_attribute(_file: &File) -> bool {
+    false
+}
+
+/// Whether Windows has marked a file as hidden, which Explorer treats the
+/// same way as `ls` treats dotfiles.
+#[cfg(windows)]
+pub fn has_hidden_attribute(file: &File) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    file.metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(windows))]
+pub fn has_hidden_attribute(_file: &File) -> bool {
+    false
+}
+
+/// Whether Windows has marked a file as part of the operating system, as
+/// it does for `pagefile.sys` and `desktop.ini`. Explorer hides these
+/// files, so exa dims them instead.
+#[cfg(windows)]
+pub fn has_system_attribute(file: &File) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    file.metadata.file_attributes() & FILE_ATTRIBUTE_SYSTEM != 0
+}
+
+#[cfg(not(windows))]
+pub fn has_system_attribute(_file: &File) -> bool {
+    false
+}
+
+
+/// The kinds of file that are recognised by name, but, unlike the groups
+/// above, which always get the same colours, each have their own style in
+/// the colour theme, so they can be changed with `EXA_COLORS`. Apart from
+/// temporary files, these mostly turn up on Windows.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Category {
+    Temporary,
+    Installer,
+    Script,
+    Registry,
+    Shortcut,
+    System,
+}
 
-    fn is_compiled(&self, file: &File) -> bool {
-        if file.extension_is_one_of( &[ "class", "elc", "hi", "o", "pyc" ]) {
-            true
+impl Category {
+
+    /// Works out which of these categories a file is in, if any.
+    pub fn of(file: &File) -> Option<Category> {
+        if is_temporary(file) {
+            Some(Category::Temporary)
+        }
+        else if file.extension_is_one_of( &[ "msi", "msix", "msixbundle", "appx", "appxbundle" ]) {
+            Some(Category::Installer)
+        }
+        else if file.extension_is_one_of( &[ "ps1", "psm1", "psd1", "bat", "cmd", "vbs" ]) {
+            Some(Category::Script)
+        }
+        else if file.extension_is_one_of( &[ "reg" ]) {
+            Some(Category::Registry)
+        }
+        else if file.extension_is_one_of( &[ "lnk", "url" ]) {
+            Some(Category::Shortcut)
         }
-        else if let Some(dir) = file.parent_dir {
-            file.get_source_files().iter().any(|path| dir.contains(path))
+        else if file.extension_is_one_of( &[ "dll", "sys", "drv" ]) {
+            Some(Category::System)
         }
         else {
-            false
+            None
+        }
+    }
+}
+
+
+/// The groups that sorting by type puts files in, in the order they get
+/// listed. Regular files are grouped by the same kinds that pick their
+/// colours, with the ones that aren’t any of these coming after them, and
+/// anything that isn’t a regular file, directory, or link comes last.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+pub enum TypeGroup {
+    Directory,
+    Link,
+    Executable,
+
+    // Regular files, by kind
+    Immediate,
+    Image,
+    Video,
+    Music,
+    Lossless,
+    Crypto,
+    Document,
+    Compressed,
+    Installer,
+    Script,
+    Registry,
+    Shortcut,
+    System,
+    Temporary,
+    OtherFile,
+
+    /// Pipes, sockets, and devices.
+    Special,
+}
+
+impl TypeGroup {
+
+    /// Works out which group a file gets sorted into. Only the built-in
+    /// immediate file patterns are used, so the order doesn’t depend on
+    /// the environment.
+    pub fn of(file: &File) -> TypeGroup {
+        let exts = FileExtensions::default();
+
+        if file.is_directory()            { return TypeGroup::Directory }
+        if file.is_link()                 { return TypeGroup::Link }
+        if !file.is_file()                { return TypeGroup::Special }
+        if file.is_executable_file()      { return TypeGroup::Executable }
+
+        match Category::of(file) {
+            Some(Category::Temporary)     => TypeGroup::Temporary,
+            Some(Category::Installer)     => TypeGroup::Installer,
+            Some(Category::Script)        => TypeGroup::Script,
+            Some(Category::Registry)      => TypeGroup::Registry,
+            Some(Category::Shortcut)      => TypeGroup::Shortcut,
+            Some(Category::System)        => TypeGroup::System,
+            None if exts.is_immediate(file)   => TypeGroup::Immediate,
+            None if exts.is_image(file)       => TypeGroup::Image,
+            None if exts.is_video(file)       => TypeGroup::Video,
+            None if exts.is_music(file)       => TypeGroup::Music,
+            None if exts.is_lossless(file)    => TypeGroup::Lossless,
+            None if exts.is_crypto(file)      => TypeGroup::Crypto,
+            None if exts.is_document(file)    => TypeGroup::Document,
+            None if exts.is_compressed(file)  => TypeGroup::Compressed,
+            None                              => TypeGroup::OtherFile,
         }
     }
 }
 
+
 impl FileColours for FileExtensions {
     fn colour_file(&self, file: &File) -> Option<Style> {
         use ansi_term::Colour::*;
 
         Some(match file {
-            f if self.is_temp(f)        => Fixed(244).normal(),
+            f if is_temporary(f)        => return None,  // styled by its Category
             f if self.is_immediate(f)   => Yellow.bold().underline(),
             f if self.is_image(f)       => Fixed(133).normal(),
             f if self.is_video(f)       => Fixed(135).normal(),
@@ -110,8 +321,58 @@ impl FileColours for FileExtensions {
             f if self.is_crypto(f)      => Fixed(109).normal(),
             f if self.is_document(f)    => Fixed(105).normal(),
             f if self.is_compressed(f)  => Red.normal(),
-            f if self.is_compiled(f)    => Fixed(137).normal(),
             _                           => return None,
         })
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::{is_temporary_name, name_matches, FileExtensions};
+
+    #[test]
+    fn whole_names() {
+        assert!(name_matches("Makefile", "Makefile"));
+        assert!(name_matches("Directory.Build.props", "Directory.Build.props"));
+        assert!(!name_matches("makefile.bak", "Makefile"));
+        assert!(!name_matches("package.json5", "package.json"));
+    }
+
+    #[test]
+    fn extensions() {
+        assert!(name_matches("exa.sln", "*.sln"));
+        assert!(name_matches("Exa.Tests.CSPROJ", "*.csproj"));
+        assert!(!name_matches(".sln", "*.sln"));
+        assert!(!name_matches("sln", "*.sln"));
+        assert!(!name_matches("exa.slnx", "*.sln"));
+    }
+
+    #[test]
+    fn star_without_a_dot() {
+        assert!(name_matches("*file", "*file"));
+        assert!(!name_matches("justfile", "*file"));
+    }
+
+    #[test]
+    fn extra_immediate_patterns() {
+        let exts = FileExtensions::with_immediate("justfile::*.vcxitems");
+        assert_eq!(exts.immediate, vec![ "justfile".to_string(), "*.vcxitems".to_string() ]);
+    }
+
+    #[test]
+    fn temporary_names() {
+        for name in &[ "foo~", "#foo#", "~$Report.docx", "~WRL0001.tmp", ".~lock.foo.ods#",
+                       "notes.TEMP", "main.rs.bak", "config.old", ".main.rs.swp" ] {
+            assert!(is_temporary_name(name), "{:?} should be temporary", name);
+        }
+    }
+
+    #[test]
+    fn permanent_names() {
+        for name in &[ "~important-notes.txt", "#", "#hashtag", "$Report.docx", "template.txt",
+                       "foo.bold", ".~lock", "tmp", "old", "~user" ] {
+            assert!(!is_temporary_name(name), "{:?} should not be temporary", name);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Working out what kind of file something is from the first few bytes of
+//! its contents, rather than trusting its extension.
+//!
+//! This only knows about a handful of common formats, so it isn’t meant to
+//! be a replacement for `file(1)`: just enough to tell whether something
+//! called `notes.txt` is really an executable.
+
+use fs::fields as f;
+
+
+/// How many bytes from the start of a file get examined. Every format
+/// below can be recognised from this much, and it keeps the reads cheap.
+pub const SAMPLE_SIZE: usize = 512;
+
+/// Classifies a file from a sample of the bytes at its start.
+pub fn detect(bytes: &[u8]) -> f::ContentType {
+    use fs::fields::ContentType::*;
+
+    if bytes.is_empty() {
+        Empty
+    }
+    else if bytes.starts_with(b"MZ") {
+        PE
+    }
+    else if bytes.starts_with(b"\x7FELF") {
+        Elf
+    }
+    else if bytes.starts_with(b"PK\x03\x04") {
+        // Office Open XML documents are zip files whose first entry is
+        // almost always the list of content types.
+        if bytes.len() >= 30 && bytes[30 ..].starts_with(b"[Content_Types].xml") { Ooxml }
+                                                                             else { Zip }
+    }
+    else if bytes.starts_with(b"\x89PNG\r\n\x1A\n") {
+        Png
+    }
+    else if bytes.starts_with(b"\xFF\xD8\xFF") {
+        Jpeg
+    }
+    else if bytes.starts_with(b"%PDF-") {
+        Pdf
+    }
+    else if bytes.starts_with(b"\xFF\xFE") || bytes.starts_with(b"\xFE\xFF") {
+        UTF16
+    }
+    else if is_text(bytes) {
+        Text
+    }
+    else {
+        Binary
+    }
+}
+
+/// Whether these bytes look like UTF-8 text: they have to be valid UTF-8,
+/// apart from a character that got cut off by the end of the sample, and
+/// can’t contain any control characters other than whitespace.
+fn is_text(bytes: &[u8]) -> bool {
+    let valid = match ::std::str::from_utf8(bytes) {
+        Ok(s)   => s,
+        Err(e)  => {
+            // A sequence that’s just incomplete, rather than invalid, only
+            // means the sample ended in the middle of a character.
+            if e.error_len().is_some() {
+                return false;
+            }
+
+            ::std::str::from_utf8(&bytes[.. e.valid_up_to()]).unwrap()
+        }
+    };
+
+    !valid.chars().any(|c| c.is_control() && !c.is_whitespace() && c != '\x1B')
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::fields::ContentType::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(detect(b""), Empty);
+    }
+
+    #[test]
+    fn pe() {
+        assert_eq!(detect(b"MZ\x90\x00\x03\x00\x00\x00"), PE);
+    }
+
+    #[test]
+    fn elf() {
+        assert_eq!(detect(b"\x7FELF\x02\x01\x01\x00"), Elf);
+    }
+
+    #[test]
+    fn zip() {
+        assert_eq!(detect(b"PK\x03\x04\x14\x00\x00\x00\x08\x00"), Zip);
+    }
+
+    #[test]
+    fn ooxml() {
+        let mut docx = b"PK\x03\x04".to_vec();
+        docx.extend_from_slice(&[0; 26]);
+        docx.extend_from_slice(b"[Content_Types].xml");
+        assert_eq!(detect(&docx), Ooxml);
+    }
+
+    #[test]
+    fn png() {
+        assert_eq!(detect(b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR"), Png);
+    }
+
+    #[test]
+    fn jpeg() {
+        assert_eq!(detect(b"\xFF\xD8\xFF\xE0\x00\x10JFIF"), Jpeg);
+    }
+
+    #[test]
+    fn pdf() {
+        assert_eq!(detect(b"%PDF-1.7\n"), Pdf);
+    }
+
+    #[test]
+    fn utf16() {
+        assert_eq!(detect(b"\xFF\xFEh\x00i\x00"), UTF16);
+        assert_eq!(detect(b"\xFE\xFF\x00h\x00i"), UTF16);
+    }
+
+    #[test]
+    fn ascii_text() {
+        assert_eq!(detect(b"fn main() {\n\tprintln!(\"hi\");\n}\n"), Text);
+    }
+
+    #[test]
+    fn utf8_text() {
+        assert_eq!(detect("Ärger über Größe\n".as_bytes()), Text);
+    }
+
+    #[test]
+    fn utf8_cut_off() {
+        let bytes = "café".as_bytes();
+        assert_eq!(detect(&bytes[.. bytes.len() - 1]), Text);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(detect(b"caf\xE9 au lait"), Binary);
+    }
+
+    #[test]
+    fn binary() {
+        assert_eq!(detect(b"\x00\x01\x02\x03\x04"), Binary);
+    }
+}
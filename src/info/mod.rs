@@ -4,4 +4,5 @@
 //! (This counts the file name as metadata.)
 
 pub mod filetype;
-mod sources;
+pub mod magic;
+pub mod sources;
@@ -1,6 +1,84 @@
 use std::path::PathBuf;
 
-use fs::File;
+use fs::{File, OwnedFile};
+
+
+/// The extensions of compiled files, and the extensions of the source files
+/// they could have been compiled from.
+static BUILT_IN_PAIRS: &[(&str, &[&str])] = &[
+    ("class", &["java"]),            // Java
+    ("css",   &["sass", "less"]),    // SASS, Less
+    ("elc",   &["el"]),              // Emacs Lisp
+    ("hi",    &["hs"]),              // Haskell
+    ("js",    &["coffee", "ts"]),    // CoffeeScript, TypeScript
+    ("o",     &["c", "cpp"]),        // C, C++
+    ("pyc",   &["py"]),              // Python
+
+    ("obj",   &["c", "cpp", "cc", "cxx"]),  // MSVC: object file
+    ("pdb",   &["c", "cpp", "cc", "cxx"]),  // MSVC: debugging symbols
+    ("ilk",   &["c", "cpp", "cc", "cxx"]),  // MSVC: incremental linker file
+    ("exe",   &["rs"]),                     // rustc on Windows
+
+    ("aux",   &["tex"]),  // TeX: auxiliary file
+    ("bbl",   &["tex"]),  // BibTeX bibliography file
+    ("blg",   &["tex"]),  // BibTeX log file
+    ("lof",   &["tex"]),  // TeX list of figures
+    ("log",   &["tex"]),  // TeX log file
+    ("lot",   &["tex"]),  // TeX list of tables
+    ("toc",   &["tex"]),  // TeX table of contents
+];
+
+
+/// Extra pairs of compiled and source file extensions, on top of the
+/// built-in ones, which can be given in the `EXA_COMPILED` environment
+/// variable.
+#[derive(PartialEq, Debug, Default, Clone)]
+pub struct SourcePairs {
+    extra: Vec<(String, Vec<String>)>,
+}
+
+impl SourcePairs {
+
+    /// Parses pairs written like `obj=cpp,c:map=ts`: for each compiled
+    /// file extension, the source file extensions it could come from.
+    /// Entries that don’t make sense get skipped.
+    pub fn parse(input: &str) -> SourcePairs {
+        let mut pairs = SourcePairs::default();
+
+        for entry in input.split(':') {
+            let mut split = entry.splitn(2, '=');
+
+            match (split.next(), split.next()) {
+                (Some(compiled), Some(sources)) if !compiled.is_empty() => {
+                    let sources = sources.split(',')
+                                         .filter(|s| !s.is_empty())
+                                         .map(str::to_lowercase)
+                                         .collect();
+
+                    pairs.extra.push((compiled.to_lowercase(), sources));
+                }
+                _ => warn!("Couldn't parse compiled file pair {:?}", entry),
+            }
+        }
+
+        pairs
+    }
+
+    /// The extensions of the files that a file with the given extension
+    /// could have been compiled from. Extra pairs come before the built-in
+    /// ones, so they get checked first.
+    fn sources_for(&self, ext: &str) -> Vec<&str> {
+        let extra = self.extra.iter()
+                        .filter(|pair| pair.0 == ext)
+                        .flat_map(|pair| pair.1.iter().map(String::as_str));
+
+        let built_in = BUILT_IN_PAIRS.iter()
+                                     .filter(|pair| pair.0 == ext)
+                                     .flat_map(|pair| pair.1.iter().cloned());
+
+        extra.chain(built_in).collect()
+    }
+}
 
 
 impl<'a> File<'a> {
@@ -13,30 +91,120 @@ impl<'a> File<'a> {
     /// dangerous to highlight *all* compiled, so the paths in this vector
     /// are checked for existence first: for example, `foo.js` is perfectly
     /// valid without `foo.coffee`.
-    pub fn get_source_files(&self) -> Vec<PathBuf> {
-        if let Some(ref ext) = self.ext {
-            match &ext[..] {
-                "class" => vec![self.path.with_extension("java")],  // Java
-                "css"   => vec![self.path.with_extension("sass"),   self.path.with_extension("less")],  // SASS, Less
-                "elc"   => vec![self.path.with_extension("el")],    // Emacs Lisp
-                "hi"    => vec![self.path.with_extension("hs")],    // Haskell
-                "js"    => vec![self.path.with_extension("coffee"), self.path.with_extension("ts")],  // CoffeeScript, TypeScript
-                "o"     => vec![self.path.with_extension("c"),      self.path.with_extension("cpp")], // C, C++
-                "pyc"   => vec![self.path.with_extension("py")],    // Python
-
-                "aux" |                                          // TeX: auxiliary file
-                "bbl" |                                          // BibTeX bibliography file
-                "blg" |                                          // BibTeX log file
-                "lof" |                                          // TeX list of figures
-                "log" |                                          // TeX log file
-                "lot" |                                          // TeX list of tables
-                "toc" => vec![self.path.with_extension("tex")],  // TeX table of contents
-
-                _ => vec![],  // No source files if none of the above
-            }
+    pub fn get_source_files(&self, pairs: &SourcePairs) -> Vec<PathBuf> {
+        match self.ext {
+            Some(ref ext)  => pairs.sources_for(ext).into_iter().map(|s| self.path.with_extension(s)).collect(),
+            None           => vec![],  // No source files if there's no extension
+        }
+    }
+
+    /// Whether this file looks like it was compiled from another file in the
+    /// same directory, or has an extension that only compiled files have.
+    /// This checks the directory’s list of files, which has
+    /// already been read, so it doesn’t touch the filesystem. The names are
+    /// compared ignoring case, as `FOO.OBJ` could have come from `foo.cpp`.
+    pub fn is_compiled(&self, pairs: &SourcePairs) -> bool {
+        if self.extension_is_one_of( &[ "class", "elc", "hi", "o", "pyc" ]) {
+            true
+        }
+        else if let Some(dir) = self.parent_dir {
+            self.get_source_files(pairs).iter().any(|path| dir.contains_ignoring_case(path))
         }
         else {
-            vec![]  // No source files if there's no extension, either!
+            false
         }
     }
 }
+
+impl OwnedFile {
+
+    /// Whether this file looks like it was compiled from another file in the
+    /// same directory. (See `File::is_compiled`)
+    ///
+    /// An owned file doesn’t have its directory, so this looks for the
+    /// source files in the names it kept from it instead, which were read
+    /// along with the directory, so it doesn’t touch the filesystem either.
+    pub fn is_compiled(&self, pairs: &SourcePairs) -> bool {
+        let file = self.as_file(None);
+
+        match self.sibling_names() {
+            _ if file.is_compiled(pairs)  => true,
+            Some(names)                   => file.get_source_files(pairs).iter().any(|path| names.contains(path)),
+            None                          => false,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn built_in() {
+        assert_eq!(SourcePairs::default().sources_for("o"), vec![ "c", "cpp" ]);
+        assert_eq!(SourcePairs::default().sources_for("pdb"), vec![ "c", "cpp", "cc", "cxx" ]);
+    }
+
+    #[test]
+    fn unknown() {
+        assert!(SourcePairs::default().sources_for("txt").is_empty());
+    }
+
+    #[test]
+    fn extra_first() {
+        let pairs = SourcePairs::parse("o=s:MAP=Ts,js");
+        assert_eq!(pairs.sources_for("o"), vec![ "s", "c", "cpp" ]);
+        assert_eq!(pairs.sources_for("map"), vec![ "ts", "js" ]);
+    }
+
+    #[test]
+    fn nonsense() {
+        assert_eq!(SourcePairs::parse("o:=c:"), SourcePairs::default());
+    }
+
+    #[test]
+    fn owned_in_another_thread() {
+        use fs::Dir;
+        use fs::test_dir::TempDir;
+        use std::thread;
+
+        let temp = TempDir::new("sources");
+        let _ = temp.touch("Main.JAVA");
+        let _ = temp.touch("main.js");
+
+        let owned: Vec<OwnedFile> = {
+            let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+            dir.files(Default::default(), None).map(|f| f.unwrap().into()).collect()
+        };
+
+        let compiled = thread::spawn(move || {
+            let mut compiled: Vec<_> = owned.iter()
+                                            .map(|f| (f.name.clone(), f.is_compiled(&SourcePairs::parse("js=java"))))
+                                            .collect();
+            compiled.sort();
+            compiled
+        }).join().unwrap();
+
+        assert_eq!(compiled, vec![ ("Main.JAVA".into(), false), ("main.js".into(), true) ]);
+    }
+
+    #[test]
+    fn owned_without_reading_again() {
+        use fs::Dir;
+        use fs::test_dir::TempDir;
+        use std::fs;
+
+        let temp = TempDir::new("sources-kept");
+        let _ = temp.touch("main.c");
+        let object = temp.touch("main.obj");
+
+        let owned: OwnedFile = {
+            let dir = Dir::read_dir(temp.path().to_path_buf()).unwrap();
+            File::new(object, Some(&dir), None).unwrap().into()
+        };
+
+        fs::remove_file(temp.join("main.c")).unwrap();
+        assert!(owned.is_compiled(&SourcePairs::default()));
+    }
+}
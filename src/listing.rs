@@ -0,0 +1,222 @@
+//! Listing a directory without going through the command line, for
+//! programs that use exa as a library.
+//!
+//! This runs the same filtering and sorting pipeline as the `exa` binary,
+//! configured with a plain `FileFilter` value rather than parsed arguments,
+//! and hands back an owned `Entry` for each file, so callers don’t have to
+//! keep a `Dir` alive for as long as they hold on to the results.
+//!
+//! Everything in this module is part of exa’s stable API, and only changes
+//! in a backwards-compatible way between minor versions. So that new
+//! options and fields can keep being added, the structs here can’t be built
+//! field-by-field from outside exa, and the enums can’t be matched without
+//! a wildcard arm. A filter gets made by starting from the default one and
+//! changing the fields that matter:
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use exa::listing::{self, FileFilter, SortField};
+//!
+//! let mut filter = FileFilter::default();
+//! filter.sort_field = SortField::Size;
+//! filter.reverse = true;
+//!
+//! for entry in listing::list(Path::new("."), &filter).unwrap().entries {
+//!     println!("{}", entry.name);
+//! }
+//! ```
+
+use std::io::{Error as IOError, Result as IOResult};
+use std::path::{Path, PathBuf};
+
+use fs::{Dir, File, FileTarget};
+use fs::feature::GitBackend;
+use fs::feature::git::GitCache;
+use fs::feature::ignore::IgnoreCache;
+
+pub use fs::DotFilter;
+pub use fs::fields::{Type, Size, DeviceIDs, Permissions, Time, uid_t, gid_t};
+pub use fs::filter::{FileFilter, SortField, SortCase, IgnorePatterns, GitIgnore, ExaIgnore, TimeRange};
+pub use output::table::TimeType;
+
+
+/// The contents of one directory, after filtering and sorting.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Listing {
+
+    /// The files that passed the filter, in sorted order.
+    pub entries: Vec<Entry>,
+
+    /// The files that were found but couldn’t be read, with the reason why.
+    /// The rest of the directory still gets listed when this happens.
+    pub errors: Vec<(PathBuf, IOError)>,
+}
+
+/// One file’s fields, owned, and with nothing left to be worked out.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Entry {
+
+    /// The file’s name, as exa would display it.
+    pub name: String,
+
+    /// The path to the file, starting with the path that was listed.
+    pub path: PathBuf,
+
+    /// Whether this is a file, directory, link, or something else.
+    pub file_type: Type,
+
+    /// The file’s size, or its device IDs if it’s a device.
+    pub size: Size,
+
+    /// The number of hard links to the file.
+    pub links: u64,
+
+    /// The file’s inode number.
+    pub inode: u64,
+
+    /// The ID of the user that owns the file.
+    pub user: uid_t,
+
+    /// The ID of the group that owns the file.
+    pub group: gid_t,
+
+    /// The file’s permission bits.
+    pub permissions: Permissions,
+
+    /// The file’s timestamps, when the filesystem has them.
+    pub modified: Option<Time>,
+    pub changed:  Option<Time>,
+    pub accessed: Option<Time>,
+    pub created:  Option<Time>,
+
+    /// Where the file points to, if it’s a symlink whose target could be
+    /// read, whether or not that target exists.
+    pub link_target: Option<PathBuf>,
+}
+
+impl Entry {
+
+    /// Copies out the fields of a file.
+    pub fn from_file(file: &File) -> Entry {
+        let link_target = if file.is_link() {
+            match file.link_target() {
+                FileTarget::Ok(target)       => Some(target.path),
+                FileTarget::Broken(path, _)  => Some(path),
+                FileTarget::Err(..)          => None,
+            }
+        }
+        else {
+            None
+        };
+
+        Entry {
+            name:         file.display_name().into_owned(),
+            path:         file.path.clone(),
+            file_type:    file.type_char(),
+            size:         file.size(),
+            links:        file.links().count,
+            inode:        file.inode().0,
+            user:         file.user().0,
+            group:        file.group().0,
+            permissions:  file.permissions(),
+            modified:     file.modified_time(),
+            changed:      file.changed_time(),
+            accessed:     file.accessed_time(),
+            created:      file.created_time(),
+            link_target,
+        }
+    }
+}
+
+
+/// Reads the directory at the given path, then filters and sorts its
+/// contents the same way the `exa` binary would with the same filter.
+///
+/// Returns an error if the directory itself can’t be read. Files inside it
+/// that can’t be read end up in the listing’s `errors` instead.
+///
+/// When the filter sorts by Git status or keeps only changed files, the
+/// repository the directory is in gets read for its statuses. Without Git
+/// support compiled in, nothing counts as changed, and sorting by Git
+/// status sorts by name.
+pub fn list(path: &Path, filter: &FileFilter) -> IOResult<Listing> {
+    let dir = Dir::read_dir(path.to_path_buf())?;
+
+    let ignore = IgnoreCache::for_filter(filter);
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for file in dir.files(filter.dot_filter, ignore.as_ref()) {
+        match file {
+            Ok(file)  => files.push(file),
+            Err(e)    => errors.push(e),
+        }
+    }
+
+    let git = if filter.sort_field == SortField::GitStatus || filter.only_git_changes {
+        Some(GitCache::new(vec![ path.to_path_buf() ], GitBackend::Fallback, &[]))
+    }
+    else {
+        None
+    };
+
+    filter.filter_child_files(&mut files, false);
+    filter.filter_git_changes(&mut files, git.as_ref());
+    filter.sort_files(&mut files, git.as_ref());
+
+    let entries = files.iter().map(Entry::from_file).collect();
+    Ok(Listing { entries, errors })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use std::fs as std_fs;
+
+    fn names(listing: &Listing) -> Vec<&str> {
+        listing.entries.iter().map(|e| &*e.name).collect()
+    }
+
+    #[test]
+    fn lists_without_options() {
+        let temp = TempDir::new("listing");
+        std_fs::write(temp.join("b"), b"four").unwrap();
+        let _ = temp.touch("a");
+        let _ = temp.touch(".hidden");
+        std_fs::create_dir(temp.join("c")).unwrap();
+
+        let default = list(temp.path(), &FileFilter::default()).unwrap();
+
+        let filter = FileFilter { dot_filter: DotFilter::Dotfiles, list_dirs_first: true, ..FileFilter::default() };
+        let all = list(temp.path(), &filter).unwrap();
+
+        assert_eq!(names(&default), vec![ "a", "b", "c" ]);
+        assert_eq!(default.entries[1].size, Size::Some(4));
+        assert_eq!(default.entries[2].file_type, Type::Directory);
+        assert!(default.errors.is_empty());
+
+        assert_eq!(names(&all), vec![ "c", ".hidden", "a", "b" ]);
+    }
+
+    #[test]
+    fn git_status_outside_repository() {
+        let temp = TempDir::new("listing-git");
+        let _ = temp.touch("b");
+        let _ = temp.touch("a");
+
+        let filter = FileFilter { sort_field: SortField::GitStatus, ..FileFilter::default() };
+        assert_eq!(names(&list(temp.path(), &filter).unwrap()), vec![ "a", "b" ]);
+
+        let filter = FileFilter { only_git_changes: true, ..FileFilter::default() };
+        assert!(list(temp.path(), &filter).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn missing_directory() {
+        assert!(list(Path::new("/this/does/not/exist"), &FileFilter::default()).is_err());
+    }
+}
@@ -1,24 +1,37 @@
 //! Parsing the options for `FileFilter`.
 
+use std::ffi::OsStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use fs::DotFilter;
-use fs::filter::{FileFilter, SortField, SortCase, IgnorePatterns, GitIgnore};
+use fs::fields as f;
+use fs::filter::{FileFilter, SortField, SortCase, IgnorePatterns, GitIgnore, ExaIgnore, TimeRange};
+use fs::pattern::PatternCase;
+use output::table::{TimeType, TimeTypes};
 
-use options::{flags, Misfire};
-use options::parser::MatchedFlags;
+use options::{flags, Misfire, Vars};
+use options::parser::{Arg, MatchedFlags};
 
 
 impl FileFilter {
 
     /// Determines which of all the file filter options to use.
-    pub fn deduce(matches: &MatchedFlags) -> Result<FileFilter, Misfire> {
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<FileFilter, Misfire> {
         Ok(FileFilter {
             list_dirs_first: matches.has(&flags::DIRS_FIRST)?,
+            list_hidden_last: matches.has(&flags::HIDDEN_LAST)?,
             reverse:         matches.has(&flags::REVERSE)?,
             only_dirs:       matches.has(&flags::ONLY_DIRS)?,
+            only_git_changes: matches.has(&flags::GIT_ONLY_CHANGED)?,
+            dereference:     matches.has(&flags::DEREFERENCE)?,
+            dereference_args: matches.has(&flags::DEREFERENCE_ARGS)?,
             sort_field:      SortField::deduce(matches)?,
             dot_filter:      DotFilter::deduce(matches)?,
             ignore_patterns: IgnorePatterns::deduce(matches)?,
             git_ignore:      GitIgnore::deduce(matches)?,
+            exa_ignore:      ExaIgnore::deduce(matches, vars)?,
+            pattern_case:    PatternCase::deduce(matches)?,
+            time_range:      TimeRange::deduce(matches)?,
         })
     }
 }
@@ -42,6 +55,9 @@ impl SortField {
         else if word == "Name" || word == "Filename" {
             Ok(SortField::Name(SortCase::ABCabc))
         }
+        else if word == "name-raw" {
+            Ok(SortField::NameRaw)
+        }
         else if word == ".name" || word == ".filename" {
             Ok(SortField::NameMixHidden(SortCase::AaBbCc))
         }
@@ -49,7 +65,15 @@ impl SortField {
             Ok(SortField::NameMixHidden(SortCase::ABCabc))
         }
         else if word == "size" || word == "filesize" {
-            Ok(SortField::Size)
+            if matches.get(&flags::SIZE)?.map(|s| s == "disk") == Some(true) {
+                Ok(SortField::DiskSize)
+            }
+            else if matches.has(&flags::DIR_ENTRIES)? {
+                Ok(SortField::SizeOrEntries)
+            }
+            else {
+                Ok(SortField::Size)
+            }
         }
         else if word == "ext" || word == "extension" {
             Ok(SortField::Extension(SortCase::AaBbCc))
@@ -57,7 +81,17 @@ impl SortField {
         else if word == "Ext" || word == "Extension" {
             Ok(SortField::Extension(SortCase::ABCabc))
         }
-        else if word == "date" || word == "time" || word == "mod" || word == "modified" || word == "new" || word == "newest" {
+        else if word == "date" || word == "time" {
+            // Like ls, sorting by “the time” uses whichever timestamp is
+            // being shown, picking the first column if there are several.
+            match TimeTypes::deduce(matches)?.first() {
+                TimeType::Modified  => Ok(SortField::ModifiedDate),
+                TimeType::Changed   => Ok(SortField::ChangedDate),
+                TimeType::Created   => Ok(SortField::CreatedDate),
+                TimeType::Accessed  => Ok(SortField::AccessedDate),
+            }
+        }
+        else if word == "mod" || word == "modified" || word == "new" || word == "newest" {
             // “new” sorts oldest at the top and newest at the bottom; “old”
             // sorts newest at the top and oldest at the bottom. I think this
             // is the right way round to do this: “size” puts the smallest at
@@ -73,6 +107,9 @@ impl SortField {
         else if word == "acc" || word == "accessed" {
             Ok(SortField::AccessedDate)
         }
+        else if word == "ch" || word == "changed" {
+            Ok(SortField::ChangedDate)
+        }
         else if word == "cr" || word == "created" {
             Ok(SortField::CreatedDate)
         }
@@ -82,6 +119,12 @@ impl SortField {
         else if word == "type" {
             Ok(SortField::FileType)
         }
+        else if word == "owner" {
+            Ok(SortField::Owner)
+        }
+        else if word == "git" || word == "status" {
+            Ok(SortField::GitStatus)
+        }
         else if word == "none" {
             Ok(SortField::Unsorted)
         }
@@ -174,9 +217,11 @@ impl IgnorePatterns {
             Some(is) => is,
         };
 
+        let case = PatternCase::deduce(matches)?;
+
         // Awkwardly, though, a glob pattern can be invalid, and we need to
         // deal with invalid patterns somehow.
-        let (patterns, mut errors) = IgnorePatterns::parse_from_iter(inputs.to_string_lossy().split('|'));
+        let (patterns, mut errors) = IgnorePatterns::parse_from_iter_with_case(inputs.to_string_lossy().split('|'), case);
 
         // It can actually return more than one glob error,
         // but we only use one. (TODO)
@@ -188,6 +233,17 @@ impl IgnorePatterns {
 }
 
 
+impl PatternCase {
+
+    /// Determines whether file name patterns care about case. They do when
+    /// `--case-sensitive-patterns` is passed, and otherwise they follow the
+    /// platform, ignoring case on Windows.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
+        Ok(if matches.has(&flags::CASE_SENSITIVE_PATTERNS)? { PatternCase::Sensitive }
+                                                       else { PatternCase::default() })
+    }
+}
+
 impl GitIgnore {
     pub fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
         Ok(if matches.has(&flags::GIT_IGNORE)? { GitIgnore::CheckAndIgnore }
@@ -195,6 +251,163 @@ impl GitIgnore {
     }
 }
 
+impl ExaIgnore {
+
+    /// Determines whether to read `.exaignore` files, which happens unless
+    /// `--no-exaignore` is passed, and how far up to look for them, which
+    /// is set with the `EXA_IGNORE_DEPTH` environment variable.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, Misfire> {
+        use options::vars;
+
+        if matches.has(&flags::NO_EXAIGNORE)? {
+            return Ok(ExaIgnore::Off);
+        }
+
+        match vars.get(vars::EXA_IGNORE_DEPTH).and_then(|s| s.into_string().ok()) {
+            Some(depth) => match depth.parse() {
+                Ok(d)   => Ok(ExaIgnore::CheckAndIgnore { depth: Some(d) }),
+                Err(e)  => Err(Misfire::FailedParse(e)),
+            },
+            None => Ok(ExaIgnore::CheckAndIgnore { depth: None }),
+        }
+    }
+}
+
+impl TimeRange {
+
+    /// Determines the window of time to show files from, using the
+    /// `--newer-than` and `--older-than` arguments. Durations are counted
+    /// back from now, and the timestamp compared is the one the time
+    /// arguments pick, which is the modified time unless told otherwise.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
+        let newer = matches.get(&flags::NEWER_THAN)?;
+        let older = matches.get(&flags::OLDER_THAN)?;
+        if newer.is_none() && older.is_none() {
+            return Ok(TimeRange::default());
+        }
+
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d)  => f::Time { seconds: d.as_secs() as _, nanoseconds: d.subsec_nanos() as _ },
+            Err(_) => f::Time { seconds: 0, nanoseconds: 0 },
+        };
+
+        Ok(TimeRange {
+            time_type:  TimeTypes::deduce(matches)?.first(),
+            newer_than: match newer { Some(w) => Some(parse_time(&flags::NEWER_THAN, w, now)?), None => None },
+            older_than: match older { Some(w) => Some(parse_time(&flags::OLDER_THAN, w, now)?), None => None },
+        })
+    }
+}
+
+/// Parses a time argument, which is either a duration before `now`, such as
+/// `3h` or `2 days`, or a date, such as `2024-01-01` or
+/// `2024-01-01T09:30:00+01:00`. Dates without an offset are in local time.
+fn parse_time(arg: &'static Arg, word: &OsStr, now: f::Time) -> Result<f::Time, Misfire> {
+    let fail = || Misfire::FailedTimeParse(arg, word.to_os_string());
+    let text = word.to_str().ok_or_else(fail)?.trim();
+
+    if let Some(secs) = parse_duration(text) {
+        return Ok(f::Time { seconds: now.seconds - secs, nanoseconds: now.nanoseconds });
+    }
+
+    parse_date(text).map(|seconds| f::Time { seconds, nanoseconds: 0 })
+                    .ok_or_else(fail)
+}
+
+/// Parses a number followed by a unit into a count of seconds.
+fn parse_duration(text: &str) -> Option<i64> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let count: i64 = text[.. split].parse().ok()?;
+
+    let unit = match text[split ..].trim_start() {
+        "s" | "sec" | "secs" | "second" | "seconds"  => 1,
+        "m" | "min" | "mins" | "minute" | "minutes"  => 60,
+        "h" | "hour" | "hours"                       => 60 * 60,
+        "d" | "day" | "days"                         => 60 * 60 * 24,
+        "w" | "week" | "weeks"                       => 60 * 60 * 24 * 7,
+        _                                            => return None,
+    };
+
+    count.checked_mul(unit)
+}
+
+/// Parses an ISO 8601 date, with an optional time and UTC offset, into
+/// seconds since the epoch.
+fn parse_date(text: &str) -> Option<i64> {
+    let (date, rest) = if text.len() > 10 && text.is_char_boundary(10) { text.split_at(10) } else { (text, "") };
+    let mut ymd = date.splitn(3, '-');
+    let year:  i64 = number(ymd.next()?, 4)?;
+    let month: i64 = number(ymd.next()?, 2)?;
+    let day:   i64 = number(ymd.next()?, 2)?;
+    if !(1 ..= 12).contains(&month) || !(1 ..= 31).contains(&day) {
+        return None;
+    }
+
+    let mut rest = rest;
+    let (mut hour, mut minute, mut second) = (0, 0, 0);
+    if rest.starts_with('T') || rest.starts_with(' ') {
+        let time_len = rest[1 ..].find(['Z', '+', '-']).map_or(rest.len(), |i| i + 1);
+        let mut hms = rest[1 .. time_len].split(':');
+        hour   = number(hms.next()?, 2)?;
+        minute = number(hms.next()?, 2)?;
+        second = match hms.next() { Some(s) => number(s, 2)?, None => 0 };
+        if hms.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        rest = &rest[time_len ..];
+    }
+
+    let offset = match rest {
+        ""  => return local_seconds(year, month, day, hour, minute, second),
+        "Z" => 0,
+        _   => {
+            let sign = if rest.starts_with('-') { -1 } else if rest.starts_with('+') { 1 } else { return None };
+            let mut hm = rest[1 ..].splitn(2, ':');
+            let hours: i64 = number(hm.next()?, 2)?;
+            let minutes: i64 = match hm.next() { Some(m) => number(m, 2)?, None => 0 };
+            sign * (hours * 60 + minutes) * 60
+        }
+    };
+
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+/// Parses a field of exactly `width` ASCII digits.
+fn number(text: &str, width: usize) -> Option<i64> {
+    if text.len() == width && text.bytes().all(|b| b.is_ascii_digit()) { text.parse().ok() }
+                                                                  else { None }
+}
+
+/// The number of days between the Unix epoch and the given date in the
+/// proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Turns a local date and time into seconds since the epoch, letting the C
+/// library work out the offset, including whether daylight saving applies.
+fn local_seconds(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> Option<i64> {
+    use libc;
+
+    let mut tm: libc::tm = unsafe { ::std::mem::zeroed() };
+    tm.tm_year  = (year - 1900) as _;
+    tm.tm_mon   = (month - 1) as _;
+    tm.tm_mday  = day as _;
+    tm.tm_hour  = hour as _;
+    tm.tm_min   = minute as _;
+    tm.tm_sec   = second as _;
+    tm.tm_isdst = -1;
+
+    match unsafe { libc::mktime(&mut tm) } {
+        -1 => None,
+        t  => Some(t),
+    }
+}
 
 
 #[cfg(test)]
@@ -212,12 +425,29 @@ mod test {
                 use options::test::parse_for_test;
                 use options::test::Strictnesses::*;
 
-                static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::ALL, &flags::TREE, &flags::IGNORE_GLOB, &flags::GIT_IGNORE ];
+                static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::ALL, &flags::TREE, &flags::IGNORE_GLOB, &flags::GIT_IGNORE, &flags::DIR_ENTRIES, &flags::SIZE,
+                                               &flags::TIME, &flags::MODIFIED, &flags::CHANGED, &flags::CREATED, &flags::ACCESSED,
+                                               &flags::NEWER_THAN, &flags::OLDER_THAN, &flags::CASE_SENSITIVE_PATTERNS ];
                 for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| $type::deduce(mf)) {
                     assert_eq!(result, $result);
                 }
             }
         };
+
+        ($name:ident: $type:ident <- $inputs:expr, $vars:expr; $stricts:expr => $result:expr) => {
+            /// Like above, but with $vars.
+            #[test]
+            fn $name() {
+                use options::parser::Arg;
+                use options::test::parse_for_test;
+                use options::test::Strictnesses::*;
+
+                static TEST_ARGS: &[&Arg] = &[ &flags::NO_EXAIGNORE ];
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| $type::deduce(mf, &$vars)) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
     }
 
     mod sort_fields {
@@ -232,6 +462,7 @@ mod test {
         test!(one_short:     SortField <- ["-saccessed"];      Both => Ok(SortField::AccessedDate));
         test!(lowercase:     SortField <- ["--sort", "name"];  Both => Ok(SortField::Name(SortCase::AaBbCc)));
         test!(uppercase:     SortField <- ["--sort", "Name"];  Both => Ok(SortField::Name(SortCase::ABCabc)));
+        test!(raw:           SortField <- ["--sort", "name-raw"];  Both => Ok(SortField::NameRaw));
         test!(old:           SortField <- ["--sort", "new"];   Both => Ok(SortField::ModifiedDate));
         test!(oldest:        SortField <- ["--sort=newest"];   Both => Ok(SortField::ModifiedDate));
         test!(new:           SortField <- ["--sort", "old"];   Both => Ok(SortField::ModifiedAge));
@@ -241,6 +472,26 @@ mod test {
         test!(mix_hidden_lowercase:     SortField <- ["--sort", ".name"];  Both => Ok(SortField::NameMixHidden(SortCase::AaBbCc)));
         test!(mix_hidden_uppercase:     SortField <- ["--sort", ".Name"];  Both => Ok(SortField::NameMixHidden(SortCase::ABCabc)));
 
+        // Directory entry counts
+        test!(entries:       SortField <- ["--sort=size", "--dir-entries"];  Both => Ok(SortField::SizeOrEntries));
+        test!(disk:          SortField <- ["--sort=size", "--size=disk"];    Both => Ok(SortField::DiskSize));
+        test!(both_sizes:    SortField <- ["--sort=size", "--size=both"];    Both => Ok(SortField::Size));
+        test!(entries_name:  SortField <- ["--sort=name", "--dir-entries"];  Both => Ok(SortField::Name(SortCase::AaBbCc)));
+
+        // Sorting by the time shown
+        test!(time:          SortField <- ["--sort=time"];                    Both => Ok(SortField::ModifiedDate));
+        test!(time_changed:  SortField <- ["--sort=time", "--time=changed"];  Both => Ok(SortField::ChangedDate));
+        test!(time_accessed: SortField <- ["--sort=date", "-u"];              Both => Ok(SortField::AccessedDate));
+        test!(time_created:  SortField <- ["--sort=time", "-U"];              Both => Ok(SortField::CreatedDate));
+        test!(time_several:  SortField <- ["--sort=time", "-uU"];             Both => Ok(SortField::CreatedDate));
+        test!(time_list:     SortField <- ["--sort=time", "--time=acc,ch"];   Both => Ok(SortField::AccessedDate));
+        test!(time_all:      SortField <- ["--sort=time", "--time=all"];      Both => Ok(SortField::ModifiedDate));
+        test!(time_explicit: SortField <- ["--sort=modified", "-u"];          Both => Ok(SortField::ModifiedDate));
+        test!(changed:       SortField <- ["--sort=changed"];                 Both => Ok(SortField::ChangedDate));
+        test!(owner:         SortField <- ["--sort=owner"];                   Both => Ok(SortField::Owner));
+        test!(git:           SortField <- ["--sort=git"];                     Both => Ok(SortField::GitStatus));
+        test!(status:        SortField <- ["--sort=status"];                  Both => Ok(SortField::GitStatus));
+
         // Errors
         test!(error:         SortField <- ["--sort=colour"];   Both => Err(Misfire::BadArgument(&flags::SORT, OsString::from("colour"))));
 
@@ -293,6 +544,17 @@ mod test {
         test!(overridden_2: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];      Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.MP3") ])));
         test!(overridden_3: IgnorePatterns <- ["-I=*.ogg",    "-I", "*.mp3"];  Complain => Err(Misfire::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
         test!(overridden_4: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];      Complain => Err(Misfire::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
+
+        // Case
+        test!(sensitive:    IgnorePatterns <- ["-I*.ogg", "--case-sensitive-patterns"];  Both => Ok(IgnorePatterns::parse_from_iter_with_case(vec![ "*.ogg" ], PatternCase::Sensitive).0));
+    }
+
+
+    mod pattern_cases {
+        use super::*;
+
+        test!(platform:   PatternCase <- [];                             Both => Ok(PatternCase::default()));
+        test!(sensitive:  PatternCase <- ["--case-sensitive-patterns"];  Both => Ok(PatternCase::Sensitive));
     }
 
 
@@ -302,4 +564,64 @@ mod test {
         test!(off:  GitIgnore <- [];                Both => Ok(GitIgnore::Off));
         test!(on:   GitIgnore <- ["--git-ignore"];  Both => Ok(GitIgnore::CheckAndIgnore));
     }
+
+
+    mod exa_ignores {
+        use super::*;
+
+        test!(on:         ExaIgnore <- [], None;                                  Both => Ok(ExaIgnore::CheckAndIgnore { depth: None }));
+        test!(off:        ExaIgnore <- ["--no-exaignore"], None;                  Both => Ok(ExaIgnore::Off));
+        test!(depth:      ExaIgnore <- [], Some("2".into());                      Both => Ok(ExaIgnore::CheckAndIgnore { depth: Some(2) }));
+        test!(depth_off:  ExaIgnore <- ["--no-exaignore"], Some("2".into());      Both => Ok(ExaIgnore::Off));
+        test!(not_depth:  ExaIgnore <- [], Some("up".into());                     Both => Err(Misfire::FailedParse("up".parse::<usize>().unwrap_err())));
+    }
+
+
+    mod time_ranges {
+        use super::*;
+
+        fn at(seconds: i64) -> Option<f::Time> {
+            Some(f::Time { seconds, nanoseconds: 0 })
+        }
+
+        fn range(time_type: TimeType, newer_than: Option<f::Time>, older_than: Option<f::Time>) -> TimeRange {
+            TimeRange { time_type, newer_than, older_than }
+        }
+
+        test!(empty:     TimeRange <- [];                                          Both => Ok(TimeRange::default()));
+        test!(newer:     TimeRange <- ["--newer-than=2024-01-01T00:00:00Z"];       Both => Ok(range(TimeType::Modified, at(1_704_067_200), None)));
+        test!(older:     TimeRange <- ["--older-than", "2000-03-01Z"];             Both => Ok(range(TimeType::Modified, None, at(951_868_800))));
+        test!(accessed:  TimeRange <- ["--older-than=1970-01-02Z", "-u"];          Both => Ok(range(TimeType::Accessed, None, at(86_400))));
+        test!(created:   TimeRange <- ["--newer-than=1970-01-01Z", "--time=cr"];   Both => Ok(range(TimeType::Created, at(0), None)));
+        test!(offset:    TimeRange <- ["--newer-than=2024-01-01 09:30+09:30"];     Both => Ok(range(TimeType::Modified, at(1_704_067_200), None)));
+        test!(negative:  TimeRange <- ["--newer-than=1969-12-31T23:00:00-01:00"];  Both => Ok(range(TimeType::Modified, at(0), None)));
+
+        test!(bad:       TimeRange <- ["--newer-than=yesterday"];                  Both => Err(Misfire::FailedTimeParse(&flags::NEWER_THAN, OsString::from("yesterday"))));
+        test!(no_unit:   TimeRange <- ["--older-than=3"];                          Both => Err(Misfire::FailedTimeParse(&flags::OLDER_THAN, OsString::from("3"))));
+        test!(bad_month: TimeRange <- ["--older-than=2024-13-01"];                 Both => Err(Misfire::FailedTimeParse(&flags::OLDER_THAN, OsString::from("2024-13-01"))));
+
+        test!(overridden:   TimeRange <- ["--newer-than=2024-01-01T00:00:00Z", "--newer-than=1970-01-01Z"];  Last => Ok(range(TimeType::Modified, at(0), None)));
+        test!(overridden_2: TimeRange <- ["--newer-than=2024-01-01T00:00:00Z", "--newer-than=1970-01-01Z"];  Complain => Err(Misfire::Duplicate(Flag::Long("newer-than"), Flag::Long("newer-than"))));
+
+        #[test]
+        fn durations() {
+            let now = f::Time { seconds: 1_000_000, nanoseconds: 5 };
+            let ago = |word: &str| parse_time(&flags::NEWER_THAN, OsStr::new(word), now).map(|t| now.seconds - t.seconds);
+
+            assert_eq!(ago("90s"),     Ok(90));
+            assert_eq!(ago("5m"),      Ok(300));
+            assert_eq!(ago("3h"),      Ok(10_800));
+            assert_eq!(ago("2 days"),  Ok(172_800));
+            assert_eq!(ago("1 week"),  Ok(604_800));
+            assert!(ago("3 fortnights").is_err());
+            assert!(ago("h").is_err());
+        }
+
+        #[test]
+        fn civil_days() {
+            assert_eq!(days_from_civil(1970, 1, 1),  0);
+            assert_eq!(days_from_civil(2000, 3, 1),  11_017);
+            assert_eq!(days_from_civil(1969, 12, 31), -1);
+        }
+    }
 }
@@ -10,9 +10,28 @@ pub static ONE_LINE: Arg = Arg { short: Some(b'1'), long: "oneline",  takes_valu
 pub static LONG:     Arg = Arg { short: Some(b'l'), long: "long",     takes_value: TakesValue::Forbidden };
 pub static GRID:     Arg = Arg { short: Some(b'G'), long: "grid",     takes_value: TakesValue::Forbidden };
 pub static ACROSS:   Arg = Arg { short: Some(b'x'), long: "across",   takes_value: TakesValue::Forbidden };
+pub static COLUMNS:  Arg = Arg { short: None,       long: "columns",  takes_value: TakesValue::Necessary(None) };
 pub static RECURSE:  Arg = Arg { short: Some(b'R'), long: "recurse",  takes_value: TakesValue::Forbidden };
 pub static TREE:     Arg = Arg { short: Some(b'T'), long: "tree",     takes_value: TakesValue::Forbidden };
+pub static TREE_STYLE: Arg = Arg { short: None,     long: "tree-style", takes_value: TakesValue::Necessary(Some(TREE_STYLES)) };
+const TREE_STYLES: &[&str] = &["unicode", "ascii"];
+pub static TREE_SUMMARY: Arg = Arg { short: None,   long: "tree-summary", takes_value: TakesValue::Optional(Some(TREE_SUMMARIES)) };
+const TREE_SUMMARIES: &[&str] = &["immediate", "recursive"];
+pub static MARK_EMPTY: Arg = Arg { short: None,     long: "mark-empty", takes_value: TakesValue::Forbidden };
+pub static SUMMARY:  Arg = Arg { short: None,       long: "summary",  takes_value: TakesValue::Forbidden };
 pub static CLASSIFY: Arg = Arg { short: Some(b'F'), long: "classify", takes_value: TakesValue::Forbidden };
+pub static LITERAL:  Arg = Arg { short: None,       long: "literal",  takes_value: TakesValue::Forbidden };
+pub static QUOTES:   Arg = Arg { short: None,       long: "quotes",   takes_value: TakesValue::Optional(Some(QUOTE_STYLES)) };
+const QUOTE_STYLES: &[&str] = &["shell", "powershell"];
+pub static LINK_TARGETS: Arg = Arg { short: None,   long: "link-targets", takes_value: TakesValue::Necessary(Some(LINK_TARGET_STYLES)) };
+const LINK_TARGET_STYLES: &[&str] = &["full", "name", "never"];
+pub static RELATIVE_TO: Arg = Arg { short: None,    long: "relative-to", takes_value: TakesValue::Necessary(None) };
+pub static PROTECTED: Arg = Arg { short: None,      long: "protected",  takes_value: TakesValue::Forbidden };
+pub static IN_USE:    Arg = Arg { short: None,      long: "in-use",     takes_value: TakesValue::Forbidden };
+pub static HIGHLIGHT: Arg = Arg { short: None,      long: "highlight",  takes_value: TakesValue::Necessary(None) };
+pub static MARKDOWN: Arg = Arg { short: None,       long: "markdown",   takes_value: TakesValue::Forbidden };
+pub static DIRED:    Arg = Arg { short: None,       long: "dired",      takes_value: TakesValue::Forbidden };
+pub static FORMAT:   Arg = Arg { short: None,       long: "format",     takes_value: TakesValue::Necessary(None) };
 
 pub static COLOR:  Arg = Arg { short: None, long: "color",  takes_value: TakesValue::Necessary(Some(COLOURS)) };
 pub static COLOUR: Arg = Arg { short: None, long: "colour", takes_value: TakesValue::Necessary(Some(COLOURS)) };
@@ -25,15 +44,25 @@ pub static COLOUR_SCALE: Arg = Arg { short: None, long: "colour-scale", takes_va
 pub static ALL:         Arg = Arg { short: Some(b'a'), long: "all",         takes_value: TakesValue::Forbidden };
 pub static LIST_DIRS:   Arg = Arg { short: Some(b'd'), long: "list-dirs",   takes_value: TakesValue::Forbidden };
 pub static LEVEL:       Arg = Arg { short: Some(b'L'), long: "level",       takes_value: TakesValue::Necessary(None) };
+pub static FOLLOW_LINKS: Arg = Arg { short: None,       long: "follow-links", takes_value: TakesValue::Forbidden };
+pub static JOBS:        Arg = Arg { short: None,       long: "jobs",        takes_value: TakesValue::Necessary(None) };
 pub static REVERSE:     Arg = Arg { short: Some(b'r'), long: "reverse",     takes_value: TakesValue::Forbidden };
 pub static SORT:        Arg = Arg { short: Some(b's'), long: "sort",        takes_value: TakesValue::Necessary(Some(SORTS)) };
 pub static IGNORE_GLOB: Arg = Arg { short: Some(b'I'), long: "ignore-glob", takes_value: TakesValue::Necessary(None) };
 pub static GIT_IGNORE:  Arg = Arg { short: None, long: "git-ignore",           takes_value: TakesValue::Forbidden };
+pub static CASE_SENSITIVE_PATTERNS: Arg = Arg { short: None, long: "case-sensitive-patterns", takes_value: TakesValue::Forbidden };
+pub static NO_EXAIGNORE: Arg = Arg { short: None, long: "no-exaignore",        takes_value: TakesValue::Forbidden };
 pub static DIRS_FIRST:  Arg = Arg { short: None, long: "group-directories-first",  takes_value: TakesValue::Forbidden };
+pub static HIDDEN_LAST: Arg = Arg { short: None, long: "hidden-last",          takes_value: TakesValue::Forbidden };
 pub static ONLY_DIRS:   Arg = Arg { short: Some(b'D'), long: "only-dirs", takes_value: TakesValue::Forbidden };
-const SORTS: Values = &[ "name", "Name", "size", "extension",
-                             "Extension", "modified", "accessed",
-                             "created", "inode", "type", "none" ];
+pub static DEREFERENCE: Arg = Arg { short: None, long: "dereference",          takes_value: TakesValue::Forbidden };
+pub static DEREFERENCE_ARGS: Arg = Arg { short: None, long: "dereference-command-line", takes_value: TakesValue::Forbidden };
+pub static NEWER_THAN:  Arg = Arg { short: None, long: "newer-than",           takes_value: TakesValue::Necessary(None) };
+pub static OLDER_THAN:  Arg = Arg { short: None, long: "older-than",           takes_value: TakesValue::Necessary(None) };
+const SORTS: Values = &[ "name", "Name", "name-raw", "size", "extension",
+                             "Extension", "modified", "changed", "accessed",
+                             "created", "inode", "type", "owner", "git",
+                             "none" ];
 
 // display options
 pub static BINARY:     Arg = Arg { short: Some(b'b'), long: "binary",     takes_value: TakesValue::Forbidden };
@@ -47,27 +76,61 @@ pub static BLOCKS:     Arg = Arg { short: Some(b'S'), long: "blocks",     takes_
 pub static TIME:       Arg = Arg { short: Some(b't'), long: "time",       takes_value: TakesValue::Necessary(Some(TIMES)) };
 pub static ACCESSED:   Arg = Arg { short: Some(b'u'), long: "accessed",   takes_value: TakesValue::Forbidden };
 pub static CREATED:    Arg = Arg { short: Some(b'U'), long: "created",    takes_value: TakesValue::Forbidden };
+pub static CHANGED:    Arg = Arg { short: None,       long: "changed",    takes_value: TakesValue::Forbidden };
 pub static TIME_STYLE: Arg = Arg { short: None,       long: "time-style", takes_value: TakesValue::Necessary(Some(TIME_STYLES)) };
-const TIMES: Values = &["modified", "accessed", "created"];
-const TIME_STYLES: Values = &["default", "long-iso", "full-iso", "iso"];
+pub static SEPARATOR:  Arg = Arg { short: None,       long: "separator",  takes_value: TakesValue::Necessary(None) };
+pub static DEDUP_COLUMNS: Arg = Arg { short: None,    long: "dedup-columns", takes_value: TakesValue::Forbidden };
+pub static NUMERIC:    Arg = Arg { short: Some(b'n'), long: "numeric",    takes_value: TakesValue::Forbidden };
+pub static OWNER_WIDTH: Arg = Arg { short: None,     long: "owner-width", takes_value: TakesValue::Necessary(None) };
+pub static OWNER:      Arg = Arg { short: None,       long: "owner",      takes_value: TakesValue::Necessary(Some(OWNERS)) };
+pub static UNIFORM_COLUMNS: Arg = Arg { short: None,  long: "uniform-columns", takes_value: TakesValue::Forbidden };
+pub static TIME_CLOCK: Arg = Arg { short: None,       long: "time-clock", takes_value: TakesValue::Necessary(Some(TIME_CLOCKS)) };
+pub static LOCALE:     Arg = Arg { short: None,       long: "locale",     takes_value: TakesValue::Necessary(None) };
+pub static SIZE:       Arg = Arg { short: None,       long: "size",       takes_value: TakesValue::Necessary(Some(SIZES)) };
+pub static DIR_ENTRIES: Arg = Arg { short: None,      long: "dir-entries", takes_value: TakesValue::Forbidden };
+pub static VOLUME:     Arg = Arg { short: None,       long: "volume",     takes_value: TakesValue::Forbidden };
+pub static HARD_LINKS: Arg = Arg { short: None,       long: "hard-links", takes_value: TakesValue::Forbidden };
+pub static NETWORK:    Arg = Arg { short: None,       long: "network",    takes_value: TakesValue::Forbidden };
+pub static MOTW:       Arg = Arg { short: None,       long: "motw",       takes_value: TakesValue::Forbidden };
+pub static MY_ACCESS:  Arg = Arg { short: None,       long: "my-access",  takes_value: TakesValue::Forbidden };
+pub static SIGNATURES: Arg = Arg { short: None,       long: "signatures", takes_value: TakesValue::Forbidden };
+pub static VERSION_INFO: Arg = Arg { short: None,     long: "version-info", takes_value: TakesValue::Forbidden };
+pub static MIME:       Arg = Arg { short: None,       long: "mime",       takes_value: TakesValue::Forbidden };
+pub static CHECKSUM:   Arg = Arg { short: None,       long: "checksum",   takes_value: TakesValue::Necessary(Some(CHECKSUMS)) };
+pub static CHECKSUM_LIMIT: Arg = Arg { short: None,   long: "checksum-limit", takes_value: TakesValue::Necessary(None) };
+pub static TOTAL:      Arg = Arg { short: None,       long: "total",      takes_value: TakesValue::Forbidden };
+pub static ORDER:      Arg = Arg { short: None,       long: "order",      takes_value: TakesValue::Necessary(Some(COLUMN_NAMES)) };
+const TIMES: Values = &["modified", "changed", "accessed", "created", "all"];
+const CHECKSUMS: Values = &["crc32", "md5", "sha1", "sha256"];
+const SIZES: Values = &["logical", "disk", "both"];
+const OWNERS: Values = &["user", "combined", "none"];
+const TIME_STYLES: Values = &["default", "long-iso", "full-iso", "iso", "unix", "unix-ms"];
+const TIME_CLOCKS: Values = &["12", "24", "auto"];
+pub const COLUMN_NAMES: Values = &["inode", "permissions", "access", "links", "size", "disk", "blocks",
+                                   "user", "group", "owner", "modified", "changed", "created", "accessed", "volume", "network",
+                                   "zone", "type", "checksum", "signature", "version", "git"];
 
 // optional feature options
-pub static GIT:       Arg = Arg { short: None,       long: "git",      takes_value: TakesValue::Forbidden };
+pub static GIT:       Arg = Arg { short: None,       long: "git",      takes_value: TakesValue::Optional(Some(GIT_BACKENDS)) };
+const GIT_BACKENDS: Values = &["libgit2", "command"];
+pub static GIT_CEILING: Arg = Arg { short: None,     long: "git-ceiling", takes_value: TakesValue::Necessary(None) };
+pub static GIT_ONLY_CHANGED: Arg = Arg { short: None, long: "git-only-changed", takes_value: TakesValue::Forbidden };
 pub static EXTENDED:  Arg = Arg { short: Some(b'@'), long: "extended", takes_value: TakesValue::Forbidden };
 
 
 pub static ALL_ARGS: Args = Args(&[
     &VERSION, &HELP,
 
-    &ONE_LINE, &LONG, &GRID, &ACROSS, &RECURSE, &TREE, &CLASSIFY,
+    &ONE_LINE, &LONG, &GRID, &ACROSS, &COLUMNS, &RECURSE, &TREE, &TREE_STYLE, &TREE_SUMMARY, &MARK_EMPTY, &SUMMARY, &CLASSIFY, &LITERAL, &QUOTES, &LINK_TARGETS, &RELATIVE_TO, &PROTECTED, &IN_USE, &HIGHLIGHT, &MARKDOWN, &DIRED, &FORMAT,
     &COLOR, &COLOUR, &COLOR_SCALE, &COLOUR_SCALE,
 
-    &ALL, &LIST_DIRS, &LEVEL, &REVERSE, &SORT, &DIRS_FIRST,
-    &IGNORE_GLOB, &GIT_IGNORE, &ONLY_DIRS,
+    &ALL, &LIST_DIRS, &LEVEL, &FOLLOW_LINKS, &JOBS, &REVERSE, &SORT, &DIRS_FIRST, &HIDDEN_LAST,
+    &IGNORE_GLOB, &GIT_IGNORE, &NO_EXAIGNORE, &CASE_SENSITIVE_PATTERNS, &ONLY_DIRS, &DEREFERENCE, &DEREFERENCE_ARGS, &NEWER_THAN, &OLDER_THAN,
 
     &BINARY, &BYTES, &GROUP, &HEADER, &INODE, &LINKS, &MODIFIED, &BLOCKS,
-    &TIME, &ACCESSED, &CREATED, &TIME_STYLE,
+    &TIME, &ACCESSED, &CHANGED, &CREATED, &TIME_STYLE, &TIME_CLOCK, &LOCALE, &SIZE, &DIR_ENTRIES, &TOTAL, &VOLUME, &NETWORK, &MOTW, &HARD_LINKS, &MY_ACCESS, &SIGNATURES, &VERSION_INFO, &MIME,
+    &CHECKSUM, &CHECKSUM_LIMIT, &ORDER, &SEPARATOR, &DEDUP_COLUMNS, &UNIFORM_COLUMNS, &NUMERIC, &OWNER_WIDTH, &OWNER,
 
-    &GIT, &EXTENDED,
+    &GIT, &GIT_CEILING, &GIT_ONLY_CHANGED, &EXTENDED,
 ]);
 
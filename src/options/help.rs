@@ -12,11 +12,27 @@ static OPTIONS: &str = r##"
 DISPLAY OPTIONS
   -1, --oneline      display one entry per line
   -l, --long         display extended file metadata as a table
+  --markdown         display the long view's columns as a Markdown table
+  --format TEMPLATE  print a line for each file, such as '{name}\t{size}'
   -G, --grid         display entries as a grid (default)
   -x, --across       sort the grid across, rather than downwards
+  --columns COUNT    lay the grid out in exactly this many columns
   -R, --recurse      recurse into directories
   -T, --tree         recurse into directories as a tree
+  --tree-style STYLE  which characters to draw the tree with (unicode, ascii)
+  --tree-summary[=WHICH]  count the files in each directory of the tree (immediate, recursive)
+  --mark-empty       mark empty directories in the tree with '(empty)'
+  --summary          count the files listed and add up their sizes
+  --dereference      describe the files that symlinks point to, not the links
+  --dereference-command-line  do that only for symlinks given as arguments
   -F, --classify     display type indicator by file names
+  --literal          print control characters in file names without escaping
+  --quotes[=SHELL]   quote file names with special characters (shell, powershell)
+  --link-targets WHICH  how much of symlinks' targets to show (full, name, never)
+  --relative-to DIR  display each file's path relative to DIR
+  --protected        mark files that only root can change (with '!' if classifying)
+  --in-use           mark files that other processes have open (with '%' if classifying)
+  --highlight TEXT   paint the parts of file names that match TEXT or a glob
   --colo[u]r=WHEN    when to use terminal colours (always, auto, never)
   --colo[u]r-scale   highlight levels of file sizes distinctly
 
@@ -24,15 +40,24 @@ FILTERING AND SORTING OPTIONS
   -a, --all                  show hidden and 'dot' files
   -d, --list-dirs            list directories like regular files
   -L, --level DEPTH          limit the depth of recursion
+  --follow-links             recurse into symlinked directories, once each
+  --jobs COUNT               how many threads to read directories with
   -r, --reverse              reverse the sort order
   -s, --sort SORT_FIELD      which field to sort by
   --group-directories-first  list directories before other files
+  --hidden-last              list hidden and system files after other files
   -D, --only-dirs            list only directories
   -I, --ignore-glob GLOBS    glob patterns (pipe-separated) of files to ignore
   --git-ignore               Ignore files mentioned in '.gitignore'
-  Valid sort fields:         name, Name, extension, Extension, size, type,
-                             modified, accessed, created, inode, and none.
-                             date, time, old, and new all refer to modified.
+  --no-exaignore             don't ignore files mentioned in '.exaignore'
+  --case-sensitive-patterns  make globs and --highlight care about case
+  --newer-than WHEN          only show files with a timestamp at or after WHEN
+  --older-than WHEN          only show files with a timestamp at or before WHEN
+  Valid sort fields:         name, Name, name-raw, extension, Extension, size,
+                             type, owner, git, modified, changed, accessed,
+                             created, inode, and none. old and new refer to
+                             modified; date and time refer to the timestamp
+                             being shown.
 "##;
 
 static LONG_OPTIONS: &str = r##"
@@ -40,17 +65,47 @@ LONG VIEW OPTIONS
   -b, --binary       list file sizes with binary prefixes
   -B, --bytes        list file sizes in bytes, without any prefixes
   -g, --group        list each file's group
+  --owner WHICH      how to list each file's user (user, combined, none)
   -h, --header       add a header row to each column
+  -n, --numeric      list owners and groups by ID rather than by name
   -H, --links        list each file's number of hard links
+  --hard-links       list the other names of files with several hard links
+  --my-access        list whether you can read, write, and execute each file
   -i, --inode        list each file's inode number
+  --volume           list the mount point of each file's volume
+  --network          mark files that are on a network filesystem with 'N'
+  --motw             mark files downloaded from the internet, and show their zone
+  --mime             show what type each file is, judging by its contents
+  --checksum ALGO    show a hash of each file (crc32, md5, sha1, sha256)
+  --checksum-limit SIZE  don't hash files bigger than SIZE (such as 100M)
+  --signatures       show whether Windows executables are signed
+  --version-info     show the file version of Windows executables
   -m, --modified     use the modified timestamp field
   -S, --blocks       show number of file system blocks
-  -t, --time FIELD   which timestamp field to list (modified, accessed, created)
+  --size WHICH       which sizes to list (logical, disk, both)
+  --dir-entries      show how many entries directories contain as their size
+  --total            show the total space the files take up, like 'ls -l'
+  --order COLUMNS    the order of the columns, such as 'size,user,permissions'
+  --separator=STR    the string to put after each column, such as a tab
+  --dedup-columns    use a ditto mark for owners and groups repeated from above
+  --owner-width=N    cut owner and group names longer than N columns short
+  --uniform-columns  give every directory's table the same column widths
+  --dired            write the output for Emacs's dired mode, like 'ls --dired'
+  -t, --time FIELDS  which timestamp fields to list (modified, changed, accessed, created, all)
   -u, --accessed     use the accessed timestamp field
   -U, --created      use the created timestamp field
-  --time-style       how to format timestamps (default, iso, long-iso, full-iso)"##;
-
-static GIT_HELP:      &str = r##"  --git              list each file's Git status, if tracked or ignored"##;
+  --changed          use the changed timestamp field
+  --time-style       how to format timestamps (default, iso, long-iso, full-iso,
+                     unix, unix-ms, or +FORMAT, which can use %f for fractions
+                     of a second)
+  --time-clock=CLOCK whether the default time style uses a 12- or 24-hour clock
+                     (12, 24 (default), or auto to follow the locale)
+  --locale=LOCALE    which locale's month names to use in timestamps"##;
+
+static GIT_HELP:      &str = r##"  --git              list each file's Git status, if tracked or ignored
+  --git=BACKEND      read statuses with only one backend (libgit2, command)
+  --git-ceiling=DIR  stop searching for a repository before reaching DIR
+  --git-only-changed only list files with changes, and the directories above them"##;
 static EXTENDED_HELP: &str = r##"  -@, --extended     list each file's extended attributes and sizes"##;
 
 
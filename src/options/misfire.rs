@@ -6,6 +6,7 @@ use glob;
 
 use options::{flags, HelpString, VersionString};
 use options::parser::{Arg, Flag, ParseError};
+use output::template::TemplateError;
 
 
 /// A **misfire** is a thing that can happen instead of listing files -- a
@@ -43,11 +44,23 @@ pub enum Misfire {
     /// A very specific edge case where --tree can’t be used with --all twice.
     TreeAllAll,
 
+    /// Files were to be sorted by their Git statuses, but --git wasn’t
+    /// given, so there wouldn’t be any. This holds the word that was used
+    /// to ask for the Git sort.
+    GitSortWithoutGit(OsString),
+
     /// A numeric option was given that failed to be parsed as a number.
     FailedParse(ParseIntError),
 
     /// A glob ignore was given that failed to be parsed as a pattern.
     FailedGlobPattern(String),
+
+    /// A time option was given something that’s neither a duration nor a
+    /// date.
+    FailedTimeParse(&'static Arg, OsString),
+
+    /// An output template was given that failed to be parsed.
+    FailedTemplate(TemplateError),
 }
 
 impl Misfire {
@@ -62,6 +75,12 @@ impl Misfire {
     }
 }
 
+impl From<TemplateError> for Misfire {
+    fn from(error: TemplateError) -> Misfire {
+        Misfire::FailedTemplate(error)
+    }
+}
+
 impl From<glob::PatternError> for Misfire {
     fn from(error: glob::PatternError) -> Misfire {
         Misfire::FailedGlobPattern(error.to_string())
@@ -78,6 +97,9 @@ impl fmt::Display for Misfire {
                 if let TakesValue::Necessary(Some(values)) = arg.takes_value {
                     write!(f, "Option {} has no {:?} setting ({})", arg, attempt, Choices(values))
                 }
+                else if let TakesValue::Optional(Some(values)) = arg.takes_value {
+                    write!(f, "Option {} has no {:?} setting ({})", arg, attempt, Choices(values))
+                }
                 else {
                     write!(f, "Option {} has no {:?} setting", arg, attempt)
                 }
@@ -92,8 +114,11 @@ impl fmt::Display for Misfire {
             Useless(ref a, true, ref b)      => write!(f, "Option {} is useless given option {}", a, b),
             Useless2(ref a, ref b1, ref b2)  => write!(f, "Option {} is useless without options {} or {}", a, b1, b2),
             TreeAllAll                       => write!(f, "Option --tree is useless given --all --all"),
+            GitSortWithoutGit(ref word)      => write!(f, "Option --sort={} is useless without option --git", word.to_string_lossy()),
             FailedParse(ref e)               => write!(f, "Failed to parse number: {}", e),
             FailedGlobPattern(ref e)         => write!(f, "Failed to parse glob pattern: {}", e),
+            FailedTimeParse(ref a, ref t)    => write!(f, "Option {} needs a duration such as \"3h\" or \"2d\", or a date such as \"2024-01-01\", not {:?}", a, t),
+            FailedTemplate(ref e)            => write!(f, "Option {} has {}", flags::FORMAT, e),
         }
     }
 }
@@ -70,10 +70,14 @@
 
 
 use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
 
 use fs::dir_action::DirAction;
-use fs::filter::FileFilter;
-use output::{View, Mode, details, grid_details};
+use fs::feature::GitBackend;
+use fs::filter::{FileFilter, SortField};
+use output::{View, Mode, details, grid_details, markdown, format};
+use output::file_name::Quotes;
+use output::table::Options as TableOptions;
 
 mod style;
 mod dir_action;
@@ -111,8 +115,15 @@ pub struct Options {
 
     /// The type of output to use (lines, grid, or details).
     pub view: View,
+
+    /// How many threads to read files and directories with at once.
+    pub jobs: u32,
 }
 
+/// The most threads `--jobs` can ask for. Reading directories stops
+/// getting any faster long before this.
+pub const MAX_JOBS: u32 = 256;
+
 impl Options {
 
     /// Parse the given iterator of command-line strings into an Options
@@ -143,14 +154,70 @@ impl Options {
         Ok((options, frees))
     }
 
-    /// Whether the View specified in this set of options includes a Git
-    /// status column. It’s only worth trying to discover a repository if the
-    /// results will end up being displayed.
-    pub fn should_scan_for_git(&self) -> bool {
+    /// How to read Git statuses, if the View specified in this set of
+    /// options includes a Git status column. It’s only worth trying to
+    /// discover a repository if the results will end up being displayed.
+    pub fn git_backend(&self) -> Option<GitBackend> {
+        self.table_options().and_then(|table| table.extra_columns.git)
+    }
+
+    /// The directories that searches for Git repositories should stop
+    /// before reaching.
+    pub fn git_ceilings(&self) -> &[PathBuf] {
+        self.table_options().map(|table| &*table.git_ceilings).unwrap_or(&[])
+    }
+
+    /// The options for the table, if the View has one.
+    fn table_options(&self) -> Option<&TableOptions> {
         match self.view.mode {
             Mode::Details(details::Options { table: Some(ref table), .. }) |
-            Mode::GridDetails(grid_details::Options { details: details::Options { table: Some(ref table), .. }, .. }) => table.extra_columns.git,
-            _ => false,
+            Mode::GridDetails(grid_details::Options { details: details::Options { table: Some(ref table), .. }, .. }) |
+            Mode::Markdown(markdown::Options { ref table }) |
+            Mode::Format(format::Options { ref table, .. }) => Some(table),
+            _ => None,
+        }
+    }
+
+    /// Whether directories can be listed as their entries get read, rather
+    /// than after reading all of them. Only the lines view can do this, as
+    /// it doesn’t need to know about any other file to print one, and only
+    /// when the files don’t need to be sorted or recursed into.
+    ///
+    /// Highlighting compiled files means looking for the files they were
+    /// compiled from next to them, so directories get read in full first
+    /// when the theme gives compiled files a style.
+    pub fn lists_as_read(&self) -> bool {
+        use ansi_term::Style;
+        use fs::filter::SortField;
+
+        if let Mode::Lines = self.view.mode {
+            self.filter.sort_field == SortField::Unsorted
+                && self.view.colours.filekinds.compiled == Style::default()
+                && !self.filter.reverse
+                && !self.filter.list_dirs_first
+                && !self.filter.list_hidden_last
+                && self.dir_action.recurse_options().is_none()
+        }
+        else {
+            false
+        }
+    }
+
+    /// Whether the output is for Emacs’s dired mode, which needs the offsets
+    /// of every file name written after it.
+    pub fn is_dired(&self) -> bool {
+        match self.view.mode {
+            Mode::Details(ref details) => details.dired,
+            _                          => false,
+        }
+    }
+
+    /// Whether every table in the listing should get the same column widths,
+    /// which means holding back the output until they’ve all been filled in.
+    pub fn has_uniform_columns(&self) -> bool {
+        match self.view.mode {
+            Mode::Details(ref details) => details.uniform_columns && details.table.is_some(),
+            _                          => false,
         }
     }
 
@@ -158,10 +225,52 @@ impl Options {
     /// arguments, after they’ve been parsed.
     fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Options, Misfire> {
         let dir_action = DirAction::deduce(matches)?;
-        let filter = FileFilter::deduce(matches)?;
+        let filter = FileFilter::deduce(matches, vars)?;
         let view = View::deduce(matches, vars)?;
+        let jobs = Options::deduce_jobs(matches)?;
+        let options = Options { dir_action, view, filter, jobs };
 
-        Ok(Options { dir_action, view, filter })
+        // Without Git statuses, nothing could ever count as changed, so
+        // this gets refused rather than listing nothing.
+        if options.filter.only_git_changes && options.git_backend().is_none() {
+            return Err(Misfire::Useless(&flags::GIT_ONLY_CHANGED, false, &flags::GIT));
+        }
+
+        // The same goes for sorting by Git status, which would otherwise
+        // quietly sort everything by name.
+        if options.filter.sort_field == SortField::GitStatus && options.git_backend().is_none() {
+            let word = matches.get(&flags::SORT)?.unwrap_or_default();
+            return Err(Misfire::GitSortWithoutGit(word.to_os_string()));
+        }
+
+        // Dired only knows how to read names quoted in the ways that ls
+        // quotes them, which doesn’t include PowerShell’s.
+        if options.is_dired() && options.view.style.quotes == Quotes::PowerShell {
+            return Err(Misfire::Conflict(&flags::DIRED, &flags::QUOTES));
+        }
+
+        Ok(options)
+    }
+
+    /// Determines how many threads to use: the number given with `--jobs`,
+    /// or one for each CPU. Zero threads would never get anything done, so
+    /// that’s an error. Every thread gets started up front, so so is a
+    /// number over `MAX_JOBS`, and the default never goes over it either.
+    fn deduce_jobs(matches: &MatchedFlags) -> Result<u32, Misfire> {
+        use std::convert::TryFrom;
+        use num_cpus;
+
+        if let Some(word) = matches.get(&flags::JOBS)? {
+            match word.to_string_lossy().parse() {
+                Ok(0)                         => Err(Misfire::BadArgument(&flags::JOBS, word.into())),
+                Ok(jobs) if jobs > MAX_JOBS   => Err(Misfire::BadArgument(&flags::JOBS, word.into())),
+                Ok(jobs)                      => Ok(jobs),
+                Err(e)                        => Err(Misfire::FailedParse(e)),
+            }
+        }
+        else {
+            Ok(u32::try_from(num_cpus::get()).unwrap_or(MAX_JOBS).min(MAX_JOBS))
+        }
     }
 }
 
@@ -169,7 +278,7 @@ impl Options {
 
 #[cfg(test)]
 pub mod test {
-    use super::{Options, Misfire, flags};
+    use super::{Options, Misfire, MAX_JOBS, flags};
     use options::parser::{Arg, MatchedFlags};
     use std::ffi::OsString;
 
@@ -238,6 +347,89 @@ pub mod test {
         assert_eq!(opts.unwrap_err(), Misfire::Useless(&flags::ACROSS, true, &flags::LONG))
     }
 
+    #[test]
+    fn jobs() {
+        let args = [ os("--jobs=3") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap().0.jobs, 3)
+    }
+
+    #[test]
+    fn no_jobs() {
+        let args = [ os("--jobs"), os("0") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::BadArgument(&flags::JOBS, os("0")))
+    }
+
+    #[test]
+    fn more_jobs_than_allowed() {
+        let args = [ os("--jobs=4294967295") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::BadArgument(&flags::JOBS, os("4294967295")))
+    }
+
+    #[test]
+    fn most_jobs_allowed() {
+        let args = [ os("--jobs=256") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap().0.jobs, MAX_JOBS)
+    }
+
+    #[test]
+    fn too_many_jobs() {
+        let args = [ os("--jobs=4294967296") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::FailedParse("4294967296".parse::<u32>().unwrap_err()))
+    }
+
+    #[test]
+    fn git_only_changed_without_git() {
+        let args = [ os("--git-only-changed") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::Useless(&flags::GIT_ONLY_CHANGED, false, &flags::GIT))
+    }
+
+    #[test]
+    fn lists_unsorted_as_read() {
+        let args = [ os("--oneline"), os("--sort=none"), os("--colour=never") ];
+        assert!(Options::parse(&args, &None).unwrap().0.lists_as_read());
+    }
+
+    #[test]
+    fn reads_first_to_highlight_compiled_files() {
+        let args = [ os("--oneline"), os("--sort=none"), os("--colour=always") ];
+        assert!(!Options::parse(&args, &None).unwrap().0.lists_as_read());
+    }
+
+    #[test]
+    fn git_sort_without_git() {
+        let args = [ os("--sort=git") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::GitSortWithoutGit(os("git")))
+    }
+
+    #[test]
+    fn status_sort_without_git() {
+        let args = [ os("--sort=status") ];
+        let opts = Options::parse(&args, &None).unwrap_err();
+        assert_eq!(opts.to_string(), "Option --sort=status is useless without option --git")
+    }
+
+    #[test]
+    #[cfg(feature="git")]
+    fn git_sort_with_git() {
+        let args = [ os("-l"), os("--git"), os("--sort=status") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap().0.filter.sort_field, ::fs::filter::SortField::GitStatus)
+    }
+
+    #[test]
+    fn dired_powershell() {
+        let args = [ os("-l"), os("--dired"), os("--quotes=powershell") ];
+        let opts = Options::parse(&args, &None);
+        assert_eq!(opts.unwrap_err(), Misfire::Conflict(&flags::DIRED, &flags::QUOTES))
+    }
+
     #[test]
     fn oneline_across() {
         let args = [ os("--oneline"), os("--across") ];
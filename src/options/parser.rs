@@ -7,6 +7,7 @@
 //! - Long options with values: `--sort size`, `--level=4`
 //! - Short options: `-i`, `-G`
 //! - Short options with values: `-ssize`, `-L=4`
+//! - Options with optional values: `--quotes`, `--quotes=powershell`
 //!
 //! These values can be mixed and matched: `exa -lssize --grid`. If you’ve used
 //! other command-line programs, then hopefully it’ll work much like them.
@@ -101,6 +102,10 @@ pub enum TakesValue {
 
     /// This flag will throw an error if there’s a value after it.
     Forbidden,
+
+    /// This flag can be given a value, but only with an equals sign, so a
+    /// following argument never gets mistaken for its value.
+    Optional(Option<Values>),
 }
 
 
@@ -180,7 +185,8 @@ impl Args {
                     let arg = self.lookup_long(before)?;
                     let flag = Flag::Long(arg.long);
                     match arg.takes_value {
-                        Necessary(_)  => result_flags.push((flag, Some(after))),
+                        Necessary(_) |
+                        Optional(_)   => result_flags.push((flag, Some(after))),
                         Forbidden     => return Err(ParseError::ForbiddenValue { flag })
                     }
                 }
@@ -191,7 +197,8 @@ impl Args {
                     let arg = self.lookup_long(long_arg_name)?;
                     let flag = Flag::Long(arg.long);
                     match arg.takes_value {
-                        Forbidden         => result_flags.push((flag, None)),
+                        Forbidden |
+                        Optional(_)       => result_flags.push((flag, None)),
                         Necessary(values) => {
                             if let Some(next_arg) = inputs.next() {
                                 result_flags.push((flag, Some(next_arg)));
@@ -229,7 +236,8 @@ impl Args {
                         let arg = self.lookup_short(*byte)?;
                         let flag = Flag::Short(*byte);
                         match arg.takes_value {
-                            Forbidden          => result_flags.push((flag, None)),
+                            Forbidden |
+                            Optional(_)        => result_flags.push((flag, None)),
                             Necessary(values)  => return Err(ParseError::NeedsValue { flag, values })
                         }
                     }
@@ -238,7 +246,8 @@ impl Args {
                     let arg = self.lookup_short(*arg_with_value)?;
                     let flag = Flag::Short(arg.short.unwrap());
                     match arg.takes_value {
-                        Necessary(_)  => result_flags.push((flag, Some(after))),
+                        Necessary(_) |
+                        Optional(_)   => result_flags.push((flag, Some(after))),
                         Forbidden     => return Err(ParseError::ForbiddenValue { flag })
                     }
                 }
@@ -260,7 +269,8 @@ impl Args {
                         let arg = self.lookup_short(*byte)?;
                         let flag = Flag::Short(*byte);
                         match arg.takes_value {
-                            Forbidden         => result_flags.push((flag, None)),
+                            Forbidden |
+                            Optional(_)       => result_flags.push((flag, None)),
                             Necessary(values) => {
                                 if index < bytes.len() - 1 {
                                     let remnants = &bytes[index+1 ..];
@@ -558,7 +568,8 @@ mod parse_test {
         &Arg { short: Some(b'l'), long: "long",     takes_value: TakesValue::Forbidden },
         &Arg { short: Some(b'v'), long: "verbose",  takes_value: TakesValue::Forbidden },
         &Arg { short: Some(b'c'), long: "count",    takes_value: TakesValue::Necessary(None) },
-        &Arg { short: Some(b't'), long: "type",     takes_value: TakesValue::Necessary(Some(SUGGESTIONS)) }
+        &Arg { short: Some(b't'), long: "type",     takes_value: TakesValue::Necessary(Some(SUGGESTIONS)) },
+        &Arg { short: Some(b'o'), long: "quote",    takes_value: TakesValue::Optional(Some(SUGGESTIONS)) }
     ];
 
 
@@ -591,6 +602,12 @@ mod parse_test {
     test!(arg_then_s:    ["--type", "exa"]  => frees: [],  flags: [ (Flag::Long("type"), Some(OsStr::new("exa"))) ]);
 
 
+    // Long args with optional values
+    test!(opt_none:      ["--quote"]         => frees: [],         flags: [ (Flag::Long("quote"), None) ]);
+    test!(opt_equals:    ["--quote=exa"]     => frees: [],         flags: [ (Flag::Long("quote"), Some(OsStr::new("exa"))) ]);
+    test!(opt_then:      ["--quote", "exa"]  => frees: [ "exa" ],  flags: [ (Flag::Long("quote"), None) ]);
+
+
     // Short args
     test!(short:       ["-l"]            => frees: [],       flags: [ (Flag::Short(b'l'), None) ]);
     test!(short_then:  ["-l", "4"]       => frees: [ "4" ],  flags: [ (Flag::Short(b'l'), None) ]);
@@ -613,6 +630,13 @@ mod parse_test {
     test!(short_two_next_s:     ["-t", "exa"]  => frees: [],  flags: [(Flag::Short(b't'), Some(OsStr::new("exa"))) ]);
 
 
+    // Short args with optional values
+    test!(short_opt_none:       ["-o"]         => frees: [],         flags: [(Flag::Short(b'o'), None) ]);
+    test!(short_opt_equals:     ["-o=exa"]     => frees: [],         flags: [(Flag::Short(b'o'), Some(OsStr::new("exa"))) ]);
+    test!(short_opt_then:       ["-o", "exa"]  => frees: [ "exa" ],  flags: [(Flag::Short(b'o'), None) ]);
+    test!(short_opt_together:   ["-ol"]        => frees: [],         flags: [(Flag::Short(b'o'), None), (Flag::Short(b'l'), None) ]);
+
+
     // Unknown args
     test!(unknown_long:          ["--quiet"]      => error UnknownArgument      { attempt: os("quiet") });
     test!(unknown_long_eq:       ["--quiet=shhh"] => error UnknownArgument      { attempt: os("quiet") });
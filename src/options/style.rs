@@ -1,10 +1,15 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
 use ansi_term::Style;
-use glob;
 
 use fs::File;
+use fs::feature::access;
+use fs::pattern::{NamePattern, PatternCase};
 use options::{flags, Vars, Misfire};
 use options::parser::MatchedFlags;
-use output::file_name::{FileStyle, Classify};
+use output::Highlight;
+use output::file_name::{FileStyle, Classify, Escaping, Quotes, LinkTargets, Arrows};
 use style::Colours;
 
 
@@ -79,39 +84,69 @@ pub struct Styles {
 impl Styles {
 
     #[allow(trivial_casts)]   // the "as Box<_>" stuff below warns about this for some reason
-    pub fn deduce<V, TW>(matches: &MatchedFlags, vars: &V, widther: TW) -> Result<Self, Misfire>
-    where TW: Fn() -> Option<usize>, V: Vars {
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, Misfire> {
         use self::TerminalColours::*;
         use info::filetype::FileExtensions;
+        use info::sources::SourcePairs;
+        use options::vars;
         use output::file_name::NoFileColours;
 
         let classify = Classify::deduce(matches)?;
+        let escaping = Escaping::deduce(matches)?;
+        let quotes = Quotes::deduce(matches)?;
+        let link_targets = LinkTargets::deduce(matches)?;
+        let arrows = Arrows::deduce(vars);
+        let placeholder = placeholder_text(vars);
+        let relative_to = relative_base(matches)?;
+        let protected = matches.has(&flags::PROTECTED)? && !access::is_elevated();
+        let in_use = matches.has(&flags::IN_USE)?;
+        let motw = matches.has(&flags::MOTW)?;
+        let sensitive = matches.has(&flags::CASE_SENSITIVE_PATTERNS)?;
+        let highlight_case = if sensitive { PatternCase::Sensitive } else { PatternCase::Insensitive };
+        let highlight = matches.get(&flags::HIGHLIGHT)?.map(|h| Highlight::new(&h.to_string_lossy(), highlight_case));
 
         // Before we do anything else, figure out if we need to consider
         // custom colours at all
+        // The output for dired has to be exactly what its offsets say, so
+        // it never gets any colours.
         let tc = TerminalColours::deduce(matches)?;
-        if tc == Never || (tc == Automatic && widther().is_none()) {
+        if tc == Never || (tc == Automatic && !vars.is_terminal()) || matches.has(&flags::DIRED)? {
+            let mut colours = Colours::plain();
+            colours.placeholder.text = placeholder;
+
             return Ok(Styles {
-                colours: Colours::plain(),
-                style: FileStyle { classify, exts: Box::new(NoFileColours) },
+                colours,
+                style: FileStyle { classify, escaping, quotes, exts: Box::new(NoFileColours), sources: SourcePairs::default(), link_targets, arrows, relative_to, protected, in_use, motw, highlight },
             });
         }
 
         // Parse the environment variables into colours and extension mappings
         let scale = matches.has_where(|f| f.matches(&flags::COLOR_SCALE) || f.matches(&flags::COLOUR_SCALE))?;
         let mut colours = Colours::colourful(scale.is_some());
+        colours.placeholder.text = placeholder;
 
-        let (exts, use_default_filetypes) = parse_color_vars(vars, &mut colours);
+        let glob_case = if sensitive { PatternCase::Sensitive } else { glob_case(vars) };
+        let (exts, use_default_filetypes) = parse_color_vars(vars, &mut colours, glob_case);
+
+        let filetypes = match vars.get(vars::EXA_IMMEDIATE) {
+            Some(patterns)  => FileExtensions::with_immediate(&patterns.to_string_lossy()),
+            None            => FileExtensions::default(),
+        };
 
         // Use between 0 and 2 file name highlighters
         let exts = match (exts.is_non_empty(), use_default_filetypes) {
-            (false, false)  => Box::new(NoFileColours)           as Box<_>,
-            (false,  true)  => Box::new(FileExtensions)          as Box<_>,
-            ( true, false)  => Box::new(exts)                    as Box<_>,
-            ( true,  true)  => Box::new((exts, FileExtensions))  as Box<_>,
+            (false, false)  => Box::new(NoFileColours)      as Box<_>,
+            (false,  true)  => Box::new(filetypes)          as Box<_>,
+            ( true, false)  => Box::new(exts)               as Box<_>,
+            ( true,  true)  => Box::new((exts, filetypes))  as Box<_>,
         };
 
-        let style = FileStyle { classify, exts };
+        let sources = match vars.get(vars::EXA_COMPILED) {
+            Some(pairs)  => SourcePairs::parse(&pairs.to_string_lossy()),
+            None         => SourcePairs::default(),
+        };
+
+        let style = FileStyle { classify, escaping, quotes, exts, sources, link_targets, arrows, relative_to, protected, in_use, motw, highlight };
         Ok(Styles { colours, style })
     }
 }
@@ -122,20 +157,17 @@ impl Styles {
 ///
 /// Also returns if the EXA_COLORS variable should reset the existing file
 /// type mappings or not. The `reset` code needs to be the first one.
-fn parse_color_vars<V: Vars>(vars: &V, colours: &mut Colours) -> (ExtensionMappings, bool) {
+fn parse_color_vars<V: Vars>(vars: &V, colours: &mut Colours, case: PatternCase) -> (ExtensionMappings, bool) {
     use options::vars;
     use style::LSColors;
 
-    let mut exts = ExtensionMappings::default();
+    let mut exts = ExtensionMappings { case, .. ExtensionMappings::default() };
 
     if let Some(lsc) = vars.get(vars::LS_COLORS) {
         let lsc = lsc.to_string_lossy();
         LSColors(lsc.as_ref()).each_pair(|pair| {
             if !colours.set_ls(&pair) {
-                match glob::Pattern::new(pair.key) {
-                    Ok(pat) => exts.add(pat, pair.to_style()),
-                    Err(e)  => warn!("Couldn't parse glob pattern {:?}: {}", pair.key, e),
-                }
+                exts.add(pair.key, pair.to_style());
             }
         });
     }
@@ -148,14 +180,12 @@ fn parse_color_vars<V: Vars>(vars: &V, colours: &mut Colours) -> (ExtensionMappi
         // Is this hacky? Yes.
         if exa == "reset" || exa.starts_with("reset:") {
             use_default_filetypes = false;
+            colours.filekinds.reset_categories();
         }
 
         LSColors(exa.as_ref()).each_pair(|pair| {
             if !colours.set_ls(&pair) && !colours.set_exa(&pair) {
-                match glob::Pattern::new(pair.key) {
-                    Ok(pat) => exts.add(pat, pair.to_style()),
-                    Err(e)  => warn!("Couldn't parse glob pattern {:?}: {}", pair.key, e),
-                }
+                exts.add(pair.key, pair.to_style());
             };
         });
     }
@@ -164,9 +194,30 @@ fn parse_color_vars<V: Vars>(vars: &V, colours: &mut Colours) -> (ExtensionMappi
 }
 
 
+/// Which case the globs in the colour variables match file names in, from
+/// the `EXA_GLOB_CASE` variable. Without it, they follow the platform.
+fn glob_case<V: Vars>(vars: &V) -> PatternCase {
+    use options::vars;
+
+    match vars.get(vars::EXA_GLOB_CASE) {
+        Some(ref case) if case == "sensitive"    => PatternCase::Sensitive,
+        Some(ref case) if case == "insensitive"  => PatternCase::Insensitive,
+        Some(case) => {
+            warn!("Unknown glob case {:?}", case);
+            PatternCase::default()
+        },
+        None => PatternCase::default(),
+    }
+}
+
+
 #[derive(PartialEq, Debug, Default)]
 struct ExtensionMappings {
-    mappings: Vec<(glob::Pattern, Style)>
+    mappings: Vec<(NamePattern, Style)>,
+
+    /// Whether the globs should match file names case-sensitively. This
+    /// has to be set before any globs get added.
+    case: PatternCase,
 }
 
 // Loop through backwards so that colours specified later in the list override
@@ -175,21 +226,32 @@ struct ExtensionMappings {
 use output::file_name::FileColours;
 impl FileColours for ExtensionMappings {
     fn colour_file(&self, file: &File) -> Option<Style> {
+        self.colour_name(&file.display_name())
+    }
+}
+
+impl ExtensionMappings {
+    fn colour_name(&self, name: &str) -> Option<Style> {
+        let name = self.case.fold(name);
+
         self.mappings
             .iter()
             .rev()
-            .find(|t| t.0.matches(&file.name))
+            .find(|t| t.0.matches_folded(&name))
             .map (|t| t.1)
     }
-}
 
-impl ExtensionMappings {
     fn is_non_empty(&self) -> bool {
         !self.mappings.is_empty()
     }
 
-    fn add(&mut self, pattern: glob::Pattern, style: Style) {
-        self.mappings.push((pattern, style))
+    /// Adds a glob and the style to use for files that match it, skipping
+    /// globs that don’t parse.
+    fn add(&mut self, pattern: &str, style: Style) {
+        match NamePattern::new(pattern, self.case) {
+            Ok(pat)  => self.mappings.push((pat, style)),
+            Err(e)   => warn!("Couldn't parse glob pattern {:?}: {}", pattern, e),
+        }
     }
 }
 
@@ -204,6 +266,116 @@ impl Classify {
     }
 }
 
+impl Escaping {
+    fn deduce(matches: &MatchedFlags) -> Result<Escaping, Misfire> {
+        let flagged = matches.has(&flags::LITERAL)?;
+
+        Ok(if flagged { Escaping::Literal }
+                 else { Escaping::Escape })
+    }
+}
+
+impl Quotes {
+    fn deduce(matches: &MatchedFlags) -> Result<Quotes, Misfire> {
+        if let Some(word) = matches.get(&flags::QUOTES)? {
+            if word == "shell" {
+                Ok(Quotes::Shell)
+            }
+            else if word == "powershell" {
+                Ok(Quotes::PowerShell)
+            }
+            else {
+                Err(Misfire::BadArgument(&flags::QUOTES, word.into()))
+            }
+        }
+        else if matches.has(&flags::QUOTES)? {
+            Ok(Quotes::Shell)
+        }
+        else {
+            Ok(Quotes::Never)
+        }
+    }
+}
+
+
+impl LinkTargets {
+    fn deduce(matches: &MatchedFlags) -> Result<LinkTargets, Misfire> {
+        let word = match matches.get(&flags::LINK_TARGETS)? {
+            Some(w)  => w,
+            None     => return Ok(LinkTargets::Full),
+        };
+
+        if word == "full" {
+            Ok(LinkTargets::Full)
+        }
+        else if word == "name" {
+            Ok(LinkTargets::Name)
+        }
+        else if word == "never" {
+            Ok(LinkTargets::Never)
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::LINK_TARGETS, word.into()))
+        }
+    }
+}
+
+
+/// Determines the directory to display paths relative to, made absolute
+/// straight away so that each file’s path can be compared against it.
+fn relative_base(matches: &MatchedFlags) -> Result<Option<PathBuf>, Misfire> {
+    use fs::normalise;
+
+    let dir = match matches.get(&flags::RELATIVE_TO)? {
+        Some(d)  => Path::new(d),
+        None     => return Ok(None),
+    };
+
+    if dir.is_absolute() {
+        Ok(Some(normalise(dir)))
+    }
+    else {
+        match env::current_dir() {
+            Ok(cwd)  => Ok(Some(normalise(&cwd.join(dir)))),
+            Err(_)   => Err(Misfire::BadArgument(&flags::RELATIVE_TO, dir.into())),
+        }
+    }
+}
+
+
+impl Arrows {
+
+    /// Reads the arrows from the environment, falling back to `->` for
+    /// both. Empty values count as unset, as an arrow that isn’t there
+    /// would make links impossible to tell apart from their targets.
+    fn deduce<V: Vars>(vars: &V) -> Arrows {
+        use options::vars;
+
+        let get = |name| vars.get(name).map(|a| a.to_string_lossy().to_string())
+                                       .filter(|a| !a.is_empty());
+
+        let normal = get(vars::EXA_LINK_ARROW).unwrap_or_else(|| Arrows::default().normal);
+        let broken = get(vars::EXA_BROKEN_LINK_ARROW).unwrap_or_else(|| normal.clone());
+        Arrows { normal, broken }
+    }
+}
+
+
+/// Reads the text to show for values that files don’t have from the `nt`
+/// key in `EXA_COLORS`, falling back to a hyphen. This gets looked at even
+/// when there are no colours, because the text is still shown. Unlike the
+/// other keys, an empty value counts, and leaves the cells for those values
+/// blank.
+fn placeholder_text<V: Vars>(vars: &V) -> String {
+    use options::vars;
+    use style::Placeholder;
+
+    let exa = vars.get(vars::EXA_COLORS).map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    match exa.rsplit(':').find_map(|pair| pair.strip_prefix("nt=")) {
+        Some(text)  => text.to_string(),
+        None        => Placeholder::default().text,
+    }
+}
 
 
 #[cfg(test)]
@@ -269,6 +441,234 @@ mod terminal_test {
 }
 
 
+#[cfg(test)]
+mod escaping_test {
+    use super::*;
+    use options::flags;
+    use options::parser::{Flag, Arg};
+
+    use options::test::parse_for_test;
+    use options::test::Strictnesses::*;
+
+    static TEST_ARGS: &[&Arg] = &[ &flags::LITERAL ];
+
+    macro_rules! test {
+        ($name:ident:  $inputs:expr;  $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Escaping::deduce(mf)) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    test!(empty:      [];                          Both => Ok(Escaping::Escape));
+    test!(literal:    ["--literal"];               Both => Ok(Escaping::Literal));
+    test!(twice:      ["--literal", "--literal"];  Last => Ok(Escaping::Literal));
+    test!(complain:   ["--literal", "--literal"];  Complain => Err(Misfire::Duplicate(Flag::Long("literal"), Flag::Long("literal"))));
+}
+
+
+#[cfg(test)]
+mod quotes_test {
+    use super::*;
+    use std::ffi::OsString;
+    use options::flags;
+    use options::parser::Arg;
+
+    use options::test::parse_for_test;
+    use options::test::Strictnesses::*;
+
+    static TEST_ARGS: &[&Arg] = &[ &flags::QUOTES ];
+
+    macro_rules! test {
+        ($name:ident:  $inputs:expr;  $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Quotes::deduce(mf)) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    test!(empty:       [];                       Both => Ok(Quotes::Never));
+    test!(bare:        ["--quotes"];             Both => Ok(Quotes::Shell));
+    test!(shell:       ["--quotes=shell"];       Both => Ok(Quotes::Shell));
+    test!(powershell:  ["--quotes=powershell"];  Both => Ok(Quotes::PowerShell));
+    test!(unknown:     ["--quotes=fish"];        Both => Err(Misfire::BadArgument(&flags::QUOTES, OsString::from("fish"))));
+    test!(overridden:  ["--quotes=powershell", "--quotes=shell"];  Last => Ok(Quotes::Shell));
+}
+
+
+#[cfg(test)]
+mod link_targets_test {
+    use super::*;
+    use std::ffi::OsString;
+    use options::flags;
+    use options::parser::Arg;
+
+    use options::test::parse_for_test;
+    use options::test::Strictnesses::*;
+
+    static TEST_ARGS: &[&Arg] = &[ &flags::LINK_TARGETS ];
+
+    macro_rules! test {
+        ($name:ident:  $inputs:expr;  $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| LinkTargets::deduce(mf)) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    test!(empty:    [];                          Both => Ok(LinkTargets::Full));
+    test!(full:     ["--link-targets=full"];     Both => Ok(LinkTargets::Full));
+    test!(name:     ["--link-targets=name"];     Both => Ok(LinkTargets::Name));
+    test!(never:    ["--link-targets", "never"]; Both => Ok(LinkTargets::Never));
+    test!(unknown:  ["--link-targets=some"];     Both => Err(Misfire::BadArgument(&flags::LINK_TARGETS, OsString::from("some"))));
+}
+
+
+#[cfg(test)]
+mod relative_base_test {
+    use super::*;
+    use options::flags;
+    use options::parser::Arg;
+
+    use options::test::parse_for_test;
+    use options::test::Strictnesses::*;
+
+    static TEST_ARGS: &[&Arg] = &[ &flags::RELATIVE_TO ];
+
+    macro_rules! test {
+        ($name:ident:  $inputs:expr;  $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, relative_base) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    test!(empty:       [];                                     Both => Ok(None));
+    test!(absolute:    ["--relative-to=/project"];             Both => Ok(Some(PathBuf::from("/project"))));
+    test!(normalised:  ["--relative-to", "/project/./src/.."];  Both => Ok(Some(PathBuf::from("/project"))));
+
+    #[test]
+    fn relative() {
+        let expected = env::current_dir().unwrap().join("target");
+        for result in parse_for_test(&["--relative-to=target/"], TEST_ARGS, Both, relative_base) {
+            assert_eq!(result, Ok(Some(expected.clone())));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod arrows_test {
+    use std::ffi::OsString;
+
+    use super::*;
+    use options::{vars, Vars};
+
+    struct MockVars {
+        normal: &'static str,
+        broken: Option<&'static str>,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            if name == vars::EXA_LINK_ARROW {
+                Some(OsString::from(self.normal))
+            }
+            else if name == vars::EXA_BROKEN_LINK_ARROW {
+                self.broken.map(OsString::from)
+            }
+            else {
+                None
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(Arrows::deduce(&MockVars { normal: "", broken: None }), Arrows::default());
+    }
+
+    #[test]
+    fn broken_follows_normal() {
+        let arrows = Arrows::deduce(&MockVars { normal: "→", broken: None });
+        assert_eq!(arrows, Arrows { normal: "→".into(), broken: "→".into() });
+    }
+
+    #[test]
+    fn both() {
+        let arrows = Arrows::deduce(&MockVars { normal: "→", broken: Some("⇢") });
+        assert_eq!(arrows, Arrows { normal: "→".into(), broken: "⇢".into() });
+    }
+}
+
+
+#[cfg(test)]
+mod placeholder_test {
+    use std::ffi::OsString;
+
+    use super::*;
+    use options::{vars, Vars};
+
+    struct MockVars(Option<&'static str>);
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            if name == vars::EXA_COLORS {
+                self.0.map(OsString::from)
+            }
+            else {
+                None
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn hyphen() {
+        assert_eq!(placeholder_text(&MockVars(None)), "-");
+    }
+
+    #[test]
+    fn other_keys() {
+        assert_eq!(placeholder_text(&MockVars(Some("di=34:na=2"))), "-");
+    }
+
+    #[test]
+    fn custom() {
+        assert_eq!(placeholder_text(&MockVars(Some("nt=n/a"))), "n/a");
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(placeholder_text(&MockVars(Some("nt="))), "");
+    }
+
+    #[test]
+    fn among_other_keys() {
+        assert_eq!(placeholder_text(&MockVars(Some("di=34:nt=?:na=2"))), "?");
+    }
+}
+
+
 #[cfg(test)]
 mod colour_test {
     use super::*;
@@ -278,32 +678,34 @@ mod colour_test {
     use options::test::parse_for_test;
     use options::test::Strictnesses::*;
 
+    use options::vars::MockTerminal;
+
     static TEST_ARGS: &[&Arg] = &[ &flags::COLOR,       &flags::COLOUR,
                                    &flags::COLOR_SCALE, &flags::COLOUR_SCALE ];
 
     macro_rules! test {
-        ($name:ident:  $inputs:expr, $widther:expr;  $stricts:expr => $result:expr) => {
+        ($name:ident:  $inputs:expr, $terminal:expr;  $stricts:expr => $result:expr) => {
             #[test]
             fn $name() {
-                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &None, &$widther).map(|s| s.colours)) {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &MockTerminal($terminal)).map(|s| s.colours)) {
                     assert_eq!(result, $result);
                 }
             }
         };
 
-        ($name:ident:  $inputs:expr, $widther:expr;  $stricts:expr => err $result:expr) => {
+        ($name:ident:  $inputs:expr, $terminal:expr;  $stricts:expr => err $result:expr) => {
             #[test]
             fn $name() {
-                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &None, &$widther).map(|s| s.colours)) {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &MockTerminal($terminal)).map(|s| s.colours)) {
                     assert_eq!(result.unwrap_err(), $result);
                 }
             }
         };
 
-        ($name:ident:  $inputs:expr, $widther:expr;  $stricts:expr => like $pat:pat) => {
+        ($name:ident:  $inputs:expr, $terminal:expr;  $stricts:expr => like $pat:pat) => {
             #[test]
             fn $name() {
-                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &None, &$widther).map(|s| s.colours)) {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| Styles::deduce(mf, &MockTerminal($terminal)).map(|s| s.colours)) {
                     println!("Testing {:?}", result);
                     match result {
                         $pat => assert!(true),
@@ -314,24 +716,24 @@ mod colour_test {
         };
     }
 
-    test!(width_1:  ["--colour", "always"],    || Some(80);  Both => Ok(Colours::colourful(false)));
-    test!(width_2:  ["--colour", "always"],    || None;      Both => Ok(Colours::colourful(false)));
-    test!(width_3:  ["--colour", "never"],     || Some(80);  Both => Ok(Colours::plain()));
-    test!(width_4:  ["--colour", "never"],     || None;      Both => Ok(Colours::plain()));
-    test!(width_5:  ["--colour", "automatic"], || Some(80);  Both => Ok(Colours::colourful(false)));
-    test!(width_6:  ["--colour", "automatic"], || None;      Both => Ok(Colours::plain()));
-    test!(width_7:  [],                        || Some(80);  Both => Ok(Colours::colourful(false)));
-    test!(width_8:  [],                        || None;      Both => Ok(Colours::plain()));
-
-    test!(scale_1:  ["--color=always", "--color-scale", "--colour-scale"], || None;   Last => like Ok(Colours { scale: true,  .. }));
-    test!(scale_2:  ["--color=always", "--color-scale",                 ], || None;   Last => like Ok(Colours { scale: true,  .. }));
-    test!(scale_3:  ["--color=always",                  "--colour-scale"], || None;   Last => like Ok(Colours { scale: true,  .. }));
-    test!(scale_4:  ["--color=always",                                  ], || None;   Last => like Ok(Colours { scale: false, .. }));
-
-    test!(scale_5:  ["--color=always", "--color-scale", "--colour-scale"], || None;   Complain => err Misfire::Duplicate(Flag::Long("color-scale"),  Flag::Long("colour-scale")));
-    test!(scale_6:  ["--color=always", "--color-scale",                 ], || None;   Complain => like Ok(Colours { scale: true,  .. }));
-    test!(scale_7:  ["--color=always",                  "--colour-scale"], || None;   Complain => like Ok(Colours { scale: true,  .. }));
-    test!(scale_8:  ["--color=always",                                  ], || None;   Complain => like Ok(Colours { scale: false, .. }));
+    test!(terminal_1: ["--colour", "always"],    true;   Both => Ok(Colours::colourful(false)));
+    test!(terminal_2: ["--colour", "always"],    false;  Both => Ok(Colours::colourful(false)));
+    test!(terminal_3: ["--colour", "never"],     true;        Both => Ok(Colours::plain()));
+    test!(terminal_4: ["--colour", "never"],     false;    Both => Ok(Colours::plain()));
+    test!(terminal_5: ["--colour", "automatic"], true;   Both => Ok(Colours::colourful(false)));
+    test!(terminal_6: ["--colour", "automatic"], false;  Both => Ok(Colours::plain()));
+    test!(terminal_7: [],                        true;        Both => Ok(Colours::colourful(false)));
+    test!(terminal_8: [],                        false;    Both => Ok(Colours::plain()));
+
+    test!(scale_1:  ["--color=always", "--color-scale", "--colour-scale"], false; Last => like Ok(Colours { scale: true,  .. }));
+    test!(scale_2:  ["--color=always", "--color-scale",                 ], false; Last => like Ok(Colours { scale: true,  .. }));
+    test!(scale_3:  ["--color=always",                  "--colour-scale"], false; Last => like Ok(Colours { scale: true,  .. }));
+    test!(scale_4:  ["--color=always",                                  ], false; Last => like Ok(Colours { scale: false, .. }));
+
+    test!(scale_5:  ["--color=always", "--color-scale", "--colour-scale"], false; Complain => err Misfire::Duplicate(Flag::Long("color-scale"),  Flag::Long("colour-scale")));
+    test!(scale_6:  ["--color=always", "--color-scale",                 ], false; Complain => like Ok(Colours { scale: true,  .. }));
+    test!(scale_7:  ["--color=always",                  "--colour-scale"], false; Complain => like Ok(Colours { scale: true,  .. }));
+    test!(scale_8:  ["--color=always",                                  ], false; Complain => like Ok(Colours { scale: false, .. }));
 }
 
 
@@ -356,23 +758,23 @@ mod customs_test {
                 let vars = MockVars { ls: $ls, exa: $exa };
 
                 let mut result = Colours::colourful(false);
-                let (_exts, _reset) = parse_color_vars(&vars, &mut result);
+                let (_exts, _reset) = parse_color_vars(&vars, &mut result, PatternCase::default());
                 assert_eq!($expected, result);
             }
         };
         ($name:ident:  ls $ls:expr, exa $exa:expr  =>  exts $mappings:expr) => {
             #[test]
             fn $name() {
-                let mappings: Vec<(glob::Pattern, Style)>
+                let mappings: Vec<(NamePattern, Style)>
                     = $mappings.into_iter()
-                               .map(|t| (glob::Pattern::new(t.0).unwrap(), t.1))
+                               .map(|t| (NamePattern::new(t.0, PatternCase::default()).unwrap(), t.1))
                                .collect();
 
                 let vars = MockVars { ls: $ls, exa: $exa };
 
                 let mut meh = Colours::colourful(false);
-                let (result, _reset) = parse_color_vars(&vars, &mut meh);
-                assert_eq!(ExtensionMappings { mappings }, result);
+                let (result, _reset) = parse_color_vars(&vars, &mut meh, PatternCase::default());
+                assert_eq!(ExtensionMappings { mappings, .. ExtensionMappings::default() }, result);
             }
         };
         ($name:ident:  ls $ls:expr, exa $exa:expr  =>  colours $expected:ident -> $process_expected:expr, exts $mappings:expr) => {
@@ -382,16 +784,16 @@ mod customs_test {
                 let mut $expected = Colours::colourful(false);
                 $process_expected();
 
-                let mappings: Vec<(glob::Pattern, Style)>
+                let mappings: Vec<(NamePattern, Style)>
                     = $mappings.into_iter()
-                               .map(|t| (glob::Pattern::new(t.0).unwrap(), t.1))
+                               .map(|t| (NamePattern::new(t.0, PatternCase::default()).unwrap(), t.1))
                                .collect();
 
                 let vars = MockVars { ls: $ls, exa: $exa };
 
                 let mut meh = Colours::colourful(false);
-                let (result, _reset) = parse_color_vars(&vars, &mut meh);
-                assert_eq!(ExtensionMappings { mappings }, result);
+                let (result, _reset) = parse_color_vars(&vars, &mut meh, PatternCase::default());
+                assert_eq!(ExtensionMappings { mappings, .. ExtensionMappings::default() }, result);
                 assert_eq!($expected, meh);
             }
         };
@@ -417,6 +819,10 @@ mod customs_test {
                 None
             }
         }
+
+        fn is_terminal(&self) -> bool {
+            true
+        }
     }
 
     // LS_COLORS can affect all of these colours:
@@ -479,6 +885,7 @@ mod customs_test {
     test!(exa_gd:  ls "", exa "gd=38;5;125"  =>  colours c -> { c.git.deleted               = Fixed(125).normal(); });
     test!(exa_gv:  ls "", exa "gv=38;5;126"  =>  colours c -> { c.git.renamed               = Fixed(126).normal(); });
     test!(exa_gt:  ls "", exa "gt=38;5;127"  =>  colours c -> { c.git.typechange            = Fixed(127).normal(); });
+    test!(exa_gf:  ls "", exa "gf=38;5;128"  =>  colours c -> { c.git.conflicted            = Fixed(128).normal(); });
 
     test!(exa_xx:  ls "", exa "xx=38;5;128"  =>  colours c -> { c.punctuation               = Fixed(128).normal(); });
     test!(exa_da:  ls "", exa "da=38;5;129"  =>  colours c -> { c.date                      = Fixed(129).normal(); });
@@ -487,7 +894,17 @@ mod customs_test {
     test!(exa_hd:  ls "", exa "hd=38;5;132"  =>  colours c -> { c.header                    = Fixed(132).normal(); });
     test!(exa_lp:  ls "", exa "lp=38;5;133"  =>  colours c -> { c.symlink_path              = Fixed(133).normal(); });
     test!(exa_cc:  ls "", exa "cc=38;5;134"  =>  colours c -> { c.control_char              = Fixed(134).normal(); });
+    test!(exa_hl:  ls "", exa "hl=38;5;135"  =>  colours c -> { c.highlight                 = Fixed(135).normal(); });
+    test!(exa_ed:  ls "", exa "ed=38;5;135"  =>  colours c -> { c.filekinds.empty_directory = Fixed(135).normal(); });
     test!(exa_bo:  ls "", exa "bO=4"         =>  colours c -> { c.broken_path_overlay       = Style::default().underline(); });
+    test!(exa_so_0: ls "", exa "sO=0"         =>  colours c -> { c.system_overlay            = Style::default(); });
+    test!(exa_is:  ls "", exa "is=33"        =>  colours c -> { c.filekinds.installer       = Yellow.normal(); });
+    test!(exa_sy:  ls "", exa "sy=1"         =>  colours c -> { c.filekinds.system          = Style::default().bold(); });
+    test!(exa_na:  ls "", exa "na=2"         =>  colours c -> { c.placeholder.style         = Some(Style::default().dimmed()); });
+    test!(exa_nt:  ls "", exa "nt=n/a"       =>  colours c -> { c.placeholder.text          = "n/a".into(); });
+
+    // Resetting turns off the categories, but they can be set again
+    test!(exa_reset_categories:  ls "", exa "reset:sc=32"  =>  colours c -> { c.filekinds.reset_categories(); c.filekinds.script = Green.normal(); });
 
     // All the while, LS_COLORS treats them as filenames:
     test!(ls_uu:   ls "uu=38;5;117", exa ""  =>  exts [ ("uu", Fixed(117).normal()) ]);
@@ -518,3 +935,69 @@ mod customs_test {
     test!(ls_overwrite:  ls "pi=31:pi=32:pi=33", exa ""  =>  colours c -> { c.filekinds.pipe = Yellow.normal(); });
     test!(exa_overwrite: ls "", exa "da=36:da=35:da=34"  =>  colours c -> { c.date = Blue.normal(); });
 }
+
+
+#[cfg(test)]
+mod glob_case_test {
+    use std::ffi::OsString;
+
+    use super::*;
+    use options::{vars, Vars};
+
+    use ansi_term::Colour::*;
+
+    struct MockVars {
+        exa: &'static str,
+        case: &'static str,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            if name == vars::EXA_COLORS {
+                Some(OsString::from(self.exa))
+            }
+            else if name == vars::EXA_GLOB_CASE && !self.case.is_empty() {
+                Some(OsString::from(self.case))
+            }
+            else {
+                None
+            }
+        }
+
+        fn is_terminal(&self) -> bool {
+            true
+        }
+    }
+
+    fn mappings(case: &'static str) -> ExtensionMappings {
+        let vars = MockVars { exa: "*.jpg=31:*.ÄRCHIV=32", case };
+        parse_color_vars(&vars, &mut Colours::colourful(false), glob_case(&vars)).0
+    }
+
+    #[test]
+    fn default_case() {
+        let exts = mappings("");
+        assert_eq!(exts.colour_name("photo.jpg"), Some(Red.normal()));
+        assert_eq!(exts.colour_name("PHOTO.JPG").is_some(), cfg!(windows));
+    }
+
+    #[test]
+    fn sensitive() {
+        let exts = mappings("sensitive");
+        assert_eq!(exts.colour_name("photo.jpg"), Some(Red.normal()));
+        assert_eq!(exts.colour_name("PHOTO.JPG"), None);
+    }
+
+    #[test]
+    fn insensitive() {
+        let exts = mappings("insensitive");
+        assert_eq!(exts.colour_name("PHOTO.JPG"), Some(Red.normal()));
+        assert_eq!(exts.colour_name("Photo.JpG"), Some(Red.normal()));
+    }
+
+    #[test]
+    fn insensitive_non_ascii() {
+        let exts = mappings("insensitive");
+        assert_eq!(exts.colour_name("backup.ärchiv"), Some(Green.normal()));
+    }
+}
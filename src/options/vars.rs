@@ -15,6 +15,18 @@ pub static COLUMNS: &str = "COLUMNS";
 /// Environment variable used to datetime format.
 pub static TIME_STYLE: &str = "TIME_STYLE";
 
+/// Environment variable used by Git to stop searching for a repository
+/// before reaching any of the directories it lists, separated by colons.
+/// exa stops its searches at the same places.
+pub static GIT_CEILING_DIRECTORIES: &str = "GIT_CEILING_DIRECTORIES";
+
+/// Environment variables that set the locale, in order of precedence,
+/// whose character encoding decides whether the tree view gets drawn with
+/// box-drawing characters.
+pub static LC_ALL: &str = "LC_ALL";
+pub static LC_CTYPE: &str = "LC_CTYPE";
+pub static LANG: &str = "LANG";
+
 // exa-specific variables
 
 /// Environment variable used to colour exa’s interface when colours are
@@ -38,18 +50,91 @@ pub static EXA_DEBUG: &str = "EXA_DEBUG";
 /// number of rows of output.
 pub static EXA_GRID_ROWS: &str = "EXA_GRID_ROWS";
 
+/// Environment variable used to limit how many of a listed directory’s
+/// ancestors get searched for `.exaignore` files. `0` means only the
+/// directory itself gets searched.
+pub static EXA_IGNORE_DEPTH: &str = "EXA_IGNORE_DEPTH";
+
+/// Environment variable used to choose whether the file name globs in
+/// LS_COLORS and EXA_COLORS match case-sensitively. It can be set to
+/// `sensitive` or `insensitive`; otherwise, globs are case-insensitive on
+/// Windows and case-sensitive everywhere else.
+pub static EXA_GLOB_CASE: &str = "EXA_GLOB_CASE";
+
+/// Environment variable used to add more extensions of compiled files, and
+/// the source files they come from, to the ones that get highlighted when
+/// both are in the same directory, such as `obj=cpp,c:map=ts`.
+pub static EXA_COMPILED: &str = "EXA_COMPILED";
+
+/// Environment variable used to add more files to the ones that get
+/// highlighted as kicking off a project’s build, as whole names or as
+/// extensions, such as `justfile:*.vcxitems`.
+pub static EXA_IMMEDIATE: &str = "EXA_IMMEDIATE";
+
+/// Environment variable used to choose the locale whose month names get
+/// used in timestamps, such as `de_DE.UTF-8`, instead of the one from
+/// `LC_ALL`, `LC_TIME`, or `LANG`.
+pub static EXA_LOCALE: &str = "EXA_LOCALE";
+
+/// Environment variable used to change the string put after each column
+/// of the details view from a single space, such as to a tab or `│`.
+pub static EXA_SEPARATOR: &str = "EXA_SEPARATOR";
+
+/// Environment variable used to set the widest that owner and group names
+/// can be in the details view before they get cut short.
+pub static EXA_OWNER_WIDTH: &str = "EXA_OWNER_WIDTH";
+
+/// Environment variable used to change the string put in between each
+/// column of the grid view from two spaces.
+pub static EXA_GRID_SEPARATOR: &str = "EXA_GRID_SEPARATOR";
+
+/// Environment variable used to change the arrow between a symlink and its
+/// target from `->` to something else, such as `→`.
+pub static EXA_LINK_ARROW: &str = "EXA_LINK_ARROW";
+
+/// Environment variable used to change the arrow between a broken symlink
+/// and its target. If it’s not set, broken links use the normal arrow.
+pub static EXA_BROKEN_LINK_ARROW: &str = "EXA_BROKEN_LINK_ARROW";
+
 
 
-/// Mockable wrapper for `std::env::var_os`.
+/// Mockable wrapper for `std::env::var_os`, and for whether the output is
+/// going to a terminal.
 pub trait Vars {
     fn get(&self, name: &'static str) -> Option<OsString>;
+
+    /// Whether standard output is a terminal, rather than a pipe or a file.
+    /// When it isn’t, the output defaults to one file per line with no
+    /// colours, as it does with `ls`.
+    fn is_terminal(&self) -> bool;
 }
 
 
-// Test impl that just returns the value it has.
+// Test impl that just returns the value it has, with the output going to a
+// terminal.
 #[cfg(test)]
 impl Vars for Option<OsString> {
     fn get(&self, _name: &'static str) -> Option<OsString> {
         self.clone()
     }
+
+    fn is_terminal(&self) -> bool {
+        true
+    }
+}
+
+/// Test impl with no variables set, for output that either is or isn’t
+/// going to a terminal.
+#[cfg(test)]
+pub struct MockTerminal(pub bool);
+
+#[cfg(test)]
+impl Vars for MockTerminal {
+    fn get(&self, _name: &'static str) -> Option<OsString> {
+        None
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.0
+    }
 }
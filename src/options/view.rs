@@ -1,11 +1,20 @@
-use output::{View, Mode, grid, details};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use output::{View, Mode, grid, details, markdown, format};
+use output::details::TreeSummary;
 use output::grid_details::{self, RowThreshold};
-use output::table::{TimeTypes, Environment, SizeFormat, Columns, Options as TableOptions};
-use output::time::TimeFormat;
+use output::template::{Template, Field};
+use output::table::{TimeType, TimeTypes, Environment, SizeFormat, UserFormat, OwnerWidth, SizeColumns, OwnerColumns, Columns, ColumnOrder, Options as TableOptions};
+use output::time::{TimeFormat, TimeLocale, Clock};
+use output::tree::TreeStyle;
 
 use options::{flags, Misfire, Vars};
-use options::parser::MatchedFlags;
+use options::parser::{Arg, MatchedFlags};
 
+use fs::feature::GitBackend;
+use fs::feature::checksum::Algorithm as ChecksumAlgorithm;
 use fs::feature::xattr;
 
 
@@ -16,8 +25,10 @@ impl View {
         use options::style::Styles;
 
         let mode = Mode::deduce(matches, vars)?;
-        let Styles { colours, style } = Styles::deduce(matches, vars, || *TERM_WIDTH)?;
-        Ok(View { mode, colours, style })
+        let Styles { colours, style } = Styles::deduce(matches, vars)?;
+        let summary = if matches.has(&flags::SUMMARY)? { Some(SizeFormat::deduce(matches)?) }
+                                                  else { None };
+        Ok(View { mode, colours, style, summary })
     }
 }
 
@@ -35,60 +46,137 @@ impl Mode {
             else if matches.has(&flags::ONE_LINE)? {
                 Err(Useless(&flags::ONE_LINE, true, &flags::LONG))
             }
+            else if matches.get(&flags::COLUMNS)?.is_some() {
+                Err(Useless(&flags::COLUMNS, true, &flags::LONG))
+            }
+            else if matches.has(&flags::DIRED)? && matches.has(&flags::GRID)? {
+                Err(Conflict(&flags::DIRED, &flags::GRID))
+            }
             else {
                 Ok(details::Options {
                     table: Some(TableOptions::deduce(matches, vars)?),
                     header: matches.has(&flags::HEADER)?,
                     xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+                    mark_empty: matches.has(&flags::MARK_EMPTY)?,
+                    hard_links: matches.has(&flags::HARD_LINKS)?,
+                    total: matches.has(&flags::TOTAL)?,
+                    tree_style: TreeStyle::deduce(matches, vars)?,
+                    tree_summary: TreeSummary::deduce(matches)?,
+                    uniform_columns: matches.has(&flags::UNIFORM_COLUMNS)?,
+                    dired: matches.has(&flags::DIRED)?,
                 })
             }
         };
 
         let other_options_scan = || {
-            if let Some(width) = TerminalWidth::deduce(vars)?.width() {
+            // A fixed number of columns doesn’t depend on the terminal, so
+            // it gets a grid even when the output isn’t going to one.
+            if let Some(columns) = Mode::deduce_columns(matches)? {
                 if matches.has(&flags::ONE_LINE)? {
-                    if matches.has(&flags::ACROSS)? {
-                        Err(Useless(&flags::ACROSS, true, &flags::ONE_LINE))
-                    }
-                    else {
-                        Ok(Mode::Lines)
-                    }
+                    return Err(Conflict(&flags::COLUMNS, &flags::ONE_LINE));
                 }
                 else if matches.has(&flags::TREE)? {
-                    let details = details::Options {
-                        table: None,
-                        header: false,
-                        xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
-                    };
+                    return Err(Useless(&flags::COLUMNS, true, &flags::TREE));
+                }
+                else if columns == 1 {
+                    return Ok(Mode::Lines);
+                }
+
+                let grid = grid::Options {
+                    across: matches.has(&flags::ACROSS)?,
+                    console_width: TerminalWidth::deduce(vars)?.width().unwrap_or(0),
+                    columns: Some(columns),
+                    separator: grid_separator(vars),
+                };
+
+                return Ok(Mode::Grid(grid));
+            }
 
-                    Ok(Mode::Details(details))
+            if matches.has(&flags::ONE_LINE)? {
+                if matches.has(&flags::ACROSS)? {
+                    Err(Useless(&flags::ACROSS, true, &flags::ONE_LINE))
                 }
                 else {
-                    let grid = grid::Options {
-                        across: matches.has(&flags::ACROSS)?,
-                        console_width: width,
-                    };
-
-                    Ok(Mode::Grid(grid))
+                    Ok(Mode::Lines)
                 }
             }
-            // If the terminal width couldn’t be matched for some reason, such
-            // as the program’s stdout being connected to a file, then
-            // fallback to the lines view.
             else if matches.has(&flags::TREE)? {
                 let details = details::Options {
                     table: None,
                     header: false,
                     xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+                    mark_empty: matches.has(&flags::MARK_EMPTY)?,
+                    hard_links: matches.has(&flags::HARD_LINKS)?,
+                    total: false,
+                    tree_style: TreeStyle::deduce(matches, vars)?,
+                    tree_summary: TreeSummary::deduce(matches)?,
+                    uniform_columns: false,
+                    dired: false,
                 };
 
                 Ok(Mode::Details(details))
             }
+            // Output that isn’t going to a terminal, such as when it’s piped
+            // to another program or written to a file, gets one file per
+            // line, unless the user asked for a grid anyway.
+            else if vars.is_terminal() || matches.has(&flags::GRID)? || matches.has(&flags::ACROSS)? {
+                let grid = grid::Options {
+                    across: matches.has(&flags::ACROSS)?,
+                    console_width: TerminalWidth::deduce(vars)?.width().unwrap_or(DEFAULT_WIDTH),
+                    columns: None,
+                    separator: grid_separator(vars),
+                };
+
+                Ok(Mode::Grid(grid))
+            }
             else {
                 Ok(Mode::Lines)
             }
         };
 
+        // A template prints whatever fields it names, one line per file, so
+        // it can’t be laid out any other way either.
+        if let Some(text) = matches.get(&flags::FORMAT)? {
+            for option in &[ &flags::GRID, &flags::ACROSS, &flags::ONE_LINE, &flags::TREE, &flags::DIRED, &flags::MARKDOWN ] {
+                if matches.has(option)? {
+                    return Err(Conflict(&flags::FORMAT, option));
+                }
+            }
+
+            if matches.get(&flags::COLUMNS)?.is_some() {
+                return Err(Conflict(&flags::FORMAT, &flags::COLUMNS));
+            }
+
+            let template = Template::parse(&text.to_string_lossy())?;
+            let mut table = TableOptions::deduce(matches, vars)?;
+
+            // Owner names only get cut short to keep tables narrow, and
+            // this is for printing them for other programs.
+            table.owner_width = None;
+
+            if template.uses(Field::Git) && table.extra_columns.git.is_none() {
+                table.extra_columns.git = Some(GitBackend::Fallback);
+            }
+
+            return Ok(Mode::Format(format::Options { template, table }));
+        }
+
+        // The Markdown table has the same columns as the long view, but
+        // can’t be laid out any other way.
+        if matches.has(&flags::MARKDOWN)? {
+            for option in &[ &flags::GRID, &flags::ACROSS, &flags::ONE_LINE, &flags::TREE, &flags::DIRED ] {
+                if matches.has(option)? {
+                    return Err(Conflict(&flags::MARKDOWN, option));
+                }
+            }
+
+            if matches.get(&flags::COLUMNS)?.is_some() {
+                return Err(Conflict(&flags::MARKDOWN, &flags::COLUMNS));
+            }
+
+            return Ok(Mode::Markdown(markdown::Options { table: TableOptions::deduce(matches, vars)? }));
+        }
+
         if matches.has(&flags::LONG)? {
             let details = long()?;
             if matches.has(&flags::GRID)? {
@@ -109,14 +197,26 @@ impl Mode {
         // If --long hasn’t been passed, then check if we need to warn the
         // user about flags that won’t have any effect.
         if matches.is_strict() {
+            // The size flags still do something without --long if there’s
+            // a summary, as it includes the total size of the files.
+            let summary = matches.has(&flags::SUMMARY)?;
+
             for option in &[ &flags::BINARY, &flags::BYTES, &flags::INODE, &flags::LINKS,
-                             &flags::HEADER, &flags::BLOCKS, &flags::TIME, &flags::GROUP ] {
-                if matches.has(option)? {
+                             &flags::HEADER, &flags::BLOCKS, &flags::TIME, &flags::GROUP,
+                             &flags::SIZE, &flags::DIR_ENTRIES, &flags::TOTAL, &flags::VOLUME,
+                             &flags::NETWORK, &flags::MY_ACCESS,
+                             &flags::SIGNATURES, &flags::VERSION_INFO,
+                             &flags::MIME, &flags::CHECKSUM, &flags::CHECKSUM_LIMIT, &flags::ORDER,
+                             &flags::SEPARATOR, &flags::DEDUP_COLUMNS, &flags::UNIFORM_COLUMNS, &flags::DIRED,
+                             &flags::NUMERIC, &flags::OWNER_WIDTH, &flags::OWNER ] {
+                let is_size = option == &&flags::BINARY || option == &&flags::BYTES;
+
+                if matches.has(option)? && !(summary && is_size) {
                     return Err(Useless(*option, false, &flags::LONG));
                 }
             }
 
-            if cfg!(feature="git") && matches.has(&flags::GIT)? {
+            if cfg!(feature="git") && (matches.has(&flags::GIT)? || matches.get(&flags::GIT)?.is_some()) {
                 return Err(Useless(&flags::GIT, false, &flags::LONG));
             }
             else if matches.has(&flags::LEVEL)? && !matches.has(&flags::RECURSE)? && !matches.has(&flags::TREE)? {
@@ -124,13 +224,39 @@ impl Mode {
                 // There is an identical check in dir_action
                 return Err(Useless2(&flags::LEVEL, &flags::RECURSE, &flags::TREE));
             }
+            else if matches.get(&flags::TREE_STYLE)?.is_some() && !matches.has(&flags::TREE)? {
+                return Err(Useless(&flags::TREE_STYLE, false, &flags::TREE));
+            }
+            else if matches.has(&flags::TREE_SUMMARY)? && !matches.has(&flags::TREE)? {
+                return Err(Useless(&flags::TREE_SUMMARY, false, &flags::TREE));
+            }
         }
 
         other_options_scan()
     }
+
+    /// Determine the number of columns the grid was asked to have, if any.
+    /// A grid with no columns can’t hold any files, so zero is an error.
+    fn deduce_columns(matches: &MatchedFlags) -> Result<Option<usize>, Misfire> {
+        if let Some(word) = matches.get(&flags::COLUMNS)? {
+            match word.to_string_lossy().parse() {
+                Ok(0)        => Err(Misfire::BadArgument(&flags::COLUMNS, word.into())),
+                Ok(columns)  => Ok(Some(columns)),
+                Err(e)       => Err(Misfire::FailedParse(e)),
+            }
+        }
+        else {
+            Ok(None)
+        }
+    }
 }
 
 
+/// The width to lay a grid out in when the output isn’t going to a terminal
+/// that it can be found from, which is what `ls` uses too.
+const DEFAULT_WIDTH: usize = 80;
+
+
 /// The width of the terminal requested by the user.
 #[derive(PartialEq, Debug)]
 enum TerminalWidth {
@@ -203,22 +329,307 @@ impl TableOptions {
         let time_format = TimeFormat::deduce(matches, vars)?;
         let size_format = SizeFormat::deduce(matches)?;
         let extra_columns = Columns::deduce(matches)?;
-        Ok(TableOptions { env, time_format, size_format, extra_columns })
+        let dir_entries = matches.has(&flags::DIR_ENTRIES)?;
+
+        let checksum_limit = match matches.get(&flags::CHECKSUM_LIMIT)? {
+            Some(limit)  => Some(parse_size(&flags::CHECKSUM_LIMIT, limit)?),
+            None         => None,
+        };
+
+        if checksum_limit.is_some() && extra_columns.checksum.is_none() && matches.is_strict() {
+            return Err(Misfire::Useless(&flags::CHECKSUM_LIMIT, false, &flags::CHECKSUM));
+        }
+
+        if matches.get(&flags::GIT_CEILING)?.is_some() && extra_columns.git.is_none() && matches.is_strict() {
+            return Err(Misfire::Useless(&flags::GIT_CEILING, false, &flags::GIT));
+        }
+
+        let git_ceilings = git_ceilings(matches, vars)?;
+        let separator = table_separator(matches, vars)?;
+        let dedup = dedup_columns(matches, &separator)?;
+        let user_format = if matches.has(&flags::NUMERIC)? { UserFormat::Numeric } else { UserFormat::Name };
+        let owner_width = owner_width(matches, vars)?;
+        Ok(TableOptions { env, time_format, size_format, extra_columns, dir_entries, checksum_limit, git_ceilings, separator, dedup, user_format, owner_width })
+    }
+}
+
+
+/// Determines whether repeated owners and groups should be replaced with
+/// ditto marks. Tab-separated output is meant to be read by other programs,
+/// which would need every value, so it never gets them.
+fn dedup_columns(matches: &MatchedFlags, separator: &str) -> Result<bool, Misfire> {
+    Ok(matches.has(&flags::DEDUP_COLUMNS)? && !separator.contains('\t'))
+}
+
+
+/// Determines how wide owner and group names can get before they’re cut
+/// short: the width given with `--owner-width`, or the one in
+/// `EXA_OWNER_WIDTH`, where a width of zero counts as no limit. Cut names
+/// end with `…`, or with `~` when the console can’t display that.
+fn owner_width<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Option<OwnerWidth>, Misfire> {
+    use options::vars;
+
+    let max = if let Some(width) = matches.get(&flags::OWNER_WIDTH)? {
+        match width.to_string_lossy().parse() {
+            Ok(0)   => return Err(Misfire::BadArgument(&flags::OWNER_WIDTH, width.into())),
+            Ok(w)   => w,
+            Err(e)  => return Err(Misfire::FailedParse(e)),
+        }
+    }
+    else if let Some(width) = vars.get(vars::EXA_OWNER_WIDTH).and_then(|s| s.into_string().ok()) {
+        match width.parse() {
+            Ok(0)   => return Ok(None),
+            Ok(w)   => w,
+            Err(e)  => return Err(Misfire::FailedParse(e)),
+        }
+    }
+    else {
+        return Ok(None);
+    };
+
+    let ellipsis = if console_is_utf8(vars) { "…" } else { "~" };
+    Ok(Some(OwnerWidth { max, ellipsis }))
+}
+
+
+/// Determines the string to put after each column of the details view:
+/// the one given with `--separator`, or the one in `EXA_SEPARATOR`, or a
+/// single space. A tab works well for output that gets cut into fields by
+/// other programs.
+fn table_separator<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<String, Misfire> {
+    use options::vars;
+
+    if let Some(separator) = matches.get(&flags::SEPARATOR)? {
+        return Ok(separator.to_string_lossy().to_string());
+    }
+
+    Ok(separator_var(vars, vars::EXA_SEPARATOR).unwrap_or_else(|| " ".into()))
+}
+
+/// Determines the string to put in between each column of the grid view:
+/// the one in `EXA_GRID_SEPARATOR`, or two spaces.
+fn grid_separator<V: Vars>(vars: &V) -> String {
+    use options::vars;
+
+    separator_var(vars, vars::EXA_GRID_SEPARATOR).unwrap_or_else(|| "  ".into())
+}
+
+/// Reads a separator from an environment variable. Empty values count as
+/// unset, as columns with nothing between them would run together.
+fn separator_var<V: Vars>(vars: &V, name: &'static str) -> Option<String> {
+    vars.get(name).map(|s| s.to_string_lossy().to_string())
+                  .filter(|s| !s.is_empty())
+}
+
+
+/// Determines the directories that searches for Git repositories should
+/// stop before reaching: the one given with `--git-ceiling`, made absolute,
+/// and the ones in `GIT_CEILING_DIRECTORIES`. Git ignores relative paths in
+/// the variable, so they get ignored here too.
+fn git_ceilings<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Vec<PathBuf>, Misfire> {
+    use fs::normalise;
+    use options::vars;
+
+    let mut ceilings = Vec::new();
+
+    if let Some(dir) = matches.get(&flags::GIT_CEILING)? {
+        let dir = Path::new(dir);
+        if dir.is_absolute() {
+            ceilings.push(normalise(dir));
+        }
+        else {
+            match env::current_dir() {
+                Ok(cwd)  => ceilings.push(normalise(&cwd.join(dir))),
+                Err(_)   => return Err(Misfire::BadArgument(&flags::GIT_CEILING, dir.into())),
+            }
+        }
     }
+
+    if let Some(dirs) = vars.get(vars::GIT_CEILING_DIRECTORIES) {
+        ceilings.extend(env::split_paths(&dirs).filter(|d| d.is_absolute()));
+    }
+
+    Ok(ceilings)
 }
 
 
 impl Columns {
     fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
         let time_types = TimeTypes::deduce(matches)?;
-        let git = cfg!(feature="git") && matches.has(&flags::GIT)?;
+        let size = SizeColumns::deduce(matches)?;
+        let owner = OwnerColumns::deduce(matches)?;
+        let git = if cfg!(feature="git") { GitBackend::deduce(matches)? } else { None };
 
         let blocks = matches.has(&flags::BLOCKS)?;
         let group  = matches.has(&flags::GROUP)?;
         let inode  = matches.has(&flags::INODE)?;
         let links  = matches.has(&flags::LINKS)?;
+        let volume = matches.has(&flags::VOLUME)?;
+        let network = matches.has(&flags::NETWORK)?;
+        let zone = matches.has(&flags::MOTW)?;
+        let my_access = matches.has(&flags::MY_ACCESS)?;
+        let signatures = matches.has(&flags::SIGNATURES)?;
+        let version_info = matches.has(&flags::VERSION_INFO)?;
+        let content_type = matches.has(&flags::MIME)?;
+        let checksum = ChecksumAlgorithm::deduce(matches)?;
+        let order = ColumnOrder::deduce(matches)?;
+
+        Ok(Columns { time_types, size, owner, git, blocks, group, inode, links, volume, network, zone, my_access, signatures, version_info, content_type, checksum, order })
+    }
+}
+
+
+impl ColumnOrder {
+
+    /// Determine the order of the columns from a comma-separated list of
+    /// their names. Each name has to be one exa knows about, so that typos
+    /// get caught rather than the column silently staying where it was.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
+        let word = match matches.get(&flags::ORDER)? {
+            Some(w)  => w,
+            None     => return Ok(ColumnOrder::default()),
+        };
+
+        let mut names = Vec::new();
+        for name in word.to_string_lossy().split(',').filter(|n| !n.is_empty()) {
+            match flags::COLUMN_NAMES.iter().find(|c| **c == name) {
+                Some(column)  => names.push(*column),
+                None          => return Err(Misfire::BadArgument(&flags::ORDER, name.into())),
+            }
+        }
+
+        Ok(ColumnOrder(names))
+    }
+}
+
+
+impl GitBackend {
+
+    /// Determine how to read Git statuses, if the user wants them shown at
+    /// all. Plain `--git` uses libgit2, falling back to running `git` for
+    /// repositories that libgit2 can’t read.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<GitBackend>, Misfire> {
+        if let Some(word) = matches.get(&flags::GIT)? {
+            if word == "libgit2" {
+                Ok(Some(GitBackend::LibGit2))
+            }
+            else if word == "command" {
+                Ok(Some(GitBackend::Command))
+            }
+            else {
+                Err(Misfire::BadArgument(&flags::GIT, word.into()))
+            }
+        }
+        else if matches.has(&flags::GIT)? {
+            Ok(Some(GitBackend::Fallback))
+        }
+        else {
+            Ok(None)
+        }
+    }
+}
+
+
+impl ChecksumAlgorithm {
+
+    /// Determine which algorithm to hash files with, if the user wants
+    /// them hashed at all.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<ChecksumAlgorithm>, Misfire> {
+        let word = match matches.get(&flags::CHECKSUM)? {
+            Some(w)  => w,
+            None     => return Ok(None),
+        };
+
+        if word == "crc32" {
+            Ok(Some(ChecksumAlgorithm::CRC32))
+        }
+        else if word == "md5" {
+            Ok(Some(ChecksumAlgorithm::MD5))
+        }
+        else if word == "sha1" {
+            Ok(Some(ChecksumAlgorithm::SHA1))
+        }
+        else if word == "sha256" {
+            Ok(Some(ChecksumAlgorithm::SHA256))
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::CHECKSUM, word.into()))
+        }
+    }
+}
+
+
+/// Parses a size given as a number of bytes, optionally followed by `k`,
+/// `M`, or `G` to multiply it by a power of 1024.
+fn parse_size(flag: &'static Arg, word: &OsStr) -> Result<u64, Misfire> {
+    let text = match word.to_str() {
+        Some(t)  => t,
+        None     => return Err(Misfire::BadArgument(flag, word.into())),
+    };
+
+    let (number, multiplier) = match text.chars().last() {
+        Some('k') | Some('K')  => (&text[.. text.len() - 1], 1024),
+        Some('m') | Some('M')  => (&text[.. text.len() - 1], 1024 * 1024),
+        Some('g') | Some('G')  => (&text[.. text.len() - 1], 1024 * 1024 * 1024),
+        _                      => (text, 1),
+    };
+
+    match number.parse::<u64>() {
+        Ok(n)   => Ok(n.saturating_mul(multiplier)),
+        Err(e)  => Err(Misfire::FailedParse(e)),
+    }
+}
+
+
+impl SizeColumns {
+
+    /// Determine which of a file’s sizes to show, based on the `--size`
+    /// argument. Only the logical size gets shown by default, as that’s
+    /// what `ls` shows.
+    fn deduce(matches: &MatchedFlags) -> Result<SizeColumns, Misfire> {
+        let word = match matches.get(&flags::SIZE)? {
+            Some(w)  => w,
+            None     => return Ok(SizeColumns::Logical),
+        };
+
+        if word == "logical" {
+            Ok(SizeColumns::Logical)
+        }
+        else if word == "disk" {
+            Ok(SizeColumns::Disk)
+        }
+        else if word == "both" {
+            Ok(SizeColumns::Both)
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::SIZE, word.into()))
+        }
+    }
+}
+
+
+impl OwnerColumns {
+
+    /// Determine how to show each file’s user, based on the `--owner`
+    /// argument. The user gets its own column by default.
+    fn deduce(matches: &MatchedFlags) -> Result<OwnerColumns, Misfire> {
+        let word = match matches.get(&flags::OWNER)? {
+            Some(w)  => w,
+            None     => return Ok(OwnerColumns::User),
+        };
 
-        Ok(Columns { time_types, git, blocks, group, inode, links })
+        if word == "user" {
+            Ok(OwnerColumns::User)
+        }
+        else if word == "combined" {
+            Ok(OwnerColumns::Combined)
+        }
+        else if word == "none" {
+            Ok(OwnerColumns::None)
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::OWNER, word.into()))
+        }
     }
 }
 
@@ -249,7 +660,7 @@ impl TimeFormat {
 
     /// Determine how time should be formatted in timestamp columns.
     fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<TimeFormat, Misfire> {
-        pub use output::time::{DefaultFormat, ISOFormat};
+        pub use output::time::{DefaultFormat, ISOFormat, CustomFormat, Clock};
 
         let word = match matches.get(&flags::TIME_STYLE)? {
             Some(w) => w.to_os_string(),
@@ -257,13 +668,13 @@ impl TimeFormat {
                 use options::vars;
                 match vars.get(vars::TIME_STYLE) {
                     Some(ref t) if !t.is_empty() => t.clone(),
-                    _                            => return Ok(TimeFormat::DefaultFormat(DefaultFormat::load()))
+                    _                            => return Ok(TimeFormat::DefaultFormat(DefaultFormat::load(time_locale(matches, vars)?, Clock::deduce(matches)?)))
                 }
             },
         };
 
         if &word == "default" {
-            Ok(TimeFormat::DefaultFormat(DefaultFormat::load()))
+            Ok(TimeFormat::DefaultFormat(DefaultFormat::load(time_locale(matches, vars)?, Clock::deduce(matches)?)))
         }
         else if &word == "iso" {
             Ok(TimeFormat::ISOFormat(ISOFormat::load()))
@@ -274,6 +685,15 @@ impl TimeFormat {
         else if &word == "full-iso" {
             Ok(TimeFormat::FullISO)
         }
+        else if &word == "unix" {
+            Ok(TimeFormat::Unix)
+        }
+        else if &word == "unix-ms" {
+            Ok(TimeFormat::UnixMillis)
+        }
+        else if let Some(format) = word.to_str().and_then(|w| w.strip_prefix('+')) {
+            Ok(TimeFormat::Custom(CustomFormat::parse(format, time_locale(matches, vars)?.names)))
+        }
         else {
             Err(Misfire::BadArgument(&flags::TIME_STYLE, word.into()))
         }
@@ -281,6 +701,149 @@ impl TimeFormat {
 }
 
 
+impl Clock {
+
+    /// Determine which clock to write the time of day with in the default
+    /// time style. The ISO styles always use the twenty-four-hour clock.
+    /// Following the locale has to be asked for, so that the output doesn’t
+    /// change from what it’s always been just because of where it’s run.
+    fn deduce(matches: &MatchedFlags) -> Result<Clock, Misfire> {
+        let word = match matches.get(&flags::TIME_CLOCK)? {
+            Some(w) => w,
+            None    => return Ok(Clock::TwentyFourHour),
+        };
+
+        if word == "12" {
+            Ok(Clock::TwelveHour)
+        }
+        else if word == "24" {
+            Ok(Clock::TwentyFourHour)
+        }
+        else if word == "auto" {
+            Ok(Clock::Auto)
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::TIME_CLOCK, word.into()))
+        }
+    }
+}
+
+
+impl TreeStyle {
+
+    /// Determine which characters to draw the tree view with. Without a
+    /// `--tree-style` argument, box-drawing characters get used unless the
+    /// console isn’t going to be able to display them.
+    fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<TreeStyle, Misfire> {
+        let word = match matches.get(&flags::TREE_STYLE)? {
+            Some(w) => w,
+            None    => return Ok(if console_is_utf8(vars) { TreeStyle::Unicode }
+                                                    else { TreeStyle::Ascii }),
+        };
+
+        if word == "unicode" {
+            Ok(TreeStyle::Unicode)
+        }
+        else if word == "ascii" {
+            Ok(TreeStyle::Ascii)
+        }
+        else {
+            Err(Misfire::BadArgument(&flags::TREE_STYLE, word.into()))
+        }
+    }
+}
+
+impl TreeSummary {
+
+    /// Determine whether to count the files in each directory of the tree
+    /// view, and whether to count all the way down. Just `--tree-summary`
+    /// counts the files directly inside.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<TreeSummary>, Misfire> {
+        if let Some(word) = matches.get(&flags::TREE_SUMMARY)? {
+            if word == "immediate" {
+                Ok(Some(TreeSummary::Immediate))
+            }
+            else if word == "recursive" {
+                Ok(Some(TreeSummary::Recursive))
+            }
+            else {
+                Err(Misfire::BadArgument(&flags::TREE_SUMMARY, word.into()))
+            }
+        }
+        else if matches.has(&flags::TREE_SUMMARY)? {
+            Ok(Some(TreeSummary::Immediate))
+        }
+        else {
+            Ok(None)
+        }
+    }
+}
+
+
+/// Whether the console uses UTF-8, going by the character encoding of the
+/// first of `LC_ALL`, `LC_CTYPE`, and `LANG` to be set. If none of them
+/// are, there’s nothing to go on, so it’s assumed that it does.
+#[cfg(not(windows))]
+fn console_is_utf8<V: Vars>(vars: &V) -> bool {
+    use options::vars;
+
+    let locale = [ vars::LC_ALL, vars::LC_CTYPE, vars::LANG ].iter()
+                     .filter_map(|name| vars.get(name))
+                     .find(|value| !value.is_empty());
+
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_string_lossy().to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => true,
+    }
+}
+
+/// Whether the console uses UTF-8, going by its output code page. Legacy
+/// OEM code pages turn box-drawing characters into mojibake.
+#[cfg(windows)]
+fn console_is_utf8<V: Vars>(_vars: &V) -> bool {
+    extern "system" {
+        fn GetConsoleOutputCP() -> u32;
+    }
+
+    const CP_UTF8: u32 = 65001;
+    unsafe { GetConsoleOutputCP() == CP_UTF8 }
+}
+
+
+/// Determine which locale’s month and day names, and clock, to use in
+/// timestamps.
+///
+/// The `--locale` argument wins, followed by the `EXA_LOCALE` environment
+/// variable, followed by the usual `LC_ALL`, `LC_TIME`, and `LANG`
+/// variables. A locale named on the command-line that the system doesn’t
+/// have is an error, but one in `EXA_LOCALE` only gets a warning, as it’s
+/// likely set for every program rather than for this one invocation.
+fn time_locale<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<TimeLocale, Misfire> {
+    use output::time::{named_locale, user_locale};
+    use options::vars;
+
+    if let Some(name) = matches.get(&flags::LOCALE)? {
+        return name.to_str()
+                   .and_then(named_locale)
+                   .ok_or_else(|| Misfire::BadArgument(&flags::LOCALE, name.into()));
+    }
+
+    if let Some(name) = vars.get(vars::EXA_LOCALE) {
+        if !name.is_empty() {
+            match name.to_str().and_then(named_locale) {
+                Some(time)  => return Ok(time),
+                None        => warn!("Ignoring unknown locale {:?} in {}", name, vars::EXA_LOCALE),
+            }
+        }
+    }
+
+    Ok(user_locale())
+}
+
+
 impl TimeTypes {
 
     /// Determine which of a file’s time fields should be displayed for it
@@ -291,11 +854,14 @@ impl TimeTypes {
     /// `--time=modified`). An error is signaled if both ways are used.
     ///
     /// It’s valid to show more than one column by passing in more than one
-    /// option, but passing *no* options means that the user just wants to
-    /// see the default set.
-    fn deduce(matches: &MatchedFlags) -> Result<TimeTypes, Misfire> {
+    /// option, or a comma-separated list of fields (or `all`) as the
+    /// parameter, but passing *no* options means that the user just wants
+    /// to see the default set. The columns from a list go in the order
+    /// they were listed in.
+    pub fn deduce(matches: &MatchedFlags) -> Result<TimeTypes, Misfire> {
         let possible_word = matches.get(&flags::TIME)?;
         let modified = matches.has(&flags::MODIFIED)?;
+        let changed  = matches.has(&flags::CHANGED)?;
         let created  = matches.has(&flags::CREATED)?;
         let accessed = matches.has(&flags::ACCESSED)?;
 
@@ -303,30 +869,39 @@ impl TimeTypes {
             if modified {
                 Err(Misfire::Useless(&flags::MODIFIED, true, &flags::TIME))
             }
+            else if changed {
+                Err(Misfire::Useless(&flags::CHANGED, true, &flags::TIME))
+            }
             else if created {
                 Err(Misfire::Useless(&flags::CREATED, true, &flags::TIME))
             }
             else if accessed {
                 Err(Misfire::Useless(&flags::ACCESSED, true, &flags::TIME))
             }
-            else if word == "mod" || word == "modified" {
-                Ok(TimeTypes { accessed: false, modified: true,  created: false })
-            }
-            else if word == "acc" || word == "accessed" {
-                Ok(TimeTypes { accessed: true,  modified: false, created: false })
-            }
-            else if word == "cr" || word == "created" {
-                Ok(TimeTypes { accessed: false, modified: false, created: true  })
+            else if word == "all" {
+                Ok(TimeTypes::all())
             }
             else {
-                Err(Misfire::BadArgument(&flags::TIME, word.into()))
+                let mut types = Vec::new();
+
+                for field in word.to_string_lossy().split(',') {
+                    match field {
+                        "mod" | "modified"  => types.push(TimeType::Modified),
+                        "ch"  | "changed"   => types.push(TimeType::Changed),
+                        "acc" | "accessed"  => types.push(TimeType::Accessed),
+                        "cr"  | "created"   => types.push(TimeType::Created),
+                        _                   => return Err(Misfire::BadArgument(&flags::TIME, word.into())),
+                    }
+                }
+
+                Ok(TimeTypes::from_list(types))
             }
         }
-        else if modified || created || accessed {
-            Ok(TimeTypes { accessed, modified, created })
-        }
         else {
-            Ok(TimeTypes::default())
+            // The shortcut flags don’t say which came first, so their
+            // columns go in the same order as `all` puts them in.
+            let flags = [ (modified, TimeType::Modified), (changed, TimeType::Changed), (created, TimeType::Created), (accessed, TimeType::Accessed) ];
+            Ok(TimeTypes::from_list(flags.iter().filter(|f| f.0).map(|f| f.1)))
         }
     }
 }
@@ -357,11 +932,16 @@ mod test {
     use options::test::parse_for_test;
     use options::test::Strictnesses::*;
 
-    static TEST_ARGS: &[&Arg] = &[ &flags::BINARY, &flags::BYTES,    &flags::TIME_STYLE,
-                                   &flags::TIME,   &flags::MODIFIED, &flags::CREATED, &flags::ACCESSED,
+    static TEST_ARGS: &[&Arg] = &[ &flags::BINARY, &flags::BYTES,    &flags::TIME_STYLE, &flags::TIME_CLOCK, &flags::LOCALE,
+                                   &flags::TIME,   &flags::MODIFIED, &flags::CHANGED, &flags::CREATED, &flags::ACCESSED,
                                    &flags::HEADER, &flags::GROUP,  &flags::INODE, &flags::GIT,
                                    &flags::LINKS,  &flags::BLOCKS, &flags::LONG,  &flags::LEVEL,
-                                   &flags::GRID,   &flags::ACROSS, &flags::ONE_LINE ];
+                                   &flags::GRID,   &flags::ACROSS, &flags::ONE_LINE, &flags::SIZE,
+                                   &flags::CHECKSUM, &flags::COLUMNS, &flags::TREE, &flags::ORDER,
+                                   &flags::SEPARATOR, &flags::DEDUP_COLUMNS, &flags::TREE_STYLE,
+                                   &flags::TREE_SUMMARY, &flags::UNIFORM_COLUMNS, &flags::MARKDOWN,
+                                   &flags::DIRED, &flags::FORMAT, &flags::NUMERIC, &flags::OWNER_WIDTH, &flags::OWNER,
+                                   &flags::PROTECTED ];
 
     macro_rules! test {
 
@@ -452,6 +1032,125 @@ mod test {
     }
 
 
+    mod size_columns {
+        use super::*;
+
+        test!(empty:    SizeColumns <- [];                 Both => Ok(SizeColumns::Logical));
+        test!(logical:  SizeColumns <- ["--size=logical"];  Both => Ok(SizeColumns::Logical));
+        test!(disk:     SizeColumns <- ["--size=disk"];     Both => Ok(SizeColumns::Disk));
+        test!(both:     SizeColumns <- ["--size", "both"];  Both => Ok(SizeColumns::Both));
+
+        test!(overridden:  SizeColumns <- ["--size=disk", "--size=both"];  Last => Ok(SizeColumns::Both));
+        test!(duplicate:   SizeColumns <- ["--size=disk", "--size=both"];  Complain => Err(Misfire::Duplicate(Flag::Long("size"), Flag::Long("size"))));
+
+        test!(error:    SizeColumns <- ["--size=apparent"];  Both => Err(Misfire::BadArgument(&flags::SIZE, OsString::from("apparent"))));
+    }
+
+
+    mod owner_columns {
+        use super::*;
+
+        test!(empty:     OwnerColumns <- [];                      Both => Ok(OwnerColumns::User));
+        test!(user:      OwnerColumns <- ["--owner=user"];        Both => Ok(OwnerColumns::User));
+        test!(combined:  OwnerColumns <- ["--owner=combined"];    Both => Ok(OwnerColumns::Combined));
+        test!(none:      OwnerColumns <- ["--owner", "none"];     Both => Ok(OwnerColumns::None));
+
+        test!(overridden:  OwnerColumns <- ["--owner=none", "--owner=combined"];  Last => Ok(OwnerColumns::Combined));
+        test!(error:     OwnerColumns <- ["--owner=group"];       Both => Err(Misfire::BadArgument(&flags::OWNER, OsString::from("group"))));
+    }
+
+
+    mod column_orders {
+        use super::*;
+
+        test!(empty:     ColumnOrder <- [];                            Both => Ok(ColumnOrder::default()));
+        test!(one:       ColumnOrder <- ["--order=size"];              Both => Ok(ColumnOrder(vec![ "size" ])));
+        test!(several:   ColumnOrder <- ["--order", "user,modified,permissions"];  Both => Ok(ColumnOrder(vec![ "user", "modified", "permissions" ])));
+        test!(trailing:  ColumnOrder <- ["--order=size,"];             Both => Ok(ColumnOrder(vec![ "size" ])));
+
+        test!(overridden:  ColumnOrder <- ["--order=size", "--order=user"];  Last => Ok(ColumnOrder(vec![ "user" ])));
+        test!(duplicate:   ColumnOrder <- ["--order=size", "--order=user"];  Complain => Err(Misfire::Duplicate(Flag::Long("order"), Flag::Long("order"))));
+
+        test!(typo:      ColumnOrder <- ["--order=size,usre"];         Both => Err(Misfire::BadArgument(&flags::ORDER, OsString::from("usre"))));
+    }
+
+
+    mod git_backends {
+        use super::*;
+
+        test!(empty:    GitBackend <- [];                    Both => Ok(None));
+        test!(plain:    GitBackend <- ["--git"];             Both => Ok(Some(GitBackend::Fallback)));
+        test!(libgit2:  GitBackend <- ["--git=libgit2"];     Both => Ok(Some(GitBackend::LibGit2)));
+        test!(command:  GitBackend <- ["--git=command"];     Both => Ok(Some(GitBackend::Command)));
+        test!(error:    GitBackend <- ["--git=svn"];         Both => Err(Misfire::BadArgument(&flags::GIT, OsString::from("svn"))));
+    }
+
+
+    mod git_ceilings {
+        use super::*;
+
+        fn ceilings(inputs: &[&str], var: Option<&str>) -> Vec<Result<Vec<PathBuf>, Misfire>> {
+            static TEST_ARGS: &[&Arg] = &[ &flags::GIT, &flags::GIT_CEILING ];
+            let vars = var.map(OsString::from);
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| git_ceilings(mf, &vars))
+        }
+
+        #[test]
+        fn none() {
+            for result in ceilings(&[], None) {
+                assert_eq!(result, Ok(Vec::new()));
+            }
+        }
+
+        #[test]
+        fn argument() {
+            for result in ceilings(&[ "--git", "--git-ceiling=/mnt/share/./" ], None) {
+                assert_eq!(result, Ok(vec![ PathBuf::from("/mnt/share") ]));
+            }
+        }
+
+        #[test]
+        fn relative_argument() {
+            let cwd = env::current_dir().unwrap();
+            for result in ceilings(&[ "--git", "--git-ceiling", ".." ], None) {
+                assert_eq!(result, Ok(vec![ cwd.parent().unwrap().to_path_buf() ]));
+            }
+        }
+
+        #[test]
+        fn variable() {
+            for result in ceilings(&[], Some("/mnt:relative:/home")) {
+                assert_eq!(result, Ok(vec![ PathBuf::from("/mnt"), PathBuf::from("/home") ]));
+            }
+        }
+
+        #[test]
+        fn both() {
+            for result in ceilings(&[ "--git", "--git-ceiling=/srv" ], Some("/mnt")) {
+                assert_eq!(result, Ok(vec![ PathBuf::from("/srv"), PathBuf::from("/mnt") ]));
+            }
+        }
+    }
+
+
+    mod checksums {
+        use super::*;
+
+        test!(empty:    ChecksumAlgorithm <- [];                    Both => Ok(None));
+        test!(crc32:    ChecksumAlgorithm <- ["--checksum=crc32"];   Both => Ok(Some(ChecksumAlgorithm::CRC32)));
+        test!(sha256:   ChecksumAlgorithm <- ["--checksum", "sha256"];  Both => Ok(Some(ChecksumAlgorithm::SHA256)));
+        test!(error:    ChecksumAlgorithm <- ["--checksum=sha3"];    Both => Err(Misfire::BadArgument(&flags::CHECKSUM, OsString::from("sha3"))));
+
+        #[test]
+        fn sizes() {
+            assert_eq!(parse_size(&flags::CHECKSUM_LIMIT, OsStr::new("1000")), Ok(1000));
+            assert_eq!(parse_size(&flags::CHECKSUM_LIMIT, OsStr::new("4k")),   Ok(4096));
+            assert_eq!(parse_size(&flags::CHECKSUM_LIMIT, OsStr::new("2M")),   Ok(2 * 1024 * 1024));
+            assert!(parse_size(&flags::CHECKSUM_LIMIT, OsStr::new("lots")).is_err());
+        }
+    }
+
+
     mod time_formats {
         use super::*;
 
@@ -466,6 +1165,8 @@ mod test {
         test!(iso:       TimeFormat <- ["--time-style", "iso"], None;       Both => like Ok(TimeFormat::ISOFormat(_)));
         test!(long_iso:  TimeFormat <- ["--time-style=long-iso"], None;     Both => like Ok(TimeFormat::LongISO));
         test!(full_iso:  TimeFormat <- ["--time-style", "full-iso"], None;  Both => like Ok(TimeFormat::FullISO));
+        test!(unix:      TimeFormat <- ["--time-style=unix"], None;         Both => like Ok(TimeFormat::Unix));
+        test!(unix_ms:   TimeFormat <- ["--time-style", "unix-ms"], None;   Both => like Ok(TimeFormat::UnixMillis));
 
         // Overriding
         test!(actually:  TimeFormat <- ["--time-style=default", "--time-style", "iso"], None;  Last => like Ok(TimeFormat::ISOFormat(_)));
@@ -477,12 +1178,173 @@ mod test {
         // Errors
         test!(daily:     TimeFormat <- ["--time-style=24-hour"], None;  Both => err Misfire::BadArgument(&flags::TIME_STYLE, OsString::from("24-hour")));
 
+        // Custom formats
+        test!(custom:    TimeFormat <- ["--time-style=+%H:%M:%S.%3f"], None;  Both => like Ok(TimeFormat::Custom(_)));
+        test!(env_plus:  TimeFormat <- [], Some("+%Y".into());                Both => like Ok(TimeFormat::Custom(_)));
+
         // `TIME_STYLE` environment variable is defined.
         // If the time-style argument is not given, `TIME_STYLE` is used.
         test!(use_env:     TimeFormat <- [], Some("long-iso".into());  Both => like Ok(TimeFormat::LongISO));
 
         // If the time-style argument is given, `TIME_STYLE` is overriding.
         test!(override_env:     TimeFormat <- ["--time-style=full-iso"], Some("long-iso".into());  Both => like Ok(TimeFormat::FullISO));
+
+        // Locales
+        test!(locale:      TimeFormat <- ["--locale=C"], None;                          Both => like Ok(TimeFormat::DefaultFormat(_)));
+        test!(locale_plus: TimeFormat <- ["--locale=POSIX", "--time-style=+%B"], None;  Both => like Ok(TimeFormat::Custom(_)));
+        test!(locale_iso:  TimeFormat <- ["--locale=xx_NOPE", "--time-style=iso"], None;  Both => like Ok(TimeFormat::ISOFormat(_)));
+        test!(no_locale:   TimeFormat <- ["--locale=xx_NOPE"], None;                    Both => err Misfire::BadArgument(&flags::LOCALE, OsString::from("xx_NOPE")));
+        // The mocked variables all share one value, so this sets both
+        // `TIME_STYLE` and an `EXA_LOCALE` that doesn’t exist, which is only a warning.
+        test!(env_locale:  TimeFormat <- [], Some("default".into());                    Both => like Ok(TimeFormat::DefaultFormat(_)));
+    }
+
+
+    mod separators {
+        use super::*;
+
+        fn table(inputs: &[&str], var: Option<&str>) -> Vec<Result<String, Misfire>> {
+            let vars = var.map(OsString::from);
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| table_separator(mf, &vars))
+        }
+
+        #[test]
+        fn space_by_default() {
+            assert_eq!(table(&[], None), vec![ Ok(" ".into()), Ok(" ".into()) ]);
+        }
+
+        #[test]
+        fn tab() {
+            assert_eq!(table(&[ "--separator=\t" ], None), vec![ Ok("\t".into()), Ok("\t".into()) ]);
+        }
+
+        #[test]
+        fn from_env() {
+            assert_eq!(table(&[], Some(" │ ")), vec![ Ok(" │ ".into()), Ok(" │ ".into()) ]);
+        }
+
+        #[test]
+        fn flag_beats_env() {
+            assert_eq!(table(&[ "--separator", "  " ], Some("\t")), vec![ Ok("  ".into()), Ok("  ".into()) ]);
+        }
+
+        #[test]
+        fn empty_env() {
+            assert_eq!(table(&[], Some("")), vec![ Ok(" ".into()), Ok(" ".into()) ]);
+        }
+
+        #[test]
+        fn dedup() {
+            for result in parse_for_test(&[ "--dedup-columns" ], TEST_ARGS, Both, |mf| dedup_columns(mf, " ")) {
+                assert_eq!(result, Ok(true));
+            }
+        }
+
+        #[test]
+        fn no_dedup_with_tabs() {
+            for result in parse_for_test(&[ "--dedup-columns" ], TEST_ARGS, Both, |mf| dedup_columns(mf, "\t")) {
+                assert_eq!(result, Ok(false));
+            }
+        }
+
+        #[test]
+        fn grid() {
+            assert_eq!(grid_separator(&None), "  ");
+            assert_eq!(grid_separator(&Some(OsString::from("\t"))), "\t");
+        }
+    }
+
+
+    mod owner_widths {
+        use super::*;
+
+        fn width(inputs: &[&str], var: Option<&str>) -> Vec<Result<Option<OwnerWidth>, Misfire>> {
+            let vars = var.map(OsString::from);
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| owner_width(mf, &vars))
+        }
+
+        #[test]
+        fn no_limit() {
+            assert_eq!(width(&[], None), vec![ Ok(None), Ok(None) ]);
+        }
+
+        #[test]
+        fn flag() {
+            let expected = Some(OwnerWidth { max: 12, ellipsis: "…" });
+            assert_eq!(width(&[ "--owner-width=12" ], None), vec![ Ok(expected), Ok(expected) ]);
+        }
+
+        #[test]
+        fn zero() {
+            for result in width(&[ "--owner-width=0" ], None) {
+                assert_eq!(result, Err(Misfire::BadArgument(&flags::OWNER_WIDTH, OsString::from("0"))));
+            }
+        }
+
+        #[test]
+        fn not_a_number() {
+            for result in width(&[ "--owner-width=wide" ], None) {
+                assert_eq!(result, Err(Misfire::FailedParse("wide".parse::<usize>().unwrap_err())));
+            }
+        }
+
+        // The mocked variables all share one value, so the locale isn’t
+        // UTF-8 in these, and the ellipsis becomes a `~`.
+        #[test]
+        fn from_env() {
+            let expected = Some(OwnerWidth { max: 10, ellipsis: "~" });
+            assert_eq!(width(&[], Some("10")), vec![ Ok(expected), Ok(expected) ]);
+        }
+
+        #[test]
+        fn zero_env() {
+            assert_eq!(width(&[], Some("0")), vec![ Ok(None), Ok(None) ]);
+        }
+
+        #[test]
+        fn flag_beats_env() {
+            let expected = Some(OwnerWidth { max: 4, ellipsis: "~" });
+            assert_eq!(width(&[ "--owner-width", "4" ], Some("10")), vec![ Ok(expected), Ok(expected) ]);
+        }
+    }
+
+
+    mod clocks {
+        use super::*;
+
+        test!(empty:       Clock <- [];                        Both => Ok(Clock::TwentyFourHour));
+        test!(twelve:      Clock <- ["--time-clock=12"];       Both => Ok(Clock::TwelveHour));
+        test!(twenty_four: Clock <- ["--time-clock", "24"];    Both => Ok(Clock::TwentyFourHour));
+        test!(auto:        Clock <- ["--time-clock=auto"];     Both => Ok(Clock::Auto));
+        test!(overriding:  Clock <- ["--time-clock=12", "--time-clock=24"];  Last => Ok(Clock::TwentyFourHour));
+        test!(duplicate:   Clock <- ["--time-clock=12", "--time-clock=24"];  Complain => Err(Misfire::Duplicate(Flag::Long("time-clock"), Flag::Long("time-clock"))));
+        test!(sixty:       Clock <- ["--time-clock=60"];       Both => Err(Misfire::BadArgument(&flags::TIME_CLOCK, OsString::from("60"))));
+    }
+
+
+    mod tree_styles {
+        use super::*;
+
+        test!(empty:       TreeStyle <- [], None;                                       Both => like Ok(TreeStyle::Unicode));
+        test!(utf8:        TreeStyle <- [], Some("en_GB.UTF-8".into());                 Both => like Ok(TreeStyle::Unicode));
+        test!(utf8_lower:  TreeStyle <- [], Some("de_DE.utf8".into());                  Both => like Ok(TreeStyle::Unicode));
+        test!(c_locale:    TreeStyle <- [], Some("C".into());                           Both => like Ok(TreeStyle::Ascii));
+        test!(latin_1:     TreeStyle <- [], Some("en_GB.ISO-8859-1".into());            Both => like Ok(TreeStyle::Ascii));
+        test!(blank:       TreeStyle <- [], Some("".into());                            Both => like Ok(TreeStyle::Unicode));
+        test!(unicode:     TreeStyle <- ["--tree-style=unicode"], Some("C".into());     Both => like Ok(TreeStyle::Unicode));
+        test!(ascii:       TreeStyle <- ["--tree-style", "ascii"], None;                Both => like Ok(TreeStyle::Ascii));
+        test!(heavy:       TreeStyle <- ["--tree-style=heavy"], None;                   Both => err Misfire::BadArgument(&flags::TREE_STYLE, OsString::from("heavy")));
+    }
+
+
+    mod tree_summaries {
+        use super::*;
+
+        test!(empty:      TreeSummary <- [];                              Both => Ok(None));
+        test!(bare:       TreeSummary <- ["--tree-summary"];              Both => Ok(Some(TreeSummary::Immediate)));
+        test!(immediate:  TreeSummary <- ["--tree-summary=immediate"];    Both => Ok(Some(TreeSummary::Immediate)));
+        test!(recursive:  TreeSummary <- ["--tree-summary=recursive"];    Both => Ok(Some(TreeSummary::Recursive)));
+        test!(sideways:   TreeSummary <- ["--tree-summary=sideways"];     Both => Err(Misfire::BadArgument(&flags::TREE_SUMMARY, OsString::from("sideways"))));
     }
 
 
@@ -493,32 +1355,46 @@ mod test {
         test!(empty:     TimeTypes <- [];                      Both => Ok(TimeTypes::default()));
 
         // Modified
-        test!(modified:  TimeTypes <- ["--modified"];          Both => Ok(TimeTypes { accessed: false,  modified: true,   created: false }));
-        test!(m:         TimeTypes <- ["-m"];                  Both => Ok(TimeTypes { accessed: false,  modified: true,   created: false }));
-        test!(time_mod:  TimeTypes <- ["--time=modified"];     Both => Ok(TimeTypes { accessed: false,  modified: true,   created: false }));
-        test!(time_m:    TimeTypes <- ["-tmod"];               Both => Ok(TimeTypes { accessed: false,  modified: true,   created: false }));
+        test!(modified:  TimeTypes <- ["--modified"];          Both => Ok(TimeTypes::from_list(vec![ TimeType::Modified ])));
+        test!(m:         TimeTypes <- ["-m"];                  Both => Ok(TimeTypes::from_list(vec![ TimeType::Modified ])));
+        test!(time_mod:  TimeTypes <- ["--time=modified"];     Both => Ok(TimeTypes::from_list(vec![ TimeType::Modified ])));
+        test!(time_m:    TimeTypes <- ["-tmod"];               Both => Ok(TimeTypes::from_list(vec![ TimeType::Modified ])));
+
+        // Changed
+        test!(changed:   TimeTypes <- ["--changed"];           Both => Ok(TimeTypes::from_list(vec![ TimeType::Changed ])));
+        test!(time_ch:   TimeTypes <- ["--time=changed"];      Both => Ok(TimeTypes::from_list(vec![ TimeType::Changed ])));
+        test!(time_ch_m: TimeTypes <- ["--time=changed", "-m"];  Both => Err(Misfire::Useless(&flags::MODIFIED, true, &flags::TIME)));
 
         // Accessed
-        test!(acc:       TimeTypes <- ["--accessed"];          Both => Ok(TimeTypes { accessed: true,   modified: false,  created: false }));
-        test!(a:         TimeTypes <- ["-u"];                  Both => Ok(TimeTypes { accessed: true,   modified: false,  created: false }));
-        test!(time_acc:  TimeTypes <- ["--time", "accessed"];  Both => Ok(TimeTypes { accessed: true,   modified: false,  created: false }));
-        test!(time_a:    TimeTypes <- ["-t", "acc"];           Both => Ok(TimeTypes { accessed: true,   modified: false,  created: false }));
+        test!(acc:       TimeTypes <- ["--accessed"];          Both => Ok(TimeTypes::from_list(vec![ TimeType::Accessed ])));
+        test!(a:         TimeTypes <- ["-u"];                  Both => Ok(TimeTypes::from_list(vec![ TimeType::Accessed ])));
+        test!(time_acc:  TimeTypes <- ["--time", "accessed"];  Both => Ok(TimeTypes::from_list(vec![ TimeType::Accessed ])));
+        test!(time_a:    TimeTypes <- ["-t", "acc"];           Both => Ok(TimeTypes::from_list(vec![ TimeType::Accessed ])));
 
         // Created
-        test!(cr:        TimeTypes <- ["--created"];           Both => Ok(TimeTypes { accessed: false,  modified: false,  created: true  }));
-        test!(c:         TimeTypes <- ["-U"];                  Both => Ok(TimeTypes { accessed: false,  modified: false,  created: true  }));
-        test!(time_cr:   TimeTypes <- ["--time=created"];      Both => Ok(TimeTypes { accessed: false,  modified: false,  created: true  }));
-        test!(time_c:    TimeTypes <- ["-tcr"];                Both => Ok(TimeTypes { accessed: false,  modified: false,  created: true  }));
+        test!(cr:        TimeTypes <- ["--created"];           Both => Ok(TimeTypes::from_list(vec![ TimeType::Created ])));
+        test!(c:         TimeTypes <- ["-U"];                  Both => Ok(TimeTypes::from_list(vec![ TimeType::Created ])));
+        test!(time_cr:   TimeTypes <- ["--time=created"];      Both => Ok(TimeTypes::from_list(vec![ TimeType::Created ])));
+        test!(time_c:    TimeTypes <- ["-tcr"];                Both => Ok(TimeTypes::from_list(vec![ TimeType::Created ])));
 
         // Multiples
-        test!(time_uu:   TimeTypes <- ["-uU"];                 Both => Ok(TimeTypes { accessed: true,   modified: false,  created: true  }));
+        test!(time_uu:   TimeTypes <- ["-uU"];                 Both => Ok(TimeTypes::from_list(vec![ TimeType::Created, TimeType::Accessed ])));
 
         // Errors
         test!(time_tea:  TimeTypes <- ["--time=tea"];          Both => err Misfire::BadArgument(&flags::TIME, OsString::from("tea")));
         test!(time_ea:   TimeTypes <- ["-tea"];                Both => err Misfire::BadArgument(&flags::TIME, OsString::from("ea")));
 
+        // Lists of times
+        test!(time_list: TimeTypes <- ["--time=modified,created"];  Both => Ok(TimeTypes::from_list(vec![ TimeType::Modified, TimeType::Created ])));
+        test!(time_abbr: TimeTypes <- ["-tacc,ch"];                 Both => Ok(TimeTypes::from_list(vec![ TimeType::Accessed, TimeType::Changed ])));
+        test!(time_all:  TimeTypes <- ["--time=all"];               Both => Ok(TimeTypes::all()));
+        test!(time_back: TimeTypes <- ["--time=cr,mod"];            Both => Ok(TimeTypes::from_list(vec![ TimeType::Created, TimeType::Modified ])));
+        test!(time_twice: TimeTypes <- ["--time=ch,mod,ch"];        Both => Ok(TimeTypes::from_list(vec![ TimeType::Changed, TimeType::Modified ])));
+        test!(time_bad:  TimeTypes <- ["--time=mod,tea"];           Both => err Misfire::BadArgument(&flags::TIME, OsString::from("mod,tea")));
+        test!(time_gap:  TimeTypes <- ["--time=mod,"];              Both => err Misfire::BadArgument(&flags::TIME, OsString::from("mod,")));
+
         // Overriding
-        test!(overridden:   TimeTypes <- ["-tcr", "-tmod"];    Last => Ok(TimeTypes { accessed: false,  modified: true,   created: false }));
+        test!(overridden:   TimeTypes <- ["-tcr", "-tmod"];    Last => Ok(TimeTypes::from_list(vec![ TimeType::Modified ])));
         test!(overridden_2: TimeTypes <- ["-tcr", "-tmod"];    Complain => err Misfire::Duplicate(Flag::Short(b't'), Flag::Short(b't')));
     }
 
@@ -526,15 +1402,34 @@ mod test {
     mod views {
         use super::*;
         use output::grid::Options as GridOptions;
+        use output::template::TemplateError;
+        use options::vars::MockTerminal;
 
         // Default
         test!(empty:         Mode <- [], None;            Both => like Ok(Mode::Grid(_)));
 
         // Grid views
-        test!(original_g:    Mode <- ["-G"], None;        Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _ })));
-        test!(grid:          Mode <- ["--grid"], None;    Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _ })));
-        test!(across:        Mode <- ["--across"], None;  Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _ })));
-        test!(gracross:      Mode <- ["-xG"], None;       Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _ })));
+        test!(original_g:    Mode <- ["-G"], None;        Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _, columns: None, separator: _ })));
+        test!(grid:          Mode <- ["--grid"], None;    Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _, columns: None, separator: _ })));
+        test!(across:        Mode <- ["--across"], None;  Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _, columns: None, separator: _ })));
+        test!(gracross:      Mode <- ["-xG"], None;       Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _, columns: None, separator: _ })));
+
+        // Output that isn’t going to a terminal
+        test!(piped:         Mode <- [], MockTerminal(false);          Both => like Ok(Mode::Lines));
+        test!(piped_grid:    Mode <- ["--grid"], MockTerminal(false);  Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: 80, columns: None, separator: _ })));
+        test!(piped_across:  Mode <- ["-x"], MockTerminal(false);      Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: 80, columns: None, separator: _ })));
+        test!(piped_tree:    Mode <- ["--tree"], MockTerminal(false);  Both => like Ok(Mode::Details(_)));
+        test!(piped_oneline: Mode <- ["-1x"], MockTerminal(false);     Both => err Misfire::Useless(&flags::ACROSS, true, &flags::ONE_LINE));
+
+        // Fixed-column grid views
+        test!(columns:       Mode <- ["--columns=3"], None;        Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _, columns: Some(3), separator: _ })));
+        test!(columns_x:     Mode <- ["--columns", "4", "-x"], None; Both => like Ok(Mode::Grid(GridOptions { across: true, console_width: _, columns: Some(4), separator: _ })));
+        test!(columns_one:   Mode <- ["--columns=1"], None;        Both => like Ok(Mode::Lines));
+        test!(columns_zero:  Mode <- ["--columns=0"], None;        Both => err Misfire::BadArgument(&flags::COLUMNS, OsString::from("0")));
+        test!(columns_nan:   Mode <- ["--columns=many"], None;     Both => like Err(Misfire::FailedParse(_)));
+        test!(columns_1:     Mode <- ["--columns=2", "-1"], None;  Both => err Misfire::Conflict(&flags::COLUMNS, &flags::ONE_LINE));
+        test!(columns_tree:  Mode <- ["--columns=2", "-T"], None;  Both => err Misfire::Useless(&flags::COLUMNS, true, &flags::TREE));
+        test!(columns_long:  Mode <- ["--columns=2", "-lG"], None; Both => err Misfire::Useless(&flags::COLUMNS, true, &flags::LONG));
 
         // Lines views
         test!(lines:         Mode <- ["--oneline"], None; Both => like Ok(Mode::Lines));
@@ -548,6 +1443,30 @@ mod test {
         test!(lid:           Mode <- ["--long", "--grid"], None;  Both => like Ok(Mode::GridDetails(_)));
         test!(leg:           Mode <- ["-lG"], None;               Both => like Ok(Mode::GridDetails(_)));
 
+        // Column widths
+        test!(uniform:       Mode <- ["-l", "--uniform-columns"], None;  Both => like Ok(Mode::Details(details::Options { uniform_columns: true, .. })));
+        test!(not_uniform:   Mode <- ["-l"], None;                       Both => like Ok(Mode::Details(details::Options { uniform_columns: false, .. })));
+
+        // Dired
+        test!(dired:         Mode <- ["-l", "--dired"], None;            Both => like Ok(Mode::Details(details::Options { dired: true, .. })));
+        test!(not_dired:     Mode <- ["-l"], None;                       Both => like Ok(Mode::Details(details::Options { dired: false, .. })));
+        test!(dired_grid:    Mode <- ["-lG", "--dired"], None;           Both => err Misfire::Conflict(&flags::DIRED, &flags::GRID));
+
+        // Markdown
+        test!(markdown:      Mode <- ["--markdown"], None;               Both => like Ok(Mode::Markdown(_)));
+        test!(markdown_long: Mode <- ["--markdown", "-l"], None;         Both => like Ok(Mode::Markdown(_)));
+        test!(markdown_head: Mode <- ["--markdown", "--header"], None;   Complain => like Ok(Mode::Markdown(_)));
+        test!(markdown_grid: Mode <- ["--markdown", "-G"], None;         Both => err Misfire::Conflict(&flags::MARKDOWN, &flags::GRID));
+        test!(markdown_tree: Mode <- ["--markdown", "-T"], None;         Both => err Misfire::Conflict(&flags::MARKDOWN, &flags::TREE));
+        test!(markdown_cols: Mode <- ["--markdown", "--columns=2"], None; Both => err Misfire::Conflict(&flags::MARKDOWN, &flags::COLUMNS));
+
+        // Templates
+        test!(format:        Mode <- ["--format={name}"], None;              Both => like Ok(Mode::Format(_)));
+        test!(format_long:   Mode <- ["--format={name}", "-l"], None;        Both => like Ok(Mode::Format(_)));
+        test!(format_grid:   Mode <- ["--format={name}", "-G"], None;        Both => err Misfire::Conflict(&flags::FORMAT, &flags::GRID));
+        test!(format_md:     Mode <- ["--format={name}", "--markdown"], None; Both => err Misfire::Conflict(&flags::FORMAT, &flags::MARKDOWN));
+        test!(format_field:  Mode <- ["--format={mtime}"], None;             Both => err Misfire::FailedTemplate(TemplateError::UnknownField("mtime".into())));
+
 
         // Options that do nothing without --long
         test!(just_header:   Mode <- ["--header"], None;  Last => like Ok(Mode::Grid(_)));
@@ -568,8 +1487,24 @@ mod test {
         test!(just_blocks_2: Mode <- ["--blocks"], None;  Complain => err Misfire::Useless(&flags::BLOCKS, false, &flags::LONG));
         test!(just_binary_2: Mode <- ["--binary"], None;  Complain => err Misfire::Useless(&flags::BINARY, false, &flags::LONG));
         test!(just_bytes_2:  Mode <- ["--bytes"],  None;  Complain => err Misfire::Useless(&flags::BYTES,  false, &flags::LONG));
+        test!(just_uniform:  Mode <- ["--uniform-columns"], None;  Complain => err Misfire::Useless(&flags::UNIFORM_COLUMNS, false, &flags::LONG));
+        test!(just_dired:    Mode <- ["--dired"],  None;  Complain => err Misfire::Useless(&flags::DIRED,  false, &flags::LONG));
+        test!(just_numeric:  Mode <- ["-n"],       None;  Complain => err Misfire::Useless(&flags::NUMERIC, false, &flags::LONG));
 
         #[cfg(feature="git")]
         test!(just_git_2:    Mode <- ["--git"],    None;  Complain => err Misfire::Useless(&flags::GIT,    false, &flags::LONG));
     }
+
+
+    mod protected {
+        use super::*;
+        use fs::feature::access;
+
+        #[test]
+        fn dropped_when_running_as_root() {
+            for result in parse_for_test(["--protected"].as_ref(), TEST_ARGS, Both, |mf| View::deduce(mf, &None)) {
+                assert_eq!(result.unwrap().style.protected, !access::is_elevated());
+            }
+        }
+    }
 }
@@ -60,19 +60,6 @@ impl TextCell {
         }
     }
 
-    /// Creates a new “blank” text cell that contains a single hyphen in the
-    /// given style, which should be the “punctuation” style from a `Colours`
-    /// value.
-    ///
-    /// This is used in place of empty table cells, as it is easier to read
-    /// tabular data when there is *something* in each cell.
-    pub fn blank(style: Style) -> Self {
-        TextCell {
-            contents: vec![ style.paint("-") ].into(),
-            width:    DisplayWidth::from(1),
-        }
-    }
-
     /// Adds the given number of unstyled spaces after this cell.
     ///
     /// This method allocates a `String` to hold the spaces.
@@ -161,6 +148,12 @@ impl TextCellContents {
         ANSIStrings(&self.0)
     }
 
+    /// The text of this cell without any of its styles, as it gets printed
+    /// when colours are turned off.
+    pub fn plain_text(&self) -> String {
+        self.0.iter().map(|anstr| anstr.deref()).collect()
+    }
+
     /// Calculates the width that a cell with these contents would take up, by
     /// counting the number of characters in each unformatted ANSI string.
     pub fn width(&self) -> DisplayWidth {
@@ -273,4 +266,28 @@ mod width_unit_test {
         let cell = DisplayWidth::from("/usr/bin/");
         assert_eq!(*(cell + 8), 17);
     }
+
+    #[test]
+    fn wide_characters() {
+        let cell = DisplayWidth::from("日本語.txt");
+        assert_eq!(*cell, 10);
+    }
+
+    #[test]
+    fn combining_marks() {
+        let cell = DisplayWidth::from("cafe\u{301}");
+        assert_eq!(*cell, 4);
+    }
+
+    #[test]
+    fn emoji() {
+        let cell = DisplayWidth::from("🦀.rs");
+        assert_eq!(*cell, 5);
+    }
+
+    #[test]
+    fn emoji_zwj_sequence() {
+        let cell = DisplayWidth::from("👩\u{200D}💻");
+        assert_eq!(*cell, 2);
+    }
 }
@@ -60,23 +60,29 @@
 //! can be displayed, in order to make sure that every column is wide enough.
 
 
+use std::collections::HashMap;
 use std::io::{Write, Error as IOError, Result as IOResult};
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::vec::IntoIter as VecIntoIter;
 
 use ansi_term::Style;
 
-use fs::{Dir, File};
-use fs::dir_action::RecurseOptions;
+use fs::{Dir, DirReader, File};
+use fs::dir_action::{RecurseOptions, Visited};
 use fs::filter::FileFilter;
 use fs::feature::ignore::IgnoreCache;
 use fs::feature::git::GitCache;
 use fs::feature::xattr::{Attribute, FileAttributes};
 use style::Colours;
-use output::cell::TextCell;
-use output::tree::{TreeTrunk, TreeParams, TreeDepth};
+use output::cell::{TextCell, TextCellContents};
+use output::escape_lossy;
+use output::dired;
+use output::tree::{TreeTrunk, TreeParams, TreeDepth, TreeStyle};
 use output::file_name::FileStyle;
-use output::table::{Table, Options as TableOptions, Row as TableRow};
+use output::summary::Summary;
+use output::table::{Table, Layout, Options as TableOptions, Row as TableRow};
 use scoped_threadpool::Pool;
 
 
@@ -105,6 +111,42 @@ pub struct Options {
 
     /// Whether to show each file's extended attributes.
     pub xattr: bool,
+
+    /// Whether to mark empty directories with “(empty)” in the tree view.
+    pub mark_empty: bool,
+
+    /// Whether to list the other names of files with several hard links.
+    pub hard_links: bool,
+
+    /// Whether to print the total amount of space taken up by the files in
+    /// a directory above its table, like `ls -l` does.
+    pub total: bool,
+
+    /// Which characters to draw the branches of the tree view with.
+    pub tree_style: TreeStyle,
+
+    /// Whether to follow each directory in the tree view with a count of
+    /// the files in it, and which ones to count.
+    pub tree_summary: Option<TreeSummary>,
+
+    /// Whether every table in the listing should have the same column
+    /// widths, rather than each fitting its own files.
+    pub uniform_columns: bool,
+
+    /// Whether to write the output for Emacs’s dired mode, which needs to
+    /// know where each file name is in it.
+    pub dired: bool,
+}
+
+/// Which files the count after a directory in the tree view covers.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TreeSummary {
+
+    /// Only the files directly inside the directory.
+    Immediate,
+
+    /// Every file underneath the directory, however deep.
+    Recursive,
 }
 
 
@@ -123,12 +165,23 @@ pub struct Render<'a> {
 
     /// How to sort and filter the files after getting their details.
     pub filter: &'a FileFilter,
+
+    /// The directories that the tree view has gone into, so it doesn’t
+    /// follow links to them round in circles.
+    pub visited: Visited,
+
+    /// How many threads to read files and directories with at once.
+    pub jobs: u32,
+
+    /// What the tree view reads the directories it goes into with.
+    pub dirs: &'a dyn DirReader,
 }
 
 
 struct Egg<'a> {
     table_row: Option<TableRow>,
     xattrs:    Vec<Attribute>,
+    links:     Vec<PathBuf>,
     errors:    Vec<(IOError, Option<PathBuf>)>,
     dir:       Option<Dir>,
     file:      &'a File<'a>,
@@ -140,12 +193,38 @@ impl<'a> AsRef<File<'a>> for Egg<'a> {
     }
 }
 
+/// What the details view reads files and directories with.
+struct Reading<'ig> {
+
+    /// The threads to read with.
+    pool: Pool,
+
+    /// The ignore files that decide which files in a directory get listed.
+    ignore: Option<&'ig IgnoreCache>,
+
+    /// The directories that the tree view is going to go into, which get
+    /// read before any of them are listed, and taken out as they get listed.
+    ahead: Mutex<HashMap<PathBuf, IOResult<Dir>>>,
+}
+
 
 impl<'a> Render<'a> {
-    pub fn render<W: Write>(self, mut git: Option<&'a GitCache>, ignore: Option<&'a IgnoreCache>, w: &mut W) -> IOResult<()> {
-        use num_cpus;
-        let mut pool = Pool::new(num_cpus::get() as u32);
+    /// Renders the files, returning a summary of every file that got
+    /// listed, including the ones further down the tree.
+    pub fn render<W: Write>(self, git: Option<&'a GitCache>, ignore: Option<&'a IgnoreCache>, w: &mut W) -> IOResult<Summary> {
+        let (summary, pending) = self.render_later(git, ignore);
+        pending.write(w)?;
+        Ok(summary)
+    }
+
+    /// Fills in the table without printing it, so its columns can be made
+    /// as wide as the ones in other tables before it gets printed. Returns
+    /// a summary of every file that got listed, as `render` does.
+    pub fn render_later(self, mut git: Option<&'a GitCache>, ignore: Option<&'a IgnoreCache>) -> (Summary, Pending) {
+        let mut reading = Reading { pool: Pool::new(self.jobs), ignore, ahead: Mutex::default() };
         let mut rows = Vec::new();
+        let mut lines = Vec::new();
+        let summary;
 
         if let Some(ref table) = self.opts.table {
             match (git, self.dir) {
@@ -156,6 +235,10 @@ impl<'a> Render<'a> {
 
             let mut table = Table::new(&table, git, &self.colours);
 
+            if self.opts.total && self.dir.is_some() {
+                lines.push(PendingLine::Plain(table.total_line(&self.files)));
+            }
+
             if self.opts.header {
                 let header = table.header_row();
                 table.add_widths(&header);
@@ -165,31 +248,94 @@ impl<'a> Render<'a> {
             // This is weird, but I can’t find a way around it:
             // https://internals.rust-lang.org/t/should-option-mut-t-implement-copy/3715/6
             let mut table = Some(table);
-            self.add_files_to_table(&mut pool, &mut table, &mut rows, &self.files, ignore, TreeDepth::root());
+            self.read_ahead(&mut reading, table.as_ref().and_then(Table::git));
+            summary = self.add_files_to_table(&mut reading, &mut table, &mut rows, &self.files, TreeDepth::root());
+
+            let table = table.unwrap();
+            table.report_checksum_errors();
 
-            for row in self.iterate_with_table(table.unwrap(), rows) {
-                writeln!(w, "{}", row.strings())?
+            let mut iter = self.iterate_with_table(table, rows);
+            while let Some((cells, rest, name)) = iter.next_parts() {
+                lines.push(PendingLine::Cells(cells, rest, name));
             }
+
+            (summary, Pending { layout: Some(iter.table.into_layout()), lines })
         }
         else {
-            self.add_files_to_table(&mut pool, &mut None, &mut rows, &self.files, ignore, TreeDepth::root());
+            self.read_ahead(&mut reading, None);
+            summary = self.add_files_to_table(&mut reading, &mut None, &mut rows, &self.files, TreeDepth::root());
+            lines.extend(self.iterate(rows).map(PendingLine::Plain));
 
-            for row in self.iterate(rows) {
-                writeln!(w, "{}", row.strings())?
-            }
+            (summary, Pending { layout: None, lines })
         }
+    }
 
-        Ok(())
+    /// Reads every directory that the tree view is going to go into, before
+    /// any of them get listed. Each level of the tree gets read at once,
+    /// across all of its subtrees rather than one directory’s worth at a
+    /// time, so a deep tree doesn’t have to wait for its directories one
+    /// after another. The same filters that decide which directories get
+    /// listed decide which ones get read, so nothing pruned gets read.
+    /// Errors are kept along with the directories, to be shown where the
+    /// directory would have been listed.
+    fn read_ahead(&self, reading: &mut Reading, git: Option<&GitCache>) {
+        let Reading { ref mut pool, ignore, ahead: ref read } = *reading;
+        let mut depth = TreeDepth::root();
+        let mut level = self.files.iter()
+                            .filter(|f| self.goes_into(f, depth))
+                            .map(|f| f.path.clone())
+                            .collect::<Vec<_>>();
+
+        while !level.is_empty() {
+            let next = Mutex::new(Vec::new());
+
+            pool.scoped(|scoped| {
+                for path in &level {
+                    let (read, next) = (&read, &next);
+
+                    scoped.execute(move || {
+                        let dir = self.dirs.read_dir(path.clone());
+
+                        if let Ok(ref dir) = dir {
+                            let mut files = dir.files(self.filter.dot_filter, ignore).filter_map(Result::ok).collect();
+                            self.filter.filter_child_files(&mut files, true);
+                            self.filter.filter_git_changes(&mut files, git);
+
+                            let below = files.iter().filter(|f| self.goes_into(f, depth.deeper()));
+                            next.lock().unwrap().extend(below.map(|f| f.path.clone()));
+                        }
+
+                        let _ = read.lock().unwrap().insert(path.clone(), dir);
+                    });
+                }
+            });
+
+            level = next.into_inner().unwrap();
+            depth = depth.deeper();
+        }
+    }
+
+    /// Whether the tree view goes into the given file, at the given depth,
+    /// without having to follow a link to get there.
+    fn goes_into(&self, file: &File, depth: TreeDepth) -> bool {
+        match self.recurse {
+            Some(r) => r.tree && !r.is_too_deep(depth.0) && file.is_directory() && !file.is_dereferenced,
+            None    => false,
+        }
     }
 
     /// Adds files to the table, possibly recursively. This is easily
-    /// parallelisable, and uses a pool of threads.
-    fn add_files_to_table<'dir, 'ig>(&self, pool: &mut Pool, table: &mut Option<Table<'a>>, rows: &mut Vec<Row>, src: &[File<'dir>], ignore: Option<&'ig IgnoreCache>, depth: TreeDepth) {
+    /// parallelisable, and uses a pool of threads. The directories it goes
+    /// into have already been read ahead. Returns a summary of all the
+    /// files that were added.
+    fn add_files_to_table<'dir>(&self, reading: &mut Reading, table: &mut Option<Table<'a>>, rows: &mut Vec<Row>, src: &[File<'dir>], depth: TreeDepth) -> Summary {
         use std::sync::{Arc, Mutex};
         use fs::feature::xattr;
 
         let mut file_eggs = Vec::new();
+        let mut summary = Summary::default();
 
+        let Reading { ref mut pool, ahead: ref read_ahead, .. } = *reading;
         pool.scoped(|scoped| {
             let file_eggs = Arc::new(Mutex::new(&mut file_eggs));
             let table = table.as_ref();
@@ -239,7 +385,7 @@ impl<'a> Render<'a> {
                         }
                     }
 
-                    let table_row = table.as_ref().map(|t| t.row_for_file(&file, !xattrs.is_empty()));
+                    let table_row = table.as_ref().map(|t| t.row_for_file(file, &xattrs));
 
                     if !self.opts.xattr {
                         xattrs.clear();
@@ -247,22 +393,24 @@ impl<'a> Render<'a> {
 
                     let mut dir = None;
 
-                    if let Some(r) = self.recurse {
-                        if file.is_directory() && r.tree && !r.is_too_deep(depth.0) {
-                            match file.to_dir() {
-                                Ok(d)  => { dir = Some(d); },
-                                Err(e) => { errors.push((e, None)) },
-                            }
+                    if self.goes_into(file, depth) {
+                        let read = read_ahead.lock().unwrap().remove(&file.path);
+                        match read.unwrap_or_else(|| self.dirs.read_dir(file.path.clone())) {
+                            Ok(d)  => { dir = Some(d); },
+                            Err(e) => { errors.push((e, None)) },
                         }
-                    };
+                    }
+
+                    let links = if self.opts.hard_links { file.other_link_names() }
+                                                   else { Vec::new() };
 
-                    let egg = Egg { table_row, xattrs, errors, dir, file };
+                    let egg = Egg { table_row, xattrs, links, errors, dir, file };
                     file_eggs.lock().unwrap().push(egg);
                 });
             }
         });
 
-        self.filter.sort_files(&mut file_eggs);
+        self.filter.sort_files(&mut file_eggs, table.as_ref().and_then(Table::git));
 
         for (tree_params, egg) in depth.iterate_over(file_eggs.into_iter()) {
             let mut files = Vec::new();
@@ -272,50 +420,140 @@ impl<'a> Render<'a> {
                 t.add_widths(row);
             }
 
+            summary.add(egg.file);
+
+            // Symlinks to directories only get gone into with --follow-links,
+            // and then only when they lead somewhere that hasn’t been listed.
+            let mut dir = egg.dir;
+            let mut unfollowed = false;
+            if let Some(r) = self.recurse {
+                if r.tree && !r.is_too_deep(depth.0) && egg.file.is_link_to_directory() {
+                    if r.follow_links && self.visited.insert(&egg.file.path) {
+                        match self.dirs.read_dir(egg.file.path.clone()) {
+                            Ok(d)  => dir = Some(d),
+                            Err(e) => errors.push((e, None)),
+                        }
+                    }
+                    else {
+                        unfollowed = true;
+                    }
+                }
+                else if r.follow_links && dir.is_some() {
+                    let _ = self.visited.insert(&egg.file.path);
+                }
+            }
+
+            let file_name = self.style.for_file(&egg.file, self.colours)
+                                      .with_link_paths()
+                                      .with_broken_reasons();
+
+            let mut name = file_name.paint().promote();
+            let name_len = if self.opts.dired { Some(file_name.name_len()) }
+                                         else { None };
+
+            if self.opts.mark_empty && self.recurse.map(|r| r.tree) == Some(true) && egg.file.is_empty_dir() {
+                name.push(self.colours.punctuation.paint(" (empty)"), 8);
+            }
+
+            if unfollowed {
+                name.append(TextCell::paint_str(self.colours.punctuation, " (not followed)"));
+            }
+
             let row = Row {
                 tree:   tree_params,
                 cells:  egg.table_row,
-                name:   self.style.for_file(&egg.file, self.colours)
-                                  .with_link_paths()
-                                  .paint().promote(),
+                name,
+                name_len,
             };
 
+            let row_index = rows.len();
             rows.push(row);
 
-            if let Some(ref dir) = egg.dir {
-                for file_to_add in dir.files(self.filter.dot_filter, ignore) {
+            if self.opts.tree_summary.is_some() && dir.is_none() && self.is_truncated(egg.file, depth) {
+                rows[row_index].name.append(TextCell::paint_str(self.colours.tree_summary, " (…)"));
+            }
+
+            if let Some(ref dir) = dir {
+                for file_to_add in dir.files(self.filter.dot_filter, reading.ignore) {
                     match file_to_add {
                         Ok(f)          => files.push(f),
                         Err((path, e)) => errors.push((e, Some(path)))
                     }
                 }
 
-                self.filter.filter_child_files(&mut files);
+                self.filter.filter_child_files(&mut files, true);
+                self.filter.filter_git_changes(&mut files, table.as_ref().and_then(Table::git));
+
+                if let Some(which) = self.opts.tree_summary {
+                    if which == TreeSummary::Immediate || files.is_empty() {
+                        let mut immediate = Summary::default();
+                        for file in &files {
+                            immediate.add(file);
+                        }
+
+                        rows[row_index].name.append(self.render_tree_summary(&immediate));
+                    }
+                }
 
                 if !files.is_empty() {
                     for xattr in egg.xattrs {
                         rows.push(self.render_xattr(&xattr, TreeParams::new(depth.deeper(), false)));
                     }
 
+                    summary.errors += errors.len() as u64;
                     for (error, path) in errors {
                         rows.push(self.render_error(&error, TreeParams::new(depth.deeper(), false), path));
                     }
 
-                    self.add_files_to_table(pool, table, rows, &files, ignore, depth.deeper());
+                    let below = self.add_files_to_table(reading, table, rows, &files, depth.deeper());
+                    if self.opts.tree_summary == Some(TreeSummary::Recursive) {
+                        rows[row_index].name.append(self.render_tree_summary(&below));
+                    }
+
+                    summary.add_summary(&below);
                     continue;
                 }
             }
 
             let count = egg.xattrs.len();
             for (index, xattr) in egg.xattrs.into_iter().enumerate() {
-                rows.push(self.render_xattr(&xattr, TreeParams::new(depth.deeper(), errors.is_empty() && index == count - 1)));
+                let last = egg.links.is_empty() && errors.is_empty() && index == count - 1;
+                rows.push(self.render_xattr(&xattr, TreeParams::new(depth.deeper(), last)));
+            }
+
+            let count = egg.links.len();
+            for (index, link) in egg.links.iter().enumerate() {
+                rows.push(self.render_link_name(link, TreeParams::new(depth.deeper(), errors.is_empty() && index == count - 1)));
             }
 
             let count = errors.len();
+            summary.errors += count as u64;
             for (index, (error, path)) in errors.into_iter().enumerate() {
                 rows.push(self.render_error(&error, TreeParams::new(depth.deeper(), index == count - 1), path));
             }
         }
+
+        summary
+    }
+
+    /// Whether the given file is a directory that the tree view would have
+    /// gone into, if it weren’t already as deep as `--level` allows.
+    fn is_truncated(&self, file: &File, depth: TreeDepth) -> bool {
+        match self.recurse {
+            Some(r) => r.tree && r.is_too_deep(depth.0) && file.is_directory() && !file.is_dereferenced,
+            None    => false,
+        }
+    }
+
+    /// Renders the count that follows a directory in the tree view, such
+    /// as “(14 files, 3 dirs)”.
+    fn render_tree_summary(&self, counts: &Summary) -> TextCell {
+        let dirs = counts.dirs;
+        let files = counts.files + counts.links + counts.pipes + counts.sockets + counts.devices + counts.others;
+
+        let text = format!(" ({} {}, {} {})", files, if files == 1 { "file" } else { "files" },
+                                               dirs,  if dirs == 1  { "dir" }  else { "dirs" });
+        TextCell::paint(self.colours.tree_summary, text)
     }
 
     pub fn render_header(&self, header: TableRow) -> Row {
@@ -323,6 +561,7 @@ impl<'a> Render<'a> {
             tree:     TreeParams::new(TreeDepth::root(), false),
             cells:    Some(header),
             name:     TextCell::paint_str(self.colours.header, "Name"),
+            name_len: None,
         }
     }
 
@@ -337,25 +576,32 @@ impl<'a> Render<'a> {
 		// TODO: broken_symlink() doesn’t quite seem like the right name for
 		// the style that’s being used here. Maybe split it in two?
         let name = TextCell::paint(self.colours.broken_symlink(), error_message);
-        Row { cells: None, name, tree }
+        Row { cells: None, name, tree, name_len: None }
     }
 
     fn render_xattr(&self, xattr: &Attribute, tree: TreeParams) -> Row {
         let name = TextCell::paint(self.colours.perms.attribute, format!("{} (len {})", xattr.name, xattr.size));
-        Row { cells: None, name, tree }
+        Row { cells: None, name, tree, name_len: None }
+    }
+
+    fn render_link_name(&self, path: &Path, tree: TreeParams) -> Row {
+        let mut bits = Vec::new();
+        escape_lossy(path.to_string_lossy().into_owned(), &mut bits, self.colours.links.multi_link_file, self.colours.control_char);
+        Row { cells: None, name: TextCellContents::from(bits).promote(), tree, name_len: None }
     }
 
     pub fn render_file(&self, cells: TableRow, name: TextCell, tree: TreeParams) -> Row {
-        Row { cells: Some(cells), name, tree }
+        Row { cells: Some(cells), name, tree, name_len: None }
     }
 
     pub fn iterate_with_table(&'a self, table: Table<'a>, rows: Vec<Row>) -> TableIter<'a> {
         TableIter {
             tree_trunk: TreeTrunk::default(),
-            total_width: table.widths().total(),
+            above: None,
             table,
             inner: rows.into_iter(),
-            tree_style: self.colours.punctuation,
+            tree_colour: self.colours.punctuation,
+            tree_style: self.opts.tree_style,
         }
     }
 
@@ -363,7 +609,8 @@ impl<'a> Render<'a> {
         Iter {
             tree_trunk: TreeTrunk::default(),
             inner: rows.into_iter(),
-            tree_style: self.colours.punctuation,
+            tree_colour: self.colours.punctuation,
+            tree_style: self.opts.tree_style,
         }
     }
 }
@@ -385,6 +632,11 @@ pub struct Row {
 
     /// Information used to determine which symbols to display in a tree.
     pub tree: TreeParams,
+
+    /// How many bytes at the start of `name` are the file’s name itself,
+    /// without anything after it such as a link target. This only gets
+    /// worked out for files in dired mode, which needs to point at them.
+    pub name_len: Option<usize>,
 }
 
 
@@ -392,28 +644,43 @@ pub struct TableIter<'a> {
     inner: VecIntoIter<Row>,
     table: Table<'a>,
 
-    total_width: usize,
-    tree_style:  Style,
+    /// The cells of the last row with any, before they were deduplicated,
+    /// to compare the next row against, along with how deep in the tree
+    /// that row was.
+    above: Option<(usize, TableRow)>,
+
+    tree_colour: Style,
+    tree_style:  TreeStyle,
     tree_trunk:  TreeTrunk,
 }
 
-impl<'a> Iterator for TableIter<'a> {
-    type Item = TextCell;
+impl<'a> TableIter<'a> {
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Works out the next row without padding its cells, returning the
+    /// cells, if it has any, everything that goes after them, and where
+    /// the file’s name is in that, if the row knows.
+    fn next_parts(&mut self) -> Option<(Option<TableRow>, TextCell, Option<Range<usize>>)> {
         self.inner.next().map(|row| {
-            let mut cell =
-                if let Some(cells) = row.cells {
-                    self.table.render(cells)
+            let depth = row.tree.depth().0;
+            let cells = row.cells.map(|mut cells| {
+                if self.table.dedups() {
+                    // A row only gets compared to the one above it if they’re
+                    // in the same directory, so in the tree view, the marks
+                    // start afresh whenever the depth changes.
+                    let original = cells.clone();
+                    match self.above {
+                        Some((d, ref above)) if d == depth  => self.table.dedup(&mut cells, above),
+                        _                                   => {},
+                    }
+                    self.above = Some((depth, original));
                 }
-                else {
-                    let mut cell = TextCell::default();
-                    cell.add_spaces(self.total_width);
-                    cell
-                };
 
+                cells
+            });
+
+            let mut cell = TextCell::default();
             for tree_part in self.tree_trunk.new_row(row.tree) {
-                cell.push(self.tree_style.paint(tree_part.ascii_art()), 4);
+                cell.append(TextCell::paint_str(self.tree_colour, tree_part.ascii_art(self.tree_style)));
             }
 
             // If any tree characters have been printed, then add an extra
@@ -422,16 +689,121 @@ impl<'a> Iterator for TableIter<'a> {
                 cell.add_spaces(1);
             }
 
+            let name = row.name_len.map(|len| {
+                let start = cell.plain_text().len();
+                start .. start + len
+            });
+
             cell.append(row.name);
+            (cells, cell, name)
+        })
+    }
+}
+
+impl<'a> Iterator for TableIter<'a> {
+    type Item = TextCell;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_parts().map(|(cells, rest, _)| {
+            let mut cell = match cells {
+                Some(cells) => self.table.render(cells),
+                None        => self.table.render_blank(),
+            };
+
+            cell.append(rest);
             cell
         })
     }
 }
 
 
+/// The lines of a listing that have been worked out but not yet printed.
+pub struct Pending {
+
+    /// The columns of the table the lines line up with, if there is one.
+    pub layout: Option<Layout>,
+
+    lines: Vec<PendingLine>,
+}
+
+enum PendingLine {
+
+    /// A line that doesn’t line up with the table, such as the total.
+    Plain(TextCell),
+
+    /// The line above a directory’s listing, with the number of bytes
+    /// that the directory’s path takes up at the start of it.
+    Heading(TextCell, usize),
+
+    /// A line that lines up with the table, with the cells that haven’t
+    /// been padded yet, if it has any, everything after them, and where
+    /// the file’s name is in that, if it’s known.
+    Cells(Option<TableRow>, TextCell, Option<Range<usize>>),
+}
+
+impl Pending {
+
+    /// A single line that doesn’t line up with any table.
+    pub fn line(cell: TextCell) -> Pending {
+        Pending { layout: None, lines: vec![ PendingLine::Plain(cell) ] }
+    }
+
+    /// The line above a directory’s listing, which starts with its path.
+    pub fn heading(cell: TextCell, path_len: usize) -> Pending {
+        Pending { layout: None, lines: vec![ PendingLine::Heading(cell, path_len) ] }
+    }
+
+    pub fn write<W: Write>(self, w: &mut W) -> IOResult<()> {
+        for line in self.lines {
+            let (cell, _) = render_line(line, self.layout.as_ref());
+            writeln!(w, "{}", cell.strings())?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the lines for Emacs’s dired mode, without any colours, so
+    /// the writer can note where each file name and heading ends up.
+    pub fn write_dired<W: Write>(self, w: &mut dired::Writer<W>) -> IOResult<()> {
+        for line in self.lines {
+            if let PendingLine::Heading(ref cell, path_len) = line {
+                w.write_heading(&cell.plain_text(), path_len)?;
+                continue;
+            }
+
+            let (cell, name) = render_line(line, self.layout.as_ref());
+            w.write_line(&cell.plain_text(), name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders a pending line, padding its cells if it has any, and returns it
+/// along with where the file’s name is in it, if that’s known.
+fn render_line(line: PendingLine, layout: Option<&Layout>) -> (TextCell, Option<Range<usize>>) {
+    match (line, layout) {
+        (PendingLine::Plain(cell), _)                         => (cell, None),
+        (PendingLine::Heading(cell, _), _)                    => (cell, None),
+        (PendingLine::Cells(_, rest, name), None)             => (rest, name),
+        (PendingLine::Cells(cells, rest, name), Some(layout)) => {
+            let mut cell = match cells {
+                Some(cells) => layout.render(cells),
+                None        => layout.render_blank(),
+            };
+
+            let start = cell.plain_text().len();
+            cell.append(rest);
+            (cell, name.map(|n| start + n.start .. start + n.end))
+        },
+    }
+}
+
+
 pub struct Iter {
     tree_trunk: TreeTrunk,
-    tree_style: Style,
+    tree_colour: Style,
+    tree_style: TreeStyle,
     inner: VecIntoIter<Row>,
 }
 
@@ -443,7 +815,7 @@ impl Iterator for Iter {
             let mut cell = TextCell::default();
 
             for tree_part in self.tree_trunk.new_row(row.tree) {
-                cell.push(self.tree_style.paint(tree_part.ascii_art()), 4);
+                cell.append(TextCell::paint_str(self.tree_colour, tree_part.ascii_art(self.tree_style)));
             }
 
             // If any tree characters have been printed, then add an extra
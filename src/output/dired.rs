@@ -0,0 +1,181 @@
+//! Emacs’s dired mode lists a directory by running `ls --dired`, and then
+//! finds each file name in the output using the byte offsets that get
+//! printed after it. The offsets have to match exactly what got written, so
+//! they get counted by the writer that the output goes through.
+//!
+//! A listing looks like this, with every line indented by two spaces:
+//!
+//! ```text
+//!       /home/ben/src:
+//!       .rw-r--r-- 547 ben 23 Jun 10:54 Cargo.toml
+//!       drwxr-xr-x   - ben 29 Jun 14:50 src
+//!     //DIRED// 51 61 96 99
+//!     //SUBDIRED// 2 15
+//!     //DIRED-OPTIONS// --quoting-style=escape
+//! ```
+
+use std::io::{Write, Result as IOResult};
+use std::ops::Range;
+
+use output::file_name::{FileStyle, Escaping, Quotes};
+
+
+/// Where everything that dired needs to find is in the output so far.
+#[derive(Debug, Default)]
+pub struct Offsets {
+
+    /// How many bytes have been written.
+    position: usize,
+
+    /// Where each file’s name is.
+    names: Vec<Range<usize>>,
+
+    /// Where each directory’s path is, in the lines above their listings.
+    subdirs: Vec<Range<usize>>,
+}
+
+impl Offsets {
+
+    /// Writes the lines that go after the listing, which say where every
+    /// file name is, where every directory path is if there are any, and
+    /// how the names were quoted.
+    pub fn write_footer<W: Write>(&self, w: &mut W, quoting: &str) -> IOResult<()> {
+        write_ranges(w, "//DIRED//", &self.names)?;
+
+        if !self.subdirs.is_empty() {
+            write_ranges(w, "//SUBDIRED//", &self.subdirs)?;
+        }
+
+        writeln!(w, "//DIRED-OPTIONS// --quoting-style={}", quoting)
+    }
+}
+
+fn write_ranges<W: Write>(w: &mut W, label: &str, ranges: &[Range<usize>]) -> IOResult<()> {
+    write!(w, "{}", label)?;
+
+    for range in ranges {
+        write!(w, " {} {}", range.start, range.end)?;
+    }
+
+    writeln!(w)
+}
+
+
+/// The name that dired gives to the way file names get quoted, which it
+/// needs to turn them back into paths.
+pub fn quoting_style(style: &FileStyle) -> &'static str {
+    match (style.quotes, style.escaping) {
+        (Quotes::Shell, _)      => "shell",
+        (_, Escaping::Escape)   => "escape",
+        (_, Escaping::Literal)  => "literal",
+    }
+}
+
+
+/// A writer that counts every byte that goes through it, and writes the
+/// lines of a listing the way dired expects them.
+pub struct Writer<'a, W: Write + 'a> {
+    inner: &'a mut W,
+    offsets: &'a mut Offsets,
+}
+
+impl<'a, W: Write + 'a> Writer<'a, W> {
+    pub fn new(inner: &'a mut W, offsets: &'a mut Offsets) -> Writer<'a, W> {
+        Writer { inner, offsets }
+    }
+
+    /// Writes one line of a listing, indented by two spaces, noting where
+    /// the file name is if the line has one. Empty lines stay empty.
+    pub fn write_line(&mut self, line: &str, name: Option<Range<usize>>) -> IOResult<()> {
+        if !line.is_empty() {
+            self.write_all(b"  ")?;
+        }
+
+        if let Some(name) = name {
+            let start = self.offsets.position;
+            self.offsets.names.push(start + name.start .. start + name.end);
+        }
+
+        self.write_all(line.as_bytes())?;
+        self.write_all(b"\n")
+    }
+
+    /// Writes the line above a directory’s listing, which starts with the
+    /// directory’s path, taking up the given number of bytes.
+    pub fn write_heading(&mut self, line: &str, path_len: usize) -> IOResult<()> {
+        let start = self.offsets.position + 2;
+        self.offsets.subdirs.push(start .. start + path_len);
+        self.write_line(line, None)
+    }
+}
+
+impl<'a, W: Write + 'a> Write for Writer<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> IOResult<usize> {
+        let count = self.inner.write(buf)?;
+        self.offsets.position += count;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> IOResult<()> {
+        self.inner.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reads the numbers after the given label back out of the footer.
+    fn ranges(output: &str, label: &str) -> Vec<Range<usize>> {
+        let line = output.lines().find(|l| l.starts_with(label)).unwrap();
+        let numbers = line[label.len() ..].split_whitespace().map(|n| n.parse().unwrap()).collect::<Vec<usize>>();
+        numbers.chunks(2).map(|pair| pair[0] .. pair[1]).collect()
+    }
+
+    fn listing() -> String {
+        let mut offsets = Offsets::default();
+        let mut output = Vec::new();
+
+        {
+            let mut w = Writer::new(&mut output, &mut offsets);
+            w.write_heading("/tmp/ünïcödé: [main]", 16).unwrap();
+            w.write_line(".rw-r--r-- 1.2k ben Cargo.toml", Some(20 .. 30)).unwrap();
+            w.write_line(".rw-r--r--    0 ben ∂ir -> target", Some(20 .. 25)).unwrap();
+            w.write_line("<error reading xattrs>", None).unwrap();
+            w.write_line("", None).unwrap();
+        }
+
+        offsets.write_footer(&mut output, "literal").unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn names_slice_out() {
+        let output = listing();
+        let names = ranges(&output, "//DIRED//").into_iter().map(|r| &output[r]).collect::<Vec<_>>();
+        assert_eq!(names, vec![ "Cargo.toml", "∂ir" ]);
+    }
+
+    #[test]
+    fn subdirs_slice_out() {
+        let output = listing();
+        let subdirs = ranges(&output, "//SUBDIRED//").into_iter().map(|r| &output[r]).collect::<Vec<_>>();
+        assert_eq!(subdirs, vec![ "/tmp/ünïcödé" ]);
+    }
+
+    #[test]
+    fn lines_are_indented() {
+        let output = listing();
+        assert!(output.starts_with("  /tmp/ünïcödé: [main]\n  .rw-r--r-- 1.2k ben Cargo.toml\n"));
+        assert!(output.contains("\n\n//DIRED//"));
+        assert!(output.ends_with("//DIRED-OPTIONS// --quoting-style=literal\n"));
+    }
+
+    #[test]
+    fn no_subdirs() {
+        let mut output = Vec::new();
+        Offsets::default().write_footer(&mut output, "escape").unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "//DIRED//\n//DIRED-OPTIONS// --quoting-style=escape\n");
+    }
+}
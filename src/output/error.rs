@@ -0,0 +1,60 @@
+//! Reporting errors about particular files, such as ones that don’t exist
+//! or can’t be read, in one format that says which file each one is about.
+
+use std::fmt;
+use std::io::{stdout, stderr, Error as IOError, Write};
+use std::path::Path;
+
+
+/// An error that happened while looking at a path, which gets displayed
+/// with the path it’s about:
+///
+/// ```text
+/// exa: "target/locked.db": Permission denied (os error 13)
+/// ```
+///
+/// The path is quoted and escaped, so names with spaces or control
+/// characters in them can’t be misread. The error includes the numeric
+/// OS error code when there is one.
+pub struct PathError<'a> {
+    pub path: &'a Path,
+    pub error: &'a IOError,
+}
+
+impl<'a> fmt::Display for PathError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "exa: {:?}: {}", self.path, self.error)
+    }
+}
+
+
+/// Prints an error about the given path to standard error. Anything
+/// waiting to be written to standard output goes first, so the error
+/// can’t end up in the middle of a line of the listing.
+pub fn report(path: &Path, error: &IOError) {
+    let _ = stdout().flush();
+    let _ = writeln!(stderr(), "{}", PathError { path, error });
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::PathError;
+    use std::io::{Error as IOError, ErrorKind};
+    use std::path::Path;
+
+    #[test]
+    fn os_error() {
+        let error = IOError::from_raw_os_error(2);
+        let message = PathError { path: Path::new("dir/file"), error: &error }.to_string();
+        assert!(message.starts_with("exa: \"dir/file\": "));
+        assert!(message.ends_with("(os error 2)"));
+    }
+
+    #[test]
+    fn escaped_path() {
+        let error = IOError::new(ErrorKind::Other, "oh no");
+        let message = PathError { path: Path::new("new\nline"), error: &error }.to_string();
+        assert_eq!(message, "exa: \"new\\nline\": oh no");
+    }
+}
@@ -1,8 +1,10 @@
+use std::char::REPLACEMENT_CHARACTER;
+
 use ansi_term::{ANSIString, Style};
 
 
 pub fn escape<'a>(string: String, bits: &mut Vec<ANSIString<'a>>, good: Style, bad: Style) {
-    if string.chars().all(|c| c >= 0x20 as char) {
+    if !string.chars().any(is_control) {
         bits.push(good.paint(string));
     }
     else {
@@ -10,7 +12,7 @@ pub fn escape<'a>(string: String, bits: &mut Vec<ANSIString<'a>>, good: Style, b
             // The `escape_default` method on `char` is *almost* what we want here, but
             // it still escapes non-ASCII UTF-8 characters, which are still printable.
 
-            if c >= 0x20 as char {
+            if !is_control(c) {
                 // TODO: This allocates way too much,
                 // hence the `all` check above.
                 let mut s = String::new();
@@ -23,3 +25,65 @@ pub fn escape<'a>(string: String, bits: &mut Vec<ANSIString<'a>>, good: Style, b
         }
     }
 }
+
+
+/// Whether this character could change the terminal’s state if it got
+/// printed: the C0 control characters, DEL, and the C1 control characters,
+/// some of which terminals treat the same as an escape sequence.
+fn is_control(c: char) -> bool {
+    c < '\u{20}' || ('\u{7F}' ..= '\u{9F}').contains(&c)
+}
+
+
+/// Escapes a string that was converted lossily from a name that wasn’t valid
+/// Unicode, painting the replacement characters in the `bad` style so it’s
+/// obvious that what’s on screen isn’t exactly the file’s name.
+pub fn escape_lossy<'a>(string: String, bits: &mut Vec<ANSIString<'a>>, good: Style, bad: Style) {
+    let mut pieces = string.split(REPLACEMENT_CHARACTER);
+
+    if let Some(first) = pieces.next() {
+        escape(first.to_string(), bits, good, bad);
+    }
+
+    for piece in pieces {
+        bits.push(bad.paint(REPLACEMENT_CHARACTER.to_string()));
+        escape(piece.to_string(), bits, good, bad);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn escaped(input: &str) -> String {
+        let mut bits = Vec::new();
+        escape(input.to_string(), &mut bits, Style::default(), Style::default());
+        bits.iter().map(|b| b.to_string()).collect()
+    }
+
+    #[test]
+    fn printable() {
+        assert_eq!(escaped("pâté 日本"), "pâté 日本");
+    }
+
+    #[test]
+    fn new_line() {
+        assert_eq!(escaped("new\nline"), "new\\nline");
+    }
+
+    #[test]
+    fn terminal_title() {
+        assert_eq!(escaped("\x1b]0;owned\x07"), "\\u{1b}]0;owned\\u{7}");
+    }
+
+    #[test]
+    fn delete() {
+        assert_eq!(escaped("del\x7f"), "del\\u{7f}");
+    }
+
+    #[test]
+    fn c1_controls() {
+        assert_eq!(escaped("csi\u{9b}2J"), "csi\\u{9b}2J");
+    }
+}
@@ -1,10 +1,16 @@
-use std::path::Path;
+use std::ffi::OsStr;
+use std::io::{Error as IOError, ErrorKind};
+use std::path::{Path, PathBuf};
 
 use ansi_term::{ANSIString, Style};
 
 use fs::{File, FileTarget};
-use output::escape;
+use info::filetype::{Category, has_system_attribute};
+use info::sources::SourcePairs;
+use output::{escape, escape_lossy};
 use output::cell::TextCellContents;
+use output::highlight::{self, Highlight};
+use output::quote::{quote, Part};
 use output::render::FiletypeColours;
 
 
@@ -15,8 +21,42 @@ pub struct FileStyle {
     /// Whether to append file class characters to file names.
     pub classify: Classify,
 
+    /// Whether to escape control characters in file names.
+    pub escaping: Escaping,
+
+    /// Whether to quote file names that contain special characters.
+    pub quotes: Quotes,
+
     /// Mapping of file extensions to colours, to highlight regular files.
     pub exts: Box<FileColours>,
+
+    /// Which extensions of compiled files go with which source files, to
+    /// highlight files that were compiled from others next to them.
+    pub sources: SourcePairs,
+
+    /// How much of a symlink’s target to show, in views that show them.
+    pub link_targets: LinkTargets,
+
+    /// The strings to put between symlinks and their targets.
+    pub arrows: Arrows,
+
+    /// An absolute directory to display every file’s path relative to,
+    /// rather than just its name.
+    pub relative_to: Option<PathBuf>,
+
+    /// Whether to mark files that only root can change. This is never set
+    /// when exa is running as root, as it can change anything.
+    pub protected: bool,
+
+    /// Whether to mark files that another process has open.
+    pub in_use: bool,
+
+    /// Whether to mark files downloaded from the internet, which carry the
+    /// Mark of the Web.
+    pub motw: bool,
+
+    /// The part of file names to paint in the highlight style, if any.
+    pub highlight: Option<Highlight>,
 }
 
 impl FileStyle {
@@ -28,7 +68,18 @@ impl FileStyle {
             file, colours,
             link_style: LinkStyle::JustFilenames,
             classify:   self.classify,
+            escaping:   self.escaping,
+            quotes:     self.quotes,
             exts:       &*self.exts,
+            sources:    &self.sources,
+            link_targets: self.link_targets,
+            arrows:     &self.arrows,
+            relative_to: self.relative_to.as_deref(),
+            protected:  self.protected && file.only_root_can_write(),
+            in_use:     self.in_use,
+            motw:       self.motw,
+            highlight:  self.highlight.as_ref(),
+            broken_reasons: false,
             target:     if file.is_link() { Some(file.link_target()) }
                                      else { None }
         }
@@ -49,6 +100,46 @@ enum LinkStyle {
     /// arrow pointing to their path, colouring the path differently if it’s
     /// a broken link, and doing nothing if it can’t be followed.
     FullLinkPaths,
+
+    /// Like `FullLinkPaths`, but only display the name of each link’s
+    /// target, without the directories leading up to it.
+    TargetNames,
+}
+
+
+/// How much of a symlink’s target to display after its name, in the views
+/// that have room for it.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum LinkTargets {
+
+    /// Don’t display targets, and colour broken links differently instead,
+    /// as in the grid view.
+    Never,
+
+    /// Display just the name of each target.
+    Name,
+
+    /// Display the whole path to each target.
+    Full,
+}
+
+
+/// The strings to put between a symlink and its target, which can be
+/// changed to something fancier with environment variables.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Arrows {
+
+    /// The arrow pointing to a target that exists.
+    pub normal: String,
+
+    /// The arrow pointing to the target of a broken link.
+    pub broken: String,
+}
+
+impl Default for Arrows {
+    fn default() -> Arrows {
+        Arrows { normal: "->".into(), broken: "->".into() }
+    }
 }
 
 
@@ -71,6 +162,36 @@ impl Default for Classify {
 }
 
 
+/// Whether to escape the control characters in file names, or to print
+/// them to the terminal as they are.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Escaping {
+
+    /// Replace control characters with escaped forms, highlighted in a
+    /// different colour, so they can’t mess with the terminal.
+    Escape,
+
+    /// Print file names exactly as they are.
+    Literal,
+}
+
+
+/// Whether to surround file names with quotes when they contain characters
+/// that a shell would treat specially, and which shell’s rules to follow.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Quotes {
+
+    /// Display file names without any quotes.
+    Never,
+
+    /// Quote file names for a POSIX shell, using single quotes.
+    Shell,
+
+    /// Quote file names for PowerShell, using double quotes and backticks.
+    PowerShell,
+}
+
+
 
 /// A **file name** holds all the information necessary to display the name
 /// of the given file. This is used in all of the views.
@@ -91,17 +212,64 @@ pub struct FileName<'a,  'dir: 'a,  C: Colours+'a> {
     /// Whether to append file class characters to file names.
     classify: Classify,
 
+    /// Whether to escape control characters in file names.
+    escaping: Escaping,
+
+    /// Whether to quote file names that contain special characters.
+    quotes: Quotes,
+
     /// Mapping of file extensions to colours, to highlight regular files.
     exts: &'a FileColours,
+
+    /// Which extensions of compiled files go with which source files.
+    sources: &'a SourcePairs,
+
+    /// How much of a symlink’s target to show, if asked to show them.
+    link_targets: LinkTargets,
+
+    /// The strings to put between symlinks and their targets.
+    arrows: &'a Arrows,
+
+    /// The directory to display the file’s path relative to, if any.
+    relative_to: Option<&'a Path>,
+
+    /// Whether this is a file that only root can change, and those are
+    /// being marked. This asks the kernel, so it gets worked out once, when
+    /// the file name is made, rather than each time it’s needed.
+    protected: bool,
+
+    /// Whether to mark files that another process has open.
+    in_use: bool,
+
+    /// Whether to mark files that carry the Mark of the Web.
+    motw: bool,
+
+    /// The part of the name to paint in the highlight style, if any.
+    highlight: Option<&'a Highlight>,
+
+    /// Whether to say why a broken link’s target can’t be reached.
+    broken_reasons: bool,
 }
 
 
 impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
 
     /// Sets the flag on this file name to display link targets with an
-    /// arrow followed by their path.
+    /// arrow followed by their path, or as much of it as the user wants.
     pub fn with_link_paths(mut self) -> Self {
-        self.link_style = LinkStyle::FullLinkPaths;
+        self.link_style = match self.link_targets {
+            LinkTargets::Never  => LinkStyle::JustFilenames,
+            LinkTargets::Name   => LinkStyle::TargetNames,
+            LinkTargets::Full   => LinkStyle::FullLinkPaths,
+        };
+        self
+    }
+
+    /// Sets the flag on this file name to follow a broken link’s target
+    /// with the reason it couldn’t be reached, such as “(not found)”.
+    /// This only makes a difference when link targets are displayed.
+    pub fn with_broken_reasons(mut self) -> Self {
+        self.broken_reasons = true;
         self
     }
 
@@ -113,33 +281,18 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
     /// width calculated.
     pub fn paint(&self) -> TextCellContents {
         let mut bits = Vec::new();
+        self.add_name_bits(&mut bits);
 
-        if self.file.parent_dir.is_none() {
-            if let Some(parent) = self.file.path.parent() {
-                self.add_parent_bits(&mut bits, parent);
-            }
-        }
+        let show_paths = self.link_style == LinkStyle::FullLinkPaths;
 
-        if !self.file.name.is_empty() {
-        	// The “missing file” colour seems like it should be used here,
-        	// but it’s not! In a grid view, where there's no space to display
-        	// link targets, the filename has to have a different style to
-        	// indicate this fact. But when showing targets, we can just
-        	// colour the path instead (see below), and leave the broken
-        	// link’s filename as the link colour.
-            for bit in self.coloured_file_name() {
-                bits.push(bit);
-            }
-        }
-
-        if let (LinkStyle::FullLinkPaths, Some(target)) = (self.link_style, self.target.as_ref()) {
+        if let (true, Some(target)) = (self.link_style != LinkStyle::JustFilenames, self.target.as_ref()) {
             match *target {
                 FileTarget::Ok(ref target) => {
                     bits.push(Style::default().paint(" "));
-                    bits.push(self.colours.normal_arrow().paint("->"));
+                    bits.push(self.colours.normal_arrow().paint(self.arrows.normal.clone()));
                     bits.push(Style::default().paint(" "));
 
-                    if let Some(parent) = target.path.parent() {
+                    if let (true, Some(parent)) = (show_paths, target.path.parent()) {
                         self.add_parent_bits(&mut bits, parent);
                     }
 
@@ -148,9 +301,20 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                             file: target,
                             colours: self.colours,
                             target: None,
-                            link_style: LinkStyle::FullLinkPaths,
+                            link_style: self.link_style,
                             classify: Classify::JustFilenames,
+                            escaping: self.escaping,
+                            quotes: self.quotes,
                             exts: self.exts,
+                            sources: self.sources,
+                            link_targets: self.link_targets,
+                            arrows: self.arrows,
+                            relative_to: None,
+                            protected: false,
+                            in_use: false,
+                            motw: false,
+                            highlight: None,
+                            broken_reasons: false,
                         };
 
                         for bit in target.coloured_file_name() {
@@ -159,15 +323,33 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                     }
                 },
 
-                FileTarget::Broken(ref broken_path) => {
+                FileTarget::Broken(ref broken_path, ref error) => {
                     bits.push(Style::default().paint(" "));
-                    bits.push(self.colours.broken_symlink().paint("->"));
+                    bits.push(self.colours.broken_arrow().paint(self.arrows.broken.clone()));
                     bits.push(Style::default().paint(" "));
-                    escape(broken_path.display().to_string(), &mut bits, self.colours.broken_filename(), self.colours.broken_control_char());
+
+                    let shown = match broken_path.file_name() {
+                        Some(name) if !show_paths  => name.to_string_lossy().to_string(),
+                        _                          => broken_path.display().to_string(),
+                    };
+
+                    self.escape(shown, &mut bits, self.colours.broken_filename(), self.colours.broken_control_char());
+
+                    if self.broken_reasons {
+                        bits.push(self.colours.broken_reason().paint(format!(" ({})", error_label(error))));
+                    }
                 },
 
-                FileTarget::Err(_) => {
-                    // Do nothing -- the error gets displayed on the next line
+                FileTarget::Err(ref link_path, ref error) => {
+                    // The link itself couldn’t be read, so there’s no
+                    // target to show, just which link it was and why not.
+                    if self.broken_reasons {
+                        bits.push(Style::default().paint(" "));
+                        bits.push(self.colours.broken_arrow().paint(self.arrows.broken.clone()));
+                        bits.push(self.colours.broken_reason().paint(" (unreadable link "));
+                        self.escape(link_path.display().to_string(), &mut bits, self.colours.broken_reason(), self.colours.broken_control_char());
+                        bits.push(self.colours.broken_reason().paint(format!(": {})", error_label(error))));
+                    }
                 },
             }
         }
@@ -175,12 +357,72 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             if let Some(class) = self.classify_char() {
                 bits.push(Style::default().paint(class));
             }
+
+            if self.is_protected() {
+                bits.push(Style::default().paint("!"));
+            }
+
+            if self.is_in_use() {
+                bits.push(Style::default().paint("%"));
+            }
         }
 
         bits.into()
     }
 
 
+    /// Adds the bits of the file’s name to the given bits vector, along with
+    /// the path leading up to it, if the name is being shown with one.
+    fn add_name_bits(&self, bits: &mut Vec<ANSIString>) {
+        if let Some(base) = self.relative_to {
+            // Files inside directories being listed get their whole path
+            // shown too, so a recursive listing or a tree still gives
+            // paths from the base rather than from each directory.
+            let path = self.file.path_relative_to(base);
+
+            if let Some(parent) = path.parent() {
+                self.add_parent_bits(bits, parent);
+            }
+
+            if let Some(last) = path.components().next_back() {
+                for bit in self.coloured_name(last.as_os_str()) {
+                    bits.push(bit);
+                }
+            }
+        }
+        else {
+            // Files given on the command line get the path before their
+            // names shown as it was typed, so two files with the same name
+            // in different places can be told apart.
+            let dir = self.file.typed_dir();
+            if !dir.is_empty() {
+                self.escape(dir, bits, self.colours.typed_dir(), self.colours.control_char());
+            }
+
+            if !self.file.name.is_empty() {
+                // The “missing file” colour seems like it should be used here,
+                // but it’s not! In a grid view, where there's no space to display
+                // link targets, the filename has to have a different style to
+                // indicate this fact. But when showing targets, we can just
+                // colour the path instead (see below), and leave the broken
+                // link’s filename as the link colour.
+                for bit in self.coloured_file_name() {
+                    bits.push(bit);
+                }
+            }
+        }
+    }
+
+    /// The number of bytes that the file’s name, and the path leading up to
+    /// it if there is one, take up at the start of what `paint` returns,
+    /// leaving out any classify characters or link targets after it.
+    pub fn name_len(&self) -> usize {
+        let mut bits = Vec::new();
+        self.add_name_bits(&mut bits);
+        bits.iter().map(|bit| bit.len()).sum()
+    }
+
+
     /// Adds the bits of the parent path to the given bits vector.
     /// The path gets its characters escaped based on the colours.
     fn add_parent_bits(&self, bits: &mut Vec<ANSIString>, parent: &Path) {
@@ -190,7 +432,7 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             bits.push(self.colours.symlink_path().paint("/"));
         }
         else if coconut >= 1 {
-            escape(parent.to_string_lossy().to_string(), bits, self.colours.symlink_path(), self.colours.control_char());
+            self.escape(parent.to_string_lossy().to_string(), bits, self.colours.symlink_path(), self.colours.control_char());
             bits.push(self.colours.symlink_path().paint("/"));
         }
     }
@@ -224,15 +466,62 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
     /// to the screen directly, because then there’ll be newlines in weird places.
     ///
     /// So in that situation, those characters will be escaped and highlighted in
-    /// a different colour.
+    /// a different colour. The same goes for the replacement characters in a
+    /// name that isn’t valid Unicode.
+    ///
+    /// If the name needs quoting, the quotes get painted in their own style,
+    /// so they can be told apart from the name itself.
     fn coloured_file_name<'unused>(&self) -> Vec<ANSIString<'unused>> {
+        self.coloured_name(&self.file.name)
+    }
+
+    /// Paints the given name in the file’s style, which is usually its own
+    /// name, but can be the last component of a path that leads to it.
+    ///
+    /// Any parts of the name that match the highlight get painted in the
+    /// highlight style instead, with the rest keeping the file’s style.
+    fn coloured_name<'unused>(&self, name: &OsStr) -> Vec<ANSIString<'unused>> {
         let file_style = self.style();
+        let is_lossy = name.to_str().is_none();
+        let shown = name.to_string_lossy();
+        let matches = self.highlight.map(|h| h.find(&shown)).unwrap_or_default();
         let mut bits = Vec::new();
-        escape(self.file.name.clone(), &mut bits, file_style, self.colours.control_char());
+
+        for part in quote(&shown, self.quotes) {
+            let (n, offset) = match part {
+                Part::Quote(q) => {
+                    bits.push(self.colours.quotes().paint(q));
+                    continue;
+                },
+                Part::Name(n, offset) => (n, offset),
+            };
+
+            for (piece, matched) in highlight::split(n, offset, &matches) {
+                let good = if matched { self.colours.highlight() } else { file_style };
+
+                if is_lossy {
+                    escape_lossy(piece.to_string(), &mut bits, good, self.colours.control_char());
+                }
+                else {
+                    self.escape(piece.to_string(), &mut bits, good, self.colours.control_char());
+                }
+            }
+        }
+
         bits
     }
 
 
+    /// Adds the given string to the bits vector, escaping its control
+    /// characters unless the user asked for them to be left alone.
+    fn escape<'unused>(&self, string: String, bits: &mut Vec<ANSIString<'unused>>, good: Style, bad: Style) {
+        match self.escaping {
+            Escaping::Escape   => escape(string, bits, good, bad),
+            Escaping::Literal  => bits.push(good.paint(string)),
+        }
+    }
+
+
     /// Figures out which colour to paint the filename part of the output,
     /// depending on which “type” of file it appears to be -- either from the
     /// class on the filesystem or from its name. (Or the broken link colour,
@@ -246,14 +535,75 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             }
         }
 
-        self.kind_style()
+        let style = self.protected_style()
+            .or_else(|| self.in_use_style())
+            .or_else(|| self.motw_style())
+            .or_else(|| self.kind_style())
             .or_else(|| self.exts.colour_file(self.file))
-            .unwrap_or_else(|| self.colours.normal())
+            .or_else(|| self.compiled_style())
+            .or_else(|| self.category_style())
+            .unwrap_or_else(|| self.colours.normal());
+
+        if has_system_attribute(self.file) { self.colours.system_attribute(style) } else { style }
+    }
+
+    /// Whether this file should be marked as one that only root can change.
+    fn is_protected(&self) -> bool {
+        self.protected
+    }
+
+    /// The style for a file that only root can change, if the user asked
+    /// for them to be marked. This comes before the file’s type, as it’s
+    /// the thing the user wants to know.
+    fn protected_style(&self) -> Option<Style> {
+        if self.is_protected() { Some(self.colours.protected_file()) } else { None }
+    }
+
+    /// Whether this file should be marked as one that another process has
+    /// open.
+    fn is_in_use(&self) -> bool {
+        self.in_use && self.file.is_in_use()
+    }
+
+    /// The style for a file that another process has open, if the user
+    /// asked for them to be marked.
+    fn in_use_style(&self) -> Option<Style> {
+        if self.is_in_use() { Some(self.colours.in_use_file()) } else { None }
+    }
+
+    /// The style for a file downloaded from the internet, if the user
+    /// asked for them to be marked.
+    fn motw_style(&self) -> Option<Style> {
+        if self.motw && self.file.has_motw() { Some(self.colours.downloaded_file()) } else { None }
+    }
+
+    /// The style for a file that was compiled from a source file next to
+    /// it, if the colour theme has a style for them.
+    fn compiled_style(&self) -> Option<Style> {
+        let style = self.colours.compiled_file();
+
+        if style != Style::default() && self.file.is_compiled(self.sources) { Some(style) } else { None }
+    }
+
+    /// The style for a file in one of the categories that the colour theme
+    /// has its own styles for. A category without a style gets painted like
+    /// any other file.
+    fn category_style(&self) -> Option<Style> {
+        let style = match Category::of(self.file)? {
+            Category::Temporary  => self.colours.temporary_file(),
+            Category::Installer  => self.colours.installer_file(),
+            Category::Script     => self.colours.script_file(),
+            Category::Registry   => self.colours.registry_file(),
+            Category::Shortcut   => self.colours.shortcut_file(),
+            Category::System     => self.colours.system_file(),
+        };
+
+        if style == Style::default() { None } else { Some(style) }
     }
 
     fn kind_style(&self) -> Option<Style> {
         Some(match self.file {
-            f if f.is_directory()        => self.colours.directory(),
+            f if f.is_directory()        => self.directory_style(),
             f if f.is_executable_file()  => self.colours.executable_file(),
             f if f.is_link()             => self.colours.symlink(),
             f if f.is_pipe()             => self.colours.pipe(),
@@ -264,6 +614,42 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             _                            => return None,
         })
     }
+
+    /// The style to paint a directory, which is only different if it’s
+    /// empty and the colours say empty directories should stand out.
+    fn directory_style(&self) -> Style {
+        match self.colours.empty_directory() {
+            Some(style) if self.file.is_empty_dir() => style,
+            _                                       => self.colours.directory(),
+        }
+    }
+}
+
+
+/// A terse description of why a link’s target couldn’t be reached, to be
+/// displayed after it.
+fn error_label(error: &IOError) -> &'static str {
+    use libc;
+
+    match error.kind() {
+        ErrorKind::NotFound          => return "not found",
+        ErrorKind::PermissionDenied  => return "access denied",
+        _                            => {},
+    }
+
+    match error.raw_os_error() {
+        Some(libc::ELOOP)         => "loop",
+        Some(libc::ENOTDIR)       => "not a directory",
+        Some(libc::ENAMETOOLONG)  => "name too long",
+        Some(libc::EIO)       |
+        Some(libc::ENXIO)     |
+        Some(libc::ENODEV)    |
+        Some(libc::ESTALE)    |
+        Some(libc::ETIMEDOUT) |
+        Some(libc::EHOSTDOWN) => "unavailable",
+        Some(libc::EINVAL)        => "not a link",
+        _                         => "error",
+    }
 }
 
 
@@ -274,9 +660,19 @@ pub trait Colours: FiletypeColours {
     /// including the file’s name.
     fn symlink_path(&self) -> Style;
 
+    /// The style to paint the path before the name of a file given on the
+    /// command line.
+    fn typed_dir(&self) -> Style;
+
     /// The style to paint the arrow between a link and its target.
     fn normal_arrow(&self) -> Style;
 
+    /// The style to paint the arrow between a broken link and its target.
+    fn broken_arrow(&self) -> Style;
+
+    /// The style to paint the reason a link is broken, after its target.
+    fn broken_reason(&self) -> Style;
+
 	/// The style to paint the filenames of broken links in views that don’t
 	/// show link targets, and the style to paint the *arrow* between the link
 	/// and its target in views that *do* show link targets.
@@ -294,6 +690,48 @@ pub trait Colours: FiletypeColours {
 
     /// The style to paint a file that has its executable bit set.
     fn executable_file(&self) -> Style;
+
+    /// The style to paint the quotes around a file name that needs them.
+    fn quotes(&self) -> Style;
+
+    /// The style to paint the parts of file names that match `--highlight`.
+    fn highlight(&self) -> Style;
+
+    /// The style to paint a file that was compiled from a source file in the
+    /// same directory, such as a `.o` file next to a `.c` file.
+    fn compiled_file(&self) -> Style;
+
+    /// The style to paint a temporary file, such as a backup or lock file.
+    fn temporary_file(&self) -> Style;
+
+    /// The style to paint a file that only root can change.
+    fn protected_file(&self) -> Style;
+    fn in_use_file(&self) -> Style;
+    fn downloaded_file(&self) -> Style;
+
+    /// The style to paint an installer package, such as an `.msi` file.
+    fn installer_file(&self) -> Style;
+
+    /// The style to paint a Windows script, such as a `.ps1` or `.bat` file.
+    fn script_file(&self) -> Style;
+
+    /// The style to paint an exported registry file.
+    fn registry_file(&self) -> Style;
+
+    /// The style to paint a Windows shortcut, such as a `.lnk` file.
+    fn shortcut_file(&self) -> Style;
+
+    /// The style to paint a Windows system file, such as a `.dll` file.
+    fn system_file(&self) -> Style;
+
+    /// The style to paint a file that has the Windows system attribute,
+    /// given the style it would get otherwise.
+    fn system_attribute(&self, style: Style) -> Style;
+
+    /// The style to paint an empty directory, or `None` if they shouldn’t
+    /// be told apart from other directories, in which case there’s no need
+    /// to read the directory to find out.
+    fn empty_directory(&self) -> Option<Style>;
 }
 
 
@@ -321,3 +759,107 @@ where A: FileColours, B: FileColours {
         self.0.colour_file(file).or_else(|| self.1.colour_file(file))
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fs::test_dir::TempDir;
+    use style::Colours as PlainColours;
+    use libc;
+
+    fn file_style() -> FileStyle {
+        FileStyle {
+            classify: Classify::JustFilenames,
+            escaping: Escaping::Escape,
+            quotes: Quotes::Never,
+            exts: Box::new(NoFileColours),
+            sources: SourcePairs::default(),
+            link_targets: LinkTargets::Full,
+            arrows: Arrows::default(),
+            relative_to: None,
+            protected: false,
+            in_use: false,
+            motw: false,
+            highlight: None,
+        }
+    }
+
+    /// The plain colours, with the one for protected files made to stand
+    /// out, so it can be told apart from the colour the file would get
+    /// otherwise.
+    fn protected_colours() -> PlainColours {
+        let mut colours = PlainColours::plain();
+        colours.filekinds.protected = Style::default().underline();
+        colours
+    }
+
+    #[test]
+    fn protected_file_is_marked() {
+        let temp = TempDir::new("protected");
+        let file = File::new(temp.touch("locked"), None, None).unwrap();
+        let style = FileStyle { classify: Classify::AddFileIndicators, ..file_style() };
+        let colours = protected_colours();
+
+        let mut name = style.for_file(&file, &colours);
+        name.protected = true;
+
+        assert_eq!(name.style(), Style::default().underline());
+        assert_eq!(name.paint().strings().to_string().chars().last(), Some('!'));
+    }
+
+    #[test]
+    fn writable_file_is_not_protected() {
+        let temp = TempDir::new("not-protected");
+        let file = File::new(temp.touch("open"), None, None).unwrap();
+        let style = FileStyle { classify: Classify::AddFileIndicators, protected: true, ..file_style() };
+        let colours = protected_colours();
+
+        let name = style.for_file(&file, &colours);
+        assert!(!name.is_protected());
+        assert_eq!(name.style(), Style::default());
+        assert!(!name.paint().strings().to_string().ends_with('!'));
+    }
+
+    #[test]
+    fn unreadable_link_names_the_link() {
+        let temp = TempDir::new("unreadable-link");
+        let path = temp.touch("link");
+        let file = File::new(path.clone(), None, None).unwrap();
+        let style = file_style();
+        let colours = PlainColours::plain();
+
+        let mut name = style.for_file(&file, &colours).with_link_paths().with_broken_reasons();
+        name.target = Some(FileTarget::Err(PathBuf::from("dir/li\nk"), IOError::from(ErrorKind::PermissionDenied)));
+
+        let painted = name.paint().strings().to_string();
+        assert!(painted.ends_with("link -> (unreadable link dir/li\\nk: access denied)"), "{}", painted);
+    }
+
+    #[test]
+    fn not_found() {
+        assert_eq!(error_label(&IOError::from(ErrorKind::NotFound)), "not found");
+        assert_eq!(error_label(&IOError::from_raw_os_error(libc::ENOENT)), "not found");
+    }
+
+    #[test]
+    fn access_denied() {
+        assert_eq!(error_label(&IOError::from_raw_os_error(libc::EACCES)), "access denied");
+    }
+
+    #[test]
+    fn loop_() {
+        assert_eq!(error_label(&IOError::from_raw_os_error(libc::ELOOP)), "loop");
+    }
+
+    #[test]
+    fn unplugged() {
+        assert_eq!(error_label(&IOError::from_raw_os_error(libc::ENXIO)), "unavailable");
+        assert_eq!(error_label(&IOError::from_raw_os_error(libc::ESTALE)), "unavailable");
+    }
+
+    #[test]
+    fn other() {
+        assert_eq!(error_label(&IOError::new(ErrorKind::Other, "boom")), "error");
+    }
+}
@@ -0,0 +1,115 @@
+//! The **format** output view prints one line for each file by filling in a
+//! template that the user gave with `--format`, such as:
+//!
+//! ```text
+//!     $ exa --format '{name}: {size}, {modified}'
+//!     Cargo.lock: 9.6k, 29 Jun 16:16
+//!     src: -, 29 Jun 14:50
+//! ```
+//!
+//! The fields come out the same as they would in the details view, so
+//! options such as `--time-style` and `--binary` still apply to them, but
+//! nothing gets padded or coloured.
+
+use std::fs::read_link;
+use std::io::{Write, Result as IOResult};
+
+use fs::{Dir, File};
+use fs::fields as f;
+use fs::feature::git::GitCache;
+use fs::feature::xattr::{self, FileAttributes};
+use output::table::{Table, Column, TimeType, Options as TableOptions};
+use output::template::{Template, Field};
+use style::Colours;
+
+
+#[derive(Debug)]
+pub struct Options {
+
+    /// What to print for each file.
+    pub template: Template,
+
+    /// How to format the fields that the details view has columns for.
+    pub table: TableOptions,
+}
+
+
+pub struct Render<'a> {
+    pub dir: Option<&'a Dir>,
+    pub files: Vec<File<'a>>,
+    pub colours: &'a Colours,
+    pub opts: &'a Options,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(&self, mut git: Option<&'a GitCache>, w: &mut W) -> IOResult<()> {
+        match (git, self.dir) {
+            (Some(g), Some(d))  => if !g.has_anything_for(&d.path) { git = None },
+            (Some(g), None)     => if !self.files.iter().any(|f| g.has_anything_for(&f.path)) { git = None },
+            (None,    _)        => {/* Keep Git how it is */},
+        }
+
+        let table = Table::new(&self.opts.table, git, self.colours);
+
+        for file in &self.files {
+            let line = self.opts.template.expand(|field| self.value(&table, file, field));
+            writeln!(w, "{}", line)?;
+        }
+
+        Ok(())
+    }
+
+    /// The text to print for one field of one file.
+    fn value(&self, table: &Table, file: &File, field: Field) -> String {
+        let column = match field {
+            Field::Name         => return file.display_name().into_owned(),
+            Field::Path         => return file.path.display().to_string(),
+            Field::SizeBytes    => return size_bytes(file),
+            Field::Type         => return type_name(file).into(),
+            Field::Target       => return link_target(file),
+            Field::Size         => Column::FileSize,
+            Field::Modified     => Column::Timestamp(TimeType::Modified),
+            Field::Created      => Column::Timestamp(TimeType::Created),
+            Field::Owner        => Column::User,
+            Field::Permissions  => Column::Permissions,
+            Field::Git          => Column::GitStatus,
+        };
+
+        let xattrs = if field == Field::Permissions && xattr::ENABLED { file.path.attributes().unwrap_or_default() }
+                                                                  else { Vec::new() };
+
+        table.display(file, &column, &xattrs).plain_text()
+    }
+}
+
+
+/// The size of a file as a plain number of bytes, or a `-` for files that
+/// the details view doesn’t give a size to, such as directories.
+fn size_bytes(file: &File) -> String {
+    match file.size() {
+        f::Size::Some(bytes)  => bytes.to_string(),
+        _                     => "-".into(),
+    }
+}
+
+/// The kind of file this is, as a word.
+fn type_name(file: &File) -> &'static str {
+    if file.is_link()               { "link" }
+    else if file.is_directory()     { "directory" }
+    else if file.is_file()          { "file" }
+    else if file.is_pipe()          { "pipe" }
+    else if file.is_socket()        { "socket" }
+    else if file.is_block_device()  { "block-device" }
+    else if file.is_char_device()   { "char-device" }
+    else                            { "special" }
+}
+
+/// Where a symlink points, exactly as the link says, or nothing for files
+/// that aren’t links.
+fn link_target(file: &File) -> String {
+    if !file.is_link() {
+        return String::new();
+    }
+
+    read_link(&file.path).map(|t| t.display().to_string()).unwrap_or_default()
+}
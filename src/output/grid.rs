@@ -4,13 +4,22 @@ use term_grid as tg;
 
 use fs::File;
 use style::Colours;
+use output::cell::TextCellContents;
 use output::file_name::FileStyle;
 
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Options {
     pub across: bool,
     pub console_width: usize,
+
+    /// A number of columns the user asked for, which gets used instead of
+    /// fitting the names into the width of the console.
+    pub columns: Option<usize>,
+
+    /// The string to put in between each column of file names, which is
+    /// two spaces unless the user asked for something else.
+    pub separator: String,
 }
 
 impl Options {
@@ -32,22 +41,20 @@ impl<'a> Render<'a> {
     pub fn render<W: Write>(&self, w: &mut W) -> IOResult<()> {
         let mut grid = tg::Grid::new(tg::GridOptions {
             direction:  self.opts.direction(),
-            filling:    tg::Filling::Spaces(2),
+            filling:    tg::Filling::Text(self.opts.separator.clone()),
         });
 
         grid.reserve(self.files.len());
 
         for file in &self.files {
             let filename = self.style.for_file(file, self.colours).paint();
-            let width = filename.width();
-
-            grid.add(tg::Cell {
-                contents:  filename.strings().to_string(),
-                width:     *width,
-            });
+            grid.add(grid_cell(filename));
         }
 
-        if let Some(display) = grid.fit_into_width(self.opts.console_width) {
+        if let Some(columns) = self.opts.columns {
+            write!(w, "{}", grid.fit_into_columns(columns))
+        }
+        else if let Some(display) = grid.fit_into_width(self.opts.console_width) {
             write!(w, "{}", display)
         }
         else {
@@ -62,3 +69,102 @@ impl<'a> Render<'a> {
         }
     }
 }
+
+
+/// Turns some painted contents into a grid cell, giving it the display width
+/// of its text rather than its length, so names with wide characters still
+/// line up.
+fn grid_cell(contents: TextCellContents) -> tg::Cell {
+    let width = contents.width();
+
+    tg::Cell {
+        contents:  contents.strings().to_string(),
+        width:     *width,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::grid_cell;
+    use term_grid as tg;
+    use ansi_term::Style;
+
+    fn grid(names: &[&'static str], direction: tg::Direction) -> tg::Grid {
+        let mut grid = tg::Grid::new(tg::GridOptions {
+            direction,
+            filling: tg::Filling::Spaces(2),
+        });
+
+        for name in names {
+            grid.add(grid_cell(vec![ Style::default().paint(*name) ].into()));
+        }
+
+        grid
+    }
+
+    fn layout(names: &[&'static str], direction: tg::Direction, width: usize) -> String {
+        grid(names, direction).fit_into_width(width).unwrap().to_string()
+    }
+
+    fn fixed_layout(names: &[&'static str], direction: tg::Direction, columns: usize) -> String {
+        grid(names, direction).fit_into_columns(columns).to_string()
+    }
+
+    #[test]
+    fn mixed_widths_down() {
+        let names = [ "a.txt", "日本語.txt", "🦀.rs", "cafe\u{301}", "b" ];
+        let display = layout(&names, tg::Direction::TopToBottom, 20);
+
+        assert_eq!(display, "a.txt       cafe\u{301}\n\
+                             日本語.txt  b\n\
+                             🦀.rs       \n");
+    }
+
+    #[test]
+    fn mixed_widths_across() {
+        let names = [ "日本", "x", "👩\u{200D}💻", "abcdef" ];
+        let display = layout(&names, tg::Direction::LeftToRight, 13);
+
+        assert_eq!(display, "日本  x\n\
+                             👩\u{200D}💻    abcdef\n");
+    }
+
+    #[test]
+    fn one_long_name_down() {
+        // Each column is only as wide as its own longest name, so one long
+        // name doesn’t stop the short ones from being spread out.
+        let names = [ "a-rather-long-file-name.txt", "b", "c", "d", "e", "f", "g", "h", "i" ];
+        let display = layout(&names, tg::Direction::TopToBottom, 40);
+
+        assert_eq!(display, "a-rather-long-file-name.txt  c  e  g  i\n\
+                             b                            d  f  h  \n");
+        assert!(display.lines().all(|line| line.len() <= 40));
+    }
+
+    #[test]
+    fn fixed_columns_down() {
+        let names = [ "one", "two", "three", "four", "five" ];
+        let display = fixed_layout(&names, tg::Direction::TopToBottom, 3);
+
+        assert_eq!(display, "one  three  five\n\
+                             two  four   \n");
+    }
+
+    #[test]
+    fn fixed_columns_across() {
+        let names = [ "one", "two", "three", "four", "five" ];
+        let display = fixed_layout(&names, tg::Direction::LeftToRight, 3);
+
+        assert_eq!(display, "one   two   three\n\
+                             four  five  \n");
+    }
+
+    #[test]
+    fn fixed_columns_ignore_width() {
+        let names = [ "a-rather-long-name", "another-long-name" ];
+        let display = fixed_layout(&names, tg::Direction::LeftToRight, 2);
+
+        assert_eq!(display, "a-rather-long-name  another-long-name\n");
+    }
+}
@@ -5,9 +5,10 @@ use std::io::{Write, Result as IOResult};
 use ansi_term::ANSIStrings;
 use term_grid as grid;
 
-use fs::{Dir, File};
+use fs::{Dir, File, FsReader};
+use fs::dir_action::Visited;
 use fs::feature::git::GitCache;
-use fs::feature::xattr::FileAttributes;
+use fs::feature::xattr::{Attribute, FileAttributes};
 use fs::filter::FileFilter;
 
 use style::Colours;
@@ -75,6 +76,9 @@ pub struct Render<'a> {
     /// The minimum number of rows that there need to be before grid-details
     /// mode is activated.
     pub row_threshold: RowThreshold,
+
+    /// How many threads to read files with at once.
+    pub jobs: u32,
 }
 
 impl<'a> Render<'a> {
@@ -94,6 +98,9 @@ impl<'a> Render<'a> {
             opts: self.details,
             recurse: None,
             filter: self.filter,
+            visited: Visited::default(),
+            jobs: self.jobs,
+            dirs: &FsReader,
         }
     }
 
@@ -108,6 +115,9 @@ impl<'a> Render<'a> {
             opts: self.details,
             recurse: None,
             filter: &self.filter,
+            visited: Visited::default(),
+            jobs: self.jobs,
+            dirs: &FsReader,
         }
     }
 
@@ -116,10 +126,16 @@ impl<'a> Render<'a> {
 
     pub fn render<W: Write>(self, git: Option<&GitCache>, w: &mut W) -> IOResult<()> {
         if let Some((grid, width)) = self.find_fitting_grid(git) {
+            if self.details.total && self.dir.is_some() {
+                let options = self.details.table.as_ref().expect("Details table options not given!");
+                let table = Table::new(options, git, self.colours);
+                writeln!(w, "{}", table.total_line(&self.files).strings())?;
+            }
+
             write!(w, "{}", grid.fit_into_columns(width))
         }
         else {
-            self.give_up().render(git, None, w)
+            self.give_up().render(git, None, w).map(|_| ())
         }
     }
 
@@ -131,9 +147,11 @@ impl<'a> Render<'a> {
         let (first_table, _) = self.make_table(options, git, &drender);
 
         let rows = self.files.iter()
-                       .map(|file| first_table.row_for_file(file, file_has_xattrs(file)))
+                       .map(|file| first_table.row_for_file(file, &file_xattrs(file)))
                        .collect::<Vec<TableRow>>();
 
+        first_table.report_checksum_errors();
+
         let file_names = self.files.iter()
                              .map(|file| self.style.for_file(file, self.colours).paint().promote())
                              .collect::<Vec<TextCell>>();
@@ -269,9 +287,6 @@ fn divide_rounding_up(a: usize, b: usize) -> usize {
 }
 
 
-fn file_has_xattrs(file: &File) -> bool {
-    match file.path.attributes() {
-        Ok(attrs) => !attrs.is_empty(),
-        Err(_) => false,
-    }
+fn file_xattrs(file: &File) -> Vec<Attribute> {
+    file.path.attributes().unwrap_or_default()
 }
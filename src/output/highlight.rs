@@ -0,0 +1,194 @@
+//! Finding the parts of file names that match `--highlight`, so they can be
+//! painted in their own style, as grep does with its matches.
+
+use std::ops::Range;
+
+use fs::pattern::{NamePattern, PatternCase};
+
+
+/// What to look for in each file name. This is a glob pattern if it has any
+/// of the glob characters in it, and a plain substring otherwise.
+#[derive(Debug, Clone)]
+pub enum Highlight {
+
+    /// A string to find anywhere in a name, kept already folded with its
+    /// case, as each character of a name gets folded to be compared with it.
+    Substring(Vec<char>, PatternCase),
+
+    /// A glob pattern that has to match a run of characters in a name.
+    Glob(NamePattern),
+}
+
+impl Highlight {
+
+    /// Works out whether the given text is a glob or a substring. Text with
+    /// glob characters that don’t make up a valid pattern, such as a lone
+    /// `[`, gets looked for as it is.
+    pub fn new(text: &str, case: PatternCase) -> Highlight {
+        if text.contains(['*', '?', '[']) {
+            if let Ok(pattern) = NamePattern::new(text, case) {
+                return Highlight::Glob(pattern);
+            }
+        }
+
+        Highlight::Substring(case.fold(text).chars().collect(), case)
+    }
+
+    /// Finds the byte ranges of every match in the given name, from left to
+    /// right, without any overlapping. Each glob match is the longest one
+    /// starting at its position, and matches are never empty.
+    pub fn find(&self, name: &str) -> Vec<Range<usize>> {
+        let bounds = name.char_indices().map(|(i, _)| i).chain(Some(name.len())).collect::<Vec<_>>();
+        let mut found = Vec::new();
+        let mut i = 0;
+
+        while i + 1 < bounds.len() {
+            match self.longest_at(name, &bounds, i) {
+                Some(j) => { found.push(bounds[i] .. bounds[j]); i = j; },
+                None    => i += 1,
+            }
+        }
+
+        found
+    }
+
+    /// The index into `bounds` of the end of the longest match starting at
+    /// the character with index `i`, if there is one.
+    fn longest_at(&self, name: &str, bounds: &[usize], i: usize) -> Option<usize> {
+        match *self {
+            Highlight::Substring(ref folded, case) => {
+                // Folding a character can turn it into more than one, so
+                // the name gets folded one character at a time, to know
+                // where in it the match ends.
+                let mut matched = 0;
+                for j in i + 1 .. bounds.len() {
+                    for c in case.fold(&name[bounds[j - 1] .. bounds[j]]).chars() {
+                        if folded.get(matched) != Some(&c) {
+                            return None;
+                        }
+
+                        matched += 1;
+                    }
+
+                    if matched == folded.len() {
+                        return Some(j);
+                    }
+                }
+
+                None
+            },
+            Highlight::Glob(ref pattern) => {
+                (i + 1 .. bounds.len()).rev()
+                    .find(|&j| pattern.matches(&name[bounds[i] .. bounds[j]]))
+            },
+        }
+    }
+}
+
+
+/// Splits a run of a name, which starts `offset` bytes into the name, into
+/// pieces that are each either inside one of the matches or outside all of
+/// them. The matches have to be in order and not overlap, as `find` gives
+/// them.
+pub fn split<'a>(run: &'a str, offset: usize, matches: &[Range<usize>]) -> Vec<(&'a str, bool)> {
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+
+    for m in matches {
+        let start = m.start.saturating_sub(offset).min(run.len());
+        let end   = m.end.saturating_sub(offset).min(run.len());
+        if start >= end {
+            continue;
+        }
+
+        if start > pos {
+            pieces.push((&run[pos .. start], false));
+        }
+
+        pieces.push((&run[start .. end], true));
+        pos = end;
+    }
+
+    if pos < run.len() {
+        pieces.push((&run[pos ..], false));
+    }
+
+    pieces
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn found(highlight: &str, name: &str) -> Vec<String> {
+        found_with(highlight, PatternCase::Insensitive, name)
+    }
+
+    fn found_with(highlight: &str, case: PatternCase, name: &str) -> Vec<String> {
+        Highlight::new(highlight, case).find(name).into_iter().map(|r| name[r].to_string()).collect()
+    }
+
+    #[test]
+    fn substring() {
+        assert_eq!(found("port", "report.txt"), vec![ "port" ]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(found("readme", "README.md"), vec![ "README" ]);
+        assert_eq!(found("MD", "README.md"), vec![ "md" ]);
+    }
+
+    #[test]
+    fn case_sensitive() {
+        assert_eq!(found_with("readme", PatternCase::Sensitive, "README readme"), vec![ "readme" ]);
+        assert_eq!(found_with("R*E", PatternCase::Sensitive, "README.md"), vec![ "README" ]);
+        assert!(found_with("r*e", PatternCase::Sensitive, "README.md").is_empty());
+    }
+
+    #[test]
+    fn several_matches() {
+        assert_eq!(found("ab", "abcabAB"), vec![ "ab", "ab", "AB" ]);
+    }
+
+    #[test]
+    fn no_overlaps() {
+        assert_eq!(found("aa", "aaaaa"), vec![ "aa", "aa" ]);
+    }
+
+    #[test]
+    fn glob() {
+        assert_eq!(found("report*2024", "old-report-march-2024.pdf"), vec![ "report-march-2024" ]);
+        assert_eq!(found("r?p", "rep rip"), vec![ "rep", "rip" ]);
+    }
+
+    #[test]
+    fn glob_is_longest() {
+        assert_eq!(found("a*b", "a1b2b3"), vec![ "a1b2b" ]);
+    }
+
+    #[test]
+    fn unclosed_bracket() {
+        assert_eq!(found("[1", "file[1].txt"), vec![ "[1" ]);
+    }
+
+    #[test]
+    fn nothing() {
+        assert!(found("xyz", "report.txt").is_empty());
+        assert!(found("", "report.txt").is_empty());
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(found("été", "ÉTÉ-été"), vec![ "ÉTÉ", "été" ]);
+    }
+
+    #[test]
+    fn split_runs() {
+        let matches = vec![ 2 .. 4, 7 .. 9 ];
+        assert_eq!(split("abcdefghij", 0, &matches), vec![ ("ab", false), ("cd", true), ("efg", false), ("hi", true), ("j", false) ]);
+        assert_eq!(split("defgh", 3, &matches), vec![ ("d", true), ("efg", false), ("h", true) ]);
+        assert_eq!(split("xyz", 20, &matches), vec![ ("xyz", false) ]);
+    }
+}
@@ -17,13 +17,19 @@ pub struct Render<'a> {
 impl<'a> Render<'a> {
     pub fn render<W: Write>(&self, w: &mut W) -> IOResult<()> {
         for file in &self.files {
-            let name_cell = self.render_file(file).paint();
-            writeln!(w, "{}", ANSIStrings(&name_cell))?;
+            self.render_line(file, w)?;
         }
 
         Ok(())
     }
 
+    /// Writes the line for one file, which can be any file, not just one
+    /// of the files being rendered, so files can be printed as they’re read.
+    pub fn render_line<W: Write>(&self, file: &File<'a>, w: &mut W) -> IOResult<()> {
+        let name_cell = self.render_file(file).paint();
+        writeln!(w, "{}", ANSIStrings(&name_cell))
+    }
+
     fn render_file<'f>(&self, file: &'f File<'a>) -> FileName<'f, 'a, Colours> {
         self.style.for_file(file, self.colours).with_link_paths()
     }
@@ -0,0 +1,157 @@
+//! The **Markdown** output view prints the details of each file as a
+//! GitHub-flavoured Markdown table, to be pasted into pull requests and
+//! wikis.
+//!
+//! It has the same columns as the details view, in the same order, with a
+//! header row made from their names. It looks like this:
+//!
+//! ```text
+//!     | Permissions | Size | User | Date Modified | Name |
+//!     | --- | ---: | --- | --- | --- |
+//!     | .rw-r--r-- | 9.6k | ben | 29 Jun 16:16 | Cargo.lock |
+//!     | drwxr-xr-x | - | ben | 29 Jun 14:50 | src |
+//! ```
+//!
+//! None of the cells get coloured, and the characters in file names that
+//! Markdown would treat specially get escaped, so no name can break the
+//! table or turn itself into formatting.
+
+use std::io::{Write, Result as IOResult};
+
+use fs::{Dir, File};
+use fs::feature::git::GitCache;
+use fs::feature::xattr::{self, FileAttributes};
+use output::cell::TextCell;
+use output::file_name::FileStyle;
+use output::table::{Table, Alignment, Options as TableOptions};
+use style::Colours;
+
+
+#[derive(Debug)]
+pub struct Options {
+
+    /// Which columns to put in the table, as the details view would have.
+    pub table: TableOptions,
+}
+
+
+pub struct Render<'a> {
+    pub dir: Option<&'a Dir>,
+    pub files: Vec<File<'a>>,
+    pub colours: &'a Colours,
+    pub style: &'a FileStyle,
+    pub opts: &'a Options,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(&self, mut git: Option<&'a GitCache>, w: &mut W) -> IOResult<()> {
+        match (git, self.dir) {
+            (Some(g), Some(d))  => if !g.has_anything_for(&d.path) { git = None },
+            (Some(g), None)     => if !self.files.iter().any(|f| g.has_anything_for(&f.path)) { git = None },
+            (None,    _)        => {/* Keep Git how it is */},
+        }
+
+        let table = Table::new(&self.opts.table, git, self.colours);
+
+        let mut header = table.header_row().into_cells();
+        header.push(TextCell::paint_str(self.colours.header, "Name"));
+        write_row(w, &header)?;
+
+        let mut rule = table.alignments();
+        rule.push(Alignment::Left);
+        write_rule(w, &rule)?;
+
+        for file in &self.files {
+            let xattrs = if xattr::ENABLED { file.path.attributes().unwrap_or_default() }
+                                      else { Vec::new() };
+            let mut cells = table.row_for_file(file, &xattrs).into_cells();
+            cells.push(self.style.for_file(file, self.colours).with_link_paths().paint().promote());
+            write_row(w, &cells)?;
+        }
+
+        table.report_checksum_errors();
+        Ok(())
+    }
+}
+
+
+/// Writes one row of the table, leaving out the colours of its cells.
+fn write_row<W: Write>(w: &mut W, cells: &[TextCell]) -> IOResult<()> {
+    let texts = cells.iter().map(|cell| escape(&cell.plain_text())).collect::<Vec<_>>();
+    writeln!(w, "| {} |", texts.join(" | "))
+}
+
+/// Writes the line under the header, which says which way each column is
+/// aligned, with numbers aligned to the right.
+fn write_rule<W: Write>(w: &mut W, alignments: &[Alignment]) -> IOResult<()> {
+    let rules = alignments.iter().map(|a| match *a {
+        Alignment::Left   => "---",
+        Alignment::Right  => "---:",
+    }).collect::<Vec<_>>();
+
+    writeln!(w, "| {} |", rules.join(" | "))
+}
+
+/// Escapes the characters that would end a cell or start some formatting
+/// with a backslash. A backslash itself gets escaped too, so one at the end
+/// of a name can’t escape the pipe that ends the cell.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if let '\\' | '|' | '`' | '*' | '_' | '~' | '[' | ']' | '<' | '>' = c {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ansi_term::Colour::*;
+
+    #[test]
+    fn plain_names() {
+        assert_eq!(escape("Cargo.toml"), "Cargo.toml");
+        assert_eq!(escape("ünïcödé file"), "ünïcödé file");
+    }
+
+    #[test]
+    fn pipes_and_backticks() {
+        assert_eq!(escape("a|b"), "a\\|b");
+        assert_eq!(escape("`ls`"), "\\`ls\\`");
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(escape("__init__.py"), "\\_\\_init\\_\\_.py");
+        assert_eq!(escape("[draft]*.md"), "\\[draft\\]\\*.md");
+    }
+
+    #[test]
+    fn trailing_backslash() {
+        assert_eq!(escape("dir\\"), "dir\\\\");
+    }
+
+    #[test]
+    fn rows_lose_their_colours() {
+        let mut cell = TextCell::paint_str(Red.bold(), "9.6");
+        cell.append(TextCell::paint_str(Blue.normal(), "k"));
+
+        let mut out = Vec::new();
+        write_row(&mut out, &[ cell, TextCell::paint_str(Green.normal(), "a|b") ]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "| 9.6k | a\\|b |\n");
+    }
+
+    #[test]
+    fn rule() {
+        let mut out = Vec::new();
+        write_rule(&mut out, &[ Alignment::Left, Alignment::Right, Alignment::Left ]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "| --- | ---: | --- |\n");
+    }
+}
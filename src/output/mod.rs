@@ -1,21 +1,31 @@
 use output::file_name::FileStyle;
+use output::table::SizeFormat;
 use style::Colours;
 
 pub use self::cell::{TextCell, TextCellContents, DisplayWidth};
-pub use self::escape::escape;
+pub use self::escape::{escape, escape_lossy};
+pub use self::highlight::Highlight;
 
 pub mod details;
+pub mod dired;
+pub mod error;
 pub mod file_name;
+pub mod format;
 pub mod grid_details;
 pub mod grid;
 pub mod lines;
+pub mod markdown;
 pub mod render;
+pub mod summary;
 pub mod table;
+pub mod template;
 pub mod time;
+pub mod tree;
 
 mod cell;
 mod escape;
-mod tree;
+mod highlight;
+mod quote;
 
 
 /// The **view** contains all information about how to format output.
@@ -24,6 +34,10 @@ pub struct View {
     pub mode: Mode,
     pub colours: Colours,
     pub style: FileStyle,
+
+    /// Whether to print a summary footer after each listing, and if so,
+    /// how to format the total size of the files in it.
+    pub summary: Option<SizeFormat>,
 }
 
 
@@ -34,4 +48,6 @@ pub enum Mode {
     Details(details::Options),
     GridDetails(grid_details::Options),
     Lines,
+    Markdown(markdown::Options),
+    Format(format::Options),
 }
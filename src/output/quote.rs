@@ -0,0 +1,132 @@
+//! Quoting file names so they can be pasted back into a shell.
+
+use output::file_name::Quotes;
+
+
+/// One part of a quoted file name: either a run of the name itself, or
+/// some quoting punctuation that was added around or inside it.
+#[derive(PartialEq, Debug)]
+pub enum Part<'a> {
+
+    /// A run of the name, with the byte offset in the name that it starts at.
+    Name(&'a str, usize),
+
+    /// Punctuation that isn’t part of the name.
+    Quote(&'static str),
+}
+
+/// Splits the given name into the parts it should be displayed as, adding
+/// quotes if it contains any characters that the chosen shell would treat
+/// specially. Names that don’t need quoting come back as a single part.
+pub fn quote(name: &str, quotes: Quotes) -> Vec<Part<'_>> {
+    match quotes {
+        Quotes::Never                                    => vec![ Part::Name(name, 0) ],
+        Quotes::Shell if !needs_shell_quotes(name)       => vec![ Part::Name(name, 0) ],
+        Quotes::PowerShell if !needs_pwsh_quotes(name)   => vec![ Part::Name(name, 0) ],
+
+        // Inside single quotes, nothing is special apart from the single
+        // quote itself, which has to end the quoted string, be escaped, and
+        // start a new one.
+        Quotes::Shell       => wrap(name, "'", |c| if c == '\'' { Some("'\\''") } else { None }),
+
+        // Inside double quotes, PowerShell still expands variables and
+        // escapes, so those characters get escaped with a backtick.
+        Quotes::PowerShell  => wrap(name, "\"", |c| match c {
+            '"'  => Some("`\""),
+            '$'  => Some("`$"),
+            '`'  => Some("``"),
+            _    => None,
+        }),
+    }
+}
+
+fn wrap<'a, F>(name: &'a str, quote: &'static str, escape: F) -> Vec<Part<'a>>
+where F: Fn(char) -> Option<&'static str> {
+    let mut parts = vec![ Part::Quote(quote) ];
+    let mut start = 0;
+
+    for (index, c) in name.char_indices() {
+        if let Some(escaped) = escape(c) {
+            if index > start {
+                parts.push(Part::Name(&name[start .. index], start));
+            }
+
+            parts.push(Part::Quote(escaped));
+            start = index + c.len_utf8();
+        }
+    }
+
+    if start < name.len() {
+        parts.push(Part::Name(&name[start ..], start));
+    }
+
+    parts.push(Part::Quote(quote));
+    parts
+}
+
+/// Whether a POSIX shell would treat any of this name’s characters
+/// specially if it got pasted in unquoted.
+fn needs_shell_quotes(name: &str) -> bool {
+    name.starts_with('~') || name.starts_with('#')
+        || name.chars().any(|c| c.is_whitespace() || c.is_control() || "'\"\\$`!&*()[]{};<>|?".contains(c))
+}
+
+/// Whether PowerShell would treat any of this name’s characters specially
+/// if it got pasted in unquoted.
+fn needs_pwsh_quotes(name: &str) -> bool {
+    name.starts_with('@') || name.starts_with('-')
+        || name.chars().any(|c| c.is_whitespace() || c.is_control() || "'\"$`&(){};,<>|#".contains(c))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::Part::*;
+
+    #[test]
+    fn never() {
+        assert_eq!(quote("my file", Quotes::Never), vec![ Name("my file", 0) ]);
+    }
+
+    #[test]
+    fn shell_plain() {
+        assert_eq!(quote("file.txt", Quotes::Shell), vec![ Name("file.txt", 0) ]);
+    }
+
+    #[test]
+    fn shell_space() {
+        assert_eq!(quote("my file.txt", Quotes::Shell), vec![ Quote("'"), Name("my file.txt", 0), Quote("'") ]);
+    }
+
+    #[test]
+    fn shell_apostrophe() {
+        assert_eq!(quote("it's", Quotes::Shell), vec![ Quote("'"), Name("it", 0), Quote("'\\''"), Name("s", 3), Quote("'") ]);
+    }
+
+    #[test]
+    fn shell_only_apostrophe() {
+        assert_eq!(quote("'", Quotes::Shell), vec![ Quote("'"), Quote("'\\''"), Quote("'") ]);
+    }
+
+    #[test]
+    fn shell_tilde() {
+        assert_eq!(quote("~backup", Quotes::Shell), vec![ Quote("'"), Name("~backup", 0), Quote("'") ]);
+        assert_eq!(quote("backup~", Quotes::Shell), vec![ Name("backup~", 0) ]);
+    }
+
+    #[test]
+    fn powershell_plain() {
+        assert_eq!(quote("file.txt", Quotes::PowerShell), vec![ Name("file.txt", 0) ]);
+    }
+
+    #[test]
+    fn powershell_space() {
+        assert_eq!(quote("my file.txt", Quotes::PowerShell), vec![ Quote("\""), Name("my file.txt", 0), Quote("\"") ]);
+    }
+
+    #[test]
+    fn powershell_variable() {
+        assert_eq!(quote("$cost (1).txt", Quotes::PowerShell), vec![ Quote("\""), Quote("`$"), Name("cost (1).txt", 1), Quote("\"") ]);
+    }
+}
@@ -0,0 +1,83 @@
+use ansi_term::Style;
+
+use fs::fields as f;
+use output::cell::{TextCell, DisplayWidth};
+
+
+impl f::Access {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        let bit = |bit, chr: &'static str| {
+            if bit { colours.allowed().paint(chr) } else { colours.denied().paint("-") }
+        };
+
+        let chars = vec![
+            bit(self.read,     "r"),
+            bit(self.write,    "w"),
+            bit(self.execute,  "x"),
+        ];
+
+        TextCell {
+            width:    DisplayWidth::from(chars.len()),
+            contents: chars.into(),
+        }
+    }
+}
+
+
+pub trait Colours {
+    fn allowed(&self) -> Style;
+    fn denied(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::{TextCell, DisplayWidth};
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn allowed(&self) -> Style { Green.bold() }
+        fn denied(&self)  -> Style { Red.normal() }
+    }
+
+
+    #[test]
+    fn everything() {
+        let access = f::Access { read: true, write: true, execute: true };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(3),
+            contents: vec![
+                Green.bold().paint("r"),
+                Green.bold().paint("w"),
+                Green.bold().paint("x"),
+            ].into(),
+        };
+
+        assert_eq!(expected, access.render(&TestColours));
+    }
+
+
+    #[test]
+    fn read_only() {
+        let access = f::Access { read: true, write: false, execute: false };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(3),
+            contents: vec![
+                Green.bold().paint("r"),
+                Red.normal().paint("-"),
+                Red.normal().paint("-"),
+            ].into(),
+        };
+
+        assert_eq!(expected, access.render(&TestColours));
+    }
+}
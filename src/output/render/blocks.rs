@@ -1,6 +1,7 @@
 use ansi_term::Style;
 
 use output::cell::TextCell;
+use output::render::Absent;
 use fs::fields as f;
 
 
@@ -8,15 +9,14 @@ impl f::Blocks {
     pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
         match *self {
             f::Blocks::Some(ref blk)  => TextCell::paint(colours.block_count(), blk.to_string()),
-            f::Blocks::None           => TextCell::blank(colours.no_blocks()),
+            f::Blocks::None           => colours.absent(),
         }
     }
 }
 
 
-pub trait Colours {
+pub trait Colours: Absent {
     fn block_count(&self) -> Style;
-    fn no_blocks(&self) -> Style;
 }
 
 
@@ -27,6 +27,7 @@ pub mod test {
 
     use super::Colours;
     use output::cell::TextCell;
+    use output::render::Absent;
     use fs::fields as f;
 
 
@@ -34,14 +35,17 @@ pub mod test {
 
     impl Colours for TestColours {
         fn block_count(&self) -> Style { Red.blink() }
-        fn no_blocks(&self)   -> Style { Green.italic() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Green.italic(), "-") }
     }
 
 
     #[test]
     fn blocklessness() {
         let blox = f::Blocks::None;
-        let expected = TextCell::blank(Green.italic());
+        let expected = TextCell::paint_str(Green.italic(), "-");
 
         assert_eq!(expected, blox.render(&TestColours).into());
     }
@@ -0,0 +1,58 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+impl f::Checksum {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        match *self {
+            f::Checksum::Some(ref hash)  => TextCell::paint(colours.checksum(), hash.clone()),
+            f::Checksum::Skipped         => colours.absent(),
+            f::Checksum::Error           => colours.absent(),
+        }
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn checksum(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn checksum(&self)    -> Style { Fixed(66).normal() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn hash() {
+        let expected = TextCell::paint_str(Fixed(66).normal(), "cbf43926");
+        assert_eq!(expected, f::Checksum::Some("cbf43926".into()).render(&TestColours));
+    }
+
+
+    #[test]
+    fn error() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Checksum::Error.render(&TestColours));
+    }
+}
@@ -0,0 +1,71 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+impl f::ContentType {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        use fs::fields::ContentType::*;
+
+        let label = match *self {
+            PE       => "PE",
+            Elf      => "ELF",
+            Zip      => "zip",
+            Ooxml    => "OOXML",
+            Png      => "PNG",
+            Jpeg     => "JPEG",
+            Pdf      => "PDF",
+            Text     => "text",
+            UTF16    => "UTF-16",
+            Binary   => "binary",
+            Empty    => "empty",
+            Unknown  => return colours.absent(),
+        };
+
+        TextCell::paint_str(colours.content_type(), label)
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn content_type(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn content_type(&self) -> Style { Purple.normal() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn text() {
+        let expected = TextCell::paint_str(Purple.normal(), "text");
+        assert_eq!(expected, f::ContentType::Text.render(&TestColours));
+    }
+
+
+    #[test]
+    fn unknown() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::ContentType::Unknown.render(&TestColours));
+    }
+}
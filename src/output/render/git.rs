@@ -14,6 +14,34 @@ impl f::Git {
             ].into(),
         }
     }
+
+    /// Where a file with this status gets listed when sorting by Git
+    /// status, with lower ranks coming first. The letters are the ones
+    /// `render` shows, in the staged column then the unstaged one:
+    ///
+    /// | Rank | Status                        | Letters                |
+    /// |------|-------------------------------|------------------------|
+    /// | 0    | conflicted                    | `U` in either          |
+    /// | 1    | changed in the working tree   | `-N`, `-M`, `-D`, `-T` |
+    /// | 2    | changed in the index (staged) | `N-`, `M-`, `D-`, `T-` |
+    /// | 3    | renamed                       | `R` in either          |
+    /// | 4    | clean, or not in a repository | `--`                   |
+    /// | 5    | ignored                       | `-I`                   |
+    ///
+    /// A file with changes in both columns gets the lowest rank either of
+    /// them gives it, so `MM` ranks with the working tree changes.
+    pub fn sort_rank(&self) -> u8 {
+        use fs::fields::GitStatus::*;
+
+        match (&self.staged, &self.unstaged) {
+            (&Conflicted, _) | (_, &Conflicted)  => 0,
+            (_, &New) | (_, &Modified) | (_, &Deleted) | (_, &TypeChange)  => 1,
+            (&New, _) | (&Modified, _) | (&Deleted, _) | (&TypeChange, _)  => 2,
+            (&Renamed, _) | (_, &Renamed)        => 3,
+            (&NotModified, &NotModified)         => 4,
+            (&Ignored, _) | (_, &Ignored)        => 5,
+        }
+    }
 }
 
 
@@ -27,12 +55,45 @@ impl f::GitStatus {
             f::GitStatus::Renamed      => colours.renamed().paint("R"),
             f::GitStatus::TypeChange   => colours.type_change().paint("T"),
             f::GitStatus::Ignored      => colours.ignored().paint("I"),
+            f::GitStatus::Conflicted   => colours.conflicted().paint("U"),
         }
     }
 }
 
 
+impl f::GitRepoState {
+
+    /// Renders the annotation that goes after a directory’s header, such as
+    /// “[main *]”, where the star means something has changed. Branches
+    /// without any commits yet get “(no commits)” after their name.
+    pub fn render<C: Colours>(&self, colours: &C) -> Vec<ANSIString<'static>> {
+        let head = match self.head {
+            f::GitHead::Branch(ref name)    => name.clone(),
+            f::GitHead::Detached(ref hash)  => hash.clone(),
+            f::GitHead::Unborn(ref name)    => format!("{} (no commits)", name),
+            f::GitHead::Unknown             => "HEAD".into(),
+        };
+
+        let mut bits = vec![
+            colours.punctuation().paint("["),
+            colours.branch().paint(head),
+        ];
+
+        if self.dirty {
+            bits.push(colours.punctuation().paint(" "));
+            bits.push(colours.dirty().paint("*"));
+        }
+
+        bits.push(colours.punctuation().paint("]"));
+        bits
+    }
+}
+
+
 pub trait Colours {
+    fn punctuation(&self) -> Style;
+    fn branch(&self) -> Style;
+    fn dirty(&self) -> Style;
     fn not_modified(&self) -> Style;
     fn new(&self) -> Style;
     fn modified(&self) -> Style;
@@ -40,6 +101,7 @@ pub trait Colours {
     fn renamed(&self) -> Style;
     fn type_change(&self) -> Style;
     fn ignored(&self) -> Style;
+    fn conflicted(&self) -> Style;
 }
 
 
@@ -50,12 +112,15 @@ pub mod test {
     use fs::fields as f;
 
     use ansi_term::Colour::*;
-    use ansi_term::Style;
+    use ansi_term::{ANSIStrings, Style};
 
 
     struct TestColours;
 
     impl Colours for TestColours {
+        fn punctuation(&self)  -> Style { Fixed(80).normal() }
+        fn branch(&self)       -> Style { Fixed(81).normal() }
+        fn dirty(&self)        -> Style { Fixed(82).normal() }
         fn not_modified(&self) -> Style { Fixed(90).normal() }
         fn new(&self)          -> Style { Fixed(91).normal() }
         fn modified(&self)     -> Style { Fixed(92).normal() }
@@ -63,6 +128,7 @@ pub mod test {
         fn renamed(&self)      -> Style { Fixed(94).normal() }
         fn type_change(&self)  -> Style { Fixed(95).normal() }
         fn ignored(&self)      -> Style { Fixed(96).normal() }
+        fn conflicted(&self)   -> Style { Fixed(97).normal() }
     }
 
 
@@ -102,4 +168,84 @@ pub mod test {
 
         assert_eq!(expected, stati.render(&TestColours).into())
     }
+
+
+    #[test]
+    fn git_conflicted() {
+        let stati = f::Git {
+            staged:   f::GitStatus::Conflicted,
+            unstaged: f::GitStatus::Conflicted,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                Fixed(97).paint("U"),
+                Fixed(97).paint("U"),
+            ].into(),
+        };
+
+        assert_eq!(expected, stati.render(&TestColours).into())
+    }
+
+
+    fn rank(staged: f::GitStatus, unstaged: f::GitStatus) -> u8 {
+        f::Git { staged, unstaged }.sort_rank()
+    }
+
+    #[test]
+    fn ranks_in_order() {
+        use fs::fields::GitStatus::*;
+
+        let ranks = [
+            rank(Conflicted,  Conflicted),
+            rank(NotModified, Modified),
+            rank(Modified,    NotModified),
+            rank(Renamed,     NotModified),
+            rank(NotModified, NotModified),
+            rank(NotModified, Ignored),
+        ];
+        assert_eq!(ranks, [ 0, 1, 2, 3, 4, 5 ]);
+    }
+
+    #[test]
+    fn rank_uses_the_most_interesting_column() {
+        use fs::fields::GitStatus::*;
+
+        assert_eq!(rank(Modified, Modified), 1);
+        assert_eq!(rank(New, Conflicted), 0);
+        assert_eq!(rank(Renamed, Modified), 1);
+        assert_eq!(rank(New, Renamed), 2);
+        assert_eq!(rank(NotModified, New), 1);
+    }
+
+
+    fn repo_state(head: f::GitHead, dirty: bool) -> String {
+        let state = f::GitRepoState { head, dirty };
+        ANSIStrings(&state.render(&TestColours)).to_string()
+    }
+
+    #[test]
+    fn repo_clean_branch() {
+        let expected = vec![ Fixed(80).paint("["), Fixed(81).paint("main"), Fixed(80).paint("]") ];
+        assert_eq!(repo_state(f::GitHead::Branch("main".into()), false), ANSIStrings(&expected).to_string());
+    }
+
+    #[test]
+    fn repo_dirty_branch() {
+        let expected = vec![ Fixed(80).paint("["), Fixed(81).paint("main"), Fixed(80).paint(" "), Fixed(82).paint("*"), Fixed(80).paint("]") ];
+        assert_eq!(repo_state(f::GitHead::Branch("main".into()), true), ANSIStrings(&expected).to_string());
+    }
+
+    #[test]
+    fn repo_detached() {
+        let expected = vec![ Fixed(80).paint("["), Fixed(81).paint("1a2b3c4"), Fixed(80).paint("]") ];
+        assert_eq!(repo_state(f::GitHead::Detached("1a2b3c4".into()), false), ANSIStrings(&expected).to_string());
+    }
+
+    #[test]
+    fn repo_unborn() {
+        let expected = vec![ Fixed(80).paint("["), Fixed(81).paint("main (no commits)"), Fixed(80).paint(" "), Fixed(82).paint("*"), Fixed(80).paint("]") ];
+        assert_eq!(repo_state(f::GitHead::Unborn("main".into()), true), ANSIStrings(&expected).to_string());
+    }
 }
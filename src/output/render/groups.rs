@@ -3,10 +3,11 @@ use users::{Users, Groups};
 
 use fs::fields as f;
 use output::cell::TextCell;
+use output::table::{UserFormat, OwnerWidth};
 
 
 impl f::Group {
-    pub fn render<C: Colours, U: Users+Groups>(&self, colours: &C, users: &U) -> TextCell {
+    pub fn render<C: Colours, U: Users+Groups>(&self, colours: &C, users: &U, format: UserFormat, width: Option<OwnerWidth>) -> TextCell {
         use users::os::unix::GroupExt;
 
         let mut style = colours.not_yours();
@@ -24,7 +25,14 @@ impl f::Group {
             }
         }
 
-        TextCell::paint(style, group.name().to_string_lossy().into())
+        // Only names get cut short: a cut-down ID is no use to anyone.
+        let group_name = match (format, width) {
+            (UserFormat::Name, Some(width))  => width.truncate(group.name().to_string_lossy().into()),
+            (UserFormat::Name, None)         => group.name().to_string_lossy().into(),
+            (UserFormat::Numeric, _)         => group.gid().to_string(),
+        };
+
+        TextCell::paint(style, group_name)
     }
 }
 
@@ -41,6 +49,7 @@ pub mod test {
     use super::Colours;
     use fs::fields as f;
     use output::cell::TextCell;
+    use output::table::{UserFormat, OwnerWidth};
 
     use users::{User, Group};
     use users::mock::MockUsers;
@@ -64,7 +73,7 @@ pub mod test {
 
         let group = f::Group(100);
         let expected = TextCell::paint_str(Fixed(81).normal(), "folk");
-        assert_eq!(expected, group.render(&TestColours, &users))
+        assert_eq!(expected, group.render(&TestColours, &users, UserFormat::Name, None))
     }
 
     #[test]
@@ -73,7 +82,7 @@ pub mod test {
 
         let group = f::Group(100);
         let expected = TextCell::paint_str(Fixed(81).normal(), "100");
-        assert_eq!(expected, group.render(&TestColours, &users));
+        assert_eq!(expected, group.render(&TestColours, &users, UserFormat::Name, None));
     }
 
     #[test]
@@ -84,7 +93,7 @@ pub mod test {
 
         let group = f::Group(100);
         let expected = TextCell::paint_str(Fixed(80).normal(), "folk");
-        assert_eq!(expected, group.render(&TestColours, &users))
+        assert_eq!(expected, group.render(&TestColours, &users, UserFormat::Name, None))
     }
 
     #[test]
@@ -97,13 +106,44 @@ pub mod test {
 
         let group = f::Group(100);
         let expected = TextCell::paint_str(Fixed(80).normal(), "folk");
-        assert_eq!(expected, group.render(&TestColours, &users))
+        assert_eq!(expected, group.render(&TestColours, &users, UserFormat::Name, None))
     }
 
     #[test]
     fn overflow() {
         let group = f::Group(2_147_483_648);
         let expected = TextCell::paint_str(Fixed(81).normal(), "2147483648");
-        assert_eq!(expected, group.render(&TestColours, &MockUsers::with_current_uid(0)));
+        assert_eq!(expected, group.render(&TestColours, &MockUsers::with_current_uid(0), UserFormat::Name, None));
+    }
+
+    #[test]
+    fn numeric() {
+        let mut users = MockUsers::with_current_uid(2);
+        users.add_user(User::new(2, "eve", 100));
+        users.add_group(Group::new(100, "folk"));
+
+        let group = f::Group(100);
+        let expected = TextCell::paint_str(Fixed(80).normal(), "100");
+        assert_eq!(expected, group.render(&TestColours, &users, UserFormat::Numeric, None))
+    }
+
+    #[test]
+    fn truncated() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_group(Group::new(100, "administrators"));
+
+        let width = OwnerWidth { max: 6, ellipsis: "…" };
+        let expected = TextCell::paint_str(Fixed(81).normal(), "admin…");
+        assert_eq!(expected, f::Group(100).render(&TestColours, &users, UserFormat::Name, Some(width)))
+    }
+
+    #[test]
+    fn numeric_not_truncated() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_group(Group::new(12345, "administrators"));
+
+        let width = OwnerWidth { max: 3, ellipsis: "…" };
+        let expected = TextCell::paint_str(Fixed(81).normal(), "12345");
+        assert_eq!(expected, f::Group(12345).render(&TestColours, &users, UserFormat::Numeric, Some(width)))
     }
 }
@@ -1,6 +1,26 @@
+use output::cell::TextCell;
+
+
+/// Renders a value that a file doesn’t have, such as the size of a
+/// directory. Every column goes through this, so they all show missing
+/// values the same way.
+pub trait Absent {
+    fn absent(&self) -> TextCell;
+}
+
+
+mod access;
+pub use self::access::Colours as AccessColours;
+
 mod blocks;
 pub use self::blocks::Colours as BlocksColours;
 
+mod checksum;
+pub use self::checksum::Colours as ChecksumColours;
+
+mod content;
+pub use self::content::Colours as ContentColours;
+
 mod filetype;
 pub use self::filetype::Colours as FiletypeColours;
 
@@ -16,14 +36,30 @@ mod inode;
 mod links;
 pub use self::links::Colours as LinksColours;
 
+mod network;
+pub use self::network::Colours as NetworkColours;
+
 mod permissions;
 pub use self::permissions::Colours as PermissionsColours;
 
+mod signature;
+pub use self::signature::Colours as SignatureColours;
+
 mod size;
 pub use self::size::Colours as SizeColours;
 
 mod times;
 // times does too
+pub use self::times::Render as TimeRender;
 
 mod users;
 pub use self::users::Colours as UserColours;
+
+mod version;
+pub use self::version::Colours as VersionColours;
+
+mod zone;
+pub use self::zone::Colours as ZoneColours;
+
+mod volume;
+pub use self::volume::Colours as VolumeColours;
@@ -0,0 +1,53 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use fs::fields as f;
+
+
+impl f::Location {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        match *self {
+            f::Location::Network  => TextCell::paint_str(colours.network(), "N"),
+            f::Location::Local    => TextCell::paint_str(colours.local(), "-"),
+        }
+    }
+}
+
+
+pub trait Colours {
+    fn network(&self) -> Style;
+    fn local(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn network(&self) -> Style { Yellow.bold() }
+        fn local(&self)   -> Style { Black.italic() }
+    }
+
+
+    #[test]
+    fn network() {
+        let expected = TextCell::paint_str(Yellow.bold(), "N");
+        assert_eq!(expected, f::Location::Network.render(&TestColours));
+    }
+
+
+    #[test]
+    fn local() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Location::Local.render(&TestColours));
+    }
+}
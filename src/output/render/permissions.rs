@@ -1,3 +1,5 @@
+use std::ops::Deref;
+
 use ansi_term::{ANSIString, Style};
 
 use fs::fields as f;
@@ -14,6 +16,15 @@ impl f::PermissionsPlus {
            chars.push(colours.attribute().paint("@"));
         }
 
+        if self.acl {
+           chars.push(colours.acl().paint("+"));
+        }
+
+        if self.is_world_writable() {
+            let style = colours.world_writable();
+            chars = chars.iter().map(|c| style.paint(c.deref().to_owned())).collect();
+        }
+
         // As these are all ASCII characters, we can guarantee that they’re
         // all going to be one character wide, and don’t need to compute the
         // cell’s display width.
@@ -97,6 +108,8 @@ pub trait Colours {
     fn special_other(&self) -> Style;
 
     fn attribute(&self) -> Style;
+    fn acl(&self) -> Style;
+    fn world_writable(&self) -> Style;
 }
 
 
@@ -105,6 +118,7 @@ pub trait Colours {
 pub mod test {
     use super::Colours;
     use output::cell::TextCellContents;
+    use output::render::FiletypeColours;
     use fs::fields as f;
 
     use ansi_term::Colour::*;
@@ -128,6 +142,19 @@ pub mod test {
         fn special_user_file(&self)   -> Style { Fixed(110).normal() }
         fn special_other(&self)       -> Style { Fixed(111).normal() }
         fn attribute(&self)           -> Style { Fixed(112).normal() }
+        fn acl(&self)                 -> Style { Fixed(115).normal() }
+        fn world_writable(&self)      -> Style { Fixed(114).normal() }
+    }
+
+    impl FiletypeColours for TestColours {
+        fn normal(&self)        -> Style { Fixed(1).normal() }
+        fn directory(&self)     -> Style { Fixed(2).normal() }
+        fn pipe(&self)          -> Style { Fixed(3).normal() }
+        fn symlink(&self)       -> Style { Fixed(4).normal() }
+        fn block_device(&self)  -> Style { Fixed(5).normal() }
+        fn char_device(&self)   -> Style { Fixed(6).normal() }
+        fn socket(&self)        -> Style { Fixed(7).normal() }
+        fn special(&self)       -> Style { Fixed(8).normal() }
     }
 
 
@@ -201,4 +228,47 @@ pub mod test {
 
         assert_eq!(expected, bits.render(&TestColours, true).into())
     }
+
+
+    #[test]
+    fn world_writable() {
+        let bits = f::Permissions {
+            user_read:  true,  user_write:  true,  user_execute:  true,  setuid: false,
+            group_read: true,  group_write: false, group_execute: true,  setgid: false,
+            other_read: true,  other_write: true,  other_execute: true,  sticky: false,
+        };
+
+        let perms = f::PermissionsPlus { file_type: f::Type::Directory, permissions: bits, xattrs: false, acl: false };
+
+        let expected = TextCellContents::from(vec![
+            Fixed(114).paint("d"),
+            Fixed(114).paint("r"),  Fixed(114).paint("w"),  Fixed(114).paint("x"),
+            Fixed(114).paint("r"),  Fixed(114).paint("-"),  Fixed(114).paint("x"),
+            Fixed(114).paint("r"),  Fixed(114).paint("w"),  Fixed(114).paint("x"),
+        ]);
+
+        assert_eq!(expected, perms.render(&TestColours).contents)
+    }
+
+
+    #[test]
+    fn acl_after_attributes() {
+        let bits = f::Permissions {
+            user_read:  true,  user_write:  true,  user_execute:  false,  setuid: false,
+            group_read: true,  group_write: false, group_execute: false,  setgid: false,
+            other_read: false, other_write: false, other_execute: false,  sticky: false,
+        };
+
+        let perms = f::PermissionsPlus { file_type: f::Type::File, permissions: bits, xattrs: true, acl: true };
+
+        let expected = TextCellContents::from(vec![
+            Fixed(1).paint("."),
+            Fixed(101).paint("r"),  Fixed(102).paint("w"),  Fixed(11).paint("-"),
+            Fixed(104).paint("r"),  Fixed(11).paint("-"),   Fixed(11).paint("-"),
+            Fixed(11).paint("-"),   Fixed(11).paint("-"),   Fixed(11).paint("-"),
+            Fixed(112).paint("@"),  Fixed(115).paint("+"),
+        ]);
+
+        assert_eq!(expected, perms.render(&TestColours).contents)
+    }
 }
@@ -0,0 +1,70 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+impl f::Signature {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        match *self {
+            f::Signature::Signed    => TextCell::paint_str(colours.signed(),   "signed"),
+            f::Signature::Unsigned  => TextCell::paint_str(colours.unsigned(), "unsigned"),
+            f::Signature::Invalid   => TextCell::paint_str(colours.invalid(),  "invalid"),
+            f::Signature::NotPE     => colours.absent(),
+        }
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn signed(&self) -> Style;
+    fn unsigned(&self) -> Style;
+    fn invalid(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn signed(&self)   -> Style { Green.bold() }
+        fn unsigned(&self) -> Style { Yellow.normal() }
+        fn invalid(&self)  -> Style { Red.bold() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn signed() {
+        let expected = TextCell::paint_str(Green.bold(), "signed");
+        assert_eq!(expected, f::Signature::Signed.render(&TestColours));
+    }
+
+
+    #[test]
+    fn invalid() {
+        let expected = TextCell::paint_str(Red.bold(), "invalid");
+        assert_eq!(expected, f::Signature::Invalid.render(&TestColours));
+    }
+
+
+    #[test]
+    fn not_pe() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Signature::NotPE.render(&TestColours));
+    }
+}
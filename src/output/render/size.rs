@@ -3,6 +3,7 @@ use locale::Numeric as NumericLocale;
 
 use fs::fields as f;
 use output::cell::{TextCell, DisplayWidth};
+use output::render::Absent;
 use output::table::SizeFormat;
 
 
@@ -14,8 +15,9 @@ impl f::Size {
 
         let size = match *self {
             f::Size::Some(s)             => s,
-            f::Size::None                => return TextCell::blank(colours.no_size()),
+            f::Size::None                => return colours.absent(),
             f::Size::DeviceIDs(ref ids)  => return ids.render(colours),
+            f::Size::Entries(count)      => return TextCell::paint(colours.entries(), numerics.format_int(count)),
         };
 
         let result = match size_format {
@@ -36,9 +38,9 @@ impl f::Size {
         let number = if n < 10f64 { numerics.format_float(n, 1) }
                              else { numerics.format_int(n as isize) };
 
-        // The numbers and symbols are guaranteed to be written in ASCII, so
-        // we can skip the display width calculation.
-        let width = DisplayWidth::from(number.len() + symbol.len());
+        // The symbols are guaranteed to be written in ASCII, but the number
+        // may contain a locale’s separators, which aren’t.
+        let width = DisplayWidth::from(&*number) + symbol.len();
 
         TextCell {
             width,
@@ -68,10 +70,10 @@ impl f::DeviceIDs {
 }
 
 
-pub trait Colours {
+pub trait Colours: Absent {
     fn size(&self, size: u64) -> Style;
     fn unit(&self) -> Style;
-    fn no_size(&self) -> Style;
+    fn entries(&self) -> Style;
 
     fn major(&self) -> Style;
     fn comma(&self) -> Style;
@@ -83,6 +85,7 @@ pub trait Colours {
 pub mod test {
     use super::Colours;
     use output::cell::{TextCell, DisplayWidth};
+    use output::render::Absent;
     use output::table::SizeFormat;
     use fs::fields as f;
 
@@ -96,18 +99,22 @@ pub mod test {
     impl Colours for TestColours {
         fn size(&self, _size: u64) -> Style { Fixed(66).normal() }
         fn unit(&self)             -> Style { Fixed(77).bold() }
-        fn no_size(&self)          -> Style { Black.italic() }
+        fn entries(&self)          -> Style { Purple.underline() }
 
         fn major(&self) -> Style { Blue.on(Red) }
         fn comma(&self) -> Style { Green.italic() }
         fn minor(&self) -> Style { Cyan.on(Yellow) }
     }
 
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
 
     #[test]
     fn directory() {
         let directory = f::Size::None;
-        let expected = TextCell::blank(Black.italic());
+        let expected = TextCell::paint_str(Black.italic(), "-");
         assert_eq!(expected, directory.render(&TestColours, SizeFormat::JustBytes, &NumericLocale::english()))
     }
 
@@ -156,6 +163,20 @@ pub mod test {
     }
 
 
+    #[test]
+    fn entries() {
+        let directory = f::Size::Entries(12_345);
+        let expected = TextCell {
+            width: DisplayWidth::from(6),
+            contents: vec![
+                Purple.underline().paint("12,345"),
+            ].into(),
+        };
+
+        assert_eq!(expected, directory.render(&TestColours, SizeFormat::DecimalBytes, &NumericLocale::english()))
+    }
+
+
     #[test]
     fn device_ids() {
         let directory = f::Size::DeviceIDs(f::DeviceIDs { major: 10, minor: 80 });
@@ -3,21 +3,75 @@ use ansi_term::Style;
 
 use fs::fields as f;
 use output::cell::TextCell;
+use output::render::Absent;
 use output::time::TimeFormat;
 
 
-impl f::Time {
-    pub fn render(self, style: Style,
-                        tz: &Option<TimeZone>,
-                        format: &TimeFormat) -> TextCell {
+pub trait Render {
+    fn render<A: Absent>(self, style: Style,
+                               absent: &A,
+                               tz: &Option<TimeZone>,
+                               format: &TimeFormat) -> TextCell;
+}
+
+impl Render for Option<f::Time> {
+    fn render<A: Absent>(self, style: Style,
+                               absent: &A,
+                               tz: &Option<TimeZone>,
+                               format: &TimeFormat) -> TextCell {
+
+        let time = match self {
+            Some(time)  => time,
+            None        => return absent.absent(),
+        };
 
         if let Some(ref tz) = *tz {
-            let datestamp = format.format_zoned(self, tz);
+            let datestamp = format.format_zoned(time, tz);
             TextCell::paint(style, datestamp)
         }
         else {
-            let datestamp = format.format_local(self);
+            let datestamp = format.format_local(time);
             TextCell::paint(style, datestamp)
         }
     }
 }
+
+
+#[cfg(test)]
+pub mod test {
+    use super::Render;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use output::time::TimeFormat;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+
+
+    struct TestColours;
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn missing() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, None.render(Blue.normal(), &TestColours, &None, &TimeFormat::Unix));
+    }
+
+    #[test]
+    fn unix() {
+        let time = f::Time::from_raw(-86_400, 0);
+        let expected = TextCell::paint_str(Blue.normal(), "-86400");
+        assert_eq!(expected, time.render(Blue.normal(), &TestColours, &None, &TimeFormat::Unix));
+    }
+
+    #[test]
+    fn unix_millis() {
+        let time = f::Time::from_raw(1_577_836_800, 123_456_789);
+        let expected = TextCell::paint_str(Blue.normal(), "1577836800123");
+        assert_eq!(expected, time.render(Blue.normal(), &TestColours, &None, &TimeFormat::UnixMillis));
+    }
+}
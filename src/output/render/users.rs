@@ -3,14 +3,22 @@ use users::Users;
 
 use fs::fields as f;
 use output::cell::TextCell;
+use output::table::{UserFormat, OwnerWidth};
 
 
 
 impl f::User {
-    pub fn render<C: Colours, U: Users>(&self, colours: &C, users: &U) -> TextCell {
-        let user_name = match users.get_user_by_uid(self.0) {
-            Some(user)  => user.name().to_string_lossy().into(),
-            None        => self.0.to_string(),
+    pub fn render<C: Colours, U: Users>(&self, colours: &C, users: &U, format: UserFormat, width: Option<OwnerWidth>) -> TextCell {
+        let user = match format {
+            UserFormat::Name     => users.get_user_by_uid(self.0),
+            UserFormat::Numeric  => None,
+        };
+
+        // Only names get cut short: a cut-down ID is no use to anyone.
+        let user_name = match (user, width) {
+            (Some(user), Some(width))  => width.truncate(user.name().to_string_lossy().into()),
+            (Some(user), None)         => user.name().to_string_lossy().into(),
+            (None, _)                  => self.0.to_string(),
         };
 
         let style =  if users.get_current_uid() == self.0 { colours.you() }
@@ -32,6 +40,7 @@ pub mod test {
     use super::Colours;
     use fs::fields as f;
     use output::cell::TextCell;
+    use output::table::{UserFormat, OwnerWidth};
 
     use users::User;
     use users::mock::MockUsers;
@@ -54,7 +63,7 @@ pub mod test {
 
         let user = f::User(1000);
         let expected = TextCell::paint_str(Red.bold(), "enoch");
-        assert_eq!(expected, user.render(&TestColours, &users))
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Name, None))
     }
 
     #[test]
@@ -63,7 +72,7 @@ pub mod test {
 
         let user = f::User(1000);
         let expected = TextCell::paint_str(Red.bold(), "1000");
-        assert_eq!(expected, user.render(&TestColours, &users));
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Name, None));
     }
 
     #[test]
@@ -73,20 +82,95 @@ pub mod test {
 
         let user = f::User(1000);
         let expected = TextCell::paint_str(Blue.underline(), "enoch");
-        assert_eq!(expected, user.render(&TestColours, &users));
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Name, None));
     }
 
     #[test]
     fn different_unnamed() {
         let user = f::User(1000);
         let expected = TextCell::paint_str(Blue.underline(), "1000");
-        assert_eq!(expected, user.render(&TestColours, &MockUsers::with_current_uid(0)));
+        assert_eq!(expected, user.render(&TestColours, &MockUsers::with_current_uid(0), UserFormat::Name, None));
     }
 
     #[test]
     fn overflow() {
         let user = f::User(2_147_483_648);
         let expected = TextCell::paint_str(Blue.underline(), "2147483648");
-        assert_eq!(expected, user.render(&TestColours, &MockUsers::with_current_uid(0)));
+        assert_eq!(expected, user.render(&TestColours, &MockUsers::with_current_uid(0), UserFormat::Name, None));
+    }
+
+    #[test]
+    fn numeric() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_user(User::new(1000, "enoch", 100));
+
+        let user = f::User(1000);
+        let expected = TextCell::paint_str(Red.bold(), "1000");
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Numeric, None));
+    }
+
+    #[test]
+    fn different_numeric() {
+        let mut users = MockUsers::with_current_uid(0);
+        users.add_user(User::new(1000, "enoch", 100));
+
+        let user = f::User(1000);
+        let expected = TextCell::paint_str(Blue.underline(), "1000");
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Numeric, None));
+    }
+
+    fn long_user(name: &str, max: usize, ellipsis: &'static str) -> TextCell {
+        let mut users = MockUsers::with_current_uid(0);
+        users.add_user(User::new(1000, name, 100));
+
+        let width = OwnerWidth { max, ellipsis };
+        f::User(1000).render(&TestColours, &users, UserFormat::Name, Some(width))
+    }
+
+    #[test]
+    fn fits() {
+        assert_eq!(long_user("enoch", 5, "…"), TextCell::paint_str(Blue.underline(), "enoch"));
+    }
+
+    #[test]
+    fn truncated() {
+        let cell = long_user("SomeVeryLongServiceAccountName", 8, "…");
+        assert_eq!(cell, TextCell::paint_str(Blue.underline(), "SomeVer…"));
+        assert_eq!(*cell.width, 8);
+    }
+
+    #[test]
+    fn truncated_ascii() {
+        assert_eq!(long_user("enochroot", 5, "~"), TextCell::paint_str(Blue.underline(), "enoc~"));
+    }
+
+    #[test]
+    fn truncated_wide() {
+        // Each of these is two columns wide, so only two of them fit in
+        // front of the ellipsis, leaving the cell a column narrower.
+        let cell = long_user("管理者アカウント", 6, "…");
+        assert_eq!(cell, TextCell::paint_str(Blue.underline(), "管理…"));
+        assert_eq!(*cell.width, 5);
+    }
+
+    #[test]
+    fn truncated_combining() {
+        // The accent stays with the e it goes on.
+        let cell = long_user("rene\u{301}es", 5, "…");
+        assert_eq!(cell, TextCell::paint_str(Blue.underline(), "rene\u{301}…"));
+    }
+
+    #[test]
+    fn numeric_not_truncated() {
+        let width = OwnerWidth { max: 3, ellipsis: "…" };
+        let cell = f::User(1000).render(&TestColours, &MockUsers::with_current_uid(0), UserFormat::Numeric, Some(width));
+        assert_eq!(cell, TextCell::paint_str(Blue.underline(), "1000"));
+    }
+
+    #[test]
+    fn unnamed_not_truncated() {
+        let width = OwnerWidth { max: 3, ellipsis: "…" };
+        let cell = f::User(1000).render(&TestColours, &MockUsers::with_current_uid(0), UserFormat::Name, Some(width));
+        assert_eq!(cell, TextCell::paint_str(Blue.underline(), "1000"));
     }
 }
@@ -0,0 +1,80 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+/// The widest a version can be before it gets cut short, so one odd file
+/// doesn’t make the whole column wide.
+const MAX_WIDTH: usize = 16;
+
+impl f::Version {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        match *self {
+            f::Version::Some(ref v) if v.chars().count() > MAX_WIDTH => {
+                let cut: String = v.chars().take(MAX_WIDTH - 1).collect();
+                TextCell::paint(colours.version(), cut + "…")
+            }
+            f::Version::Some(ref v)   => TextCell::paint(colours.version(), v.clone()),
+            f::Version::None          => colours.absent(),
+            f::Version::Unreadable    => colours.absent(),
+        }
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn version(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn version(&self)    -> Style { Green.normal() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn version() {
+        let expected = TextCell::paint_str(Green.normal(), "10.0.19041.1");
+        assert_eq!(expected, f::Version::Some("10.0.19041.1".into()).render(&TestColours));
+    }
+
+
+    #[test]
+    fn too_long() {
+        let expected = TextCell::paint_str(Green.normal(), "65535.65535.655…");
+        assert_eq!(expected, f::Version::Some("65535.65535.65535.65535".into()).render(&TestColours));
+    }
+
+
+    #[test]
+    fn none() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Version::None.render(&TestColours));
+    }
+
+
+    #[test]
+    fn unreadable() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Version::Unreadable.render(&TestColours));
+    }
+}
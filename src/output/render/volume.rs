@@ -0,0 +1,74 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+impl f::Volume {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        let style = if self.is_mount_point { colours.mount_point() }
+                                      else { colours.volume() };
+
+        match self.mount_point {
+            Some(ref path)  => TextCell::paint(style, path.display().to_string()),
+            None            => colours.absent(),
+        }
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn volume(&self) -> Style;
+    fn mount_point(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use std::path::PathBuf;
+
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn volume(&self)       -> Style { Cyan.normal() }
+        fn mount_point(&self)  -> Style { Cyan.bold() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn same_volume() {
+        let volume = f::Volume { mount_point: Some(PathBuf::from("/home")), is_mount_point: false };
+        let expected = TextCell::paint_str(Cyan.normal(), "/home");
+        assert_eq!(expected, volume.render(&TestColours));
+    }
+
+
+    #[test]
+    fn mount_point() {
+        let volume = f::Volume { mount_point: Some(PathBuf::from("/mnt/usb")), is_mount_point: true };
+        let expected = TextCell::paint_str(Cyan.bold(), "/mnt/usb");
+        assert_eq!(expected, volume.render(&TestColours));
+    }
+
+
+    #[test]
+    fn unknown() {
+        let volume = f::Volume { mount_point: None, is_mount_point: false };
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, volume.render(&TestColours));
+    }
+}
@@ -0,0 +1,65 @@
+use ansi_term::Style;
+
+use output::cell::TextCell;
+use output::render::Absent;
+use fs::fields as f;
+
+
+impl f::Zone {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        match *self {
+            f::Zone::Marked(Some(id))  => TextCell::paint(colours.zone(), id.to_string()),
+            f::Zone::Marked(None)      => TextCell::paint_str(colours.zone(), "?"),
+            f::Zone::Unmarked          => colours.absent(),
+        }
+    }
+}
+
+
+pub trait Colours: Absent {
+    fn zone(&self) -> Style;
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use ansi_term::Style;
+    use ansi_term::Colour::*;
+
+    use super::Colours;
+    use output::cell::TextCell;
+    use output::render::Absent;
+    use fs::fields as f;
+
+
+    struct TestColours;
+
+    impl Colours for TestColours {
+        fn zone(&self) -> Style { Red.bold() }
+    }
+
+    impl Absent for TestColours {
+        fn absent(&self) -> TextCell { TextCell::paint_str(Black.italic(), "-") }
+    }
+
+
+    #[test]
+    fn internet() {
+        let expected = TextCell::paint_str(Red.bold(), "3");
+        assert_eq!(expected, f::Zone::Marked(Some(3)).render(&TestColours));
+    }
+
+
+    #[test]
+    fn unreadable() {
+        let expected = TextCell::paint_str(Red.bold(), "?");
+        assert_eq!(expected, f::Zone::Marked(None).render(&TestColours));
+    }
+
+
+    #[test]
+    fn unmarked() {
+        let expected = TextCell::paint_str(Black.italic(), "-");
+        assert_eq!(expected, f::Zone::Unmarked.render(&TestColours));
+    }
+}
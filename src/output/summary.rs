@@ -0,0 +1,179 @@
+//! The summary footer that gets printed after a listing, counting up the
+//! files of each type that were listed and how big they are.
+
+use ansi_term::Style;
+use locale::Numeric as NumericLocale;
+
+use fs::{File, fields as f};
+use output::cell::TextCell;
+use output::table::SizeFormat;
+use style::Colours;
+
+
+/// The counts of each type of file that got listed, and the total size of
+/// the regular files among them.
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+pub struct Summary {
+    pub files:    u64,
+    pub dirs:     u64,
+    pub links:    u64,
+    pub pipes:    u64,
+    pub sockets:  u64,
+    pub devices:  u64,
+    pub others:   u64,
+
+    /// The sum of the sizes of the regular files, in bytes. Other types of
+    /// file don’t have a meaningful size, so they don’t count.
+    pub size: u64,
+
+    /// The number of files and directories that couldn’t be read along the
+    /// way. These don’t get shown in the footer, but they do make exa exit
+    /// with a different status.
+    pub errors: u64,
+}
+
+impl Summary {
+
+    /// Counts a file that has been listed.
+    pub fn add(&mut self, file: &File) {
+        self.add_type(file.type_char(), file.metadata.len())
+    }
+
+    /// Adds all the counts from another summary to this one.
+    pub fn add_summary(&mut self, other: &Summary) {
+        self.files    += other.files;
+        self.dirs     += other.dirs;
+        self.links    += other.links;
+        self.pipes    += other.pipes;
+        self.sockets  += other.sockets;
+        self.devices  += other.devices;
+        self.others   += other.others;
+        self.size     += other.size;
+        self.errors   += other.errors;
+    }
+
+    fn add_type(&mut self, file_type: f::Type, size: u64) {
+        match file_type {
+            f::Type::File         => { self.files += 1; self.size += size; },
+            f::Type::Directory    => self.dirs += 1,
+            f::Type::Link         => self.links += 1,
+            f::Type::Pipe         => self.pipes += 1,
+            f::Type::Socket       => self.sockets += 1,
+            f::Type::CharDevice   |
+            f::Type::BlockDevice  => self.devices += 1,
+            f::Type::Special      => self.others += 1,
+        }
+    }
+
+    /// Renders this summary as a line such as “37 files, 4 dirs — 183.4M”.
+    /// Types of file that weren’t listed at all get left out, unless
+    /// nothing was listed at all, so there’s always something to say.
+    pub fn render(&self, colours: &Colours, size_format: SizeFormat, numerics: &NumericLocale) -> TextCell {
+        let counts = [
+            (self.files,    "file",    "files"),
+            (self.dirs,     "dir",     "dirs"),
+            (self.links,    "link",    "links"),
+            (self.pipes,    "pipe",    "pipes"),
+            (self.sockets,  "socket",  "sockets"),
+            (self.devices,  "device",  "devices"),
+            (self.others,   "other",   "others"),
+        ];
+
+        let mut cell = TextCell::default();
+
+        for (index, &(count, singular, plural)) in counts.iter().filter(|c| c.0 > 0).enumerate() {
+            if index > 0 {
+                cell.append(TextCell::paint_str(colours.punctuation, ", "));
+            }
+
+            let word = if count == 1 { singular } else { plural };
+            cell.append(TextCell::paint(Style::default(), numerics.format_int(count)));
+            cell.append(TextCell::paint(colours.punctuation, format!(" {}", word)));
+        }
+
+        if *cell.width == 0 {
+            cell.append(TextCell::paint_str(colours.punctuation, "0 files"));
+        }
+
+        if self.files > 0 {
+            cell.append(TextCell::paint_str(colours.punctuation, " — "));
+            cell.append(f::Size::Some(self.size).render(colours, size_format, numerics));
+        }
+
+        cell
+    }
+}
+
+
+/// The running total of every listing so far, for the footer that adds
+/// them all up at the end.
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+pub struct GrandTotal {
+
+    /// Every listing’s counts, added together.
+    pub total: Summary,
+
+    /// The number of listings that have been added.
+    pub listings: usize,
+}
+
+impl GrandTotal {
+
+    /// Adds another listing’s summary to the total.
+    pub fn add(&mut self, summary: &Summary) {
+        self.total.add_summary(summary);
+        self.listings += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rendered(summary: &Summary, size_format: SizeFormat) -> String {
+        let cell = summary.render(&Colours::plain(), size_format, &NumericLocale::english());
+        cell.strings().to_string()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(rendered(&Summary::default(), SizeFormat::DecimalBytes), "0 files");
+    }
+
+    #[test]
+    fn counts() {
+        let mut summary = Summary::default();
+        summary.add_type(f::Type::File, 1_000);
+        summary.add_type(f::Type::File, 2_500);
+        summary.add_type(f::Type::Directory, 4_096);
+        summary.add_type(f::Type::Link, 12);
+        summary.add_type(f::Type::Link, 12);
+
+        assert_eq!(summary.size, 3_500);
+        assert_eq!(rendered(&summary, SizeFormat::DecimalBytes), "2 files, 1 dir, 2 links — 3.5k");
+        assert_eq!(rendered(&summary, SizeFormat::JustBytes), "2 files, 1 dir, 2 links — 3,500");
+    }
+
+    #[test]
+    fn devices() {
+        let mut summary = Summary::default();
+        summary.add_type(f::Type::CharDevice, 0);
+        summary.add_type(f::Type::BlockDevice, 0);
+
+        assert_eq!(rendered(&summary, SizeFormat::DecimalBytes), "2 devices");
+    }
+
+    #[test]
+    fn add_summary() {
+        let mut first = Summary::default();
+        first.add_type(f::Type::File, 1_024);
+
+        let mut second = Summary::default();
+        second.add_type(f::Type::File, 1_024);
+        second.add_type(f::Type::Pipe, 0);
+
+        first.add_summary(&second);
+        assert_eq!(rendered(&first, SizeFormat::BinaryBytes), "2 files, 1 pipe — 2.0Ki");
+    }
+}
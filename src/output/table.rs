@@ -1,9 +1,14 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::fmt;
+use std::io::Error as IOError;
 use std::ops::Deref;
+use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard};
 
+use ansi_term::Style;
 use datetime::TimeZone;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use zoneinfo_compiled::{CompiledData, Result as TZResult};
 
 use locale;
@@ -12,8 +17,13 @@ use users::UsersCache;
 
 use style::Colours;
 use output::cell::TextCell;
+use output::error;
+use output::render::{Absent, TimeRender};
 use output::time::TimeFormat;
 use fs::{File, fields as f};
+use fs::feature::checksum::Algorithm as ChecksumAlgorithm;
+use fs::feature::GitBackend;
+use fs::feature::xattr::{self, Attribute};
 use fs::feature::git::GitCache;
 
 
@@ -23,6 +33,30 @@ pub struct Options {
     pub size_format: SizeFormat,
     pub time_format: TimeFormat,
     pub extra_columns: Columns,
+
+    /// Whether directories should show how many entries they contain in
+    /// the size column, rather than nothing.
+    pub dir_entries: bool,
+
+    /// Files bigger than this, in bytes, don’t get their checksums computed.
+    pub checksum_limit: Option<u64>,
+
+    /// Directories that searches for Git repositories stop before reaching.
+    pub git_ceilings: Vec<PathBuf>,
+
+    /// The string to put after each column, which is a single space unless
+    /// the user asked for something else.
+    pub separator: String,
+
+    /// Whether owner and group cells that are the same as the ones in the
+    /// row above get replaced with a ditto mark.
+    pub dedup: bool,
+
+    /// Whether owners and groups get shown by name or by number.
+    pub user_format: UserFormat,
+
+    /// How wide owner and group names can be before they get cut short.
+    pub owner_width: Option<OwnerWidth>,
 }
 
 // I had to make other types derive Debug,
@@ -40,12 +74,29 @@ pub struct Columns {
     /// At least one of these timestamps will be shown.
     pub time_types: TimeTypes,
 
+    /// Which of a file’s sizes to show.
+    pub size: SizeColumns,
+
+    /// Whether to show a file’s user, on its own or along with its group.
+    pub owner: OwnerColumns,
+
     // The rest are just on/off
     pub inode: bool,
     pub links: bool,
     pub blocks: bool,
     pub group: bool,
-    pub git: bool,
+    pub git: Option<GitBackend>,
+    pub volume: bool,
+    pub network: bool,
+    pub zone: bool,
+    pub my_access: bool,
+    pub signatures: bool,
+    pub version_info: bool,
+    pub content_type: bool,
+    pub checksum: Option<ChecksumAlgorithm>,
+
+    /// The order the user wants the columns to be in.
+    pub order: ColumnOrder,
 }
 
 impl Columns {
@@ -58,54 +109,116 @@ impl Columns {
 
         columns.push(Column::Permissions);
 
+        if self.my_access {
+            columns.push(Column::MyAccess);
+        }
+
         if self.links {
             columns.push(Column::HardLinks);
         }
 
-        columns.push(Column::FileSize);
+        if self.size != SizeColumns::Disk {
+            columns.push(Column::FileSize);
+        }
+
+        if self.size != SizeColumns::Logical {
+            columns.push(Column::DiskSize);
+        }
 
         if self.blocks {
             columns.push(Column::Blocks);
         }
 
-        columns.push(Column::User);
+        match self.owner {
+            OwnerColumns::User      => columns.push(Column::User),
+            OwnerColumns::Combined  => columns.push(Column::Owner),
+            OwnerColumns::None      => {},
+        }
 
-        if self.group {
+        if self.group && self.owner != OwnerColumns::Combined {
             columns.push(Column::Group);
         }
 
-        if self.time_types.modified {
-            columns.push(Column::Timestamp(TimeType::Modified));
+        columns.extend(self.time_types.iter().map(Column::Timestamp));
+
+        if self.volume {
+            columns.push(Column::Volume);
+        }
+
+        if self.network {
+            columns.push(Column::Network);
+        }
+
+        if self.zone {
+            columns.push(Column::Zone);
+        }
+
+        if self.content_type {
+            columns.push(Column::ContentType);
+        }
+
+        if let Some(algorithm) = self.checksum {
+            columns.push(Column::Checksum(algorithm));
         }
 
-        if self.time_types.created {
-            columns.push(Column::Timestamp(TimeType::Created));
+        if self.signatures {
+            columns.push(Column::Signature);
         }
 
-        if self.time_types.accessed {
-            columns.push(Column::Timestamp(TimeType::Accessed));
+        if self.version_info {
+            columns.push(Column::VersionInfo);
         }
 
-        if cfg!(feature="git") && self.git && actually_enable_git {
+        if cfg!(feature="git") && self.git.is_some() && actually_enable_git {
             columns.push(Column::GitStatus);
         }
 
+        // The sort is stable, so any columns the user didn’t mention stay
+        // in their usual order after the ones they did.
+        columns.sort_by_key(|c| self.order.position(c));
         columns
     }
 }
 
 
+/// The order of the columns the user asked for, as a list of the names of
+/// columns, such as `size` or `modified`. Columns that aren’t being shown
+/// can still be named; they just get skipped over.
+#[derive(PartialEq, Debug, Default, Clone)]
+pub struct ColumnOrder(pub Vec<&'static str>);
+
+impl ColumnOrder {
+
+    /// Where in the order the given column should go. Columns that weren’t
+    /// mentioned go at the end.
+    fn position(&self, column: &Column) -> usize {
+        let name = column.name();
+        self.0.iter().position(|n| *n == name).unwrap_or(self.0.len())
+    }
+}
+
+
 /// A table contains these.
 #[derive(Debug)]
 pub enum Column {
     Permissions,
+    MyAccess,
     FileSize,
+    DiskSize,
     Timestamp(TimeType),
     Blocks,
     User,
     Group,
+    Owner,
     HardLinks,
     Inode,
+    Volume,
+    Network,
+    Zone,
+    ContentType,
+    Checksum(ChecksumAlgorithm),
+    Signature,
+    VersionInfo,
     GitStatus,
 }
 
@@ -122,6 +235,7 @@ impl Column {
     pub fn alignment(&self) -> Alignment {
         match *self {
             Column::FileSize
+            | Column::DiskSize
             | Column::HardLinks
             | Column::Inode
             | Column::Blocks
@@ -130,24 +244,96 @@ impl Column {
         }
     }
 
+    /// Get the name that the user can refer to this column by when giving
+    /// the order of the columns.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::Permissions                   => "permissions",
+            Column::MyAccess                      => "access",
+            Column::FileSize                      => "size",
+            Column::DiskSize                      => "disk",
+            Column::Timestamp(TimeType::Modified) => "modified",
+            Column::Timestamp(TimeType::Changed)  => "changed",
+            Column::Timestamp(TimeType::Created)  => "created",
+            Column::Timestamp(TimeType::Accessed) => "accessed",
+            Column::Blocks                        => "blocks",
+            Column::User                          => "user",
+            Column::Group                         => "group",
+            Column::Owner                         => "owner",
+            Column::HardLinks                     => "links",
+            Column::Inode                         => "inode",
+            Column::Volume                        => "volume",
+            Column::Network                       => "network",
+            Column::Zone                          => "zone",
+            Column::ContentType                   => "type",
+            Column::Checksum(_)                   => "checksum",
+            Column::Signature                     => "signature",
+            Column::VersionInfo                   => "version",
+            Column::GitStatus                     => "git",
+        }
+    }
+
     /// Get the text that should be printed at the top, when the user elects
     /// to have a header row printed.
     pub fn header(&self) -> &'static str {
         match *self {
             Column::Permissions   => "Permissions",
+            Column::MyAccess      => "Access",
             Column::FileSize      => "Size",
+            Column::DiskSize      => "Disk",
             Column::Timestamp(t)  => t.header(),
             Column::Blocks        => "Blocks",
             Column::User          => "User",
             Column::Group         => "Group",
+            Column::Owner         => "Owner",
             Column::HardLinks     => "Links",
             Column::Inode         => "inode",
+            Column::Volume        => "Volume",
+            Column::Network       => "Net",
+            Column::Zone          => "Zone",
+            Column::ContentType   => "Type",
+            Column::Checksum(a)   => a.header(),
+            Column::Signature     => "Signature",
+            Column::VersionInfo   => "Version",
             Column::GitStatus     => "Git",
         }
     }
 }
 
 
+/// How a file’s user gets shown. Its group only gets shown as well when
+/// the user asks for it with `--group`, unless they’re shown together.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum OwnerColumns {
+
+    /// Show the user in its own column, and the group in another.
+    User,
+
+    /// Show the user and group in one column, as `user:group`.
+    Combined,
+
+    /// Don’t show the user at all.
+    None,
+}
+
+
+/// Which of a file’s sizes get shown. The *logical* size is the number of
+/// bytes in the file, and the *disk* size is the amount of space it takes
+/// up on the filesystem, which can be smaller for compressed or sparse
+/// files, and is usually larger for small ones.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum SizeColumns {
+
+    /// Show only the logical size, in the “Size” column.
+    Logical,
+
+    /// Show only the disk size, in the “Disk” column.
+    Disk,
+
+    /// Show both sizes, logical first.
+    Both,
+}
+
 /// Formatting options for file sizes.
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum SizeFormat {
@@ -170,6 +356,65 @@ impl Default for SizeFormat {
     }
 }
 
+/// How to show the owners and groups of files.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum UserFormat {
+
+    /// Look up their names, showing the ID when there isn’t one.
+    Name,
+
+    /// Just show their IDs, like `ls -n`, without looking anything up.
+    Numeric,
+}
+
+/// The most columns that an owner or group name can take up, so that one
+/// long account name doesn’t make the whole table wider.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct OwnerWidth {
+
+    /// The widest a name can be, including the ellipsis.
+    pub max: usize,
+
+    /// What to end names that got cut short with: `…`, or `~` when the
+    /// console can’t display that.
+    pub ellipsis: &'static str,
+}
+
+impl OwnerWidth {
+
+    /// Cuts a name down to the maximum width, if it’s wider, ending it with
+    /// the ellipsis. Widths are display widths, so a double-width character
+    /// that doesn’t fit gets left out whole. Zero-width characters, such as
+    /// combining accents, stay with the character before them, as does the
+    /// character after a zero-width joiner, so joined emoji stay together.
+    /// This isn’t full grapheme segmentation: a regional-indicator flag,
+    /// a Hangul syllable spelt out in jamo, or a letter with an Indic
+    /// spacing mark can still get cut in two.
+    pub fn truncate(self, name: String) -> String {
+        if UnicodeWidthStr::width(&*name) <= self.max {
+            return name;
+        }
+
+        let room = self.max.saturating_sub(UnicodeWidthStr::width(self.ellipsis));
+        let mut width = 0;
+        let mut end = name.len();
+        let mut joined = false;
+
+        for (index, c) in name.char_indices() {
+            let char_width = if joined { 0 } else { UnicodeWidthChar::width(c).unwrap_or(0) };
+            if width + char_width > room {
+                end = index;
+                break;
+            }
+
+            width += char_width;
+            joined = c == '\u{200D}';
+        }
+
+        format!("{}{}", &name[.. end], self.ellipsis)
+    }
+}
+
 
 /// The types of a file’s time fields. These three fields are standard
 /// across most (all?) operating systems.
@@ -182,7 +427,10 @@ pub enum TimeType {
     /// The file’s modified time (`st_mtime`).
     Modified,
 
-    /// The file’s creation time (`st_ctime`).
+    /// The time the file’s status last changed (`st_ctime`).
+    Changed,
+
+    /// The file’s creation time (`stx_btime`), which isn’t always known.
     Created,
 }
 
@@ -193,30 +441,72 @@ impl TimeType {
         match self {
             TimeType::Accessed  => "Date Accessed",
             TimeType::Modified  => "Date Modified",
+            TimeType::Changed   => "Date Changed",
             TimeType::Created   => "Date Created",
         }
     }
+
+    /// Gets this time field from the given file, if it has one.
+    pub fn of(self, file: &File) -> Option<f::Time> {
+        match self {
+            TimeType::Accessed  => file.accessed_time(),
+            TimeType::Modified  => file.modified_time(),
+            TimeType::Changed   => file.changed_time(),
+            TimeType::Created   => file.created_time(),
+        }
+    }
 }
 
 
-/// Fields for which of a file’s time fields should be displayed in the
-/// columns output.
+/// Which of a file’s time fields should be displayed in the columns
+/// output, in the order their columns go in, which is the order they were
+/// asked for in. Each one only appears once.
 ///
 /// There should always be at least one of these--there's no way to disable
 /// the time columns entirely (yet).
-#[derive(PartialEq, Debug, Copy, Clone)]
-pub struct TimeTypes {
-    pub accessed: bool,
-    pub modified: bool,
-    pub created:  bool,
-}
+#[derive(PartialEq, Debug, Clone)]
+pub struct TimeTypes(Vec<TimeType>);
 
 impl Default for TimeTypes {
 
     /// By default, display just the ‘modified’ time. This is the most
     /// common option, which is why it has this shorthand.
     fn default() -> TimeTypes {
-        TimeTypes { accessed: false, modified: true, created: false }
+        TimeTypes(vec![ TimeType::Modified ])
+    }
+}
+
+impl TimeTypes {
+
+    /// Every time field, in the order their columns go in when they’re
+    /// all shown at once.
+    pub fn all() -> TimeTypes {
+        TimeTypes(vec![ TimeType::Modified, TimeType::Changed, TimeType::Created, TimeType::Accessed ])
+    }
+
+    /// The given time fields, in the given order, leaving out any that
+    /// have already been given, or the default if none were.
+    pub fn from_list<I: IntoIterator<Item=TimeType>>(types: I) -> TimeTypes {
+        let mut list = Vec::new();
+        for time_type in types {
+            if !list.contains(&time_type) {
+                list.push(time_type);
+            }
+        }
+
+        if list.is_empty() { TimeTypes::default() }
+                      else { TimeTypes(list) }
+    }
+
+    /// The timestamp to use when only one of them can be, such as when
+    /// sorting by “the time”, which is the first one that gets listed.
+    pub fn first(&self) -> TimeType {
+        self.0[0]
+    }
+
+    /// The time fields, in order.
+    pub fn iter(&self) -> impl Iterator<Item=TimeType> + '_ {
+        self.0.iter().cloned()
     }
 }
 
@@ -275,7 +565,28 @@ pub struct Table<'a> {
     widths: TableWidths,
     time_format: &'a TimeFormat,
     size_format: SizeFormat,
+    dir_entries: bool,
+    checksum_limit: Option<u64>,
     git: Option<&'a GitCache>,
+    separator: &'a str,
+    dedup: bool,
+    user_format: UserFormat,
+    owner_width: Option<OwnerWidth>,
+
+    /// The files that couldn’t be read to compute their checksums, which
+    /// get reported once the whole table has been filled in.
+    checksum_errors: Mutex<Vec<(PathBuf, IOError)>>,
+
+    /// Cache of the mount point of each device, as almost every file in a
+    /// listing is on the same one.
+    volumes: Mutex<HashMap<u64, Option<PathBuf>>>,
+
+    /// Cache of whether each device is on the network, for the same reason.
+    locations: Mutex<HashMap<u64, f::Location>>,
+
+    /// Cache of whether each device records files’ owners, for the same
+    /// reason again.
+    owned_devices: Mutex<HashMap<u64, bool>>,
 }
 
 #[derive(Clone)]
@@ -283,6 +594,14 @@ pub struct Row {
     cells: Vec<TextCell>,
 }
 
+impl Row {
+
+    /// The cells of this row, one for each column, before any padding.
+    pub fn into_cells(self) -> Vec<TextCell> {
+        self.cells
+    }
+}
+
 impl<'a, 'f> Table<'a> {
     pub fn new(options: &'a Options, git: Option<&'a GitCache>, colours: &'a Colours) -> Table<'a> {
         let columns = options.extra_columns.collect(git.is_some());
@@ -293,11 +612,23 @@ impl<'a, 'f> Table<'a> {
             env:         &options.env,
             time_format: &options.time_format,
             size_format:  options.size_format,
+            dir_entries:  options.dir_entries,
+            checksum_limit:  options.checksum_limit,
+            separator:      &options.separator,
+            dedup:          options.dedup,
+            user_format:    options.user_format,
+            owner_width:    options.owner_width,
+            checksum_errors: Mutex::new(Vec::new()),
+            volumes:      Mutex::new(HashMap::new()),
+            locations:    Mutex::new(HashMap::new()),
+            owned_devices: Mutex::new(HashMap::new()),
         }
     }
 
-    pub fn widths(&self) -> &TableWidths {
-        &self.widths
+    /// The Git cache that this table reads statuses from, if there’s a
+    /// repository to read them from.
+    pub fn git(&self) -> Option<&'a GitCache> {
+        self.git
     }
 
     pub fn header_row(&self) -> Row {
@@ -308,7 +639,7 @@ impl<'a, 'f> Table<'a> {
         Row { cells }
     }
 
-    pub fn row_for_file(&self, file: &File, xattrs: bool) -> Row {
+    pub fn row_for_file(&self, file: &File, xattrs: &[Attribute]) -> Row {
         let cells = self.columns.iter()
                         .map(|c| self.display(file, c, xattrs))
                         .collect();
@@ -320,55 +651,280 @@ impl<'a, 'f> Table<'a> {
         self.widths.add_widths(row)
     }
 
-    fn permissions_plus(&self, file: &File, xattrs: bool) -> f::PermissionsPlus {
+    fn permissions_plus(&self, file: &File, xattrs: &[Attribute]) -> f::PermissionsPlus {
         f::PermissionsPlus {
             file_type: file.type_char(),
             permissions: file.permissions(),
-            xattrs,
+            xattrs: xattrs.iter().any(|a| !a.is_acl()),
+            acl: xattr::has_acl(xattrs),
         }
     }
 
-    fn display(&self, file: &File, column: &Column, xattrs: bool) -> TextCell {
-        use output::table::TimeType::*;
-
+    /// Renders the cell for one column of a file’s row.
+    pub fn display(&self, file: &File, column: &Column, xattrs: &[Attribute]) -> TextCell {
         match *column {
+            Column::Permissions if !self.records_owners(file)  => self.unowned_permissions(file),
+            Column::User | Column::Group | Column::Owner if !self.records_owners(file)  => self.colours.absent(),
+
             Column::Permissions    => self.permissions_plus(file, xattrs).render(self.colours),
-            Column::FileSize       => file.size().render(self.colours, self.size_format, &self.env.numeric),
+            Column::MyAccess       => file.my_access().render(self.colours),
+            Column::FileSize       => self.file_size(file).render(self.colours, self.size_format, &self.env.numeric),
+            Column::DiskSize       => file.disk_size().render(self.colours, self.size_format, &self.env.numeric),
             Column::HardLinks      => file.links().render(self.colours, &self.env.numeric),
             Column::Inode          => file.inode().render(self.colours.inode),
             Column::Blocks         => file.blocks().render(self.colours),
-            Column::User           => file.user().render(self.colours, &*self.env.lock_users()),
-            Column::Group          => file.group().render(self.colours, &*self.env.lock_users()),
-            Column::GitStatus      => self.git_status(file).render(self.colours),
+            Column::User           => file.user().render(self.colours, &*self.env.lock_users(), self.user_format, self.owner_width),
+            Column::Group          => file.group().render(self.colours, &*self.env.lock_users(), self.user_format, self.owner_width),
+            Column::Owner          => self.owner(file),
+            Column::Volume         => self.volume(file).render(self.colours),
+            Column::Network        => self.location(file).render(self.colours),
+            Column::Zone           => file.zone().render(self.colours),
+            Column::ContentType    => file.content_type().render(self.colours),
+            Column::Checksum(a)    => self.checksum(file, a).render(self.colours),
+            Column::Signature      => file.signature().render(self.colours),
+            Column::VersionInfo    => file.version_info().render(self.colours),
+            Column::GitStatus      => match self.git_status(file) {
+                Some(git)  => git.render(self.colours),
+                None       => self.colours.absent(),
+            },
+            Column::Timestamp(t)   => self.time(file, t).render(self.colours.date, self.colours, &self.env.tz, self.time_format),
+        }
+    }
+
+    /// Renders a file’s user and group in one cell, with a colon between
+    /// them, each in the style it would have in its own column.
+    fn owner(&self, file: &File) -> TextCell {
+        let users = self.env.lock_users();
+        let mut cell = file.user().render(self.colours, &*users, self.user_format, self.owner_width);
+        cell.push(self.colours.punctuation.paint(":"), 1);
+        cell.append(file.group().render(self.colours, &*users, self.user_format, self.owner_width));
+        cell
+    }
+
+    /// Renders the line that goes above the table, like the one `ls -l`
+    /// prints, adding up how much space the given files take up on disk.
+    pub fn total_line(&self, files: &[File]) -> TextCell {
+        let total = files.iter().map(File::allocated_size).sum();
+
+        let mut cell = TextCell::paint_str(Style::default(), "total ");
+        cell.append(f::Size::Some(total).render(self.colours, self.size_format, &self.env.numeric));
+        cell
+    }
 
-            Column::Timestamp(Modified)  => file.modified_time().render(self.colours.date, &self.env.tz, &self.time_format),
-            Column::Timestamp(Created)   => file.created_time() .render(self.colours.date, &self.env.tz, &self.time_format),
-            Column::Timestamp(Accessed)  => file.accessed_time().render(self.colours.date, &self.env.tz, &self.time_format),
+    fn checksum(&self, file: &File, algorithm: ChecksumAlgorithm) -> f::Checksum {
+        match file.checksum(algorithm, self.checksum_limit) {
+            Ok(checksum)  => checksum,
+            Err(e)        => {
+                self.checksum_errors.lock().unwrap().push((file.path.clone(), e));
+                f::Checksum::Error
+            }
         }
     }
 
-    fn git_status(&self, file: &File) -> f::Git {
+    /// Prints the files that couldn’t be read to compute their checksums to
+    /// standard error. The rows get filled in by several threads at once, so
+    /// the files get sorted first to keep the output the same every time.
+    pub fn report_checksum_errors(&self) {
+        let mut errors = self.checksum_errors.lock().unwrap();
+        errors.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (path, e) in errors.drain(..) {
+            error::report(&path, &e);
+        }
+    }
+
+    /// The size to display for a file. Directories only get their entries
+    /// counted when the user asks, as it means reading each one.
+    fn file_size(&self, file: &File) -> f::Size {
+        if self.dir_entries && file.is_directory() {
+            file.entry_count().map_or(f::Size::None, f::Size::Entries)
+        }
+        else {
+            file.size()
+        }
+    }
+
+    fn volume(&self, file: &File) -> f::Volume {
+        let mount_point = self.volumes.lock().unwrap()
+                              .entry(file.device())
+                              .or_insert_with(|| file.mount_point())
+                              .clone();
+
+        f::Volume { mount_point, is_mount_point: file.is_mount_point() }
+    }
+
+    fn records_owners(&self, file: &File) -> bool {
+        *self.owned_devices.lock().unwrap()
+             .entry(file.device())
+             .or_insert_with(|| file.records_owners())
+    }
+
+    /// Renders the permissions of a file whose filesystem doesn’t record
+    /// them: just its type, and a placeholder for the rest.
+    fn unowned_permissions(&self, file: &File) -> TextCell {
+        let mut cell = TextCell::default();
+        cell.push(file.type_char().render(self.colours), 1);
+        cell.append(self.colours.absent());
+        cell
+    }
+
+    fn location(&self, file: &File) -> f::Location {
+        *self.locations.lock().unwrap()
+             .entry(file.device())
+             .or_insert_with(|| file.location())
+    }
+
+    /// One of the file’s times, leaving out the ones that FAT and exFAT
+    /// only give because they didn’t record them.
+    fn time(&self, file: &File, time_type: TimeType) -> Option<f::Time> {
+        recorded(time_type.of(file), || file.is_on_fat())
+    }
+
+    /// The file’s Git status, or `None` if it isn’t in a repository, so
+    /// there’s no status to show.
+    fn git_status(&self, file: &File) -> Option<f::Git> {
         debug!("Getting Git status for file {:?}", file.path);
         self.git
+            .filter(|g| g.has_anything_for(&file.path))
             .map(|g| g.get(&file.path, file.is_directory()))
-            .unwrap_or_default()
     }
 
     pub fn render(&self, row: Row) -> TextCell {
-        let mut cell = TextCell::default();
+        render_cells(row, &self.widths, self.alignments().into_iter(), self.separator)
+    }
+
+    /// The alignment of each of this table’s columns, in order. Timestamps
+    /// that are printed as numbers get aligned to the right, like other
+    /// numbers.
+    pub fn alignments(&self) -> Vec<Alignment> {
+        self.columns.iter().map(|column| match *column {
+            Column::Timestamp(_) if self.time_format.is_numeric()  => Alignment::Right,
+            _                                                      => column.alignment(),
+        }).collect()
+    }
 
-        for (n, (this_cell, width)) in row.cells.into_iter().zip(self.widths.iter()).enumerate() {
-            let padding = width - *this_cell.width;
+    /// Whether rows should be passed through `dedup` before being rendered.
+    pub fn dedups(&self) -> bool {
+        self.dedup
+    }
+
+    /// Replaces the owner and group cells that are the same as the ones in
+    /// the row above with a dimmed ditto mark. The column widths have
+    /// already been worked out from the full values, so this doesn’t move
+    /// any of the other columns.
+    pub fn dedup(&self, row: &mut Row, above: &Row) {
+        for (n, column) in self.columns.iter().enumerate() {
+            let is_owner = matches!(*column, Column::User | Column::Group | Column::Owner);
 
-            match self.columns[n].alignment() {
-                Alignment::Left  => { cell.append(this_cell); cell.add_spaces(padding); }
-                Alignment::Right => { cell.add_spaces(padding); cell.append(this_cell); }
+            if is_owner && row.cells.get(n) == above.cells.get(n) {
+                row.cells[n] = TextCell::paint_str(self.colours.punctuation, "\"");
             }
+        }
+    }
+
+    /// Renders the space taken up by a row without any cells, such as one
+    /// for an extended attribute, so whatever comes after it lines up with
+    /// the file names. The separators get kept rather than turned into
+    /// spaces, as a tab’s width depends on where it is in the line.
+    pub fn render_blank(&self) -> TextCell {
+        render_blank(&self.widths, self.separator)
+    }
+
+    /// Keeps what’s needed to render rows after the table itself has gone,
+    /// so that rows can be printed once other tables have been filled in.
+    pub fn into_layout(self) -> Layout {
+        Layout {
+            names:      self.columns.iter().map(Column::name).collect(),
+            alignments: self.alignments(),
+            widths:     self.widths,
+            separator:  self.separator.to_owned(),
+        }
+    }
+}
+
+/// Leaves out a time at the start of 1980 if it’s on a FAT filesystem. The
+/// filesystem only gets looked up for the times that could be one of these.
+fn recorded(time: Option<f::Time>, is_fat: impl FnOnce() -> bool) -> Option<f::Time> {
+    match time {
+        Some(t) if t.is_fat_epoch() && is_fat()  => None,
+        _                                        => time,
+    }
+}
+
+fn render_cells<A: Iterator<Item=Alignment>>(row: Row, widths: &[usize], alignments: A, separator: &str) -> TextCell {
+    let mut cell = TextCell::default();
 
-            cell.add_spaces(1);
+    for ((this_cell, width), alignment) in row.cells.into_iter().zip(widths.iter()).zip(alignments) {
+        let padding = width - *this_cell.width;
+
+        match alignment {
+            Alignment::Left  => { cell.append(this_cell); cell.add_spaces(padding); }
+            Alignment::Right => { cell.add_spaces(padding); cell.append(this_cell); }
         }
 
-        cell
+        add_separator(&mut cell, separator);
+    }
+
+    cell
+}
+
+fn render_blank(widths: &[usize], separator: &str) -> TextCell {
+    let mut cell = TextCell::default();
+
+    for width in widths {
+        cell.add_spaces(*width);
+        add_separator(&mut cell, separator);
+    }
+
+    cell
+}
+
+fn add_separator(cell: &mut TextCell, separator: &str) {
+    cell.append(TextCell::paint(Style::default(), separator.to_owned()));
+}
+
+
+/// The columns of a table that has been filled in, without any of the
+/// files, for rendering its rows later on.
+pub struct Layout {
+    names: Vec<&'static str>,
+    alignments: Vec<Alignment>,
+    widths: TableWidths,
+    separator: String,
+}
+
+impl Layout {
+    pub fn render(&self, row: Row) -> TextCell {
+        render_cells(row, &self.widths, self.alignments.iter().cloned(), &self.separator)
+    }
+
+    pub fn render_blank(&self) -> TextCell {
+        render_blank(&self.widths, &self.separator)
+    }
+}
+
+
+/// The widest that each column has been in any of several tables, which
+/// lets them all get printed with the same widths. The columns are matched
+/// up by name, as a table can have a column that the others don’t, such as
+/// the Git column for a directory in a repository.
+#[derive(Default)]
+pub struct SharedWidths(HashMap<&'static str, usize>);
+
+impl SharedWidths {
+    pub fn add(&mut self, layout: &Layout) {
+        for (name, width) in layout.names.iter().zip(layout.widths.iter()) {
+            let shared = self.0.entry(name).or_insert(0);
+            *shared = max(*shared, *width);
+        }
+    }
+
+    pub fn apply(&self, layout: &mut Layout) {
+        for (name, width) in layout.names.iter().zip(layout.widths.0.iter_mut()) {
+            if let Some(shared) = self.0.get(name) {
+                *width = *shared;
+            }
+        }
     }
 }
 
@@ -394,8 +950,280 @@ impl TableWidths {
             *old_width = max(*old_width, *cell.width);
         }
     }
+}
+
 
-    pub fn total(&self) -> usize {
-        self.0.len() + self.0.iter().sum::<usize>()
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(columns: &Columns) -> Vec<&'static str> {
+        columns.collect(false).iter().map(Column::name).collect()
+    }
+
+    fn columns(order: &[&'static str]) -> Columns {
+        Columns {
+            time_types: TimeTypes::default(),
+            size: SizeColumns::Logical,
+            owner: OwnerColumns::User,
+            inode: false,
+            links: true,
+            blocks: false,
+            group: true,
+            git: None,
+            volume: false,
+            network: false,
+            zone: false,
+            my_access: false,
+            signatures: false,
+            version_info: false,
+            content_type: false,
+            checksum: None,
+            order: ColumnOrder(order.to_vec()),
+        }
+    }
+
+    #[test]
+    fn default_order() {
+        assert_eq!(names(&columns(&[])), vec![ "permissions", "links", "size", "user", "group", "modified" ]);
+    }
+
+    #[test]
+    fn reordered() {
+        let order = [ "modified", "permissions", "links", "size", "user", "group" ];
+        assert_eq!(names(&columns(&order)), order.to_vec());
+    }
+
+    #[test]
+    fn partly_reordered() {
+        assert_eq!(names(&columns(&[ "size", "user" ])), vec![ "size", "user", "permissions", "links", "group", "modified" ]);
+    }
+
+    #[test]
+    fn combined_owner() {
+        let columns = Columns { owner: OwnerColumns::Combined, ..columns(&[]) };
+        assert_eq!(names(&columns), vec![ "permissions", "links", "size", "owner", "modified" ]);
+    }
+
+    #[test]
+    fn combined_owner_without_group() {
+        let columns = Columns { owner: OwnerColumns::Combined, group: false, ..columns(&[ "owner" ]) };
+        assert_eq!(names(&columns), vec![ "owner", "permissions", "links", "size", "modified" ]);
+    }
+
+    #[test]
+    fn no_owner() {
+        let columns = Columns { owner: OwnerColumns::None, ..columns(&[]) };
+        assert_eq!(names(&columns), vec![ "permissions", "links", "size", "group", "modified" ]);
+    }
+
+    #[test]
+    fn changed_after_modified() {
+        let mut columns = columns(&[]);
+        columns.time_types = TimeTypes::from_list(vec![ TimeType::Modified, TimeType::Changed, TimeType::Accessed ]);
+        assert_eq!(names(&columns), vec![ "permissions", "links", "size", "user", "group", "modified", "changed", "accessed" ]);
+    }
+
+    #[test]
+    fn times_in_the_order_given() {
+        let mut columns = columns(&[]);
+        columns.time_types = TimeTypes::from_list(vec![ TimeType::Accessed, TimeType::Created ]);
+        assert_eq!(names(&columns), vec![ "permissions", "links", "size", "user", "group", "accessed", "created" ]);
+    }
+
+    #[test]
+    fn hidden_columns_skipped() {
+        assert_eq!(names(&columns(&[ "inode", "group" ])), vec![ "group", "permissions", "links", "size", "user", "modified" ]);
+    }
+
+
+    mod shared_widths {
+        use super::*;
+
+        fn row(cells: &[&str]) -> Row {
+            Row { cells: cells.iter().map(|c| TextCell::paint(Style::default(), c.to_string())).collect() }
+        }
+
+        /// Lays out a table with the given columns, fitting the given rows.
+        fn layout(names: &[&'static str], rows: &[Row]) -> Layout {
+            let mut widths = TableWidths::zero(names.len());
+            for row in rows {
+                widths.add_widths(row);
+            }
+
+            Layout {
+                names:      names.to_vec(),
+                alignments: names.iter().map(|n| if *n == "size" { Alignment::Right } else { Alignment::Left }).collect(),
+                widths,
+                separator:  " ".to_owned(),
+            }
+        }
+
+        fn render(layout: &Layout, row: Row) -> String {
+            layout.render(row).strings().to_string()
+        }
+
+        #[test]
+        fn same_offsets() {
+            let short = [ row(&[ "4", "ben", "staff" ]) ];
+            let long  = [ row(&[ "1.2G", "a-very-long-user-name", "wheel" ]), row(&[ "-", "root", "a-long-group" ]) ];
+
+            let mut first = layout(&[ "size", "user", "group" ], &short);
+            let mut second = layout(&[ "size", "user", "group" ], &long);
+
+            let mut widths = SharedWidths::default();
+            widths.add(&first);
+            widths.add(&second);
+            widths.apply(&mut first);
+            widths.apply(&mut second);
+
+            assert_eq!(render(&first, row(&[ "4", "ben", "staff" ])),
+                       "   4 ben                   staff        ");
+            assert_eq!(render(&second, row(&[ "1.2G", "a-very-long-user-name", "wheel" ])),
+                       "1.2G a-very-long-user-name wheel        ");
+            assert_eq!(first.render_blank().width(), second.render_blank().width());
+        }
+
+        #[test]
+        fn unshared_columns_keep_their_widths() {
+            let mut first = layout(&[ "size", "git" ], &[ row(&[ "4", "-N" ]) ]);
+            let mut second = layout(&[ "size" ], &[ row(&[ "1.2G" ]) ]);
+
+            let mut widths = SharedWidths::default();
+            widths.add(&first);
+            widths.add(&second);
+            widths.apply(&mut first);
+            widths.apply(&mut second);
+
+            assert_eq!(render(&first, row(&[ "4", "-N" ])), "   4 -N ");
+            assert_eq!(render(&second, row(&[ "1.2G" ])), "1.2G ");
+        }
+    }
+
+
+    mod timestamps {
+        use super::*;
+        use fs::test_dir::TempDir;
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::Path;
+        use libc;
+
+        /// 3rd March 2003, long enough ago that it can’t be the time the
+        /// test file’s status last changed.
+        const MODIFIED: f::time_t = 1_046_649_600;
+
+        fn set_modified_time(path: &Path) {
+            let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+            let times = [ libc::timeval { tv_sec: MODIFIED, tv_usec: 0 }; 2 ];
+            assert_eq!(unsafe { libc::utimes(c_path.as_ptr(), times.as_ptr()) }, 0);
+        }
+
+        #[test]
+        fn each_column_reads_its_own_field() {
+            let temp = TempDir::new("timestamps");
+            let path = temp.touch("file");
+            set_modified_time(&path);
+
+            let file = File::new(path, None, None).unwrap();
+            let modified = TimeType::Modified.of(&file).unwrap();
+            let changed  = TimeType::Changed.of(&file).unwrap();
+
+            assert_eq!(modified.seconds, MODIFIED);
+            assert!(changed.seconds > MODIFIED);
+        }
+
+        /// 15th June 2010 and 9th September 2001, both at midday, so
+        /// they’re on those days whatever the time zone.
+        const ACCESSED_2010: f::time_t = 1_276_603_200;
+        const MODIFIED_2001: f::time_t = 1_000_036_800;
+
+        #[test]
+        fn two_columns_labelled() {
+            let temp = TempDir::new("two-timestamps");
+            let path = temp.touch("restored");
+            let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+            let times = [ libc::timeval { tv_sec: ACCESSED_2010, tv_usec: 0 }, libc::timeval { tv_sec: MODIFIED_2001, tv_usec: 0 } ];
+            assert_eq!(unsafe { libc::utimes(c_path.as_ptr(), times.as_ptr()) }, 0);
+
+            let mut extra_columns = columns(&[]);
+            extra_columns.time_types = TimeTypes::from_list(vec![ TimeType::Accessed, TimeType::Modified ]);
+
+            let options = Options {
+                env: Environment { tz: None, numeric: locale::Numeric::english(), users: Mutex::new(UsersCache::new()) },
+                size_format: SizeFormat::DecimalBytes,
+                time_format: TimeFormat::LongISO,
+                extra_columns,
+                dir_entries: false,
+                checksum_limit: None,
+                git_ceilings: Vec::new(),
+                separator: " ".into(),
+                dedup: false,
+                user_format: UserFormat::Name,
+                owner_width: None,
+            };
+
+            let colours = Colours::plain();
+            let table = Table::new(&options, None, &colours);
+            let file = File::new(path, None, None).unwrap();
+            let header = table.header_row().cells.iter().map(|c| c.plain_text()).collect::<Vec<_>>();
+            let row = table.row_for_file(&file, &[]).cells.iter().map(|c| c.plain_text()).collect::<Vec<_>>();
+
+            let accessed = header.iter().position(|h| h == "Date Accessed").unwrap();
+            let modified = header.iter().position(|h| h == "Date Modified").unwrap();
+            assert_eq!(accessed + 1, modified);
+            assert!(row[accessed].starts_with("2010-06-15"), "{:?}", row);
+            assert!(row[modified].starts_with("2001-09-09"), "{:?}", row);
+        }
+
+        #[test]
+        fn headers() {
+            assert_eq!(Column::Timestamp(TimeType::Modified).header(), "Date Modified");
+            assert_eq!(Column::Timestamp(TimeType::Changed).header(),  "Date Changed");
+            assert_eq!(Column::Timestamp(TimeType::Created).header(),  "Date Created");
+        }
+    }
+
+
+    #[test]
+    fn joined_emoji_stay_together() {
+        let width = OwnerWidth { max: 4, ellipsis: "…" };
+        assert_eq!(width.truncate("👩\u{200D}💻dev".into()), "👩\u{200D}💻d…");
+    }
+
+
+    mod recorded_times {
+        use super::*;
+
+        const START_OF_1980: f::Time = f::Time { seconds: 315_532_800, nanoseconds: 0 };
+
+        #[test]
+        fn zero_filetime() {
+            assert_eq!(recorded(f::Time::from_raw(-11_644_473_600, 0), || true), None);
+            assert_eq!(recorded(f::Time::from_raw(-11_644_473_600, 0), || false), None);
+        }
+
+        #[test]
+        fn start_of_1970() {
+            let epoch = f::Time { seconds: 0, nanoseconds: 0 };
+            assert_eq!(recorded(f::Time::from_raw(0, 0), || false), Some(epoch));
+            assert_eq!(recorded(f::Time::from_raw(0, 0), || true), Some(epoch));
+        }
+
+        #[test]
+        fn start_of_1980_on_fat() {
+            assert_eq!(recorded(Some(START_OF_1980), || true), None);
+        }
+
+        #[test]
+        fn start_of_1980_elsewhere() {
+            assert_eq!(recorded(Some(START_OF_1980), || false), Some(START_OF_1980));
+        }
+
+        #[test]
+        fn other_times_on_fat() {
+            let time = f::Time { seconds: 1_500_000_000, nanoseconds: 0 };
+            assert_eq!(recorded(Some(time), || panic!("looked up the filesystem")), Some(time));
+        }
     }
 }
@@ -0,0 +1,240 @@
+//! Output templates, such as `{name}\t{size}\t{modified}`, which say what to
+//! print for each file when `--format` is given.
+//!
+//! A template is made of literal text and fields in braces. The text can
+//! use the escapes `\t`, `\n`, and `\\`, and `\{` and `\}` for braces that
+//! aren’t part of a field. Templates get checked when they’re parsed, so a
+//! misspelt field is an error rather than something that prints nothing.
+
+use std::fmt;
+
+
+/// One of the things about a file that a template can print.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Field {
+    Name,
+    Path,
+    Size,
+    SizeBytes,
+    Modified,
+    Created,
+    Owner,
+    Permissions,
+    Type,
+    Target,
+    Git,
+}
+
+impl Field {
+
+    /// Every field, in the order they get listed in error messages.
+    pub const ALL: &'static [Field] = &[
+        Field::Name, Field::Path, Field::Size, Field::SizeBytes, Field::Modified,
+        Field::Created, Field::Owner, Field::Permissions, Field::Type,
+        Field::Target, Field::Git,
+    ];
+
+    /// The name that goes between the braces to print this field.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Name         => "name",
+            Field::Path         => "path",
+            Field::Size         => "size",
+            Field::SizeBytes    => "size_bytes",
+            Field::Modified     => "modified",
+            Field::Created      => "created",
+            Field::Owner        => "owner",
+            Field::Permissions  => "permissions",
+            Field::Type         => "type",
+            Field::Target       => "target",
+            Field::Git          => "git",
+        }
+    }
+}
+
+
+/// A piece of a template: either text that gets printed as it is, or a
+/// field that gets filled in for each file.
+#[derive(PartialEq, Debug, Clone)]
+enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+
+/// A parsed template, ready to be expanded for each file.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl Template {
+
+    /// Parses a template, failing if it has a field that doesn’t exist, a
+    /// brace that doesn’t match up, or an escape that isn’t one of the
+    /// ones above.
+    pub fn parse(text: &str) -> Result<Template, TemplateError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t')   => literal.push('\t'),
+                    Some('n')   => literal.push('\n'),
+                    Some(c @ '\\') | Some(c @ '{') | Some(c @ '}')  => literal.push(c),
+                    Some(c)     => return Err(TemplateError::UnknownEscape(c)),
+                    None        => return Err(TemplateError::TrailingBackslash),
+                },
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}')  => break,
+                            Some(c)    => name.push(c),
+                            None       => return Err(TemplateError::Unclosed(name)),
+                        }
+                    }
+
+                    let field = match Field::ALL.iter().find(|f| f.name() == name) {
+                        Some(f)  => *f,
+                        None     => return Err(TemplateError::UnknownField(name)),
+                    };
+
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Text(literal.split_off(0)));
+                    }
+
+                    pieces.push(Piece::Field(field));
+                },
+                '}' => return Err(TemplateError::UnopenedBrace),
+                c   => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Text(literal));
+        }
+
+        Ok(Template { pieces })
+    }
+
+    /// Whether the template prints the given field anywhere.
+    pub fn uses(&self, field: Field) -> bool {
+        self.pieces.contains(&Piece::Field(field))
+    }
+
+    /// Fills in the template, getting the text for each of its fields from
+    /// the given function.
+    pub fn expand<F: FnMut(Field) -> String>(&self, mut value: F) -> String {
+        let mut out = String::new();
+
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Text(ref text)  => out.push_str(text),
+                Piece::Field(field)    => out.push_str(&value(field)),
+            }
+        }
+
+        out
+    }
+}
+
+
+/// Something wrong with a template, found while parsing it.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TemplateError {
+
+    /// A field with a name that isn’t one of the fields.
+    UnknownField(String),
+
+    /// A `{` with no `}` after it.
+    Unclosed(String),
+
+    /// A `}` with no `{` before it.
+    UnopenedBrace,
+
+    /// A backslash followed by a character that it doesn’t escape.
+    UnknownEscape(char),
+
+    /// A backslash right at the end, with nothing to escape.
+    TrailingBackslash,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TemplateError::UnknownField(ref name) => {
+                let names = Field::ALL.iter().map(|f| f.name()).collect::<Vec<_>>();
+                write!(f, "an unknown field {{{}}} (fields: {})", name, names.join(", "))
+            },
+            TemplateError::Unclosed(ref name)   => write!(f, "an unclosed field {{{}", name),
+            TemplateError::UnopenedBrace        => write!(f, "a }} with no {{ before it (use \\}} for a brace)"),
+            TemplateError::UnknownEscape(c)     => write!(f, "an unknown escape \\{}", c),
+            TemplateError::TrailingBackslash    => write!(f, "a \\ with nothing after it"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(template: &str) -> String {
+        Template::parse(template).unwrap().expand(|f| format!("<{}>", f.name()))
+    }
+
+    #[test]
+    fn fields_and_text() {
+        assert_eq!(expand("{name} is {size}"), "<name> is <size>");
+        assert_eq!(expand("{size_bytes}{git}"), "<size_bytes><git>");
+    }
+
+    #[test]
+    fn just_text() {
+        assert_eq!(expand("ünïcödé"), "ünïcödé");
+        assert_eq!(expand(""), "");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(expand("{name}\\t{size}\\n"), "<name>\t<size>\n");
+        assert_eq!(expand("\\{name\\} \\\\"), "{name} \\");
+    }
+
+    #[test]
+    fn uses() {
+        let template = Template::parse("{name} {git}").unwrap();
+        assert!(template.uses(Field::Git));
+        assert!(!template.uses(Field::Size));
+    }
+
+    #[test]
+    fn every_field_parses() {
+        for field in Field::ALL {
+            let template = Template::parse(&format!("{{{}}}", field.name())).unwrap();
+            assert!(template.uses(*field));
+        }
+    }
+
+    #[test]
+    fn unknown_field() {
+        let error = Template::parse("{name} {mtime}").unwrap_err();
+        assert_eq!(error, TemplateError::UnknownField("mtime".into()));
+        assert!(error.to_string().contains("name, path, size, size_bytes, modified"));
+    }
+
+    #[test]
+    fn braces_have_to_match() {
+        assert_eq!(Template::parse("{name"), Err(TemplateError::Unclosed("name".into())));
+        assert_eq!(Template::parse("name}"), Err(TemplateError::UnopenedBrace));
+    }
+
+    #[test]
+    fn bad_escapes() {
+        assert_eq!(Template::parse("\\x"), Err(TemplateError::UnknownEscape('x')));
+        assert_eq!(Template::parse("{name}\\"), Err(TemplateError::TrailingBackslash));
+    }
+}
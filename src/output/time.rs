@@ -1,10 +1,11 @@
 //! Timestamp formatting.
 
 use datetime::{LocalDateTime, TimeZone, DatePiece, TimePiece};
-use datetime::fmt::DateFormat;
-use locale;
 use std::cmp;
 
+use locale;
+use unicode_width::UnicodeWidthStr;
+
 use fs::fields::Time;
 
 
@@ -22,8 +23,8 @@ use fs::fields::Time;
 /// own enum variants. It’s not worth looking the locale up if the formatter
 /// prints month names as numbers.
 ///
-/// Currently exa does not support *custom* styles, where the user enters a
-/// format string in an environment variable or something. Just these four.
+/// As well as these four built-in styles, the user can give their own
+/// *custom* style as a format string.
 #[derive(Debug)]
 pub enum TimeFormat {
 
@@ -45,6 +46,17 @@ pub enum TimeFormat {
     /// millisecond and includes its offset down to the minute. This too uses
     /// only numbers so doesn’t require any special consideration.
     FullISO,
+
+    /// Use a **custom format** that the user gave as a `+FORMAT` string,
+    /// made up of `strftime`-like specifiers.
+    Custom(CustomFormat),
+
+    /// Print the number of **seconds since the Unix epoch**, for scripts.
+    /// Times before 1970 are negative. This doesn’t depend on the time zone.
+    Unix,
+
+    /// Print the number of **milliseconds since the Unix epoch**.
+    UnixMillis,
 }
 
 // There are two different formatting functions because local and zoned
@@ -57,6 +69,9 @@ impl TimeFormat {
             TimeFormat::ISOFormat(ref iso)     => iso.format_local(time),
             TimeFormat::LongISO                => long_local(time),
             TimeFormat::FullISO                => full_local(time),
+            TimeFormat::Custom(ref custom)     => custom.format_local(time),
+            TimeFormat::Unix                   => time.seconds.to_string(),
+            TimeFormat::UnixMillis             => unix_millis(time),
         }
     }
 
@@ -66,10 +81,109 @@ impl TimeFormat {
             TimeFormat::ISOFormat(ref iso)     => iso.format_zoned(time, zone),
             TimeFormat::LongISO                => long_zoned(time, zone),
             TimeFormat::FullISO                => full_zoned(time, zone),
+            TimeFormat::Custom(ref custom)     => custom.format_zoned(time, zone),
+            TimeFormat::Unix                   => time.seconds.to_string(),
+            TimeFormat::UnixMillis             => unix_millis(time),
+        }
+    }
+
+    /// Whether timestamps come out as plain numbers, which get aligned to
+    /// the right like the other numeric columns.
+    pub fn is_numeric(&self) -> bool {
+        matches!(*self, TimeFormat::Unix | TimeFormat::UnixMillis)
+    }
+}
+
+
+/// The parts of a locale that get used to write timestamps.
+#[derive(Debug, Clone)]
+pub struct TimeLocale {
+
+    /// The names of the months and days of the week.
+    pub names: locale::Time,
+
+    /// The designators for times before and after noon, such as “AM” and
+    /// “PM”, used with the twelve-hour clock.
+    pub am: String,
+    pub pm: String,
+
+    /// Whether the locale writes the time of day with a twelve-hour clock,
+    /// rather than a twenty-four-hour one.
+    pub twelve_hour: bool,
+}
+
+impl TimeLocale {
+    pub fn english() -> TimeLocale {
+        TimeLocale {
+            names:        locale::Time::english(),
+            am:           "AM".into(),
+            pm:           "PM".into(),
+            twelve_hour:  false,
         }
     }
 }
 
+/// Loads the user’s locale, which comes from the `LC_ALL`, `LC_TIME`, or
+/// `LANG` environment variables, using English if none of them are set.
+pub fn user_locale() -> TimeLocale {
+    named_locale("").unwrap_or_else(TimeLocale::english)
+}
+
+/// Loads the locale with the given name, such as `de_DE.UTF-8`, or returns
+/// nothing if the system doesn’t have it.
+pub fn named_locale(name: &str) -> Option<TimeLocale> {
+    use locale::LocaleFactory;
+
+    let mut factory = locale::SystemLocaleFactory::new(name).ok()?;
+    let names = factory.get_time().map(|t| *t).unwrap_or_else(locale::Time::english);
+    let (am, pm, twelve_hour) = clock_names(&factory);
+    Some(TimeLocale { names, am, pm, twelve_hour })
+}
+
+/// Looks up the locale’s AM and PM designators, and whether its time
+/// format uses a twelve-hour clock, through `nl_langinfo`.
+#[cfg(target_os = "linux")]
+fn clock_names(factory: &locale::SystemLocaleFactory) -> (String, String, bool) {
+    use locale::linux::langinfo;
+
+    let am = factory.langinfo(langinfo::AM_STR);
+    let pm = factory.langinfo(langinfo::PM_STR);
+    let format = factory.langinfo(langinfo::T_FMT);
+
+    // Locales that use the twelve-hour clock write the hour with `%I`
+    // or `%l`, or use `%r`, which is the whole twelve-hour time.
+    let twelve_hour = [ "%I", "%l", "%r" ].iter().any(|spec| format.contains(spec));
+
+    if am.is_empty() || pm.is_empty() {
+        ("AM".into(), "PM".into(), twelve_hour)
+    }
+    else {
+        (am.into_owned(), pm.into_owned(), twelve_hour)
+    }
+}
+
+/// Other systems don’t give the AM and PM designators through the locale
+/// crate, so this uses the English ones and the twenty-four-hour clock.
+#[cfg(not(target_os = "linux"))]
+fn clock_names(_factory: &locale::SystemLocaleFactory) -> (String, String, bool) {
+    ("AM".into(), "PM".into(), false)
+}
+
+
+/// Which clock the time of day gets written with in the default format.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Clock {
+
+    /// Use whichever clock the locale prefers.
+    Auto,
+
+    /// Use the twelve-hour clock, followed by AM or PM.
+    TwelveHour,
+
+    /// Use the twenty-four-hour clock.
+    TwentyFourHour,
+}
+
 
 #[derive(Debug, Clone)]
 pub struct DefaultFormat {
@@ -81,77 +195,118 @@ pub struct DefaultFormat {
     /// Localisation rules for formatting timestamps.
     pub locale: locale::Time,
 
-    /// Date format for printing out timestamps that are in the current year.
-    pub date_and_time: DateFormat<'static>,
+    /// How many columns the widest month name takes up. Every month name
+    /// gets padded to this width, so the columns after it line up.
+    pub month_width: usize,
 
-    /// Date format for printing out timestamps that *aren’t*.
-    pub date_and_year: DateFormat<'static>,
+    /// The designators to write after the time if it uses the twelve-hour
+    /// clock, or nothing if it uses the twenty-four-hour one.
+    pub meridiems: Option<Meridiems>,
 }
 
-impl DefaultFormat {
-    pub fn load() -> DefaultFormat {
-        use unicode_width::UnicodeWidthStr;
+/// The designators for times before and after noon, padded to the same
+/// width.
+#[derive(Debug, Clone)]
+pub struct Meridiems {
+    pub am: String,
+    pub pm: String,
+}
+
+impl Meridiems {
+    fn new(am: &str, pm: &str) -> Meridiems {
+        let width = cmp::max(UnicodeWidthStr::width(am), UnicodeWidthStr::width(pm));
+        Meridiems { am: pad_to_width(am, width), pm: pad_to_width(pm, width) }
+    }
 
-        let locale = locale::Time::load_user_locale()
-                       .unwrap_or_else(|_| locale::Time::english());
+    fn width(&self) -> usize {
+        UnicodeWidthStr::width(&*self.am)
+    }
+}
 
+impl DefaultFormat {
+    pub fn load(locale: TimeLocale, clock: Clock) -> DefaultFormat {
         let current_year = LocalDateTime::now().year();
+        let names = locale.names;
 
         // Some locales use a three-character wide month name (Jan to Dec);
-        // others vary between three to four (1月 to 12月, juil.). We check each month width
-        // to detect the longest and set the output format accordingly.
-        let mut maximum_month_width = 0;
-        for i in 0..11 {
-            let current_month_width = UnicodeWidthStr::width(&*locale.short_month_name(i));
-            maximum_month_width = cmp::max(maximum_month_width, current_month_width);
-        }
-
-        let date_and_time = match maximum_month_width {
-            4  => DateFormat::parse("{2>:D} {4<:M} {2>:h}:{02>:m}").unwrap(),
-            5  => DateFormat::parse("{2>:D} {5<:M} {2>:h}:{02>:m}").unwrap(),
-            _  => DateFormat::parse("{2>:D} {:M} {2>:h}:{02>:m}").unwrap(),
+        // others vary between three to four (1月 to 12月, juil.). We check each
+        // month’s width, counting double-width characters twice, to find the
+        // widest one.
+        let month_width = (0 .. 12).map(|m| UnicodeWidthStr::width(&*names.short_month_name(m)))
+                                   .max()
+                                   .unwrap_or(0);
+
+        let twelve_hour = match clock {
+            Clock::Auto            => locale.twelve_hour,
+            Clock::TwelveHour      => true,
+            Clock::TwentyFourHour  => false,
         };
 
-        let date_and_year = match maximum_month_width {
-            4 => DateFormat::parse("{2>:D} {4<:M} {5>:Y}").unwrap(),
-            5 => DateFormat::parse("{2>:D} {5<:M} {5>:Y}").unwrap(),
-            _ => DateFormat::parse("{2>:D} {:M} {5>:Y}").unwrap()
-        };
+        let meridiems = if twelve_hour { Some(Meridiems::new(&locale.am, &locale.pm)) }
+                                  else { None };
 
-        DefaultFormat { current_year, locale, date_and_time, date_and_year }
+        DefaultFormat { current_year, locale: names, month_width, meridiems }
     }
 }
 
 impl DefaultFormat {
-    fn is_recent(&self, date: LocalDateTime) -> bool {
-        date.year() == self.current_year
-    }
 
     #[allow(trivial_numeric_casts)]
     fn format_local(&self, time: Time) -> String {
         let date = LocalDateTime::at(time.seconds as i64);
-
-        if self.is_recent(date) {
-            self.date_and_time.format(&date, &self.locale)
-        }
-        else {
-            self.date_and_year.format(&date, &self.locale)
-        }
+        self.format(&date)
     }
 
     #[allow(trivial_numeric_casts)]
     fn format_zoned(&self, time: Time, zone: &TimeZone) -> String {
         let date = zone.to_zoned(LocalDateTime::at(time.seconds as i64));
+        self.format(&date)
+    }
 
-        if self.is_recent(date) {
-            self.date_and_time.format(&date, &self.locale)
+    /// Formats a date down to the minute if it’s in the current year, and
+    /// down to the day with the year if it isn’t. The year gets padded to
+    /// the width of the time, so the column stays lined up.
+    fn format<D: DatePiece + TimePiece>(&self, date: &D) -> String {
+        let month = self.locale.short_month_name(date.month().months_from_january());
+        let month = pad_to_width(&month, self.month_width);
+
+        let time_width = match self.meridiems {
+            Some(ref m)  => 6 + m.width(),
+            None         => 5,
+        };
+
+        if date.year() != self.current_year {
+            format!("{:>2} {} {:>width$}", date.day(), month, date.year(), width = time_width)
+        }
+        else if let Some(ref m) = self.meridiems {
+            let (hour, meridiem) = match date.hour() {
+                0             => (12, &m.am),
+                h @ 1 ..= 11  => (h, &m.am),
+                12            => (12, &m.pm),
+                h             => (h - 12, &m.pm),
+            };
+
+            format!("{:>2} {} {:>2}:{:02} {}", date.day(), month, hour, date.minute(), meridiem)
         }
         else {
-            self.date_and_year.format(&date, &self.locale)
+            format!("{:>2} {} {:>2}:{:02}", date.day(), month, date.hour(), date.minute())
         }
     }
 }
 
+/// Pads a string with spaces on the right until it takes up the given
+/// number of columns. This counts double-width characters as two columns,
+/// which formatting with `{:<4}` wouldn’t.
+fn pad_to_width(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(UnicodeWidthStr::width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// The number of whole milliseconds since the Unix epoch. The nanoseconds
+/// are never negative, so this rounds down for times before 1970 too.
+fn unix_millis(time: Time) -> String {
+    (i128::from(time.seconds) * 1000 + i128::from(time.nanoseconds) / 1_000_000).to_string()
+}
 
 #[allow(trivial_numeric_casts)]
 fn long_local(time: Time) -> String {
@@ -243,3 +398,333 @@ impl ISOFormat {
         }
     }
 }
+
+
+
+/// A **custom format**, parsed from a string given by the user.
+#[derive(Debug, Clone)]
+pub struct CustomFormat {
+
+    /// The literal text and fields that make up the format string.
+    pieces: Vec<Piece>,
+
+    /// Localisation rules for printing month names.
+    locale: locale::Time,
+}
+
+/// One part of a custom format string.
+#[derive(PartialEq, Debug, Clone)]
+enum Piece {
+    Literal(String),
+    Year,
+    ShortYear,
+    Month,
+    ShortMonthName,
+    LongMonthName,
+    Day,
+    PaddedDay,
+    Hour,
+    Minute,
+    Second,
+
+    /// The fraction of a second, given to this many digits, up to nine.
+    /// The digits that don’t fit are *truncated*, not rounded, so a time
+    /// never shows up as being in the next second.
+    Fraction(usize),
+
+    Offset,
+    EpochSeconds,
+}
+
+impl CustomFormat {
+
+    /// Parses a format string. Each specifier starts with a `%`:
+    ///
+    /// - `%Y`, `%y`: the year, in full or just the last two digits;
+    /// - `%m`, `%b`, `%B`: the month, as a number or a short or long name;
+    /// - `%d`, `%e`: the day of the month, padded with a zero or a space;
+    /// - `%H`, `%M`, `%S`: the hour, minute, and second;
+    /// - `%f`, `%3f`: the fraction of a second, to nine or the given number
+    ///   of digits;
+    /// - `%z`: the time zone offset;
+    /// - `%s`: the number of seconds since the Unix epoch;
+    /// - `%%`: a percent sign.
+    ///
+    /// Anything that isn’t a known specifier is printed as it is.
+    pub fn parse(input: &str, locale: locale::Time) -> CustomFormat {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            let digits = chars.next_if(|d| ('1' ..= '9').contains(d));
+            let spec = chars.next();
+
+            let piece = match (digits, spec) {
+                (Some(n), Some('f'))  => Piece::Fraction(n as usize - '0' as usize),
+                (None,    Some('Y'))  => Piece::Year,
+                (None,    Some('y'))  => Piece::ShortYear,
+                (None,    Some('m'))  => Piece::Month,
+                (None,    Some('b'))  => Piece::ShortMonthName,
+                (None,    Some('B'))  => Piece::LongMonthName,
+                (None,    Some('d'))  => Piece::PaddedDay,
+                (None,    Some('e'))  => Piece::Day,
+                (None,    Some('H'))  => Piece::Hour,
+                (None,    Some('M'))  => Piece::Minute,
+                (None,    Some('S'))  => Piece::Second,
+                (None,    Some('f'))  => Piece::Fraction(9),
+                (None,    Some('z'))  => Piece::Offset,
+                (None,    Some('s'))  => Piece::EpochSeconds,
+                (None,    Some('%'))  => { literal.push('%'); continue },
+                (_,       _)          => {
+                    literal.push('%');
+                    literal.extend(digits);
+                    literal.extend(spec);
+                    continue
+                },
+            };
+
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(literal.clone()));
+                literal.clear();
+            }
+            pieces.push(piece);
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+
+        CustomFormat { pieces, locale }
+    }
+
+    fn format_local(&self, time: Time) -> String {
+        let date = LocalDateTime::at(time.seconds);
+        self.format(&date, time, 0)
+    }
+
+    fn format_zoned(&self, time: Time, zone: &TimeZone) -> String {
+        let local = LocalDateTime::at(time.seconds);
+        let date = zone.to_zoned(local);
+        self.format(&date, time, zone.offset(local))
+    }
+
+    fn format<D: DatePiece + TimePiece>(&self, date: &D, time: Time, offset: i64) -> String {
+        use std::fmt::Write;
+
+        let mut buf = String::new();
+        for piece in &self.pieces {
+            // Writing to a String can’t fail.
+            let _ = match *piece {
+                Piece::Literal(ref s)   => write!(buf, "{}", s),
+                Piece::Year             => write!(buf, "{:04}", date.year()),
+                Piece::ShortYear        => write!(buf, "{:02}", date.year_of_century()),
+                Piece::Month            => write!(buf, "{:02}", date.month() as usize),
+                Piece::ShortMonthName   => write!(buf, "{}", self.locale.short_month_name(date.month().months_from_january())),
+                Piece::LongMonthName    => write!(buf, "{}", self.locale.long_month_name(date.month().months_from_january())),
+                Piece::PaddedDay        => write!(buf, "{:02}", date.day()),
+                Piece::Day              => write!(buf, "{:2}", date.day()),
+                Piece::Hour             => write!(buf, "{:02}", date.hour()),
+                Piece::Minute           => write!(buf, "{:02}", date.minute()),
+                Piece::Second           => write!(buf, "{:02}", date.second()),
+                Piece::Fraction(n)      => write!(buf, "{}", fraction(time.nanoseconds, n)),
+                Piece::Offset           => write!(buf, "{}{:02}{:02}", if offset < 0 { '-' } else { '+' },
+                                                  offset.abs() / 3600, offset.abs() % 3600 / 60),
+                Piece::EpochSeconds     => write!(buf, "{}", time.seconds),
+            };
+        }
+
+        buf
+    }
+}
+
+/// Formats the given number of nanoseconds as the given number of
+/// fractional digits, truncating the digits that don’t fit.
+fn fraction(nanoseconds: i64, digits: usize) -> String {
+    let nine = format!("{:09}", nanoseconds);
+    nine[.. digits].to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn seventeenth_century() {
+        let time = Time::from_raw(-11_644_473_599, 0).unwrap();
+        assert_eq!(full_local(time), "1601-01-01 00:00:01.000000000");
+    }
+
+    #[test]
+    fn just_before_1970() {
+        let time = Time::from_raw(0, -500_000_000).unwrap();
+        assert_eq!(full_local(time), "1969-12-31 23:59:59.500000000");
+    }
+
+    #[test]
+    fn nineteenth_century() {
+        let time = Time::from_raw(-3_153_600_000, 0).unwrap();
+        assert_eq!(long_local(time), "1870-01-25 00:00");
+    }
+
+    #[test]
+    fn modern() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(long_local(time), "2017-07-14 02:40");
+    }
+
+    /// Converts a Windows FILETIME, which counts hundreds of nanoseconds
+    /// since the start of 1601, the same way the metadata does.
+    fn filetime(ticks: i64) -> Time {
+        Time::from_raw(ticks / 10_000_000 - 11_644_473_600, ticks % 10_000_000 * 100).unwrap()
+    }
+
+    #[test]
+    fn unix_filetime() {
+        let time = filetime(132_223_104_005_000_000);
+        assert_eq!(TimeFormat::Unix.format_local(time), "1577836800");
+        assert_eq!(TimeFormat::UnixMillis.format_local(time), "1577836800500");
+    }
+
+    #[test]
+    fn unix_seventeenth_century() {
+        let time = filetime(10_000_000);
+        assert_eq!(TimeFormat::Unix.format_local(time), "-11644473599");
+        assert_eq!(TimeFormat::UnixMillis.format_local(time), "-11644473599000");
+    }
+
+    #[test]
+    fn unix_just_before_1970() {
+        let time = Time::from_raw(0, -500_000_000).unwrap();
+        assert_eq!(TimeFormat::Unix.format_local(time), "-1");
+        assert_eq!(TimeFormat::UnixMillis.format_local(time), "-500");
+    }
+
+    fn custom(format: &str, time: Time) -> String {
+        CustomFormat::parse(format, locale::Time::english()).format_local(time)
+    }
+
+    fn default_format(current_year: i64) -> DefaultFormat {
+        DefaultFormat { current_year, locale: locale::Time::english(), month_width: 3, meridiems: None }
+    }
+
+    fn twelve_hour_format(current_year: i64) -> DefaultFormat {
+        DefaultFormat { meridiems: Some(Meridiems::new("AM", "PM")), ..default_format(current_year) }
+    }
+
+    #[test]
+    fn default_this_year() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(default_format(2017).format_local(time), "14 Jul  2:40");
+    }
+
+    #[test]
+    fn default_other_year() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(default_format(2020).format_local(time), "14 Jul  2017");
+    }
+
+    #[test]
+    fn twelve_hour_morning() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(twelve_hour_format(2017).format_local(time), "14 Jul  2:40 AM");
+    }
+
+    #[test]
+    fn twelve_hour_afternoon() {
+        let time = Time::from_raw(1_500_050_000, 0).unwrap();
+        assert_eq!(default_format(2017).format_local(time),     "14 Jul 16:33");
+        assert_eq!(twelve_hour_format(2017).format_local(time), "14 Jul  4:33 PM");
+    }
+
+    #[test]
+    fn twelve_hour_midnight_and_noon() {
+        let midnight = Time::from_raw(1_499_990_400, 0).unwrap();
+        let noon = Time::from_raw(1_500_033_600, 0).unwrap();
+        assert_eq!(twelve_hour_format(2017).format_local(midnight), "14 Jul 12:00 AM");
+        assert_eq!(twelve_hour_format(2017).format_local(noon),     "14 Jul 12:00 PM");
+    }
+
+    #[test]
+    fn twelve_hour_other_year() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(twelve_hour_format(2020).format_local(time), "14 Jul     2017");
+    }
+
+    #[test]
+    fn meridiems_are_padded() {
+        let m = Meridiems::new("午前", "PM");
+        assert_eq!((m.am.as_str(), m.pm.as_str()), ("午前", "PM  "));
+    }
+
+    #[test]
+    fn english_month_width() {
+        assert_eq!(DefaultFormat::load(TimeLocale::english(), Clock::Auto).month_width, 3);
+    }
+
+    #[test]
+    fn clock_overrides_locale() {
+        assert!(DefaultFormat::load(TimeLocale::english(), Clock::TwelveHour).meridiems.is_some());
+        assert!(DefaultFormat::load(TimeLocale::english(), Clock::TwentyFourHour).meridiems.is_none());
+        assert!(DefaultFormat::load(TimeLocale::english(), Clock::Auto).meridiems.is_none());
+    }
+
+    #[test]
+    fn padding_counts_columns() {
+        assert_eq!(pad_to_width("juil.", 5), "juil.");
+        assert_eq!(pad_to_width("mai", 5),   "mai  ");
+        assert_eq!(pad_to_width("3月", 4),   "3月 ");
+        assert_eq!(pad_to_width("12月", 4),  "12月");
+    }
+
+    #[test]
+    fn c_locale_is_english() {
+        let c = named_locale("C").unwrap();
+        assert_eq!(c.names.short_month_name(0), "Jan");
+        assert_eq!((c.am.as_str(), c.pm.as_str(), c.twelve_hour), ("AM", "PM", false));
+    }
+
+    #[test]
+    fn custom_fields() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(custom("%Y-%m-%d %H:%M:%S", time), "2017-07-14 02:40:00");
+        assert_eq!(custom("%e %b %y, %B", time), "14 Jul 17, July");
+        assert_eq!(custom("%s %z", time), "1500000000 +0000");
+    }
+
+    #[test]
+    fn custom_literals() {
+        let time = Time::from_raw(1_500_000_000, 0).unwrap();
+        assert_eq!(custom("100%% on %Q at %", time), "100% on %Q at %");
+    }
+
+    #[test]
+    fn fraction_full() {
+        let time = Time::from_raw(1_500_000_000, 123_456_789).unwrap();
+        assert_eq!(custom("%S.%f", time), "00.123456789");
+    }
+
+    #[test]
+    fn fraction_ntfs_units() {
+        let time = Time::from_raw(1_500_000_000, 123_456_700).unwrap();
+        assert_eq!(custom("%S.%7f", time), "00.1234567");
+    }
+
+    #[test]
+    fn fraction_truncates() {
+        let time = Time::from_raw(1_500_000_000, 999_999_999).unwrap();
+        assert_eq!(custom("%S.%3f", time), "00.999");
+        assert_eq!(custom("%S.%1f", time), "00.9");
+    }
+
+    #[test]
+    fn fraction_leading_zeroes() {
+        let time = Time::from_raw(1_500_000_000, 5_000).unwrap();
+        assert_eq!(custom("%6f", time), "000005");
+    }
+}
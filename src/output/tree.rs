@@ -57,14 +57,37 @@ pub enum TreePart {
 
 impl TreePart {
 
-    /// Turn this tree part into ASCII-licious box drawing characters!
-    /// (Warning: not actually ASCII)
-    pub fn ascii_art(&self) -> &'static str {
-        match *self {
-            TreePart::Edge    => "├──",
-            TreePart::Line    => "│  ",
-            TreePart::Corner  => "└──",
-            TreePart::Blank   => "   ",
+    /// Turn this tree part into the characters that draw it in the given
+    /// style.
+    pub fn ascii_art(&self, style: TreeStyle) -> &'static str {
+        style.parts()[self.clone() as usize]
+    }
+}
+
+
+/// The set of characters used to draw the branches of the tree.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TreeStyle {
+
+    /// Unicode box-drawing characters, such as `├──`.
+    Unicode,
+
+    /// Plain ASCII characters, such as `|--`, for consoles that can’t
+    /// display box-drawing characters.
+    Ascii,
+}
+
+/// The characters for each tree part, in the same order as the variants of
+/// `TreePart`. Each string has to be the same width as the others in its
+/// style, so that the rows line up.
+static UNICODE_PARTS: [&str; 4] = [ "├──", "│  ", "└──", "   " ];
+static ASCII_PARTS:   [&str; 4] = [ "|--", "|  ", "`--", "   " ];
+
+impl TreeStyle {
+    fn parts(self) -> &'static [&'static str; 4] {
+        match self {
+            TreeStyle::Unicode  => &UNICODE_PARTS,
+            TreeStyle::Ascii    => &ASCII_PARTS,
         }
     }
 }
@@ -145,6 +168,10 @@ impl TreeParams {
     pub fn is_at_root(&self) -> bool {
         self.depth.0 == 0
     }
+
+    pub fn depth(&self) -> TreeDepth {
+        self.depth
+    }
 }
 
 impl TreeDepth {
@@ -241,6 +268,28 @@ mod trunk_test {
 
 
 
+#[cfg(test)]
+mod style_test {
+    use super::*;
+
+    #[test]
+    fn unicode() {
+        assert_eq!(TreePart::Edge.ascii_art(TreeStyle::Unicode),   "├──");
+        assert_eq!(TreePart::Line.ascii_art(TreeStyle::Unicode),   "│  ");
+        assert_eq!(TreePart::Corner.ascii_art(TreeStyle::Unicode), "└──");
+        assert_eq!(TreePart::Blank.ascii_art(TreeStyle::Unicode),  "   ");
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(TreePart::Edge.ascii_art(TreeStyle::Ascii),   "|--");
+        assert_eq!(TreePart::Line.ascii_art(TreeStyle::Ascii),   "|  ");
+        assert_eq!(TreePart::Corner.ascii_art(TreeStyle::Ascii), "`--");
+        assert_eq!(TreePart::Blank.ascii_art(TreeStyle::Ascii),  "   ");
+    }
+}
+
+
 #[cfg(test)]
 mod iter_test {
     use super::*;
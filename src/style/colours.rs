@@ -1,7 +1,8 @@
 use ansi_term::Style;
-use ansi_term::Colour::{Red, Green, Yellow, Blue, Cyan, Purple, Fixed};
+use ansi_term::Colour::{Red, Green, Yellow, Blue, Cyan, Purple, White, Fixed};
 
 use output::render;
+use output::TextCell;
 use output::file_name::Colours as FileNameColours;
 
 use style::lsc::Pair;
@@ -24,17 +25,61 @@ pub struct Colours {
     pub inode:        Style,
     pub blocks:       Style,
     pub header:       Style,
+    pub volume:       Style,
+    pub mount_point:  Style,
+    pub network:      Style,
+    pub access_allowed:  Style,
+    pub access_denied:   Style,
+    pub signed:          Style,
+    pub unsigned:        Style,
+    pub invalid_signature:  Style,
+    pub version_info:    Style,
+    pub content_type:    Style,
+    pub checksum:        Style,
+    pub zone:            Style,
+    pub tree_summary:    Style,
 
     pub symlink_path:         Style,
+    pub typed_dir:            Style,
     pub control_char:         Style,
     pub broken_symlink:       Style,
     pub broken_path_overlay:  Style,
+    pub system_overlay:       Style,
+    pub quotes:               Style,
+    pub highlight:            Style,
+
+    /// The styles of the arrows between symlinks and their targets, if
+    /// they’ve been set apart from the punctuation and broken link styles.
+    pub link_arrow:    Option<Style>,
+    pub broken_arrow:  Option<Style>,
+
+    /// What to show for a value that a file doesn’t have.
+    pub placeholder:  Placeholder,
+}
+
+/// What gets shown in place of a value that a file doesn’t have, such as
+/// the size of a directory. This is a hyphen unless the user picks some
+/// other text, which can be empty to leave the cell blank.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder {
+    pub text: String,
+
+    /// The style of the text, if it’s been set apart from the punctuation
+    /// style.
+    pub style: Option<Style>,
+}
+
+impl Default for Placeholder {
+    fn default() -> Placeholder {
+        Placeholder { text: "-".into(), style: None }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct FileKinds {
     pub normal: Style,
     pub directory: Style,
+    pub empty_directory: Style,
     pub symlink: Style,
     pub pipe: Style,
     pub block_device: Style,
@@ -42,6 +87,17 @@ pub struct FileKinds {
     pub socket: Style,
     pub special: Style,
     pub executable: Style,
+    pub compiled: Style,
+    pub temporary: Style,
+    pub protected: Style,
+    pub in_use: Style,
+    pub downloaded: Style,
+
+    pub installer: Style,
+    pub script: Style,
+    pub registry: Style,
+    pub shortcut: Style,
+    pub system: Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -63,6 +119,8 @@ pub struct Permissions {
     pub special_other:     Style,
 
     pub attribute: Style,
+    pub acl: Style,
+    pub world_writable: Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -73,6 +131,8 @@ pub struct Size {
     pub major: Style,
     pub minor: Style,
 
+    pub entries: Style,
+
     pub scale_byte: Style,
     pub scale_kilo: Style,
     pub scale_mega: Style,
@@ -102,6 +162,9 @@ pub struct Git {
     pub renamed: Style,
     pub typechange: Style,
     pub ignored: Style,
+    pub conflicted: Style,
+    pub branch: Style,
+    pub dirty: Style,
 }
 
 impl Colours {
@@ -115,15 +178,27 @@ impl Colours {
             scale,
 
             filekinds: FileKinds {
-                normal:       Style::default(),
-                directory:    Blue.bold(),
-                symlink:      Cyan.normal(),
-                pipe:         Yellow.normal(),
-                block_device: Yellow.bold(),
-                char_device:  Yellow.bold(),
-                socket:       Red.bold(),
-                special:      Yellow.normal(),
-                executable:   Green.bold(),
+                normal:          Style::default(),
+                directory:       Blue.bold(),
+                empty_directory: Blue.dimmed(),
+                symlink:         Cyan.normal(),
+                pipe:            Yellow.normal(),
+                block_device:    Yellow.bold(),
+                char_device:     Yellow.bold(),
+                socket:          Red.bold(),
+                special:         Yellow.normal(),
+                executable:      Green.bold(),
+                compiled:        Fixed(137).normal(),
+                temporary:       Fixed(244).normal(),
+                protected:       Red.underline(),
+                in_use:          Yellow.underline(),
+                downloaded:      Red.italic(),
+
+                installer:       Fixed(172).normal(),
+                script:          Fixed(71).normal(),
+                registry:        Fixed(67).normal(),
+                shortcut:        Fixed(80).normal(),
+                system:          Fixed(95).normal(),
             },
 
             perms: Permissions {
@@ -144,6 +219,8 @@ impl Colours {
                 special_other:       Purple.normal(),
 
                 attribute:           Style::default(),
+                acl:                 Style::default(),
+                world_writable:      White.on(Red),
             },
 
             size: Size {
@@ -153,6 +230,8 @@ impl Colours {
                 major:  Green.bold(),
                 minor:  Green.normal(),
 
+                entries:  Blue.normal(),
+
                 scale_byte: Fixed(118).normal(),
                 scale_kilo: Fixed(190).normal(),
                 scale_mega: Fixed(226).normal(),
@@ -179,6 +258,9 @@ impl Colours {
                 renamed:     Yellow.normal(),
                 typechange:  Purple.normal(),
                 ignored:     Style::default().dimmed(),
+                conflicted:  Red.bold(),
+                branch:      Purple.bold(),
+                dirty:       Yellow.bold(),
             },
 
             punctuation:  Fixed(244).normal(),
@@ -186,11 +268,33 @@ impl Colours {
             inode:        Purple.normal(),
             blocks:       Cyan.normal(),
             header:       Style::default().underline(),
+            volume:       Cyan.normal(),
+            mount_point:  Cyan.bold().underline(),
+            network:      Yellow.bold(),
+            access_allowed:  Green.bold(),
+            access_denied:   Red.normal(),
+            signed:          Green.normal(),
+            unsigned:        Yellow.normal(),
+            invalid_signature:  Red.bold(),
+            version_info:    Green.normal(),
+            content_type:    Purple.normal(),
+            checksum:        Fixed(244).normal(),
+            zone:            Red.normal(),
+            tree_summary:    Style::default().dimmed(),
 
             symlink_path:         Cyan.normal(),
+            typed_dir:            Style::default().dimmed(),
             control_char:         Red.normal(),
             broken_symlink:       Red.normal(),
             broken_path_overlay:  Style::default().underline(),
+            system_overlay:       Style::default().dimmed(),
+            quotes:               Style::default().dimmed(),
+            highlight:            Red.bold(),
+
+            link_arrow:    None,
+            broken_arrow:  None,
+
+            placeholder:  Placeholder::default(),
         }
     }
 }
@@ -269,11 +373,14 @@ impl Colours {
             "su" => self.perms.special_user_file  = pair.to_style(),
             "sf" => self.perms.special_other      = pair.to_style(),
             "xa" => self.perms.attribute          = pair.to_style(),
+            "ac" => self.perms.acl                = pair.to_style(),
+            "ww" => self.perms.world_writable     = pair.to_style(),
 
             "sn" => self.size.numbers             = pair.to_style(),
             "sb" => self.size.unit                = pair.to_style(),
             "df" => self.size.major               = pair.to_style(),
             "ds" => self.size.minor               = pair.to_style(),
+            "de" => self.size.entries             = pair.to_style(),
 
             "uu" => self.users.user_you           = pair.to_style(),
             "un" => self.users.user_someone_else  = pair.to_style(),
@@ -288,15 +395,49 @@ impl Colours {
             "gd" => self.git.deleted              = pair.to_style(),
             "gv" => self.git.renamed              = pair.to_style(),
             "gt" => self.git.typechange           = pair.to_style(),
+            "gf" => self.git.conflicted           = pair.to_style(),
+            "gb" => self.git.branch               = pair.to_style(),
+            "gc" => self.git.dirty                = pair.to_style(),
 
             "xx" => self.punctuation              = pair.to_style(),
+            "na" => self.placeholder.style        = Some(pair.to_style()),
+            "nt" => self.placeholder.text         = pair.value.into(),
+            "la" => self.link_arrow               = Some(pair.to_style()),
+            "lb" => self.broken_arrow             = Some(pair.to_style()),
             "da" => self.date                     = pair.to_style(),
             "in" => self.inode                    = pair.to_style(),
+            "vo" => self.volume                   = pair.to_style(),
+            "vm" => self.mount_point              = pair.to_style(),
+            "nw" => self.network                  = pair.to_style(),
+            "ma" => self.access_allowed           = pair.to_style(),
+            "md" => self.access_denied            = pair.to_style(),
+            "as" => self.signed                   = pair.to_style(),
+            "au" => self.unsigned                 = pair.to_style(),
+            "ai" => self.invalid_signature        = pair.to_style(),
+            "vi" => self.version_info             = pair.to_style(),
+            "ct" => self.content_type             = pair.to_style(),
+            "cs" => self.checksum                 = pair.to_style(),
+            "zn" => self.zone                     = pair.to_style(),
+            "ts" => self.tree_summary             = pair.to_style(),
             "bl" => self.blocks                   = pair.to_style(),
             "hd" => self.header                   = pair.to_style(),
             "lp" => self.symlink_path             = pair.to_style(),
+            "ap" => self.typed_dir                = pair.to_style(),
             "cc" => self.control_char             = pair.to_style(),
+            "hl" => self.highlight                = pair.to_style(),
+            "ed" => self.filekinds.empty_directory = pair.to_style(),
+            "co" => self.filekinds.compiled       = pair.to_style(),
+            "tm" => self.filekinds.temporary      = pair.to_style(),
+            "pr" => self.filekinds.protected      = pair.to_style(),
+            "iu" => self.filekinds.in_use         = pair.to_style(),
+            "mw" => self.filekinds.downloaded     = pair.to_style(),
+            "is" => self.filekinds.installer      = pair.to_style(),
+            "sc" => self.filekinds.script         = pair.to_style(),
+            "rg" => self.filekinds.registry       = pair.to_style(),
+            "sh" => self.filekinds.shortcut       = pair.to_style(),
+            "sy" => self.filekinds.system         = pair.to_style(),
             "bO" => self.broken_path_overlay      = pair.to_style(),
+            "sO" => self.system_overlay           = pair.to_style(),
 
              _   => return false,
         }
@@ -305,9 +446,14 @@ impl Colours {
 }
 
 
+impl render::Absent for Colours {
+    fn absent(&self) -> TextCell {
+        TextCell::paint(self.placeholder.style.unwrap_or(self.punctuation), self.placeholder.text.clone())
+    }
+}
+
 impl render::BlocksColours for Colours {
     fn block_count(&self)  -> Style { self.blocks }
-    fn no_blocks(&self)    -> Style { self.punctuation }
 }
 
 impl render::FiletypeColours for Colours {
@@ -322,6 +468,9 @@ impl render::FiletypeColours for Colours {
 }
 
 impl render::GitColours for Colours {
+    fn punctuation(&self)   -> Style { self.punctuation }
+    fn branch(&self)        -> Style { self.git.branch }
+    fn dirty(&self)         -> Style { self.git.dirty }
     fn not_modified(&self)  -> Style { self.punctuation }
     fn new(&self)           -> Style { self.git.new }
     fn modified(&self)      -> Style { self.git.modified }
@@ -329,6 +478,7 @@ impl render::GitColours for Colours {
     fn renamed(&self)       -> Style { self.git.renamed }
     fn type_change(&self)   -> Style { self.git.typechange }
     fn ignored(&self)       -> Style { self.git.ignored }
+    fn conflicted(&self)    -> Style { self.git.conflicted }
 }
 
 impl render::GroupColours for Colours {
@@ -356,6 +506,8 @@ impl render::PermissionsColours for Colours {
     fn special_user_file(&self)  -> Style { self.perms.special_user_file }
     fn special_other(&self)      -> Style { self.perms.special_other }
     fn attribute(&self)          -> Style { self.perms.attribute }
+    fn acl(&self)                -> Style { self.perms.acl }
+    fn world_writable(&self)     -> Style { self.perms.world_writable }
 }
 
 impl render::SizeColours for Colours {
@@ -383,23 +535,125 @@ impl render::SizeColours for Colours {
     }
 
     fn unit(&self)    -> Style { self.size.unit }
-    fn no_size(&self) -> Style { self.punctuation }
+    fn entries(&self) -> Style { self.size.entries }
     fn major(&self)   -> Style { self.size.major }
     fn comma(&self)   -> Style { self.punctuation }
     fn minor(&self)   -> Style { self.size.minor }
 }
 
+impl render::VolumeColours for Colours {
+    fn volume(&self)       -> Style { self.volume }
+    fn mount_point(&self)  -> Style { self.mount_point }
+}
+
+impl render::NetworkColours for Colours {
+    fn network(&self)  -> Style { self.network }
+    fn local(&self)    -> Style { self.punctuation }
+}
+
+impl render::AccessColours for Colours {
+    fn allowed(&self)  -> Style { self.access_allowed }
+    fn denied(&self)   -> Style { self.access_denied }
+}
+
+impl render::ZoneColours for Colours {
+    fn zone(&self)  -> Style { self.zone }
+}
+
+impl render::SignatureColours for Colours {
+    fn signed(&self)    -> Style { self.signed }
+    fn unsigned(&self)  -> Style { self.unsigned }
+    fn invalid(&self)   -> Style { self.invalid_signature }
+}
+
+impl render::VersionColours for Colours {
+    fn version(&self)     -> Style { self.version_info }
+}
+
+impl render::ContentColours for Colours {
+    fn content_type(&self)  -> Style { self.content_type }
+}
+
+impl render::ChecksumColours for Colours {
+    fn checksum(&self)     -> Style { self.checksum }
+}
+
 impl render::UserColours for Colours {
     fn you(&self)           -> Style { self.users.user_you }
     fn someone_else(&self)  -> Style { self.users.user_someone_else }
 }
 
+impl FileKinds {
+
+    /// Turns off the styles for the categories of file that are picked by
+    /// extension, as `EXA_COLORS=reset` does for the built-in extensions.
+    pub fn reset_categories(&mut self) {
+        self.compiled   = Style::default();
+        self.temporary  = Style::default();
+        self.installer  = Style::default();
+        self.script     = Style::default();
+        self.registry   = Style::default();
+        self.shortcut   = Style::default();
+        self.system     = Style::default();
+    }
+}
+
 impl FileNameColours for Colours {
-    fn normal_arrow(&self)        -> Style { self.punctuation }
+    fn normal_arrow(&self)        -> Style { self.link_arrow.unwrap_or(self.punctuation) }
+    fn broken_arrow(&self)        -> Style { self.broken_arrow.unwrap_or(self.broken_symlink) }
+    fn broken_reason(&self)       -> Style { self.punctuation }
     fn broken_symlink(&self)      -> Style { self.broken_symlink }
     fn broken_filename(&self)     -> Style { apply_overlay(self.broken_symlink, self.broken_path_overlay) }
     fn broken_control_char(&self) -> Style { apply_overlay(self.control_char,   self.broken_path_overlay) }
     fn control_char(&self)        -> Style { self.control_char }
     fn symlink_path(&self)        -> Style { self.symlink_path }
+    fn typed_dir(&self)           -> Style { self.typed_dir }
     fn executable_file(&self)     -> Style { self.filekinds.executable }
+    fn quotes(&self)              -> Style { self.quotes }
+    fn highlight(&self)           -> Style { self.highlight }
+    fn compiled_file(&self)       -> Style { self.filekinds.compiled }
+    fn temporary_file(&self)      -> Style { self.filekinds.temporary }
+    fn protected_file(&self)      -> Style { self.filekinds.protected }
+    fn in_use_file(&self)         -> Style { self.filekinds.in_use }
+    fn downloaded_file(&self)     -> Style { self.filekinds.downloaded }
+    fn installer_file(&self)      -> Style { self.filekinds.installer }
+    fn script_file(&self)         -> Style { self.filekinds.script }
+    fn registry_file(&self)       -> Style { self.filekinds.registry }
+    fn shortcut_file(&self)       -> Style { self.filekinds.shortcut }
+    fn system_file(&self)         -> Style { self.filekinds.system }
+    fn system_attribute(&self, style: Style) -> Style { apply_overlay(style, self.system_overlay) }
+
+    fn empty_directory(&self) -> Option<Style> {
+        if self.colourful && self.filekinds.empty_directory != self.filekinds.directory {
+            Some(self.filekinds.empty_directory)
+        }
+        else {
+            None
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn system_files_get_dimmed() {
+        let colours = Colours::colourful(false);
+        assert_eq!(colours.system_attribute(Blue.bold()), Blue.bold().dimmed());
+        assert_eq!(colours.system_attribute(Style::default()), Style::default().dimmed());
+    }
+
+    #[test]
+    fn system_overlay_turned_off() {
+        let mut colours = Colours::colourful(false);
+        colours.system_overlay = Style::default();
+        assert_eq!(colours.system_attribute(Blue.bold()), Blue.bold());
+    }
+
+    #[test]
+    fn no_colours() {
+        assert_eq!(Colours::plain().system_attribute(Style::default()), Style::default());
+    }
 }
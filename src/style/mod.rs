@@ -1,5 +1,5 @@
 mod colours;
-pub use self::colours::Colours;
+pub use self::colours::{Colours, Placeholder};
 
 mod lsc;
 pub use self::lsc::LSColors;
@@ -40,8 +40,8 @@ export EXA_COLORS=""
 # Check that no files were created more than a year ago.
 # Files not from the current year use a different date format, meaning
 # that tests will fail until the VM gets re-provisioned.
-# (Ignore the folder that deliberately has dates in the past)
-sudo find $testcases -mtime +365 -not -path "*/dates/*" -printf "File %p has not been modified since %TY! Consider re-provisioning; tests will probably fail.\n"
+# (Ignore the folders that deliberately have dates in the past)
+sudo find $testcases -mtime +365 -not -path "*/dates/*" -not -path "*/dereference/*" -printf "File %p has not been modified since %TY! Consider re-provisioning; tests will probably fail.\n"
 
 
 # Long view tests
@@ -65,6 +65,11 @@ COLUMNS=100 $exa $testcases/files/* | diff -q - $results/files_star_100  || exit
 COLUMNS=150 $exa $testcases/files/* | diff -q - $results/files_star_150  || exit 1
 COLUMNS=200 $exa $testcases/files/* | diff -q - $results/files_star_200  || exit 1
 
+# A fixed number of columns ignores the terminal’s width
+$exa $testcases/files --columns=5               | diff -q - $results/files_columns_5    || exit 1
+COLUMNS=40 $exa $testcases/files --columns=5 -x | diff -q - $results/files_columns_5_x  || exit 1
+$exa $testcases/files --columns=1               | diff -q - $results/files_1           || exit 1
+
 
 # Long grid view tests
 COLUMNS=40  $exa $testcases/files -lG | diff -q - $results/files_lG_40   || exit 1
@@ -101,6 +106,17 @@ $exa $testcases/passwd -lgh | diff -q - $results/passwd  || exit 1
 sudo -u cassowary $exa $testcases/permissions -lghR 2>&1 | diff -q - $results/permissions_sudo  || exit 1
                   $exa $testcases/permissions -lghR 2>&1 | diff -q - $results/permissions       || exit 1
 
+
+# Exit statuses, which follow ls: 1 if something couldn’t be listed but the
+# rest was, and 2 if nothing could be, or the options were wrong
+$exa $testcases/files                >/dev/null 2>&1;  [ $? -eq 0 ] || exit 1
+$exa $testcases/files /nonexistent   >/dev/null 2>&1;  [ $? -eq 1 ] || exit 1
+$exa $testcases/permissions -R       >/dev/null 2>&1;  [ $? -eq 1 ] || exit 1
+$exa $testcases/permissions -T       >/dev/null 2>&1;  [ $? -eq 1 ] || exit 1
+$exa /nonexistent                    >/dev/null 2>&1;  [ $? -eq 2 ] || exit 1
+$exa $testcases/permissions/forbidden-directory  >/dev/null 2>&1;  [ $? -eq 2 ] || exit 1
+$exa --ternary                       >/dev/null 2>&1;  [ $? -eq 2 ] || exit 1
+
 # File names
 # (Mostly escaping control characters in file names)
 COLUMNS=80 $exa $testcases/file-names     2>&1 | diff -q - $results/file_names    || exit 1
@@ -170,6 +186,8 @@ COLUMNS=80 $exa $testcases/links     2>&1 | diff -q - $results/links          ||
            $exa $testcases/links -1  2>&1 | diff -q - $results/links_1        || exit 1
            $exa $testcases/links -T  2>&1 | diff -q - $results/links_T        || exit 1
            $exa $testcases/links -T@ 2>&1 | diff -q - $results/links_T@       || exit 1
+           $exa $testcases/links -T --tree-style=ascii 2>&1 | diff -q - $results/links_T_ascii || exit 1
+env LC_ALL=C $exa $testcases/links -T  2>&1 | diff -q - $results/links_T_ascii  || exit 1
            $exa /proc/1/root     -T  2>&1 | diff -q - $results/proc_1_root    || exit 1
            $exa /proc/1/root     -T@ 2>&1 | diff -q - $results/proc_1_root_@  || exit 1
 
@@ -177,6 +195,11 @@ COLUMNS=80 $exa $testcases/links     2>&1 | diff -q - $results/links          ||
 # symlink file was specified on the command-line directly.
 $exa $testcases/links/* -1 | diff -q - $results/links_1_files || exit 1
 
+# With --dereference, a link’s row should describe its target, apart from
+# its name, unless it’s broken.
+$exa $testcases/dereference -l               | diff -q - $results/dereference_l   || exit 1
+$exa $testcases/dereference -l --dereference | diff -q - $results/dereference_lL  || exit 1
+
 
 # Colours and terminals
 # Just because COLUMNS is present, doesn’t mean output is to a terminal
@@ -185,6 +208,15 @@ COLUMNS=80 $exa_binary --colour=always    $testcases/files -l | diff -q - $resul
 COLUMNS=80 $exa_binary --colour=never     $testcases/files -l | diff -q - $results/files_l_bw  || exit 1
 COLUMNS=80 $exa_binary --colour=automatic $testcases/files -l | diff -q - $results/files_l_bw  || exit 1
 
+# A tab separator with colours off gives output that can be cut into fields
+COLUMNS=80 $exa_binary --colour=never --separator=$'\t' $testcases/files -l | diff -q - $results/files_l_tab  || exit 1
+COLUMNS=80 EXA_SEPARATOR=$'\t' $exa_binary --colour=never $testcases/files -l | diff -q - $results/files_l_tab  || exit 1
+
+# Repeated owners get ditto marks, but not in tab-separated output
+COLUMNS=80 $exa_binary --colour=never --dedup-columns $testcases/files -l | diff -q - $results/files_l_dedup  || exit 1
+COLUMNS=80 $exa_binary --colour=never --dedup-columns --separator=$'\t' $testcases/files -l | diff -q - $results/files_l_tab  || exit 1
+COLUMNS=80 $exa_binary --colour=never --dedup-columns $testcases/git2 --tree -l | diff -q - $results/git_2_tree_dedup  || exit 1
+
 # Switching colour off
 COLUMNS=80 $exa_binary --colour=never    $testcases/file-names       | diff -q - $results/file_names_bw       || exit 1
 COLUMNS=80 $exa_binary --colour=never    $testcases/file-names-exts  | diff -q - $results/file-names-exts-bw  || exit 1
@@ -223,6 +255,21 @@ $exa $testcases/git2/deeply/nested/directory $testcases/git/edits \
             $exa $testcases/files -l  --git | diff -q - $results/files_l      || exit 1    # no git status for dirs
 COLUMNS=40  $exa $testcases/files -lG --git | diff -q - $results/files_lG_40  || exit 1    # that aren't under git
 
+# Git backends: libgit2 and the git program should agree on every fixture
+for backend in libgit2 command; do
+    $exa $testcases/git              -l --git=$backend 2>&1 | diff -q - $results/git_1_long      || exit 1
+    $exa $testcases/git    --recurse -l --git=$backend 2>&1 | diff -q - $results/git_1_recurse   || exit 1
+    $exa $testcases/git/moves/thither --tree -l --git=$backend 2>&1 | diff -q - $results/git_1_file || exit 1
+    $exa $testcases/git2   --recurse -l --git=$backend 2>&1 | diff -q - $results/git_2_recurse   || exit 1
+    $exa $testcases/git2/ignoreds    -l --git=$backend 2>&1 | diff -q - $results/git_2_ignoreds  || exit 1
+    $exa $testcases/git2/deeply/nested/repository -l --git=$backend 2>&1 | diff -q - $results/git_2_repository || exit 1
+    $exa $testcases/git $testcases/git2 --long --git=$backend | diff -q - $results/git_12  || exit 1
+    $exa $testcases/files            -l --git=$backend | diff -q - $results/files_l        || exit 1
+done
+
+# With no git in the PATH, the command backend shows no Git column rather than failing
+PATH=/nonexistent $exa $testcases/git -l --git=command 2>&1 | diff -q - $results/git_1_nogit    || exit 1
+
 
 # .gitignore
 $exa $testcases/git2 --recurse --long --git-ignore 2>&1 | diff - $results/git_2_ignore_recurse